@@ -58,6 +58,10 @@ impl Substr {
 
     Substr { str: self.str.clone(), rg: start..end }
   }
+
+  /// Return if the two substrings are the same range of the same allocation.
+  #[inline]
+  pub fn ptr_eq(&self, other: &Self) -> bool { self.rg == other.rg && self.str.ptr_eq(&other.str) }
 }
 impl std::ops::Deref for Substr {
   type Target = str;
@@ -136,6 +140,15 @@ impl<T: ToOwned + ?Sized> CowArc<T> {
   #[inline]
   pub fn is_owned(&self) -> bool { matches!(self, CowArc::Owned(_)) }
 
+  /// Returns a mutable reference to the owned data if it's not borrowed and
+  /// no other pointer to the same allocation, without cloning.
+  pub fn get_mut(&mut self) -> Option<&mut T::Owned> {
+    match self {
+      CowArc::Borrowed(_) => None,
+      CowArc::Owned(owned) => Arc::get_mut(owned),
+    }
+  }
+
   /// Acquires a mutable reference to the owned form of the data.
   /// Clones the data if it is not already owned or other pointer to the same
   /// allocation
@@ -6,5 +6,7 @@ pub use cow_rc::{CowArc, Substr};
 pub use frame_cache::*;
 mod resource;
 pub use resource::*;
+mod rope;
+pub use rope::*;
 mod sc;
 pub use sc::*;
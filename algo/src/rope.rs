@@ -0,0 +1,580 @@
+use std::{
+  fmt::{Debug, Display},
+  ops::Range,
+  sync::Arc,
+};
+
+use crate::CowArc;
+
+/// A text buffer that stores the text in paragraphs, an edit only copies the
+/// paragraphs it touches, and a clone only shares the paragraphs.
+///
+/// The paragraphs are kept in a persistent balanced tree, so finding a
+/// paragraph and replacing the text cost `O(log n)` in the number of the
+/// paragraphs, and a clone costs `O(1)`.
+///
+/// Every paragraph but the last ends with a paragraph separator, and the last
+/// one never does, so a text ends with a separator has an empty last
+/// paragraph.
+///
+/// A rope marked as a secret fills the buffers of its copies of the text with
+/// zeros before they're dropped, see [`Rope::set_secret`].
+#[derive(Clone)]
+pub struct Rope {
+  root: Arc<Node>,
+  secret: bool,
+}
+
+type Link = Option<Arc<Node>>;
+
+struct Node {
+  para: CowArc<str>,
+  left: Link,
+  right: Link,
+  height: usize,
+  /// The number of the paragraphs in the subtree.
+  count: usize,
+  /// The length of the text in the subtree in bytes.
+  len: usize,
+}
+
+impl Rope {
+  #[inline]
+  pub fn new() -> Self { Self::default() }
+
+  /// The length of the text in bytes.
+  #[inline]
+  pub fn len(&self) -> usize { self.root.len }
+
+  #[inline]
+  pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+  #[inline]
+  pub fn paragraph_count(&self) -> usize { self.root.count }
+
+  /// Mark the text as a secret, such as a password. The paragraphs no longer
+  /// used by the rope and its clones, and the copies made by an edit, are
+  /// filled with zeros before they're dropped.
+  #[inline]
+  pub fn set_secret(&mut self, secret: bool) { self.secret = secret; }
+
+  #[inline]
+  pub fn is_secret(&self) -> bool { self.secret }
+
+  /// Return true if the two ropes are the same text by sharing, a clone is the
+  /// same as its origin until one of them is edited.
+  #[inline]
+  pub fn ptr_eq(&self, other: &Self) -> bool { Arc::ptr_eq(&self.root, &other.root) }
+
+  /// The paragraph at `idx`, including its separator.
+  pub fn paragraph(&self, idx: usize) -> &CowArc<str> { &self.locate(idx).0.para }
+
+  /// The byte range of the paragraph at `idx` in the text.
+  pub fn paragraph_range(&self, idx: usize) -> Range<usize> {
+    let (node, start) = self.locate(idx);
+    start..start + node.para.len()
+  }
+
+  /// The index of the paragraph that contains the byte at `byte_offset`, the
+  /// offset at the end of the text is in the last paragraph.
+  pub fn paragraph_at(&self, byte_offset: usize) -> usize {
+    assert!(byte_offset <= self.len(), "byte offset {byte_offset} out of the rope.");
+    if byte_offset == self.len() {
+      return self.paragraph_count() - 1;
+    }
+    let (mut node, mut offset, mut idx) = (&*self.root, byte_offset, 0);
+    loop {
+      let left_len = len(&node.left);
+      if offset < left_len {
+        node = node.left.as_ref().unwrap();
+        continue;
+      }
+      offset -= left_len;
+      idx += count(&node.left);
+      if offset < node.para.len() {
+        return idx;
+      }
+      offset -= node.para.len();
+      idx += 1;
+      node = node.right.as_ref().unwrap();
+    }
+  }
+
+  pub fn paragraphs(&self) -> impl Iterator<Item = &str> + '_ {
+    let mut stack = vec![];
+    let mut next = Some(&*self.root);
+    std::iter::from_fn(move || {
+      while let Some(node) = next {
+        stack.push(node);
+        next = node.left.as_deref();
+      }
+      let node = stack.pop()?;
+      next = node.right.as_deref();
+      Some(&*node.para)
+    })
+  }
+
+  pub fn bytes(&self) -> impl Iterator<Item = u8> + '_ { self.paragraphs().flat_map(str::bytes) }
+
+  /// Copy the text in the byte `range` out.
+  pub fn slice(&self, range: Range<usize>) -> String {
+    let mut text = String::with_capacity(range.len());
+    if range.is_empty() {
+      return text;
+    }
+    for idx in self.paragraph_at(range.start)..=self.paragraph_at(range.end - 1) {
+      let (node, para_start) = self.locate(idx);
+      let start = range.start.max(para_start) - para_start;
+      let end = range.end.min(para_start + node.para.len()) - para_start;
+      text.push_str(&node.para[start..end]);
+    }
+    text
+  }
+
+  /// Copy the text in the byte `range` out, or `None` if the `range` is out of
+  /// the text or not on the char boundaries.
+  pub fn get(&self, range: Range<usize>) -> Option<String> {
+    let on_boundary = |offset: usize| {
+      offset == self.len() || {
+        let (node, start) = self.locate(self.paragraph_at(offset));
+        node.para.is_char_boundary(offset - start)
+      }
+    };
+    let valid = range.start <= range.end && range.end <= self.len();
+    (valid && on_boundary(range.start) && on_boundary(range.end)).then(|| self.slice(range))
+  }
+
+  #[inline]
+  pub fn insert(&mut self, byte_offset: usize, text: &str) {
+    self.replace(byte_offset..byte_offset, text);
+  }
+
+  #[inline]
+  pub fn remove(&mut self, range: Range<usize>) { self.replace(range, ""); }
+
+  /// Replace the text in the byte `range` with `text`, only the paragraphs in
+  /// the range are rebuilt, the others are shared with the rope before.
+  pub fn replace(&mut self, range: Range<usize>, text: &str) {
+    assert!(
+      range.start <= range.end && range.end <= self.len(),
+      "range {range:?} out of the rope."
+    );
+    let mut first = self.paragraph_at(range.start);
+    let last = self.paragraph_at(range.end);
+    let mut first_start = self.paragraph_range(first).start;
+    // A "\r" and a "\n" after it are one separator, so the paragraph ends with
+    // "\r" is rebuilt too if the edit starts after it.
+    if first > 0 && range.start == first_start && self.paragraph(first - 1).ends_with('\r') {
+      first -= 1;
+      first_start = self.paragraph_range(first).start;
+    }
+
+    let last_start = self.paragraph_range(last).start;
+    let head = &self.paragraph(first)[..range.start - first_start];
+    let tail = &self.paragraph(last)[range.end - last_start..];
+    let mut joined = [head, text, tail].concat();
+    let mut paras: Vec<_> = split_paragraphs(&joined)
+      .map(|p| CowArc::owned(p.to_string()))
+      .collect();
+    if self.secret {
+      // Safety: zeros are valid UTF-8.
+      zeroize(unsafe { joined.as_bytes_mut() });
+    }
+    // The tail ends with a separator if it's not the last paragraph, the empty
+    // paragraph after it is not a new one.
+    if last + 1 < self.paragraph_count() {
+      paras.pop();
+    }
+
+    let (before, rest) = split(Some(self.root.clone()), first);
+    let (removed, after) = split(rest, last + 1 - first);
+    let root = concat(concat(before, build(&paras)), after);
+    self.root = root.expect("A rope has one paragraph at least.");
+    if let Some(mut removed) = removed.filter(|_| self.secret) {
+      zeroize_unique(&mut removed);
+    }
+  }
+
+  /// The node of the paragraph at `idx`, and the byte offset of the paragraph.
+  fn locate(&self, mut idx: usize) -> (&Node, usize) {
+    assert!(idx < self.paragraph_count(), "paragraph {idx} out of the rope.");
+    let (mut node, mut start) = (&*self.root, 0);
+    loop {
+      let left_count = count(&node.left);
+      if idx < left_count {
+        node = node.left.as_ref().unwrap();
+      } else if idx == left_count {
+        return (node, start + len(&node.left));
+      } else {
+        idx -= left_count + 1;
+        start += len(&node.left) + node.para.len();
+        node = node.right.as_ref().unwrap();
+      }
+    }
+  }
+}
+
+/// Fill the paragraphs only used by the tree of `node` with zeros.
+fn zeroize_unique(node: &mut Arc<Node>) {
+  let Some(node) = Arc::get_mut(node) else { return };
+  if let Some(para) = node.para.get_mut() {
+    // Safety: zeros are valid UTF-8.
+    zeroize(unsafe { para.as_bytes_mut() });
+  }
+  node
+    .left
+    .iter_mut()
+    .chain(node.right.iter_mut())
+    .for_each(zeroize_unique);
+}
+
+fn zeroize(bytes: &mut [u8]) {
+  for b in bytes {
+    // Write by volatile, so the compiler does not remove the writes to a buffer
+    // going to be dropped.
+    unsafe { std::ptr::write_volatile(b, 0) };
+  }
+}
+
+fn height(link: &Link) -> usize { link.as_ref().map_or(0, |n| n.height) }
+
+fn count(link: &Link) -> usize { link.as_ref().map_or(0, |n| n.count) }
+
+fn len(link: &Link) -> usize { link.as_ref().map_or(0, |n| n.len) }
+
+fn node(left: Link, para: CowArc<str>, right: Link) -> Arc<Node> {
+  Arc::new(Node {
+    height: height(&left).max(height(&right)) + 1,
+    count: count(&left) + count(&right) + 1,
+    len: len(&left) + len(&right) + para.len(),
+    para,
+    left,
+    right,
+  })
+}
+
+/// Build a node from the subtrees differ in height by two at most.
+fn balance(left: Link, para: CowArc<str>, right: Link) -> Arc<Node> {
+  let (hl, hr) = (height(&left), height(&right));
+  if hl > hr + 1 {
+    let l = left.unwrap();
+    if height(&l.left) >= height(&l.right) {
+      node(l.left.clone(), l.para.clone(), Some(node(l.right.clone(), para, right)))
+    } else {
+      let lr = l.right.as_ref().unwrap();
+      let new_left = node(l.left.clone(), l.para.clone(), lr.left.clone());
+      node(Some(new_left), lr.para.clone(), Some(node(lr.right.clone(), para, right)))
+    }
+  } else if hr > hl + 1 {
+    let r = right.unwrap();
+    if height(&r.right) >= height(&r.left) {
+      node(Some(node(left, para, r.left.clone())), r.para.clone(), r.right.clone())
+    } else {
+      let rl = r.left.as_ref().unwrap();
+      let new_right = node(rl.right.clone(), r.para.clone(), r.right.clone());
+      node(Some(node(left, para, rl.left.clone())), rl.para.clone(), Some(new_right))
+    }
+  } else {
+    node(left, para, right)
+  }
+}
+
+/// Join the `left`, the `para` and the `right` in order, the subtrees can
+/// differ in height by any.
+fn join(left: Link, para: CowArc<str>, right: Link) -> Arc<Node> {
+  let (hl, hr) = (height(&left), height(&right));
+  if hl > hr + 1 {
+    let l = left.unwrap();
+    let new_right = join(l.right.clone(), para, right);
+    balance(l.left.clone(), l.para.clone(), Some(new_right))
+  } else if hr > hl + 1 {
+    let r = right.unwrap();
+    let new_left = join(left, para, r.left.clone());
+    balance(Some(new_left), r.para.clone(), r.right.clone())
+  } else {
+    node(left, para, right)
+  }
+}
+
+fn concat(left: Link, right: Link) -> Link {
+  let Some(r) = right else { return left };
+  let (first, rest) = split(Some(r), 1);
+  let para = first.unwrap().para.clone();
+  Some(join(left, para, rest))
+}
+
+/// Split the tree into the first `idx` paragraphs and the rest.
+fn split(link: Link, idx: usize) -> (Link, Link) {
+  let Some(n) = link else { return (None, None) };
+  let left_count = count(&n.left);
+  if idx <= left_count {
+    let (ll, lr) = split(n.left.clone(), idx);
+    (ll, Some(join(lr, n.para.clone(), n.right.clone())))
+  } else {
+    let (rl, rr) = split(n.right.clone(), idx - left_count - 1);
+    (Some(join(n.left.clone(), n.para.clone(), rl)), rr)
+  }
+}
+
+fn build(paras: &[CowArc<str>]) -> Link {
+  if paras.is_empty() {
+    return None;
+  }
+  let mid = paras.len() / 2;
+  Some(node(build(&paras[..mid]), paras[mid].clone(), build(&paras[mid + 1..])))
+}
+
+impl Default for Rope {
+  fn default() -> Self { Self { root: node(None, CowArc::borrowed(""), None), secret: false } }
+}
+
+impl Drop for Rope {
+  fn drop(&mut self) {
+    if self.secret {
+      zeroize_unique(&mut self.root);
+    }
+  }
+}
+
+impl From<&str> for Rope {
+  fn from(text: &str) -> Self {
+    let mut rope = Rope::default();
+    rope.insert(0, text);
+    rope
+  }
+}
+
+impl From<String> for Rope {
+  #[inline]
+  fn from(text: String) -> Self { Self::from(text.as_str()) }
+}
+
+impl Display for Rope {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    self.paragraphs().try_for_each(|p| f.write_str(p))
+  }
+}
+
+impl Debug for Rope {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_list().entries(self.paragraphs()).finish()
+  }
+}
+
+impl PartialEq<str> for Rope {
+  fn eq(&self, other: &str) -> bool { self.len() == other.len() && self.bytes().eq(other.bytes()) }
+}
+
+impl PartialEq for Rope {
+  fn eq(&self, other: &Self) -> bool { self.len() == other.len() && self.bytes().eq(other.bytes()) }
+}
+
+impl Eq for Rope {}
+
+/// Split the `text` into paragraphs like the paragraphs of `Rope`, the
+/// separators are kept at the end of the paragraphs, and there is an empty
+/// last paragraph if the `text` is empty or ends with a separator.
+pub fn split_paragraphs(text: &str) -> impl Iterator<Item = &str> + '_ {
+  let mut rest = Some(text);
+  std::iter::from_fn(move || {
+    let text = rest?;
+    let mut iter = text.char_indices().peekable();
+    while let Some((idx, c)) = iter.next() {
+      if is_paragraph_separator(c) {
+        let mut end = idx + c.len_utf8();
+        if c == '\r' && iter.peek().is_some_and(|(_, c)| *c == '\n') {
+          end += 1;
+        }
+        rest = Some(&text[end..]);
+        return Some(&text[..end]);
+      }
+    }
+    rest = None;
+    Some(text)
+  })
+}
+
+/// The characters separate paragraphs, they are the characters of the
+/// paragraph separator class in the Unicode bidirectional algorithm.
+pub fn is_paragraph_separator(c: char) -> bool {
+  matches!(c, '\n' | '\r' | '\u{1C}'..='\u{1E}' | '\u{85}' | '\u{2029}')
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicU8, AtomicUsize, Ordering},
+  };
+
+  use super::*;
+
+  fn paras(rope: &Rope) -> Vec<&str> { rope.paragraphs().collect() }
+
+  fn assert_balanced(link: &Link) -> usize {
+    let Some(n) = link else { return 0 };
+    let (hl, hr) = (assert_balanced(&n.left), assert_balanced(&n.right));
+    assert!(hl.abs_diff(hr) <= 1);
+    assert_eq!(n.height, hl.max(hr) + 1);
+    assert_eq!(n.count, count(&n.left) + count(&n.right) + 1);
+    n.height
+  }
+
+  #[test]
+  fn split_by_paragraph() {
+    assert_eq!(paras(&Rope::new()), [""]);
+    assert_eq!(paras(&"a\nb\r\nc\rd".into()), ["a\n", "b\r\n", "c\r", "d"]);
+    assert_eq!(paras(&"a\n".into()), ["a\n", ""]);
+
+    let rope = Rope::from("ab\ncd\nef");
+    assert_eq!(rope.paragraph_at(0), 0);
+    assert_eq!(rope.paragraph_at(3), 1);
+    assert_eq!(rope.paragraph_at(8), 2);
+    assert_eq!(rope.paragraph_range(1), 3..6);
+    assert_eq!(rope.slice(1..7), "b\ncd\ne");
+    assert_eq!(rope.get(2..4).as_deref(), Some("\nc"));
+    assert_eq!(rope.get(7..9), None);
+    assert_eq!(Rope::from("aé").get(0..2), None);
+  }
+
+  #[test]
+  fn edit() {
+    let mut rope = Rope::from("ab\ncd\nef");
+    let untouched = rope.paragraph(2).clone();
+    rope.insert(4, "x\ny");
+    assert_eq!(paras(&rope), ["ab\n", "cx\n", "yd\n", "ef"]);
+    assert!(rope.paragraph(3).ptr_eq(&untouched));
+
+    rope.remove(1..7);
+    assert_eq!(paras(&rope), ["ad\n", "ef"]);
+    assert_eq!(rope.paragraph_range(1), 3..5);
+
+    rope.replace(2..5, "");
+    assert_eq!(paras(&rope), ["ad"]);
+    assert_eq!(rope, *"ad");
+  }
+
+  #[test]
+  fn join_crlf() {
+    let mut rope = Rope::from("a\rb");
+    rope.insert(2, "\n");
+    assert_eq!(paras(&rope), ["a\r\n", "b"]);
+
+    let mut rope = Rope::from("a\rx\nb");
+    rope.remove(2..3);
+    assert_eq!(paras(&rope), ["a\r\n", "b"]);
+
+    rope.remove(1..2);
+    assert_eq!(paras(&rope), ["a\n", "b"]);
+  }
+
+  #[test]
+  fn same_as_string() {
+    let mut rope = Rope::new();
+    let mut text = String::new();
+    let inserts = ["hello", "\n", "wor\r\nld", "\r", "\n\n", "!"];
+    for (i, s) in inserts.iter().cycle().take(60).enumerate() {
+      let at = (i * 7) % (text.len() + 1);
+      let at = (0..=at)
+        .rev()
+        .find(|at| text.is_char_boundary(*at))
+        .unwrap();
+      rope.insert(at, s);
+      text.insert_str(at, s);
+      if i % 3 == 0 {
+        let end = (at + i % 5).min(text.len());
+        rope.remove(at..end);
+        text.replace_range(at..end, "");
+      }
+      assert_eq!(rope.to_string(), text);
+      assert_eq!(paras(&rope), split_paragraphs(&text).collect::<Vec<_>>());
+      assert_balanced(&Some(rope.root.clone()));
+    }
+  }
+
+  #[test]
+  fn share_unchanged_paragraphs() {
+    let text = "line\n".repeat(1000);
+    let origin = Rope::from(text.as_str());
+    let mut rope = origin.clone();
+    assert_eq!(rope.paragraph_count(), 1001);
+    assert_balanced(&Some(rope.root.clone()));
+
+    rope.replace(2502..2507, "x\ny");
+    assert_eq!(rope.paragraph_count(), 1001);
+    assert_eq!(&**rope.paragraph(500), "lix\n");
+    assert_eq!(&**rope.paragraph(501), "yne\n");
+    assert_eq!(origin.to_string(), text);
+    for idx in (0..1001).filter(|idx| !(500..502).contains(idx)) {
+      assert!(rope.paragraph(idx).ptr_eq(origin.paragraph(idx)));
+    }
+    assert_balanced(&Some(rope.root.clone()));
+  }
+
+  /// An allocator records if the watched buffer is filled with zeros when it's
+  /// freed, so the test checks the buffers dropped by a secret rope.
+  struct WatchAlloc;
+
+  static WATCHED: AtomicUsize = AtomicUsize::new(0);
+  /// `NOT_FREED`, or if the watched buffer is zeroed when it's freed.
+  static FREED: AtomicU8 = AtomicU8::new(NOT_FREED);
+  const NOT_FREED: u8 = 0;
+  const ZEROED: u8 = 1;
+  const NOT_ZEROED: u8 = 2;
+
+  unsafe impl GlobalAlloc for WatchAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 { unsafe { System.alloc(layout) } }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+      if WATCHED
+        .compare_exchange(ptr as usize, 0, Ordering::SeqCst, Ordering::SeqCst)
+        .is_ok()
+      {
+        let bytes = unsafe { std::slice::from_raw_parts(ptr, layout.size()) };
+        let zeroed = bytes.iter().all(|b| *b == 0);
+        FREED.store(if zeroed { ZEROED } else { NOT_ZEROED }, Ordering::SeqCst);
+      }
+      unsafe { System.dealloc(ptr, layout) }
+    }
+  }
+
+  #[global_allocator]
+  static ALLOC: WatchAlloc = WatchAlloc;
+
+  fn watch(para: &str) {
+    FREED.store(NOT_FREED, Ordering::SeqCst);
+    WATCHED.store(para.as_ptr() as usize, Ordering::SeqCst);
+  }
+
+  fn freed() -> u8 { FREED.load(Ordering::SeqCst) }
+
+  #[test]
+  fn zero_secret_paragraphs() {
+    let mut rope = Rope::from("one\ntwo\nthree");
+    rope.set_secret(true);
+    assert!(rope.is_secret());
+
+    // The removed paragraph is still used by the clone.
+    let clone = rope.clone();
+    watch(rope.paragraph(1));
+    rope.remove(4..8);
+    assert_eq!(freed(), NOT_FREED);
+    assert_eq!(clone.to_string(), "one\ntwo\nthree");
+    drop(clone);
+    assert_eq!(freed(), ZEROED);
+
+    watch(rope.paragraph(0));
+    rope.remove(0..4);
+    assert_eq!(freed(), ZEROED);
+
+    watch(rope.paragraph(0));
+    drop(rope);
+    assert_eq!(freed(), ZEROED);
+
+    // A rope not secret does not zero its paragraphs.
+    let mut rope = Rope::from("one\ntwo");
+    watch(rope.paragraph(0));
+    rope.remove(0..4);
+    assert_eq!(freed(), NOT_ZEROED);
+  }
+}
@@ -21,6 +21,10 @@ pub enum Align {
   /// [`HAlign::Stretch`]! if direction is horizontal and [`VAlign::Stretch`]!
   /// if direction is vertical.
   Stretch,
+  /// The children are aligned by their first baselines, the children without
+  /// a baseline are aligned by their bottom edge. It only works in the
+  /// y-axis, and is the same as `Start` in the x-axis.
+  Baseline,
 }
 
 /// A enum that describe how widget align to its box in x-axis.
@@ -114,13 +118,23 @@ impl WrapRender for VAlignWidget {
 }
 
 impl Align {
+  /// Return the offset of the child in the box. The `Baseline` alignment
+  /// needs the baselines of the children, so it's the same as `Start` here,
+  /// see [`Align::baseline_offset`].
   pub fn align_value(self, child_size: f32, box_size: f32) -> f32 {
     match self {
       Align::Center => (box_size - child_size) / 2.,
       Align::End => box_size - child_size,
-      _ => 0.,
+      Align::Start | Align::Stretch | Align::Baseline => 0.,
     }
   }
+
+  /// Return the offset of the child to align its first `baseline` to the
+  /// `line_baseline`, a child without baseline sits on the line baseline by its
+  /// bottom edge.
+  pub fn baseline_offset(child_size: f32, baseline: Option<f32>, line_baseline: f32) -> f32 {
+    line_baseline - baseline.unwrap_or(child_size)
+  }
 }
 
 impl From<HAlign> for Align {
@@ -20,6 +20,16 @@ impl WrapRender for ConstrainedBox {
     let min = clamp.clamp(self.clamp.min);
     host.perform_layout(BoxClamp { min, max }, ctx)
   }
+
+  fn intrinsic_size(
+    &self, kind: IntrinsicSize, extent: f32, host: &dyn Render, ctx: &mut LayoutCtx,
+  ) -> f32 {
+    let BoxClamp { min, max } = self.clamp;
+    let extent = extent.clamp(kind.cross_of(min), kind.cross_of(max));
+    host
+      .intrinsic_size(kind, extent, ctx)
+      .clamp(kind.axis_of(min), kind.axis_of(max))
+  }
 }
 
 #[cfg(test)]
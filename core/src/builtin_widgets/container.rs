@@ -16,6 +16,10 @@ impl Render for Container {
 
   #[inline]
   fn only_sized_by_parent(&self) -> bool { true }
+
+  fn intrinsic_size(&self, kind: IntrinsicSize, _: f32, _: &mut LayoutCtx) -> f32 {
+    kind.axis_of(self.size)
+  }
 }
 
 #[cfg(test)]
@@ -6,6 +6,10 @@ impl Render for Resource<PixelImage> {
     clamp.clamp(size)
   }
 
+  fn intrinsic_size(&self, kind: IntrinsicSize, _: f32, _: &mut LayoutCtx) -> f32 {
+    if kind.is_width() { self.width() as f32 } else { self.height() as f32 }
+  }
+
   fn paint(&self, ctx: &mut PaintingCtx) {
     let size = ctx.box_size().unwrap();
     let box_rect = Rect::from_size(size);
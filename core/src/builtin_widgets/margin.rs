@@ -40,6 +40,12 @@ impl Render for Margin {
 
   #[inline]
   fn paint(&self, _: &mut PaintingCtx) {}
+
+  fn intrinsic_size(&self, kind: IntrinsicSize, extent: f32, ctx: &mut LayoutCtx) -> f32 {
+    let thickness = self.margin.thickness();
+    let extent = (extent - kind.cross_of(thickness)).max(0.);
+    ctx.single_child_intrinsic_size(kind, extent) + kind.axis_of(thickness)
+  }
 }
 
 impl Margin {
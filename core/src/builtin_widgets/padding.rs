@@ -44,6 +44,14 @@ impl WrapRender for Padding {
 
     size
   }
+
+  fn intrinsic_size(
+    &self, kind: IntrinsicSize, extent: f32, host: &dyn Render, ctx: &mut LayoutCtx,
+  ) -> f32 {
+    let thickness = self.padding.thickness();
+    let extent = (extent - kind.cross_of(thickness)).max(0.);
+    host.intrinsic_size(kind, extent, ctx) + kind.axis_of(thickness)
+  }
 }
 
 impl Padding {
@@ -1,4 +1,6 @@
-use crate::prelude::*;
+use std::{cell::Cell, rc::Rc};
+
+use crate::{prelude::*, ticker::FrameMsg};
 /// Enumerate to describe which direction allow widget to scroll.
 #[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Hash)]
 pub enum Scrollable {
@@ -14,15 +16,95 @@ pub enum Scrollable {
   Both,
 }
 
+/// The physics of a `ScrollableWidget` that moves the content after the user
+/// releases a drag or a touchpad swipe. The notches of a mouse wheel never
+/// fling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollPhysics {
+  /// The decay rate per second of the fling velocity, the velocity after `t`
+  /// seconds is `v * e^(-friction * t)`. A zero friction disables the fling.
+  pub friction: f32,
+  /// Whether the content can be dragged or flung beyond its edges, it bounces
+  /// back when released.
+  pub overscroll: bool,
+  /// The maximum distance the content can be pulled beyond its edges.
+  pub max_overscroll: f32,
+  /// The duration of the bounce-back and snap animations.
+  pub settle_duration: Duration,
+}
+
+/// The positions a `ScrollableWidget` settles at after a fling or a drag.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ScrollSnap {
+  /// Stop wherever the content comes to rest.
+  #[default]
+  None,
+  /// Snap to a multiple of the view size, at most one page away from where
+  /// the content was released.
+  Page,
+  /// Snap to the nearest of these scroll positions.
+  Points(Vec<Point>),
+}
+
 /// Helper struct for builtin scrollable field.
 #[derive(Default)]
 pub struct ScrollableWidget {
   pub scrollable: Scrollable,
+  pub physics: ScrollPhysics,
+  pub snap: ScrollSnap,
+  /// Lock a wheel gesture to the axis it starts with, the delta along the
+  /// other axis is dropped until the gesture ends.
+  pub axis_lock: bool,
   scroll_pos: Point,
   page: Size,
   content_size: Size,
+  motion: Option<ScrollMotion>,
+  velocity: VelocityTracker,
+  wheel_at: Option<Instant>,
+  wheel_gesture: bool,
+  locked_axis: Option<Scrollable>,
+  drag_pos: Option<Point>,
+  content: LazyWidgetId,
 }
 
+/// Where to place a widget in the view of a `ScrollableWidget` when scrolling
+/// it into view.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ScrollAlign {
+  /// Scroll as little as possible to show the widget, do nothing if it's
+  /// already visible.
+  #[default]
+  Nearest,
+  /// Align the widget to the start of the view.
+  Start,
+  /// Align the widget to the center of the view.
+  Center,
+  /// Align the widget to the end of the view.
+  End,
+}
+
+enum ScrollMotion {
+  Fling { velocity: Vector, last: Instant },
+  Animate { from: Point, to: Point, start: Instant, transition: Box<dyn Transition> },
+}
+
+/// Estimate the velocity from the recent deltas of the scroll input.
+#[derive(Default)]
+struct VelocityTracker {
+  samples: Vec<(Instant, Vector)>,
+}
+
+/// The fling stops when its velocity, in pixels per second, drops below this.
+const MIN_FLING_VELOCITY: f32 = 20.;
+/// The fling velocity, in pixels per second, is limited to this.
+const MAX_FLING_VELOCITY: f32 = 8000.;
+/// Only the input in this duration before the release counts to the velocity.
+const VELOCITY_WINDOW: Duration = Duration::from_millis(100);
+/// The wheel input settles after this duration without events.
+const WHEEL_IDLE: Duration = Duration::from_millis(50);
+/// The friction of a fling that goes beyond the edges of the content.
+const OVERSCROLL_FRICTION: f32 = 24.;
+
 impl Declare for ScrollableWidget {
   type Builder = FatObj<()>;
   #[inline]
@@ -32,6 +114,7 @@ impl Declare for ScrollableWidget {
 impl<'c> ComposeChild<'c> for ScrollableWidget {
   type Child = Widget<'c>;
   fn compose_child(this: impl StateWriter<Value = Self>, child: Self::Child) -> Widget<'c> {
+    let data: Box<dyn Query> = Box::new(this.clone_writer());
     fn_widget! {
       let mut view = @UnconstrainedBox {
         dir: pipe!{
@@ -53,6 +136,7 @@ impl<'c> ComposeChild<'c> for ScrollableWidget {
           Anchor::left_top(-pos.x, -pos.y)
         }
       };
+      $this.silent().content = child.lazy_id();
 
       watch!($child.layout_size())
         .distinct_until_changed()
@@ -61,14 +145,72 @@ impl<'c> ComposeChild<'c> for ScrollableWidget {
         .distinct_until_changed()
         .subscribe(move |v| $this.write().set_page(v));
 
+      // Keep the window drawing frames while the content is moving.
+      let wnd_id = BuildCtx::get().window().id();
+      let animating = Rc::new(Cell::new(false));
+      let c_animating = animating.clone();
+      watch!($this.is_active())
+        .distinct_until_changed()
+        .subscribe(move |active| {
+          if let Some(wnd) = AppCtx::get_window(wnd_id) {
+            if active != c_animating.get() {
+              c_animating.set(active);
+              if active { wnd.inc_running_animate() } else { wnd.dec_running_animate() }
+            }
+          }
+        });
+      let u = BuildCtx::get()
+        .window()
+        .frame_tick_stream()
+        .subscribe(move |msg| {
+          if let FrameMsg::NewFrame(at) = msg {
+            if $this.is_active() {
+              $this.write().advance_to(at);
+            }
+          }
+        });
+
       @Clip {
+        on_disposed: move |_| {
+          u.unsubscribe();
+          if animating.get() {
+            if let Some(wnd) = AppCtx::get_window(wnd_id) {
+              wnd.dec_running_animate();
+            }
+          }
+        },
         @ $view {
-          on_wheel: move |e| $this.write().scroll(-e.delta_x, -e.delta_y),
+          on_wheel: move |e| {
+            // Pass the delta this view can't consume to the outer scroll views.
+            let delta = Vector::new(-e.delta_x, -e.delta_y);
+            let rest = $this.write().wheel(delta, e.phase, Instant::now());
+            e.delta_x = -rest.x;
+            e.delta_y = -rest.y;
+            if rest == Vector::zero() {
+              e.stop_propagation();
+            }
+          },
+          on_pointer_down: move |e| {
+            // The mouse drag is left to the content, such as selecting text.
+            if e.point_type != PointerType::Mouse && e.is_primary {
+              $this.write().drag_start(e.global_pos(), Instant::now());
+            }
+          },
+          on_pointer_move: move |e| if e.is_primary && $this.drag_pos.is_some() {
+            $this.write().drag_move(e.global_pos(), Instant::now());
+          },
+          on_pointer_up: move |e| if e.is_primary && $this.drag_pos.is_some() {
+            $this.write().drag_end(Instant::now());
+          },
+          on_pointer_cancel: move |e| if e.is_primary && $this.drag_pos.is_some() {
+            $this.write().drag_end(Instant::now());
+          },
           @ { child }
         }
       }
     }
     .into_widget()
+    .attach_data(data)
   }
 }
 
@@ -84,11 +226,30 @@ impl ScrollableWidget {
     self.jump_to(new);
   }
 
-  pub fn jump_to(&mut self, top_left: Point) {
-    let max = self.max_scrollable();
-    self.scroll_pos = top_left.clamp(Point::zero(), max.to_vector().to_point());
+  /// Jump to the position immediately. A running fling continues from the new
+  /// position.
+  pub fn jump_to(&mut self, top_left: Point) { self.scroll_pos = self.clamp_pos(top_left); }
+
+  /// Animate the scroll position to `top_left` by the `transition`, the
+  /// position is clamped to the scrollable range.
+  pub fn animate_to(&mut self, top_left: Point, transition: impl Transition + 'static) {
+    let to = self.clamp_pos(top_left);
+    self.start_animate(to, transition.box_it(), Instant::now());
   }
 
+  /// Fling the content with the `velocity` in pixels per second, it slows
+  /// down by the friction of the `physics` and settles at a snap point if any.
+  pub fn fling(&mut self, velocity: Vector) { self.release(velocity, Instant::now()); }
+
+  /// Stop the fling or the animation of the scroll position.
+  pub fn stop_motion(&mut self) {
+    self.motion = None;
+    self.wheel_at = None;
+  }
+
+  /// Return if the scroll position is flinging or animating.
+  pub fn is_moving(&self) -> bool { self.motion.is_some() }
+
   #[inline]
   pub fn scroll_view_size(&self) -> Size { self.page }
 
@@ -122,7 +283,82 @@ impl ScrollableWidget {
     if content.is_infinite() || content.is_nan() { 0. } else { self.scroll_pos.y / content }
   }
 
-  fn sync_pos(&mut self) { self.jump_to(self.scroll_pos) }
+  /// Return the rect of the scroll view in the coordinate of the widget `id`,
+  /// or `None` if the content isn't laid out.
+  pub fn view_rect_in(&self, wnd: &Window, id: WidgetId) -> Option<Rect> {
+    let content = self.content.id()?;
+    let tree = wnd.tree();
+    if content.is_dropped(tree) || id.is_dropped(tree) {
+      return None;
+    }
+    let content_pos = wnd.widget_pos(content)?;
+    let view = wnd.map_to_global(Point::zero(), content) - content_pos.to_vector();
+    Some(Rect::new(wnd.map_from_global(view, id), self.page))
+  }
+
+  /// Scroll all the `ScrollableWidget` ancestors of the widget `id` to show
+  /// it by the `align`, the inner ones first.
+  pub fn scroll_into_view(wnd: &Window, id: WidgetId, align: ScrollAlign) {
+    if let Some(size) = wnd.widget_size(id) {
+      Self::scroll_rect_into_view(wnd, id, Rect::from_size(size), align);
+    }
+  }
+
+  /// Scroll all the `ScrollableWidget` ancestors of the widget `id` to show
+  /// the `rect` in the coordinate of the widget by the `align`.
+  pub fn scroll_rect_into_view(wnd: &Window, id: WidgetId, rect: Rect, align: ScrollAlign) {
+    let tree = wnd.tree();
+    if id.is_dropped(tree) {
+      return;
+    }
+    let mut rect = Rect::new(wnd.map_to_global(rect.origin, id), rect.size);
+    for p in id.ancestors(tree).skip(1) {
+      let Some(mut this) = p.query_write::<ScrollableWidget>(tree) else { continue };
+      let Some(content) = this.content.id() else { continue };
+      let (Some(content_pos), false) = (wnd.widget_pos(content), content.is_dropped(tree)) else {
+        continue;
+      };
+      // The layout of the content may fall behind its scroll position.
+      let laid_out = -content_pos.to_vector();
+      let content_global = wnd.map_to_global(Point::zero(), content);
+      let target = rect.translate(-content_global.to_vector());
+
+      let current = this.scroll_pos;
+      let page = this.page;
+      let mut pos = current;
+      if this.scrollable != Scrollable::Y {
+        pos.x = align_pos(current.x, page.width, target.min_x(), target.max_x(), align);
+      }
+      if this.scrollable != Scrollable::X {
+        pos.y = align_pos(current.y, page.height, target.min_y(), target.max_y(), align);
+      }
+      let pos = this.clamp_pos(pos);
+      if pos != current {
+        this.jump_to(pos);
+      }
+      rect.origin = content_global + laid_out - pos.to_vector() + target.origin.to_vector();
+    }
+  }
+
+  fn clamp_pos(&self, pos: Point) -> Point { pos.clamp(Point::zero(), self.max_scrollable()) }
+
+  fn axis_mask(&self, mut v: Vector) -> Vector {
+    match self.scrollable {
+      Scrollable::X => v.y = 0.,
+      Scrollable::Y => v.x = 0.,
+      Scrollable::Both => {}
+    }
+    v
+  }
+
+  fn is_active(&self) -> bool { self.motion.is_some() || self.wheel_at.is_some() }
+
+  fn sync_pos(&mut self) {
+    // The moving content settles itself, and it may be beyond the edges.
+    if !self.is_moving() && self.drag_pos.is_none() {
+      self.jump_to(self.scroll_pos)
+    }
+  }
 
   fn set_content_size(&mut self, content_size: Size) {
     self.content_size = content_size;
@@ -133,6 +369,284 @@ impl ScrollableWidget {
     self.page = page;
     self.sync_pos()
   }
+
+  /// Scroll by the wheel `delta` and return the part it can't consume. Only a
+  /// touchpad gesture, which starts and ends by its `phase`, flings when it
+  /// ends.
+  fn wheel(&mut self, delta: Vector, phase: TouchPhase, at: Instant) -> Vector {
+    if phase == TouchPhase::Started || (self.wheel_at.is_none() && !self.wheel_gesture) {
+      self.wheel_gesture = phase == TouchPhase::Started;
+      self.velocity.clear();
+      self.locked_axis = self
+        .axis_lock
+        .then(|| if delta.x.abs() > delta.y.abs() { Scrollable::X } else { Scrollable::Y });
+    }
+    let mut delta = delta;
+    match self.locked_axis {
+      Some(Scrollable::X) => delta.y = 0.,
+      Some(Scrollable::Y) => delta.x = 0.,
+      _ => {}
+    }
+
+    self.motion = None;
+    let before = self.scroll_pos;
+    self.scroll(delta.x, delta.y);
+    let consumed = self.scroll_pos - before;
+    if self.wheel_gesture {
+      self.velocity.add(consumed, at);
+    }
+    if matches!(phase, TouchPhase::Ended | TouchPhase::Cancelled) {
+      let velocity = if self.wheel_gesture && phase == TouchPhase::Ended {
+        self.velocity.velocity(at)
+      } else {
+        Vector::zero()
+      };
+      self.wheel_gesture = false;
+      self.wheel_at = None;
+      self.release(velocity, at);
+    } else {
+      self.wheel_at = Some(at);
+    }
+    delta - consumed
+  }
+
+  fn drag_start(&mut self, pos: Point, at: Instant) {
+    self.stop_motion();
+    self.velocity.clear();
+    self.velocity.add(Vector::zero(), at);
+    self.drag_pos = Some(pos);
+  }
+
+  fn drag_move(&mut self, pos: Point, at: Instant) {
+    let Some(last) = self.drag_pos.replace(pos) else { return };
+    let delta = self.axis_mask(last - pos);
+    self.velocity.add(delta, at);
+    self.scroll_pos = self.elastic_pos(self.scroll_pos, delta);
+  }
+
+  fn drag_end(&mut self, at: Instant) {
+    if self.drag_pos.take().is_some() {
+      let velocity = self.velocity.velocity(at);
+      self.release(velocity, at);
+    }
+  }
+
+  /// Move `pos` by `delta`, the part beyond the edges is resisted more the
+  /// further it goes and never reaches the `max_overscroll`.
+  fn elastic_pos(&self, pos: Point, delta: Vector) -> Point {
+    let ScrollPhysics { overscroll, max_overscroll: limit, .. } = self.physics;
+    let max = self.max_scrollable();
+    if !overscroll || limit <= 0. {
+      return self.clamp_pos(pos + delta);
+    }
+    // Map the position to the distance the user pulled and back.
+    let pulled = |excess: f32| -limit * (1. - excess.min(limit * 0.999) / limit).ln();
+    let excess = |pulled: f32| limit * (1. - (-pulled / limit).exp());
+    let axis = |p: f32, d: f32, max: f32| {
+      let raw = if p < 0. {
+        -pulled(-p)
+      } else if p > max {
+        max + pulled(p - max)
+      } else {
+        p
+      };
+      let raw = raw + d;
+      if raw < 0. {
+        -excess(-raw)
+      } else if raw > max {
+        max + excess(raw - max)
+      } else {
+        raw
+      }
+    };
+    Point::new(axis(pos.x, delta.x, max.x), axis(pos.y, delta.y, max.y))
+  }
+
+  fn release(&mut self, velocity: Vector, at: Instant) {
+    let velocity = self
+      .axis_mask(velocity)
+      .with_max_length(MAX_FLING_VELOCITY);
+    let friction = self.physics.friction;
+    if self.snap != ScrollSnap::None {
+      let rest =
+        if friction > 0. { self.scroll_pos + velocity / friction } else { self.scroll_pos };
+      let to = self.snap_target(rest);
+      self.settle_to(to, at);
+    } else if friction > 0. && velocity.length() >= MIN_FLING_VELOCITY {
+      self.motion = Some(ScrollMotion::Fling { velocity, last: at });
+    } else {
+      self.settle_to(self.clamp_pos(self.scroll_pos), at);
+    }
+  }
+
+  fn snap_target(&self, rest: Point) -> Point {
+    let rest = self.clamp_pos(rest);
+    let target = match &self.snap {
+      ScrollSnap::None => rest,
+      ScrollSnap::Page => {
+        let from = self.scroll_pos;
+        let page = |rest: f32, from: f32, size: f32| {
+          if size > 0. {
+            let current = (from / size).round();
+            (rest / size)
+              .round()
+              .clamp(current - 1., current + 1.)
+              * size
+          } else {
+            rest
+          }
+        };
+        let mut target = rest;
+        if self.scrollable != Scrollable::Y {
+          target.x = page(rest.x, from.x, self.page.width);
+        }
+        if self.scrollable != Scrollable::X {
+          target.y = page(rest.y, from.y, self.page.height);
+        }
+        target
+      }
+      ScrollSnap::Points(points) => points
+        .iter()
+        .map(|p| self.clamp_pos(*p))
+        .min_by(|a, b| {
+          a.distance_to(rest)
+            .total_cmp(&b.distance_to(rest))
+        })
+        .unwrap_or(rest),
+    };
+    self.clamp_pos(target)
+  }
+
+  fn settle_to(&mut self, to: Point, at: Instant) {
+    if to == self.scroll_pos {
+      self.motion = None;
+    } else {
+      let transition =
+        EasingTransition { duration: self.physics.settle_duration, easing: easing::EASE_OUT };
+      self.start_animate(to, transition.box_it(), at);
+    }
+  }
+
+  fn start_animate(&mut self, to: Point, transition: Box<dyn Transition>, start: Instant) {
+    let from = self.scroll_pos;
+    self.motion = Some(ScrollMotion::Animate { from, to, start, transition });
+  }
+
+  fn advance_to(&mut self, at: Instant) {
+    if let Some(wheel_at) = self.wheel_at {
+      // The wheel without a gesture settles when it's idle, a touchpad
+      // gesture flings when it ends.
+      if at.saturating_duration_since(wheel_at) >= WHEEL_IDLE {
+        self.wheel_at = None;
+        self.release(Vector::zero(), at);
+      }
+    }
+
+    match self.motion.take() {
+      Some(ScrollMotion::Fling { velocity, last }) => {
+        let dt = at.saturating_duration_since(last).as_secs_f32();
+        let ScrollPhysics { friction, overscroll, max_overscroll, .. } = self.physics;
+        let max = self.max_scrollable();
+        let axis = |p: f32, v: f32, max: f32| {
+          let friction = if p < 0. || p > max { OVERSCROLL_FRICTION } else { friction };
+          let decay = (-friction * dt).exp();
+          let new_p = p + v * (1. - decay) / friction;
+          let (min, max) =
+            if overscroll { (-max_overscroll, max + max_overscroll) } else { (0., max) };
+          let clamped = new_p.clamp(min, max);
+          (clamped, if clamped == new_p { v * decay } else { 0. })
+        };
+        let (x, vx) = axis(self.scroll_pos.x, velocity.x, max.x);
+        let (y, vy) = axis(self.scroll_pos.y, velocity.y, max.y);
+        self.scroll_pos = Point::new(x, y);
+        let velocity = Vector::new(vx, vy);
+        if velocity.length() < MIN_FLING_VELOCITY {
+          self.settle_to(self.clamp_pos(self.scroll_pos), at);
+        } else {
+          self.motion = Some(ScrollMotion::Fling { velocity, last: at });
+        }
+      }
+      Some(ScrollMotion::Animate { from, to, start, transition }) => {
+        match transition.rate_of_change(at.saturating_duration_since(start)) {
+          AnimateProgress::Dismissed => self.scroll_pos = from,
+          AnimateProgress::Between(rate) => self.scroll_pos = from.lerp(to, rate),
+          AnimateProgress::Finish => {
+            self.scroll_pos = to;
+            self.sync_pos();
+            return;
+          }
+        }
+        self.motion = Some(ScrollMotion::Animate { from, to, start, transition });
+      }
+      None => {}
+    }
+  }
+}
+
+/// The scroll position of a view with the `page` size to show the range
+/// `start..end` of the content.
+fn align_pos(current: f32, page: f32, start: f32, end: f32, align: ScrollAlign) -> f32 {
+  match align {
+    ScrollAlign::Start => start,
+    ScrollAlign::Center => (start + end - page) / 2.,
+    ScrollAlign::End => end - page,
+    ScrollAlign::Nearest => {
+      let visible = start >= current && end <= current + page;
+      let covered = start <= current && end >= current + page;
+      if visible || covered {
+        current
+      } else if start < current || end - start > page {
+        start
+      } else {
+        end - page
+      }
+    }
+  }
+}
+
+impl Default for ScrollPhysics {
+  fn default() -> Self {
+    Self {
+      friction: 2.,
+      overscroll: false,
+      max_overscroll: 120.,
+      settle_duration: Duration::from_millis(300),
+    }
+  }
+}
+
+impl VelocityTracker {
+  fn clear(&mut self) { self.samples.clear(); }
+
+  fn add(&mut self, delta: Vector, at: Instant) {
+    self
+      .samples
+      .retain(|(t, _)| at.saturating_duration_since(*t) <= VELOCITY_WINDOW);
+    self.samples.push((at, delta));
+  }
+
+  /// The velocity in pixels per second at the time `at`.
+  fn velocity(&self, at: Instant) -> Vector {
+    let recent: Vec<_> = self
+      .samples
+      .iter()
+      .filter(|(t, _)| at.saturating_duration_since(*t) <= VELOCITY_WINDOW)
+      .collect();
+    let (Some((first, _)), Some((last, _))) = (recent.first(), recent.last()) else {
+      return Vector::zero();
+    };
+    let span = last
+      .saturating_duration_since(*first)
+      .as_secs_f32();
+    if span <= 0. {
+      return Vector::zero();
+    }
+    let distance = recent
+      .iter()
+      .skip(1)
+      .fold(Vector::zero(), |acc, (_, d)| acc + *d);
+    distance / span
+  }
 }
 
 #[cfg(test)]
@@ -142,7 +656,7 @@ mod tests {
   use super::*;
   use crate::{
     reset_test_env,
-    test_helper::{MockBox, TestWindow},
+    test_helper::{MockBox, MockMulti, MockStack, TestWindow},
   };
 
   fn test_assert(scrollable: Scrollable, delta_x: f32, delta_y: f32, expect_x: f32, expect_y: f32) {
@@ -196,6 +710,338 @@ mod tests {
     test_assert(Scrollable::Both, 100., 100., 0., 0.);
   }
 
+  fn scroll_view(scrollable: Scrollable) -> ScrollableWidget {
+    let mut view = ScrollableWidget { scrollable, ..Default::default() };
+    view.set_content_size(Size::new(100., 1000.));
+    view.set_page(Size::new(100., 100.));
+    view
+  }
+
+  fn run_motion(view: &mut ScrollableWidget, start: Instant) -> Instant {
+    let mut at = start;
+    while view.is_moving() {
+      at += Duration::from_millis(16);
+      view.advance_to(at);
+    }
+    at
+  }
+
+  #[test]
+  fn fling_decay() {
+    let mut view = scroll_view(Scrollable::Y);
+    let start = Instant::now();
+    view.release(Vector::new(500., 1000.), start);
+
+    view.advance_to(start + Duration::from_millis(100));
+    let pos = view.get_scroll_pos();
+    assert_eq!(pos.x, 0.);
+    assert!(pos.y > 90. && pos.y < 100.);
+
+    run_motion(&mut view, start);
+    // The fling travels `velocity / friction` before it stops.
+    let y = view.get_scroll_pos().y;
+    assert!(y > 480. && y < 500., "{y}");
+  }
+
+  #[test]
+  fn fling_stops_at_edge() {
+    let mut view = scroll_view(Scrollable::Y);
+    view.jump_to(Point::new(0., 800.));
+    let start = Instant::now();
+    view.release(Vector::new(0., 2000.), start);
+    run_motion(&mut view, start);
+    assert_eq!(view.get_scroll_pos(), Point::new(0., 900.));
+  }
+
+  #[test]
+  fn elastic_overscroll() {
+    let mut view = scroll_view(Scrollable::Y);
+    view.physics.overscroll = true;
+    let start = Instant::now();
+    view.drag_start(Point::new(50., 0.), start);
+    view.drag_move(Point::new(50., 100.), start + Duration::from_millis(10));
+    let y = view.get_scroll_pos().y;
+    assert!(y < 0. && y > -100., "{y}");
+
+    // Released after holding still, the content bounces back.
+    view.drag_end(start + Duration::from_millis(500));
+    assert!(view.is_moving());
+    run_motion(&mut view, start + Duration::from_millis(500));
+    assert_eq!(view.get_scroll_pos(), Point::zero());
+
+    let mut view = scroll_view(Scrollable::Y);
+    view.drag_start(Point::new(50., 0.), start);
+    view.drag_move(Point::new(50., 100.), start + Duration::from_millis(10));
+    assert_eq!(view.get_scroll_pos(), Point::zero());
+  }
+
+  #[test]
+  fn drag_to_fling() {
+    let mut view = scroll_view(Scrollable::Y);
+    let start = Instant::now();
+    view.drag_start(Point::new(50., 100.), start);
+    view.drag_move(Point::new(50., 80.), start + Duration::from_millis(10));
+    view.drag_move(Point::new(50., 60.), start + Duration::from_millis(20));
+    assert_eq!(view.get_scroll_pos(), Point::new(0., 40.));
+
+    view.drag_end(start + Duration::from_millis(20));
+    assert!(view.is_moving());
+    run_motion(&mut view, start + Duration::from_millis(20));
+    assert!(view.get_scroll_pos().y > 500.);
+  }
+
+  #[test]
+  fn fling_by_touchpad_only() {
+    let start = Instant::now();
+    let at = |ms| start + Duration::from_millis(ms);
+
+    // The notches of a mouse wheel.
+    let mut view = scroll_view(Scrollable::Y);
+    view.wheel(Vector::new(0., 20.), TouchPhase::Moved, at(0));
+    view.wheel(Vector::new(0., 20.), TouchPhase::Moved, at(10));
+    view.advance_to(at(10) + WHEEL_IDLE);
+    assert!(!view.is_moving());
+    assert_eq!(view.get_scroll_pos(), Point::new(0., 40.));
+
+    // A touchpad swipe.
+    let mut view = scroll_view(Scrollable::Y);
+    view.wheel(Vector::new(0., 20.), TouchPhase::Started, at(0));
+    view.wheel(Vector::new(0., 20.), TouchPhase::Moved, at(10));
+    view.wheel(Vector::new(0., 20.), TouchPhase::Moved, at(20));
+    view.wheel(Vector::zero(), TouchPhase::Ended, at(20));
+    assert!(view.is_moving());
+    run_motion(&mut view, at(20));
+    assert!(view.get_scroll_pos().y > 500.);
+
+    // A canceled swipe stays where it is.
+    let mut view = scroll_view(Scrollable::Y);
+    view.wheel(Vector::new(0., 20.), TouchPhase::Started, at(0));
+    view.wheel(Vector::new(0., 20.), TouchPhase::Moved, at(10));
+    view.wheel(Vector::zero(), TouchPhase::Cancelled, at(10));
+    assert!(!view.is_moving());
+    assert_eq!(view.get_scroll_pos(), Point::new(0., 40.));
+  }
+
+  #[test]
+  fn animate_to_pos() {
+    let mut view = scroll_view(Scrollable::Y);
+    let start = Instant::now();
+    let transition =
+      EasingTransition { duration: Duration::from_millis(100), easing: easing::LINEAR };
+    view.start_animate(Point::new(0., 500.), transition.box_it(), start);
+
+    view.advance_to(start + Duration::from_millis(50));
+    assert_eq!(view.get_scroll_pos(), Point::new(0., 250.));
+    view.advance_to(start + Duration::from_millis(120));
+    assert_eq!(view.get_scroll_pos(), Point::new(0., 500.));
+    assert!(!view.is_moving());
+  }
+
+  #[test]
+  fn snap_to_page() {
+    let mut view = scroll_view(Scrollable::Y);
+    view.snap = ScrollSnap::Page;
+    view.jump_to(Point::new(0., 130.));
+    let start = Instant::now();
+    view.release(Vector::zero(), start);
+    run_motion(&mut view, start);
+    assert_eq!(view.get_scroll_pos(), Point::new(0., 100.));
+
+    // A fast fling moves at most one page.
+    view.release(Vector::new(0., 5000.), start);
+    run_motion(&mut view, start);
+    assert_eq!(view.get_scroll_pos(), Point::new(0., 200.));
+
+    view.snap = ScrollSnap::Points(vec![Point::new(0., 120.), Point::new(0., 700.)]);
+    view.release(Vector::new(0., 600.), start);
+    run_motion(&mut view, start);
+    assert_eq!(view.get_scroll_pos(), Point::new(0., 700.));
+  }
+
+  #[test]
+  fn animate_in_window() {
+    reset_test_env!();
+
+    let trigger = Stateful::new(false);
+    let c_trigger = trigger.clone_writer();
+    let w = fn_widget! {
+      let mut mock_box = @MockBox {
+        size: Size::new(1000., 1000.),
+        scrollable: Scrollable::Y,
+      };
+      let scrollable = mock_box.get_scrollable_widget();
+      watch!(*$trigger)
+        .filter(|v| *v)
+        .subscribe(move |_| {
+          let transition = EasingTransition { duration: Duration::from_millis(1), easing: easing::LINEAR };
+          $scrollable.write().animate_to(Point::new(0., 500.), transition);
+        });
+      mock_box
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(100., 100.));
+    wnd.draw_frame();
+    *c_trigger.write() = true;
+    wnd.draw_frame();
+    std::thread::sleep(Duration::from_millis(2));
+    wnd.draw_frame();
+    wnd.draw_frame();
+    let pos = wnd.layout_info_by_path(&[0, 0, 0]).unwrap().pos;
+    assert_eq!(pos, Point::new(0., -500.));
+    assert!(!wnd.need_draw());
+  }
+
+  #[test]
+  fn scroll_nested_into_view() {
+    reset_test_env!();
+
+    let target = Rc::new(Cell::new(None));
+    let c_target = target.clone();
+    let w = fn_widget! {
+      let c_target = c_target.clone();
+      @MockMulti {
+        scrollable: Scrollable::X,
+        @MockBox { size: Size::new(100., 100.) }
+        @MockBox { size: Size::new(100., 100.) }
+        @MockBox {
+          size: Size::new(100., 100.),
+          @MockStack {
+            scrollable: Scrollable::Y,
+            @MockBox { size: Size::new(100., 1000.) }
+            @MockBox {
+              size: Size::new(100., 20.),
+              anchor: Anchor::left_top(0., 500.),
+              on_mounted: move |e| c_target.set(Some(e.current_target())),
+            }
+          }
+        }
+      }
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(100., 100.));
+    wnd.draw_frame();
+    let id = target.get().unwrap();
+    ScrollableWidget::scroll_into_view(&wnd, id, ScrollAlign::Nearest);
+    wnd.draw_frame();
+    assert_eq!(wnd.map_to_global(Point::zero(), id), Point::new(0., 80.));
+
+    ScrollableWidget::scroll_into_view(&wnd, id, ScrollAlign::Start);
+    wnd.draw_frame();
+    assert_eq!(wnd.map_to_global(Point::zero(), id), Point::new(0., 0.));
+
+    ScrollableWidget::scroll_into_view(&wnd, id, ScrollAlign::Center);
+    wnd.draw_frame();
+    assert_eq!(wnd.map_to_global(Point::zero(), id), Point::new(0., 40.));
+  }
+
+  #[test]
+  fn scroll_focus_into_view() {
+    reset_test_env!();
+
+    let w = fn_widget! {
+      @MockStack {
+        scrollable: Scrollable::Y,
+        @MockBox { size: Size::new(100., 1000.) }
+        @MockBox {
+          size: Size::new(100., 20.),
+          anchor: Anchor::left_top(0., 500.),
+          tab_index: 0i16,
+        }
+      }
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(100., 100.));
+    wnd.draw_frame();
+    wnd
+      .focus_mgr
+      .borrow_mut()
+      .focus_next_widget(wnd.tree());
+    wnd.draw_frame();
+    let focused = wnd.focus_mgr.borrow().focusing().unwrap();
+    assert_eq!(wnd.map_to_global(Point::zero(), focused).y, 80.);
+  }
+
+  fn wheel(wnd: &mut TestWindow, delta_x: f32, delta_y: f32) {
+    let device_id = unsafe { DeviceId::dummy() };
+    #[allow(deprecated)]
+    wnd.processes_native_event(WindowEvent::MouseWheel {
+      device_id,
+      delta: MouseScrollDelta::PixelDelta((delta_x, delta_y).into()),
+      phase: TouchPhase::Moved,
+    });
+    wnd.draw_frame();
+  }
+
+  fn nested_scroll(
+    inner_dir: Scrollable, axis_lock: bool,
+  ) -> (TestWindow, Stateful<(Point, Point)>) {
+    let pos = Stateful::new((Point::zero(), Point::zero()));
+    let c_pos = pos.clone_writer();
+    let w = fn_widget! {
+      let inner_size = match inner_dir {
+        Scrollable::X => Size::new(300., 100.),
+        _ => Size::new(100., 300.),
+      };
+      let mut inner = @MockBox { size: inner_size, scrollable: inner_dir };
+      let inner_view = inner.get_scrollable_widget();
+      $inner_view.write().axis_lock = axis_lock;
+      let mut outer = @MockBox { size: Size::new(100., 1000.), scrollable: Scrollable::Y };
+      let outer_view = outer.get_scrollable_widget();
+      watch!({
+        let (inner, outer) = ($inner_view, $outer_view);
+        (inner.get_scroll_pos(), outer.get_scroll_pos())
+      })
+      .subscribe(move |v| *$c_pos.write() = v);
+
+      @ $outer {
+        @MockBox {
+          size: Size::new(100., 100.),
+          @ { inner }
+        }
+      }
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(100., 100.));
+    wnd.draw_frame();
+    (wnd, pos)
+  }
+
+  #[test]
+  fn chain_scroll_to_outer() {
+    reset_test_env!();
+
+    let (mut wnd, pos) = nested_scroll(Scrollable::Y, false);
+    wheel(&mut wnd, 0., -250.);
+    assert_eq!(*pos.read(), (Point::new(0., 200.), Point::new(0., 50.)));
+
+    // The inner view consumes all the delta it can.
+    wheel(&mut wnd, 0., 100.);
+    assert_eq!(*pos.read(), (Point::new(0., 100.), Point::new(0., 50.)));
+
+    let (mut wnd, pos) = nested_scroll(Scrollable::X, false);
+    wheel(&mut wnd, -30., -40.);
+    assert_eq!(*pos.read(), (Point::new(30., 0.), Point::new(0., 40.)));
+  }
+
+  #[test]
+  fn lock_wheel_axis() {
+    reset_test_env!();
+
+    let (mut wnd, pos) = nested_scroll(Scrollable::X, true);
+    wheel(&mut wnd, -30., -5.);
+    assert_eq!(*pos.read(), (Point::new(30., 0.), Point::zero()));
+    wheel(&mut wnd, 0., -40.);
+    assert_eq!(*pos.read(), (Point::new(30., 0.), Point::zero()));
+
+    // A new gesture starts with the vertical axis, it goes to the outer view.
+    wnd
+      .0
+      .frame_ticker
+      .emit(crate::ticker::FrameMsg::NewFrame(Instant::now() + WHEEL_IDLE));
+    wheel(&mut wnd, -5., -40.);
+    assert_eq!(*pos.read(), (Point::new(30., 0.), Point::new(0., 40.)));
+  }
+
   #[derive(SingleChild, Declare, Clone)]
   pub struct FixedBox {
     pub size: Size,
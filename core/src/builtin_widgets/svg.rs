@@ -4,6 +4,11 @@ impl Render for Svg {
   #[inline]
   fn perform_layout(&self, clamp: BoxClamp, _: &mut LayoutCtx) -> Size { clamp.clamp(self.size) }
 
+  #[inline]
+  fn intrinsic_size(&self, kind: IntrinsicSize, _: f32, _: &mut LayoutCtx) -> f32 {
+    kind.axis_of(self.size)
+  }
+
   fn paint(&self, ctx: &mut PaintingCtx) {
     let painter = ctx.painter();
     painter.draw_svg(self);
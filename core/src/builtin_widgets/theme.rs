@@ -242,7 +242,17 @@ fn typography_theme() -> TypographyTheme {
     };
     let overflow = Overflow::Clip;
     TextTheme {
-      text: TextStyle { line_height, font_size, letter_space, font_face, overflow },
+      text: TextStyle {
+        line_height,
+        font_size,
+        letter_space,
+        font_face,
+        overflow,
+        writing_mode: WritingMode::HorizontalTb,
+        tab_size: TabSize::Spaces(8),
+        tab_stops: Box::new([]),
+        color_palette: 0,
+      },
       decoration: TextDecorationStyle {
         decoration: TextDecoration::NONE,
         decoration_color: Color::BLACK.with_alpha(0.87).into(),
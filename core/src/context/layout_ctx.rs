@@ -2,7 +2,7 @@ use ribir_geom::{Point, Size};
 
 use super::{WidgetCtx, WidgetCtxImpl};
 use crate::{
-  widget::{BoxClamp, WidgetTree},
+  widget::{Baselines, BoxClamp, IntrinsicSize, WidgetTree},
   widget_tree::WidgetId,
   window::DelayEvent,
 };
@@ -41,9 +41,9 @@ impl<'a> LayoutCtx<'a> {
         // or modify it during perform layout.
         let tree2 = unsafe { &mut *(self.tree as *mut WidgetTree) };
         let mut ctx = LayoutCtx { id: child, tree: tree2 };
-        let size = child
-          .assert_get(self.tree)
-          .perform_layout(clamp, &mut ctx);
+        let render = child.assert_get(self.tree);
+        let size = render.perform_layout(clamp, &mut ctx);
+        let baselines = render.baselines(&ctx);
 
         self
           .window()
@@ -53,6 +53,7 @@ impl<'a> LayoutCtx<'a> {
         let size = clamp.clamp(size);
         info.clamp = clamp;
         info.size = Some(size);
+        info.baselines = baselines;
 
         size
       });
@@ -90,6 +91,67 @@ impl<'a> LayoutCtx<'a> {
     self.tree.store.layout_info_or_default(child).size = Some(size);
   }
 
+  /// Return the baselines of the `child` relative to its top, it's available
+  /// after the child has been laid out.
+  pub fn child_baselines(&self, child: WidgetId) -> Option<Baselines> {
+    self
+      .tree
+      .store
+      .layout_info(child)
+      .and_then(|info| info.baselines)
+  }
+
+  /// Return the baselines of the only child relative to this widget, or `None`
+  /// if this widget has no child or more than one child.
+  pub fn single_child_baselines(&self) -> Option<Baselines> {
+    let child = self.id.first_child(self.tree)?;
+    if self.id.last_child(self.tree) != Some(child) {
+      return None;
+    }
+    let pos = self.widget_box_pos(child).unwrap_or_default();
+    self
+      .child_baselines(child)
+      .map(|b| b.offset(pos.y))
+  }
+
+  /// Return the intrinsic size of the `kind` of the `child` for the given
+  /// `extent` of the other axis, without laying out the child. The result is
+  /// cached until the next layout pass.
+  pub fn child_intrinsic_size(&mut self, child: WidgetId, kind: IntrinsicSize, extent: f32) -> f32 {
+    if let Some(size) = self
+      .tree
+      .store
+      .intrinsic_size(child, kind, extent)
+    {
+      return size;
+    }
+    // Safety: the `tree` just use to get the widget of `id`, and `tree2` not drop
+    // or modify it during querying the intrinsic size.
+    let tree2 = unsafe { &mut *(self.tree as *mut WidgetTree) };
+    let mut ctx = LayoutCtx { id: child, tree: tree2 };
+    let size = child
+      .assert_get(self.tree)
+      .intrinsic_size(kind, extent, &mut ctx);
+    self
+      .tree
+      .store
+      .cache_intrinsic_size(child, kind, extent, size);
+    size
+  }
+
+  /// Return the intrinsic size of the only child, or zero if this widget has
+  /// no child or more than one child.
+  pub fn single_child_intrinsic_size(&mut self, kind: IntrinsicSize, extent: f32) -> f32 {
+    let Some(child) = self.id.first_child(self.tree) else { return 0. };
+    if self.id.last_child(self.tree) != Some(child) {
+      return 0.;
+    }
+    self.child_intrinsic_size(child, kind, extent)
+  }
+
+  /// Return an iterator of the children of this widget.
+  pub fn children(&self) -> impl Iterator<Item = WidgetId> + '_ { self.id.children(self.tree) }
+
   /// Split a children iterator from the context, returning a tuple of `&mut
   /// LayoutCtx` and the iterator of the children.
   pub fn split_children(&mut self) -> (&mut Self, impl Iterator<Item = WidgetId> + '_) {
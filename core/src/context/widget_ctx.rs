@@ -34,6 +34,8 @@ pub trait WidgetCtx {
   fn has_child(&self) -> bool { self.first_child().is_some() }
   /// Return the first child of widget.
   fn first_child(&self) -> Option<WidgetId>;
+  /// Return an iterator of the widget and its descendants in the tree order.
+  fn descendants(&self) -> impl Iterator<Item = WidgetId> + '_;
   /// Return the box rect of the single child of widget.
   /// # Panic
   /// panic if widget have multi child.
@@ -120,6 +122,10 @@ impl<T: WidgetCtxImpl> WidgetCtx for T {
   #[inline]
   fn first_child(&self) -> Option<WidgetId> { self.id().first_child(self.tree()) }
 
+  fn descendants(&self) -> impl Iterator<Item = WidgetId> + '_ {
+    self.id().descendants(self.tree())
+  }
+
   #[inline]
   fn box_rect(&self) -> Option<Rect> { self.widget_box_rect(self.id()) }
 
@@ -1,4 +1,6 @@
-use winit::event::{DeviceId, ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::event::{
+  DeviceId, ElementState, MouseButton, MouseScrollDelta, Touch, TouchPhase, WindowEvent,
+};
 
 use crate::{
   prelude::*,
@@ -10,11 +12,18 @@ pub(crate) struct Dispatcher {
   pub(crate) info: DispatchInfo,
   pub(crate) entered_widgets: Vec<WidgetId>,
   pub(crate) pointer_down_uid: Option<WidgetId>,
+  pub(crate) pointer_capture: Option<WidgetId>,
 }
 
 impl Dispatcher {
   pub fn new(wnd_id: WindowId) -> Self {
-    Self { wnd_id, info: <_>::default(), entered_widgets: vec![], pointer_down_uid: None }
+    Self {
+      wnd_id,
+      info: <_>::default(),
+      entered_widgets: vec![],
+      pointer_down_uid: None,
+      pointer_capture: None,
+    }
   }
 
   fn window(&self) -> Sc<Window> {
@@ -40,7 +49,25 @@ impl Dispatcher {
         self.cursor_move_to(Point::new(pos.x, pos.y))
       }
       WindowEvent::CursorLeft { .. } => self.on_cursor_left(),
-      WindowEvent::MouseWheel { delta, .. } => self.dispatch_wheel(delta, wnd_factor),
+      WindowEvent::MouseWheel { delta, phase, .. } => self.dispatch_wheel(delta, phase, wnd_factor),
+      WindowEvent::Touch(Touch { device_id, phase, location, .. }) => {
+        if phase == TouchPhase::Cancelled {
+          self.cancel_pointer();
+          return;
+        }
+        let pos = location.to_logical::<f32>(wnd_factor);
+        self.cursor_move_to(Point::new(pos.x, pos.y));
+        // A touch is dispatched as the primary button of a mouse.
+        match phase {
+          TouchPhase::Started => {
+            self.dispatch_mouse_input(device_id, ElementState::Pressed, MouseButton::Left)
+          }
+          TouchPhase::Ended => {
+            self.dispatch_mouse_input(device_id, ElementState::Released, MouseButton::Left)
+          }
+          TouchPhase::Moved | TouchPhase::Cancelled => {}
+        }
+      }
       _ => log::info!("not processed event {:?}", event),
     }
   }
@@ -80,10 +107,13 @@ impl Dispatcher {
   pub fn cursor_move_to(&mut self, position: Point) {
     self.info.cursor_pos = position;
     self.pointer_enter_leave_dispatch();
-    if let Some(hit) = self.hit_widget() {
+    if let Some(target) = self
+      .capture_widget()
+      .or_else(|| self.hit_widget())
+    {
       self
         .window()
-        .add_delay_event(DelayEvent::PointerMove(hit));
+        .add_delay_event(DelayEvent::PointerMove(target));
     }
   }
 
@@ -111,8 +141,12 @@ impl Dispatcher {
           if self.info.mouse_button.1.is_empty() {
             self.info.mouse_button.0 = None;
             let wnd = self.window();
+            // The pointer is released from the capture widget after the last button up,
+            // and the up and tap events are dispatched as if it hit the capture widget.
+            let capture = self.capture_widget();
+            self.pointer_capture = None;
             let mut dispatch = |tree: &WidgetTree| {
-              let hit = self.hit_widget()?;
+              let hit = capture.or_else(|| self.hit_widget())?;
               wnd.add_delay_event(DelayEvent::PointerUp(hit));
 
               let tap_on = self
@@ -130,7 +164,28 @@ impl Dispatcher {
     }
   }
 
-  pub fn dispatch_wheel(&mut self, delta: MouseScrollDelta, wnd_factor: f64) {
+  /// Cancel the pressed pointer, the widget that captures the pointer, or was
+  /// pressed, receives a `PointerCancel` event instead of the up and tap
+  /// events, and the pointer capture is released.
+  pub fn cancel_pointer(&mut self) {
+    if self.info.mouse_button.0.take().is_none() {
+      return;
+    }
+    self.info.mouse_button.1 = MouseButtons::empty();
+    let wnd = self.window();
+    let target = self.capture_widget().or_else(|| {
+      self
+        .pointer_down_uid
+        .filter(|id| !id.is_dropped(wnd.tree()))
+    });
+    self.pointer_capture = None;
+    self.pointer_down_uid = None;
+    if let Some(target) = target {
+      wnd.add_delay_event(DelayEvent::PointerCancel(target));
+    }
+  }
+
+  pub fn dispatch_wheel(&mut self, delta: MouseScrollDelta, phase: TouchPhase, wnd_factor: f64) {
     if let Some(wid) = self.hit_widget() {
       let (delta_x, delta_y) = match delta {
         MouseScrollDelta::LineDelta(x, y) => (x * 16., y * 16.),
@@ -142,7 +197,7 @@ impl Dispatcher {
 
       self
         .window()
-        .add_delay_event(DelayEvent::Wheel { id: wid, delta_x, delta_y });
+        .add_delay_event(DelayEvent::Wheel { id: wid, delta_x, delta_y, phase });
     }
   }
 
@@ -168,6 +223,13 @@ impl Dispatcher {
     }
   }
 
+  fn capture_widget(&self) -> Option<WidgetId> {
+    let wnd = self.window();
+    self
+      .pointer_capture
+      .filter(|id| !id.is_dropped(wnd.tree()))
+  }
+
   fn pointer_enter_leave_dispatch(&mut self) {
     let new_hit = self.hit_widget();
     let wnd = self.window();
@@ -719,4 +781,107 @@ mod tests {
     assert!(expect_hit.read().is_some());
     assert_eq!(dispatcher.hit_widget(), *expect_hit.read());
   }
+
+  #[test]
+  fn pointer_capture() {
+    reset_test_env!();
+
+    let records = Stateful::new(vec![]);
+    let w_records = records.clone_writer();
+    let w = fn_widget! {
+      @MockMulti {
+        @MockBox {
+          size: Size::new(50., 50.),
+          on_pointer_down: move |e| e.window().set_pointer_capture(e.current_target()),
+          on_pointer_move: move |_| $w_records.write().push("first move"),
+          on_pointer_up: move |_| $w_records.write().push("first up"),
+        }
+        @MockBox {
+          size: Size::new(50., 50.),
+          on_pointer_move: move |_| $w_records.write().push("second move"),
+          on_pointer_up: move |_| $w_records.write().push("second up"),
+        }
+      }
+    };
+    let mut wnd = TestWindow::new(w);
+    wnd.draw_frame();
+
+    let device_id = unsafe { DeviceId::dummy() };
+    #[allow(deprecated)]
+    wnd.processes_native_event(WindowEvent::CursorMoved { device_id, position: (10., 10.).into() });
+    wnd.process_mouse_input(device_id, ElementState::Pressed, MouseButton::Left);
+    wnd.run_frame_tasks();
+    assert!(wnd.pointer_capture().is_some());
+    records.write().clear();
+
+    #[allow(deprecated)]
+    wnd.processes_native_event(WindowEvent::CursorMoved { device_id, position: (60., 10.).into() });
+    wnd.process_mouse_input(device_id, ElementState::Released, MouseButton::Left);
+    wnd.run_frame_tasks();
+    assert_eq!(*records.read(), ["first move", "first up"]);
+    assert!(wnd.pointer_capture().is_none());
+    records.write().clear();
+
+    #[allow(deprecated)]
+    wnd.processes_native_event(WindowEvent::CursorMoved { device_id, position: (70., 10.).into() });
+    wnd.run_frame_tasks();
+    assert_eq!(*records.read(), ["second move"]);
+  }
+
+  #[test]
+  fn touch_release_and_cancel_pointer_capture() {
+    reset_test_env!();
+
+    let records = Stateful::new(vec![]);
+    let w_records = records.clone_writer();
+    let w = fn_widget! {
+      @MockBox {
+        size: Size::new(50., 50.),
+        on_pointer_down: move |e| e.window().set_pointer_capture(e.current_target()),
+        on_pointer_up: move |_| $w_records.write().push("up"),
+        on_pointer_cancel: move |_| $w_records.write().push("cancel"),
+      }
+    };
+    let mut wnd = TestWindow::new(w);
+    wnd.draw_frame();
+
+    let touch = |phase| {
+      WindowEvent::Touch(Touch {
+        device_id: unsafe { DeviceId::dummy() },
+        phase,
+        location: (10., 10.).into(),
+        force: None,
+        id: 0,
+      })
+    };
+
+    #[allow(deprecated)]
+    wnd.processes_native_event(touch(TouchPhase::Started));
+    wnd.run_frame_tasks();
+    assert!(wnd.pointer_capture().is_some());
+    #[allow(deprecated)]
+    wnd.processes_native_event(touch(TouchPhase::Ended));
+    wnd.run_frame_tasks();
+    assert!(wnd.pointer_capture().is_none());
+    assert_eq!(*records.read(), ["up"]);
+    records.write().clear();
+
+    #[allow(deprecated)]
+    wnd.processes_native_event(touch(TouchPhase::Started));
+    wnd.run_frame_tasks();
+    assert!(wnd.pointer_capture().is_some());
+    #[allow(deprecated)]
+    wnd.processes_native_event(touch(TouchPhase::Cancelled));
+    wnd.run_frame_tasks();
+    assert!(wnd.pointer_capture().is_none());
+    assert_eq!(*records.read(), ["cancel"]);
+
+    // The pointer is canceled, so the next touch is pressed again.
+    #[allow(deprecated)]
+    wnd.processes_native_event(touch(TouchPhase::Started));
+    wnd.run_frame_tasks();
+    assert!(wnd.pointer_capture().is_some());
+    wnd.process_pointer_cancel();
+    assert!(wnd.pointer_capture().is_none());
+  }
 }
@@ -543,6 +543,11 @@ impl FocusManager {
 
     self.focus_widgets = node.map_or(vec![], |wid| wid.ancestors(tree).collect::<Vec<_>>());
     self.focusing = node;
+
+    // Reveal the new focused widget if it's scrolled out of the view.
+    if let Some(new) = node {
+      ScrollableWidget::scroll_into_view(&wnd, new, ScrollAlign::Nearest);
+    }
     old
   }
 }
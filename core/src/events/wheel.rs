@@ -1,9 +1,14 @@
+pub use winit::event::TouchPhase;
+
 use crate::{impl_common_event_deref, prelude::*};
 
 #[derive(Debug)]
 pub struct WheelEvent {
   pub delta_x: f32,
   pub delta_y: f32,
+  /// The phase of the touchpad gesture. A mouse wheel has no gesture, its
+  /// notches are all `TouchPhase::Moved`.
+  pub phase: TouchPhase,
   pub common: CommonEvent,
 }
 
@@ -11,8 +16,8 @@ impl_common_event_deref!(WheelEvent);
 
 impl WheelEvent {
   #[inline]
-  pub fn new(delta_x: f32, delta_y: f32, id: WidgetId, wnd: &Window) -> Self {
-    Self { delta_x, delta_y, common: CommonEvent::new(id, wnd.tree) }
+  pub fn new(delta_x: f32, delta_y: f32, phase: TouchPhase, id: WidgetId, wnd: &Window) -> Self {
+    Self { delta_x, delta_y, phase, common: CommonEvent::new(id, wnd.tree) }
   }
 }
 
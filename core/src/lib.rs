@@ -14,6 +14,7 @@ pub mod local_sender;
 pub mod pipe;
 pub(crate) mod render_helper;
 mod state;
+pub mod text_measurer;
 pub mod ticker;
 pub mod timer;
 pub mod widget;
@@ -58,13 +59,14 @@ pub mod prelude {
   #[doc(no_inline)]
   pub use crate::widget_children::*;
   #[doc(no_inline)]
-  pub use crate::widget_tree::{BoxClamp, LayoutInfo, WidgetId};
+  pub use crate::widget_tree::{Baselines, BoxClamp, IntrinsicSize, LayoutInfo, WidgetId};
   #[doc(no_inline)]
   pub use crate::window::Window;
   pub use crate::{
     animation::*,
     class_names, multi_class_impl, providers,
     query::*,
+    text_measurer::TextMeasurer,
     ticker::{Duration, Instant},
   };
 }
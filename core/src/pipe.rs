@@ -840,6 +840,15 @@ impl Render for PipeNode {
   }
 
   fn get_transform(&self) -> Option<Transform> { self.as_ref().data.get_transform() }
+
+  fn baselines(&self, ctx: &LayoutCtx) -> Option<Baselines> { self.as_ref().data.baselines(ctx) }
+
+  fn intrinsic_size(&self, kind: IntrinsicSize, extent: f32, ctx: &mut LayoutCtx) -> f32 {
+    self
+      .as_ref()
+      .data
+      .intrinsic_size(kind, extent, ctx)
+  }
 }
 
 #[derive(Clone)]
@@ -45,6 +45,14 @@ where
 
   #[inline]
   fn get_transform(&self) -> Option<Transform> { self.proxy().get_transform() }
+
+  #[inline]
+  fn baselines(&self, ctx: &LayoutCtx) -> Option<Baselines> { self.proxy().baselines(ctx) }
+
+  #[inline]
+  fn intrinsic_size(&self, kind: IntrinsicSize, extent: f32, ctx: &mut LayoutCtx) -> f32 {
+    self.proxy().intrinsic_size(kind, extent, ctx)
+  }
 }
 
 impl<R: Render> RenderProxy for RefCell<R> {
@@ -65,6 +73,10 @@ impl Render for Resource<Path> {
     clamp.clamp(size)
   }
 
+  fn intrinsic_size(&self, kind: IntrinsicSize, _: f32, _: &mut LayoutCtx) -> f32 {
+    kind.axis_of(self.bounds().max().to_vector().to_size())
+  }
+
   #[inline]
   fn only_sized_by_parent(&self) -> bool { true }
 
@@ -90,6 +90,17 @@ impl TestWindow {
       .take()
   }
 
+  /// The last area set by `Window::set_ime_cursor_area`.
+  pub fn ime_cursor_area(&self) -> Option<Rect> {
+    self
+      .shell_wnd()
+      .borrow()
+      .as_any()
+      .downcast_ref::<TestShellWindow>()
+      .unwrap()
+      .ime_cursor_area
+  }
+
   pub fn content_count(&self) -> usize {
     let tree = self.0.tree();
     let root = tree.root();
@@ -120,6 +131,7 @@ pub struct TestShellWindow {
   pub id: WindowId,
   pub surface_color: Color,
   pub last_frame: Option<Frame>,
+  pub ime_cursor_area: Option<Rect>,
 }
 
 impl ShellWindow for TestShellWindow {
@@ -144,7 +156,7 @@ impl ShellWindow for TestShellWindow {
 
   fn set_icon(&mut self, _: &PixelImage) {}
 
-  fn set_ime_cursor_area(&mut self, _: &Rect) {}
+  fn set_ime_cursor_area(&mut self, rect: &Rect) { self.ime_cursor_area = Some(*rect); }
 
   fn set_visible(&mut self, _: bool) {}
 
@@ -192,6 +204,7 @@ impl TestShellWindow {
       id: ID.fetch_add(1, Ordering::Relaxed).into(),
       last_frame: None,
       surface_color: Color::WHITE,
+      ime_cursor_area: None,
     }
   }
 }
@@ -0,0 +1,84 @@
+use ribir_algo::Substr;
+use ribir_geom::Size;
+use ribir_painter::{TextAlign, TextMetrics, TextStyle, VisualGlyphs};
+
+use crate::context::AppCtx;
+
+/// Measure text out of the layout of widgets, for example, to compute the
+/// width of a column or the height of an item in a virtual list.
+///
+/// It only depends on the font database of the application and works without
+/// a window, and shares the typography cache with the text widgets, so the
+/// text measured in a frame is not laid out again by them.
+///
+/// # Example
+///
+/// ```
+/// use ribir_core::prelude::*;
+///
+/// let metrics = TextMeasurer::measure("Hello world!", &TextStyle::default(), 100.);
+/// assert_eq!(metrics.line_count(), 1);
+/// ```
+pub struct TextMeasurer;
+
+impl TextMeasurer {
+  /// Measure the `text` with the `style`.
+  ///
+  /// The lines wrap at `max_width` if the `overflow` of the `style` is
+  /// `Overflow::AutoWrap`, for vertical text, it's the max height of the
+  /// lines.
+  pub fn measure(text: impl Into<Substr>, style: &TextStyle, max_width: f32) -> TextMetrics {
+    Self::glyphs(text, style, max_width).metrics()
+  }
+
+  /// Lay out the `text` like `measure` and return the glyphs, it's helpful
+  /// when you need more than the metrics, like the hit-testing of the text.
+  pub fn glyphs(text: impl Into<Substr>, style: &TextStyle, max_width: f32) -> VisualGlyphs {
+    let bounds = if style.writing_mode.is_vertical() {
+      Size::new(f32::MAX, max_width)
+    } else {
+      Size::new(max_width, f32::MAX)
+    };
+    AppCtx::typography_store()
+      .borrow_mut()
+      .typography(text.into(), style, bounds, TextAlign::Start, style.writing_mode.line_dir())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{prelude::*, reset_test_env};
+
+  #[test]
+  fn measure_without_window() {
+    reset_test_env!();
+
+    let path = env!("CARGO_MANIFEST_DIR").to_owned() + "/../fonts/DejaVuSans.ttf";
+    let _ = AppCtx::font_db()
+      .borrow_mut()
+      .load_font_file(path);
+    let style = TextStyle {
+      font_size: 16.,
+      line_height: 20.,
+      overflow: Overflow::AutoWrap,
+      font_face: FontFace {
+        families: Box::new([FontFamily::Name("DejaVu Sans".into())]),
+        ..<_>::default()
+      },
+      ..<_>::default()
+    };
+
+    let metrics = TextMeasurer::measure("Hello world", &style, f32::MAX);
+    assert_eq!(metrics.line_count(), 1);
+    assert_eq!(metrics.size.height, 20.);
+    assert_eq!(metrics.baseline(), 16.);
+
+    let wrapped = TextMeasurer::measure("Hello world", &style, metrics.size.width - 1.);
+    assert_eq!(wrapped.line_count(), 2);
+    assert_eq!(wrapped.size.height, 40.);
+    assert_eq!(wrapped.lines[1].rect.min_y(), 20.);
+    assert_eq!(wrapped.lines[1].baseline, 36.);
+    assert!(wrapped.size.width < metrics.size.width);
+  }
+}
@@ -48,6 +48,28 @@ pub trait Render: 'static {
   fn hit_test(&self, ctx: &HitTestCtx, pos: Point) -> HitTest { ctx.box_hit_test(pos) }
 
   fn get_transform(&self) -> Option<Transform> { None }
+
+  /// Return the baselines of the widget after it has been laid out, or `None`
+  /// if it has no baseline. A widget with a single child takes the baselines
+  /// of its child by default.
+  fn baselines(&self, ctx: &LayoutCtx) -> Option<Baselines> { ctx.single_child_baselines() }
+
+  /// Return the intrinsic size of the `kind` for the given `extent` of the
+  /// other axis without laying out the widget, the `extent` may be infinite.
+  /// It lets a parent ask the child, for example, how wide it would like to be
+  /// at a height.
+  ///
+  /// A widget takes the largest intrinsic size of its children by default, and
+  /// `0` if it has no child, as its children are stacked. A widget sizing by
+  /// its own content or placing its children along an axis should override
+  /// it. Use `LayoutCtx::child_intrinsic_size` to query the children, the
+  /// result is cached in the layout pass.
+  fn intrinsic_size(&self, kind: IntrinsicSize, extent: f32, ctx: &mut LayoutCtx) -> f32 {
+    let (ctx, children) = ctx.split_children();
+    children
+      .map(|c| ctx.child_intrinsic_size(c, kind, extent))
+      .fold(0., f32::max)
+  }
 }
 
 /// The common type of all widget can convert to.
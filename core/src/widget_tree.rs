@@ -73,6 +73,8 @@ impl WidgetTree {
       let Some(mut needs_layout) = self.layout_list() else {
         break;
       };
+      // The intrinsic sizes are only valid in one layout pass.
+      self.store.clear_intrinsic_sizes();
       while let Some(wid) = needs_layout.pop() {
         if wid.is_dropped(self) {
           continue;
@@ -77,6 +77,77 @@ pub struct LayoutInfo {
   pub size: Option<Size>,
   /// The position render object to place, default is zero
   pub pos: Point,
+  /// The baselines of the render object, some value after the render object
+  /// has been layout and it has baselines.
+  pub baselines: Option<Baselines>,
+}
+
+/// The baselines of a widget, they are the distances from the top of the
+/// widget to the baselines of its first line and last line of text.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Baselines {
+  pub first: f32,
+  pub last: f32,
+}
+
+impl Baselines {
+  #[inline]
+  pub fn new(first: f32, last: f32) -> Self { Self { first, last } }
+
+  /// Return the baselines moved down by `dy`.
+  #[inline]
+  pub fn offset(self, dy: f32) -> Self { Self { first: self.first + dy, last: self.last + dy } }
+}
+
+/// The kind of the intrinsic size of a widget, see [`Render::intrinsic_size`].
+///
+/// [`Render::intrinsic_size`]: crate::prelude::Render::intrinsic_size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntrinsicSize {
+  /// The smallest width the widget can be laid out in without its content
+  /// overflowing, for the given height.
+  MinWidth,
+  /// The width beyond which increasing the width never decreases the height
+  /// the widget wants, for the given height.
+  MaxWidth,
+  /// The smallest height the widget can be laid out in without its content
+  /// overflowing, for the given width.
+  MinHeight,
+  /// The height beyond which increasing the height never decreases the width
+  /// the widget wants, for the given width.
+  MaxHeight,
+}
+
+impl IntrinsicSize {
+  /// Whether the intrinsic size is a width.
+  #[inline]
+  pub fn is_width(self) -> bool {
+    matches!(self, IntrinsicSize::MinWidth | IntrinsicSize::MaxWidth)
+  }
+
+  /// Whether the intrinsic size is a minimum size.
+  #[inline]
+  pub fn is_min(self) -> bool { matches!(self, IntrinsicSize::MinWidth | IntrinsicSize::MinHeight) }
+
+  /// Return the length of the `size` in the axis of the intrinsic size.
+  #[inline]
+  pub fn axis_of(self, size: Size) -> f32 { if self.is_width() { size.width } else { size.height } }
+
+  /// Return the length of the `size` in the other axis of the intrinsic size.
+  #[inline]
+  pub fn cross_of(self, size: Size) -> f32 {
+    if self.is_width() { size.height } else { size.width }
+  }
+
+  /// Return the same kind of the intrinsic size in the other axis.
+  pub fn flip(self) -> Self {
+    match self {
+      IntrinsicSize::MinWidth => IntrinsicSize::MinHeight,
+      IntrinsicSize::MaxWidth => IntrinsicSize::MaxHeight,
+      IntrinsicSize::MinHeight => IntrinsicSize::MinWidth,
+      IntrinsicSize::MaxHeight => IntrinsicSize::MaxWidth,
+    }
+  }
 }
 
 /// Store the render object's place relative to parent coordinate and the
@@ -84,6 +155,9 @@ pub struct LayoutInfo {
 #[derive(Default)]
 pub(crate) struct LayoutStore {
   data: HashMap<WidgetId, LayoutInfo, ahash::RandomState>,
+  /// The intrinsic sizes computed in the current layout pass, keyed by the
+  /// widget, the kind and the bits of the extent of the other axis.
+  intrinsic: HashMap<(WidgetId, IntrinsicSize, u32), f32, ahash::RandomState>,
 }
 
 impl LayoutStore {
@@ -107,6 +181,25 @@ impl LayoutStore {
   pub(crate) fn layout_info_or_default(&mut self, id: WidgetId) -> &mut LayoutInfo {
     self.data.entry(id).or_default()
   }
+
+  pub(crate) fn intrinsic_size(
+    &self, id: WidgetId, kind: IntrinsicSize, extent: f32,
+  ) -> Option<f32> {
+    self
+      .intrinsic
+      .get(&(id, kind, extent.to_bits()))
+      .copied()
+  }
+
+  pub(crate) fn cache_intrinsic_size(
+    &mut self, id: WidgetId, kind: IntrinsicSize, extent: f32, size: f32,
+  ) {
+    self
+      .intrinsic
+      .insert((id, kind, extent.to_bits()), size);
+  }
+
+  pub(crate) fn clear_intrinsic_sizes(&mut self) { self.intrinsic.clear(); }
 }
 
 impl WidgetTree {
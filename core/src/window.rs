@@ -148,6 +148,26 @@ impl Window {
       .dispatch_mouse_input(device_id, state, button);
   }
 
+  /// Cancel the pressed pointer, for example, when the window loses the focus
+  /// during a drag. The pressed widget receives a `PointerCancel` event and the
+  /// pointer capture is released.
+  pub fn process_pointer_cancel(&self) { self.dispatcher.borrow_mut().cancel_pointer(); }
+
+  /// Capture the pointer to the widget `id`, the following pointer move and up
+  /// events are dispatched to it even if the pointer is out of it, until the
+  /// pointer buttons are all released, the pointer is canceled or
+  /// `release_pointer_capture` is called.
+  pub fn set_pointer_capture(&self, id: WidgetId) {
+    self.dispatcher.borrow_mut().pointer_capture = Some(id);
+  }
+
+  /// Release the pointer capture, the pointer events are dispatched to the hit
+  /// widget again.
+  pub fn release_pointer_capture(&self) { self.dispatcher.borrow_mut().pointer_capture = None; }
+
+  /// Return the widget that captures the pointer, if any.
+  pub fn pointer_capture(&self) -> Option<WidgetId> { self.dispatcher.borrow().pointer_capture }
+
   /// Request switch the focus to next widget.
   pub fn request_next_focus(&self) {
     self
@@ -485,10 +505,10 @@ impl Window {
           let mut e = Event::Chars(e);
           self.bottom_up_emit(&mut e, id, None);
         }
-        DelayEvent::Wheel { id, delta_x, delta_y } => {
-          let mut e = Event::WheelCapture(WheelEvent::new(delta_x, delta_y, id, self));
+        DelayEvent::Wheel { id, delta_x, delta_y, phase } => {
+          let mut e = Event::WheelCapture(WheelEvent::new(delta_x, delta_y, phase, id, self));
           self.top_down_emit(&mut e, id, None);
-          let mut e = Event::Wheel(WheelEvent::new(delta_x, delta_y, id, self));
+          let mut e = Event::Wheel(WheelEvent::new(delta_x, delta_y, phase, id, self));
           self.bottom_up_emit(&mut e, id, None);
         }
         DelayEvent::PointerDown(id) => {
@@ -513,7 +533,7 @@ impl Window {
           let mut e = Event::PointerUp(PointerEvent::from_mouse(id, self));
           self.bottom_up_emit(&mut e, id, None);
         }
-        DelayEvent::_PointerCancel(id) => {
+        DelayEvent::PointerCancel(id) => {
           let mut e = Event::PointerCancel(PointerEvent::from_mouse(id, self));
           self.bottom_up_emit(&mut e, id, None);
         }
@@ -752,11 +772,11 @@ pub(crate) enum DelayEvent {
   KeyUp(KeyboardEvent),
   TabFocusMove,
   Chars { id: WidgetId, chars: String },
-  Wheel { id: WidgetId, delta_x: f32, delta_y: f32 },
+  Wheel { id: WidgetId, delta_x: f32, delta_y: f32, phase: TouchPhase },
   PointerDown(WidgetId),
   PointerMove(WidgetId),
   PointerUp(WidgetId),
-  _PointerCancel(WidgetId),
+  PointerCancel(WidgetId),
   PointerEnter { bottom: WidgetId, up: Option<WidgetId> },
   PointerLeave { bottom: WidgetId, up: Option<WidgetId> },
   Tap(WidgetId),
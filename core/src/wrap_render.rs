@@ -29,6 +29,16 @@ pub trait WrapRender {
 
   fn get_transform(&self, host: &dyn Render) -> Option<Transform> { host.get_transform() }
 
+  fn baselines(&self, host: &dyn Render, ctx: &LayoutCtx) -> Option<Baselines> {
+    host.baselines(ctx)
+  }
+
+  fn intrinsic_size(
+    &self, kind: IntrinsicSize, extent: f32, host: &dyn Render, ctx: &mut LayoutCtx,
+  ) -> f32 {
+    host.intrinsic_size(kind, extent, ctx)
+  }
+
   fn combine_child(this: impl StateWriter<Value = Self>, child: Widget) -> Widget
   where
     Self: Sized + 'static,
@@ -93,6 +103,16 @@ impl Render for RenderPair {
   }
 
   fn get_transform(&self) -> Option<Transform> { self.wrapper.get_transform(self.host.as_render()) }
+
+  fn baselines(&self, ctx: &LayoutCtx) -> Option<Baselines> {
+    self.wrapper.baselines(self.host.as_render(), ctx)
+  }
+
+  fn intrinsic_size(&self, kind: IntrinsicSize, extent: f32, ctx: &mut LayoutCtx) -> f32 {
+    self
+      .wrapper
+      .intrinsic_size(kind, extent, self.host.as_render(), ctx)
+  }
 }
 
 impl<R> WrapRender for R
@@ -117,6 +137,18 @@ where
   fn get_transform(&self, host: &dyn Render) -> Option<Transform> {
     self.read().get_transform(host)
   }
+
+  fn baselines(&self, host: &dyn Render, ctx: &LayoutCtx) -> Option<Baselines> {
+    self.read().baselines(host, ctx)
+  }
+
+  fn intrinsic_size(
+    &self, kind: IntrinsicSize, extent: f32, host: &dyn Render, ctx: &mut LayoutCtx,
+  ) -> f32 {
+    self
+      .read()
+      .intrinsic_size(kind, extent, host, ctx)
+  }
 }
 
 #[macro_export]
@@ -76,24 +76,26 @@ impl Compose for MessageList {
             @TabPane(
               fn_widget! {
                 @Scrollbar {
-                  @Lists {
-                    @{
-                      let message_gen = move |message: Message| {
-                        @Column {
-                          @ListItem {
-                            line_number: 1usize,
-                            @HeadlineText(Label::new(message.nick_name.clone()))
-                            @SupportingText(Label::new(message.content.clone()))
-                            @Leading(
-                              EdgeWidget::Avatar(@Avatar { @{ message.img.clone() } })
-                            )
-                            @Trailing(EdgeWidget::Icon(svgs::MORE_HORIZ.into_widget()))
+                  @SelectionArea {
+                    @Lists {
+                      @{
+                        let message_gen = move |message: Message| {
+                          @Column {
+                            @ListItem {
+                              line_number: 1usize,
+                              @HeadlineText(Label::new(message.nick_name.clone()))
+                              @SupportingText(Label::new(message.content.clone()))
+                              @Leading(
+                                EdgeWidget::Avatar(@Avatar { @{ message.img.clone() } })
+                              )
+                              @Trailing(EdgeWidget::Icon(svgs::MORE_HORIZ.into_widget()))
+                            }
+                            @Divider {}
                           }
-                          @Divider {}
-                        }
-                      };
+                        };
 
-                      $this.messages.clone().into_iter().map(message_gen)
+                        $this.messages.clone().into_iter().map(message_gen)
+                      }
                     }
                   }
                 }
@@ -5,11 +5,12 @@ use ribir_geom::{Angle, DeviceRect, Point, Rect, Size, Transform, Vector};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-  Brush, Color, Glyph, PixelImage, Svg, TextStyle, VisualGlyphs,
+  Brush, Color, ColorLayer, Glyph, GlyphOrientation, PixelImage, Svg, TextStyle, VisualGlyphs,
   color::{LinearGradient, RadialGradient},
   font_db::FontDB,
   path::*,
   path_builder::PathBuilder,
+  shaper::PLACEHOLDER_GLYPH_ID,
 };
 /// The Painter provides you the ability to render 2D elements on a
 /// two-dimensional canvas.
@@ -473,6 +474,14 @@ impl Painter {
     self
   }
 
+  /// Rotate the subsequent paint commands around the origin, a positive angle
+  /// is clockwise.
+  pub fn rotate(&mut self, angle: Angle) -> &mut Self {
+    let t = self.transform().pre_rotate(angle);
+    self.set_transform(t);
+    self
+  }
+
   /// Starts a new path by emptying the list of sub-paths.
   /// Call this method when you want to create a new path.
   #[inline]
@@ -662,12 +671,39 @@ impl Painter {
     let Some(face) = font_db.try_get_face_data(g.face_id) else { return self };
 
     let unit = face.units_per_em() as f32;
-    let scale = self.text_style().font_size / unit;
+    let font_size = self.text_style().font_size;
+    let scale = font_size / unit;
 
     let matrix = *self.transform();
 
-    let bounds = g.bounds();
-    if let Some(path) = face.outline_glyph(g.glyph_id) {
+    let mut bounds = g.bounds();
+    match g.orientation {
+      GlyphOrientation::Horizontal => {}
+      // The glyph is drawn from the top of its em box, but its cell in a vertical
+      // line starts from its vertical origin.
+      GlyphOrientation::Upright => {
+        bounds.origin.y += (face.glyph_v_origin(g.glyph_id) as f32 - unit) * scale;
+      }
+      // Draw the glyph as a horizontal one in a rotated axis.
+      GlyphOrientation::Sideways => {
+        self
+          .translate(bounds.min_x() + font_size, bounds.min_y())
+          .rotate(Angle::degrees(90.));
+        bounds = Rect::from_size(Size::new(bounds.height(), font_size));
+      }
+    }
+    let palette = self.text_style().color_palette;
+    let foreground = match self.fill_brush() {
+      Brush::Color(c) => *c,
+      _ => Color::BLACK,
+    };
+    if let Some(layers) = face.glyph_color_layers(g.glyph_id, palette, foreground) {
+      self
+        .translate(bounds.min_x(), bounds.min_y())
+        .scale(scale, -scale)
+        .translate(0., -unit)
+        .draw_color_layers(&layers);
+    } else if let Some(path) = face.outline_glyph(g.glyph_id) {
       self
         .translate(bounds.min_x(), bounds.min_y())
         .scale(scale, -scale)
@@ -687,7 +723,7 @@ impl Painter {
         .scale(scale, scale)
         .draw_svg(&svg);
     } else if let Some(img) =
-      face.glyph_raster_image(g.glyph_id, (unit / self.text_style().font_size) as u16)
+      face.glyph_raster_image(g.glyph_id, device_pixels_per_em(&matrix, font_size))
     {
       let m_width = img.width() as f32;
       let m_height = img.height() as f32;
@@ -708,6 +744,27 @@ impl Painter {
   }
 
   /// draw the text glyphs within the box_rect
+  /// Draw the layers of a color glyph in the current transform, the layers
+  /// without brush are filled with the fill brush.
+  pub(crate) fn draw_color_layers(&mut self, layers: &[ColorLayer]) -> &mut Self {
+    let base = *self.transform();
+    for ColorLayer { clips, path, transform, brush } in layers.iter() {
+      let mut painter = self.save_guard();
+      for (clip, ts) in clips.iter() {
+        painter
+          .set_transform(ts.then(&base))
+          .clip(clip.clone().into());
+      }
+      if let Some(brush) = brush {
+        painter.set_fill_brush(brush.clone());
+      }
+      painter
+        .set_transform(transform.then(&base))
+        .fill_path(path.clone().into());
+    }
+    self
+  }
+
   pub fn draw_glyphs_in_rect(
     self: &mut Painter, visual_glyphs: &VisualGlyphs, box_rect: Rect, font_db: &FontDB,
   ) -> &mut Self {
@@ -721,7 +778,10 @@ impl Painter {
     self.translate(visual_rect.origin.x, visual_rect.origin.y);
 
     for g in visual_glyphs.glyphs_in_bounds(&paint_rect) {
-      self.draw_glyph(&g, font_db);
+      // The placeholders are painted by others.
+      if g.glyph_id != PLACEHOLDER_GLYPH_ID {
+        self.draw_glyph(&g, font_db);
+      }
     }
 
     self
@@ -875,6 +935,18 @@ impl PaintPathAction {
     self
   }
 }
+/// The pixels per em of the font size on the device, it's used to select the
+/// bitmap strike of the glyph.
+fn device_pixels_per_em(matrix: &Transform, font_size: f32) -> u16 {
+  let scale = matrix
+    .m11
+    .hypot(matrix.m12)
+    .max(matrix.m21.hypot(matrix.m22));
+  (font_size * scale)
+    .ceil()
+    .clamp(1., u16::MAX as f32) as u16
+}
+
 // bounds that has a limited location and size
 fn locatable_bounds(bounds: &Rect) -> bool {
   bounds.origin.is_finite() && !bounds.width().is_nan() && !bounds.height().is_nan()
@@ -12,16 +12,20 @@ pub use fontdb::{ID, Stretch as FontStretch, Style as FontStyle, Weight as FontW
 pub use ribir_algo::Substr;
 use ribir_geom::{Rect, rect};
 use rustybuzz::{GlyphPosition, ttf_parser::GlyphId};
+pub use unicode_script::Script;
 pub mod text_reorder;
 pub mod typography;
 pub use text_reorder::TextReorder;
 mod typography_store;
-pub use typography_store::{TypographyStore, VisualGlyphs};
+pub use typography_store::{LineMetrics, TextMetrics, TypographyStore, VisualGlyphs};
+mod color_glyph;
+pub use color_glyph::ColorLayer;
 mod svg_glyph_cache;
 
 mod text_writer;
 pub use text_writer::{
-  CharacterCursor, TextWriter, select_next_word, select_prev_word, select_word,
+  CharacterCursor, TextWriter, next_paragraph_end, prev_paragraph_start, select_next_word,
+  select_prev_word, select_word,
 };
 
 mod grapheme_cursor;
@@ -58,6 +62,25 @@ pub enum FontFamily {
   Monospace,
 }
 
+/// A [language tag](https://www.rfc-editor.org/rfc/bcp/bcp47.txt) like `ja`,
+/// `zh-Hant` or `zh-CN`, it's used to select the fallback fonts of a script.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Locale(pub std::borrow::Cow<'static, str>);
+
+impl Locale {
+  pub fn new(tag: impl Into<std::borrow::Cow<'static, str>>) -> Self { Self(tag.into()) }
+
+  /// Return if the `other` locale is covered by this locale, a locale covers
+  /// itself and all its sub tags, for example, `zh` covers `zh-CN` and
+  /// `zh-Hant-TW`.
+  pub fn covers(&self, other: &Locale) -> bool {
+    let (this, other) = (self.0.as_bytes(), other.0.as_bytes());
+    other.len() >= this.len()
+      && other[..this.len()].eq_ignore_ascii_case(this)
+      && (other.len() == this.len() || matches!(other[this.len()], b'-' | b'_'))
+  }
+}
+
 /// Encapsulates the font properties of font face.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct FontFace {
@@ -93,6 +116,59 @@ pub struct TextStyle {
   pub line_height: f32,
   /// How to handle the visual overflow.
   pub overflow: Overflow,
+  /// Whether lines of text are laid out horizontally or vertically.
+  pub writing_mode: WritingMode,
+  /// The distance between the tab stops after the `tab_stops`.
+  pub tab_size: TabSize,
+  /// The custom tab stops in logical pixels relative to the start of the
+  /// line, in ascending order.
+  pub tab_stops: Box<[f32]>,
+  /// The index of the `CPAL` palette to paint the color glyphs with.
+  pub color_palette: u16,
+}
+
+/// The distance between two tab stops.
+///
+/// [tab-size](https://www.w3.org/TR/css-text-3/#tab-size-property) in CSS.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TabSize {
+  /// The multiple of the advance of the space character.
+  Spaces(u16),
+  /// The distance in logical pixels.
+  Pixels(f32),
+}
+
+impl Default for TabSize {
+  fn default() -> Self { TabSize::Spaces(8) }
+}
+
+/// The direction in which lines of text are laid out, and the direction the
+/// lines are placed.
+///
+/// [writing-mode](https://www.w3.org/TR/css-writing-modes-3/#block-flow) in CSS.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum WritingMode {
+  /// Text is set horizontally, lines are placed from top to bottom.
+  #[default]
+  HorizontalTb,
+  /// Text is set vertically, lines are placed from right to left.
+  VerticalRl,
+  /// Text is set vertically, lines are placed from left to right.
+  VerticalLr,
+}
+
+impl WritingMode {
+  #[inline]
+  pub fn is_vertical(&self) -> bool { !matches!(self, WritingMode::HorizontalTb) }
+
+  /// The direction to place the lines of this writing mode.
+  pub fn line_dir(&self) -> typography::PlaceLineDirection {
+    match self {
+      WritingMode::HorizontalTb => typography::PlaceLineDirection::TopToBottom,
+      WritingMode::VerticalRl => typography::PlaceLineDirection::RightToLeft,
+      WritingMode::VerticalLr => typography::PlaceLineDirection::LeftToRight,
+    }
+  }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
@@ -126,6 +202,43 @@ pub struct Glyph {
   pub glyph_id: GlyphId,
   /// An cluster of origin text as byte index.
   pub cluster: u32,
+  /// How the glyph is set in its line.
+  pub orientation: GlyphOrientation,
+}
+
+/// How a glyph is set in its line, it's always `Horizontal` in a horizontal
+/// line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GlyphOrientation {
+  #[default]
+  Horizontal,
+  /// The glyph is set upright in a vertical line, like most CJK characters.
+  Upright,
+  /// The glyph is rotated 90° clockwise in a vertical line, like most Latin
+  /// characters.
+  Sideways,
+}
+
+/// The character to mark where an inline placeholder is in the text, it's the
+/// `OBJECT REPLACEMENT CHARACTER`.
+pub const PLACEHOLDER_CHAR: char = '\u{FFFC}';
+
+/// A box reserved in the text to place something else, like an icon or an
+/// image. Every placeholder is set at a `PLACEHOLDER_CHAR` of the text in
+/// order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InlinePlaceholder {
+  /// The size of the placeholder in logical pixels.
+  pub size: ribir_geom::Size,
+  /// The distance from the top of the placeholder to the baseline of the
+  /// text, the placeholder bottom sits on the baseline if it's equal to the
+  /// height.
+  pub baseline_offset: f32,
+}
+
+impl InlinePlaceholder {
+  /// Create a placeholder that its bottom sits on the baseline.
+  pub fn new(size: ribir_geom::Size) -> Self { Self { size, baseline_offset: size.height } }
 }
 
 #[derive(
@@ -218,8 +331,38 @@ impl Glyph {
       y_offset: cast(pos.y_offset, scale),
       glyph_id,
       cluster,
+      orientation: GlyphOrientation::Horizontal,
     }
   }
+
+  /// Create a glyph shaped in the vertical direction. The position is
+  /// converted to be relative to the top-left of an em wide column, and the
+  /// vertical origin of the glyph is removed from `y_offset`, so the
+  /// `y_offset` and `y_advance` describe the cell of the glyph in the column.
+  fn new_vertical(glyph_id: GlyphId, cluster: u32, pos: &GlyphPosition, face: &Face) -> Self {
+    let mut g = Self::new(glyph_id, cluster, pos, face);
+    let scale = GlyphUnit::UNITS_PER_EM as f32 / face.units_per_em() as f32;
+    let v_origin = cast(face.glyph_v_origin(glyph_id), scale);
+    g.x_offset += GlyphUnit::STANDARD_EM / 2.;
+    g.y_offset = -g.y_offset - v_origin;
+    g.y_advance = -g.y_advance;
+    g.x_advance = GlyphUnit::STANDARD_EM;
+    g.orientation = GlyphOrientation::Upright;
+    g
+  }
+
+  /// Rotate a glyph shaped in the horizontal direction to set it sideways in a
+  /// vertical line.
+  pub(crate) fn into_sideways(mut self) -> Self {
+    let Glyph { x_advance, x_offset, y_offset, .. } = self;
+    self.y_advance = x_advance;
+    self.y_offset = x_offset;
+    self.x_offset = -y_offset;
+    self.x_advance = GlyphUnit::STANDARD_EM;
+    self.orientation = GlyphOrientation::Sideways;
+    self
+  }
+
   pub fn is_miss(&self) -> bool { self.glyph_id.0 == 0 }
 
   #[allow(unused)]
@@ -261,6 +404,10 @@ impl Default for TextStyle {
       letter_space: 0.,
       line_height: 16.,
       overflow: <_>::default(),
+      writing_mode: <_>::default(),
+      tab_size: <_>::default(),
+      tab_stops: Box::new([]),
+      color_palette: 0,
     }
   }
 }
@@ -0,0 +1,457 @@
+use ribir_algo::Resource;
+use ribir_geom::{Point, Rect, Size, Transform};
+use rustybuzz::ttf_parser::{GlyphId, RgbaColor, colr};
+use ttf_parser::colr as colr_v1;
+
+use crate::{
+  Brush, Color, GradientStop, Path, SpreadMethod,
+  color::{LinearGradient, RadialGradient},
+};
+
+/// A layer of a color glyph defined by the `COLR` and `CPAL` tables, it's the
+/// `path` filled with the `brush` in the `transform`, within the `clips`. A
+/// layer without `brush` is filled with the brush of the text.
+///
+/// The gradients of the `COLR` version 1 are painted by the gradient brushes,
+/// and the sweep gradients by the wedges of the solid colors. The composite
+/// modes are not supported, all the layers are painted over the others.
+#[derive(Clone)]
+pub struct ColorLayer {
+  /// The paths the layer is clipped by, with the transforms they are in.
+  pub clips: Box<[(Resource<Path>, Transform)]>,
+  /// The area filled by the layer, in the `transform`.
+  pub path: Resource<Path>,
+  /// The transform of the `path` and the `brush` in the font units, the axis
+  /// of the font units is y-up.
+  pub transform: Transform,
+  pub brush: Option<Brush>,
+}
+
+/// The degrees of the wedge to paint a sweep gradient.
+const SWEEP_STEP: f32 = 4.;
+
+/// Collect the layers of the color glyph in a `COLR` version 0 table, return
+/// `None` if the face has no color definition for the glyph.
+pub(crate) fn color_glyph_layers(
+  face: &rustybuzz::ttf_parser::Face, glyph_id: GlyphId, palette: u16,
+  outline: impl Fn(GlyphId) -> Option<Resource<Path>>,
+) -> Option<Box<[ColorLayer]>> {
+  if !face.is_color_glyph(glyph_id) {
+    return None;
+  }
+  let mut collector = LayerCollector::new(outline);
+  face.paint_color_glyph(glyph_id, palette, &mut collector)?;
+  Some(collector.layers.into_boxed_slice())
+}
+
+/// Collect the layers of the color glyph in a `COLR` version 1 table, the
+/// `foreground` is the color of the text.
+pub(crate) fn color_glyph_v1_layers(
+  table: &colr_v1::Table, glyph_id: GlyphId, palette: u16, foreground: Color,
+  outline: impl Fn(GlyphId) -> Option<Resource<Path>>,
+) -> Option<Box<[ColorLayer]>> {
+  let id = ttf_parser::GlyphId(glyph_id.0);
+  if !table.contains(id) {
+    return None;
+  }
+  let mut collector = LayerCollector::new(outline);
+  collector.palette = palette;
+  let [red, green, blue, alpha] = foreground.into_components();
+  let foreground = ttf_parser::RgbaColor::new(red, green, blue, alpha);
+  table.paint(id, palette, &mut collector, &[], foreground)?;
+  Some(collector.layers.into_boxed_slice())
+}
+
+struct LayerCollector<F> {
+  outline: F,
+  palette: u16,
+  current: Option<Resource<Path>>,
+  transforms: Vec<Transform>,
+  clips: Vec<(Resource<Path>, Transform)>,
+  layers: Vec<ColorLayer>,
+}
+
+impl<F> LayerCollector<F> {
+  fn new(outline: F) -> Self {
+    Self { outline, palette: 0, current: None, transforms: vec![], clips: vec![], layers: vec![] }
+  }
+
+  fn transform(&self) -> Transform {
+    self
+      .transforms
+      .last()
+      .copied()
+      .unwrap_or_default()
+  }
+
+  /// Paint the outline of a layer of the version 0.
+  fn push(&mut self, brush: Option<Brush>) {
+    // A layer without outline paints nothing.
+    if let Some(path) = self.current.take() {
+      let transform = Transform::default();
+      self
+        .layers
+        .push(ColorLayer { clips: Box::new([]), path, transform, brush });
+    }
+  }
+
+  /// Fill the clip area of the version 1 with the brush.
+  fn fill(&mut self, brush: Brush) {
+    let Some((last, others)) = self.clips.split_last() else { return };
+    let transform = self.transform();
+    let layer = if last.1 == transform {
+      ColorLayer { clips: others.into(), path: last.0.clone(), transform, brush: Some(brush) }
+    } else {
+      let path = Path::rect(&self.area_bounds());
+      let clips = self.clips.clone().into_boxed_slice();
+      ColorLayer { clips, path: Resource::new(path), transform, brush: Some(brush) }
+    };
+    self.layers.push(layer);
+  }
+
+  /// The bounds of the innermost clip in the current transform.
+  fn area_bounds(&self) -> Rect {
+    let Some((path, clip_ts)) = self.clips.last() else { return Rect::zero() };
+    let bounds = clip_ts.outer_transformed_rect(path.bounds());
+    self
+      .transform()
+      .inverse()
+      .map_or(Rect::zero(), |t| t.outer_transformed_rect(&bounds))
+  }
+
+  fn sweep(
+    &mut self, center: Point, start: f32, end: f32, stops: &[GradientStop], spread: SpreadMethod,
+  ) {
+    let area = self.area_bounds();
+    let radius = [
+      area.min(),
+      area.max(),
+      Point::new(area.min_x(), area.max_y()),
+      Point::new(area.max_x(), area.min_y()),
+    ]
+    .iter()
+    .map(|p| (*p - center).length())
+    .fold(0., f32::max);
+    if stops.is_empty() || radius <= 0. {
+      return;
+    }
+    let transform = self.transform();
+    let clips: Box<[_]> = self.clips.clone().into();
+    let mut angle = 0.;
+    while angle < 360. {
+      let next = (angle + SWEEP_STEP).min(360.);
+      let offset = if end == start { 0. } else { ((angle + next) / 2. - start) / (end - start) };
+      let color = color_at(stops, offset, spread);
+      let path = wedge(center, radius, angle, next);
+      self.layers.push(ColorLayer {
+        clips: clips.clone(),
+        path: Resource::new(path),
+        transform,
+        brush: Some(color.into()),
+      });
+      angle = next;
+    }
+  }
+}
+
+impl<F: Fn(GlyphId) -> Option<Resource<Path>>> colr::Painter for LayerCollector<F> {
+  fn outline(&mut self, glyph_id: GlyphId) { self.current = (self.outline)(glyph_id); }
+
+  fn paint_foreground(&mut self) { self.push(None); }
+
+  fn paint_color(&mut self, color: RgbaColor) {
+    let RgbaColor { red, green, blue, alpha } = color;
+    self.push(Some(Color::new(red, green, blue, alpha).into()));
+  }
+}
+
+impl<'a, F: Fn(GlyphId) -> Option<Resource<Path>>> colr_v1::Painter<'a> for LayerCollector<F> {
+  fn outline_glyph(&mut self, glyph_id: ttf_parser::GlyphId) {
+    self.current = (self.outline)(GlyphId(glyph_id.0));
+  }
+
+  fn paint(&mut self, paint: colr_v1::Paint<'a>) {
+    let palette = self.palette;
+    match paint {
+      colr_v1::Paint::Solid(color) => self.fill(to_color(color).into()),
+      colr_v1::Paint::LinearGradient(g) => {
+        let p0 = Point::new(g.x0, g.y0);
+        let p1 = Point::new(g.x1, g.y1);
+        // The gradient goes from `p0` to `p1` projected on the normal of the
+        // line from `p0` to `p2`.
+        let dir = Point::new(g.x2, g.y2) - p0;
+        let normal = ribir_geom::Vector::new(dir.y, -dir.x);
+        let end = if normal.square_length() > 0. {
+          p0 + normal * ((p1 - p0).dot(normal) / normal.square_length())
+        } else {
+          p1
+        };
+        let stops = sorted_stops(g.stops(palette, &[]));
+        let spread = to_spread(g.extend);
+        self.fill(Brush::LinearGradient(LinearGradient {
+          start: p0,
+          end,
+          stops,
+          spread_method: spread,
+        }));
+      }
+      colr_v1::Paint::RadialGradient(g) => {
+        let stops = sorted_stops(g.stops(palette, &[]));
+        self.fill(Brush::RadialGradient(RadialGradient {
+          start_center: Point::new(g.x0, g.y0),
+          start_radius: g.r0,
+          end_center: Point::new(g.x1, g.y1),
+          end_radius: g.r1,
+          stops,
+          spread_method: to_spread(g.extend),
+        }));
+      }
+      colr_v1::Paint::SweepGradient(g) => {
+        let stops = sorted_stops(g.stops(palette, &[]));
+        let center = Point::new(g.center_x, g.center_y);
+        // The angles are biased by 1 in the units of 180 degrees.
+        let (start, end) = ((g.start_angle + 1.) * 180., (g.end_angle + 1.) * 180.);
+        self.sweep(center, start, end, &stops, to_spread(g.extend));
+      }
+    }
+  }
+
+  fn push_clip(&mut self) {
+    let path = self
+      .current
+      .clone()
+      .unwrap_or_else(|| Resource::new(Path::rect(&Rect::zero())));
+    let transform = self.transform();
+    self.clips.push((path, transform));
+  }
+
+  fn push_clip_box(&mut self, clipbox: colr_v1::ClipBox) {
+    let rect = Rect::new(
+      Point::new(clipbox.x_min, clipbox.y_min),
+      Size::new(clipbox.x_max - clipbox.x_min, clipbox.y_max - clipbox.y_min),
+    );
+    let transform = self.transform();
+    self
+      .clips
+      .push((Resource::new(Path::rect(&rect)), transform));
+  }
+
+  fn pop_clip(&mut self) { self.clips.pop(); }
+
+  fn push_layer(&mut self, _: colr_v1::CompositeMode) {}
+
+  fn pop_layer(&mut self) {}
+
+  fn push_transform(&mut self, ts: ttf_parser::Transform) {
+    let ts = Transform::new(ts.a, ts.b, ts.c, ts.d, ts.e, ts.f);
+    let transform = ts.then(&self.transform());
+    self.transforms.push(transform);
+  }
+
+  fn pop_transform(&mut self) { self.transforms.pop(); }
+}
+
+fn to_color(color: ttf_parser::RgbaColor) -> Color {
+  let ttf_parser::RgbaColor { red, green, blue, alpha } = color;
+  Color::new(red, green, blue, alpha)
+}
+
+fn to_spread(extend: colr_v1::GradientExtend) -> SpreadMethod {
+  match extend {
+    colr_v1::GradientExtend::Pad => SpreadMethod::Pad,
+    colr_v1::GradientExtend::Repeat => SpreadMethod::Repeat,
+    colr_v1::GradientExtend::Reflect => SpreadMethod::Reflect,
+  }
+}
+
+fn sorted_stops(stops: impl Iterator<Item = colr_v1::ColorStop>) -> Vec<GradientStop> {
+  let mut stops: Vec<_> = stops
+    .map(|s| GradientStop::new(to_color(s.color), s.stop_offset))
+    .collect();
+  stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+  stops
+}
+
+/// The color of the sorted `stops` at the `offset`.
+fn color_at(stops: &[GradientStop], offset: f32, spread: SpreadMethod) -> Color {
+  let (first, last) = (stops[0].offset, stops[stops.len() - 1].offset);
+  let span = last - first;
+  let offset = if span <= 0. {
+    offset.clamp(first, last)
+  } else {
+    let t = (offset - first) / span;
+    let t = match spread {
+      SpreadMethod::Pad => t.clamp(0., 1.),
+      SpreadMethod::Repeat => t.rem_euclid(1.),
+      SpreadMethod::Reflect => 1. - (t.rem_euclid(2.) - 1.).abs(),
+    };
+    first + t * span
+  };
+  let idx = stops.partition_point(|s| s.offset <= offset);
+  if idx == 0 {
+    return stops[0].color;
+  }
+  if idx == stops.len() {
+    return stops[idx - 1].color;
+  }
+  let (a, b) = (&stops[idx - 1], &stops[idx]);
+  let t = (offset - a.offset) / (b.offset - a.offset);
+  let [r0, g0, b0, a0] = a.color.into_f32_components();
+  let [r1, g1, b1, a1] = b.color.into_f32_components();
+  let lerp = |x: f32, y: f32| x + (y - x) * t;
+  Color::from_f32_rgba(lerp(r0, r1), lerp(g0, g1), lerp(b0, b1), lerp(a0, a1))
+}
+
+/// A wedge of the circle from the `start` to the `end` degrees
+/// counter-clockwise in the y-up axis.
+fn wedge(center: Point, radius: f32, start: f32, end: f32) -> Path {
+  let at = |deg: f32| {
+    let rad = deg.to_radians();
+    center + ribir_geom::Vector::new(rad.cos(), rad.sin()) * radius
+  };
+  // The radius covers the area, so the chord outside the arc is clipped.
+  let mid = at((start + end) / 2.);
+  let mid = center + (mid - center) / ((end - start).to_radians() / 2.).cos();
+  let mut builder = Path::builder();
+  builder
+    .begin_path(center)
+    .line_to(at(start))
+    .line_to(mid)
+    .line_to(at(end))
+    .end_path(true);
+  builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+  use colr::Painter as _;
+  use ribir_geom::Point;
+
+  use super::*;
+  use crate::{PaintCommand, PaintPathAction, Painter};
+
+  fn square(id: GlyphId) -> Option<Resource<Path>> {
+    (id.0 != 0).then(|| Resource::new(Path::rect(&Rect::new(Point::zero(), Size::splat(100.)))))
+  }
+
+  #[test]
+  fn collect_layers() {
+    let mut collector = LayerCollector::new(square);
+    collector.outline(GlyphId(1));
+    collector.paint_color(RgbaColor::new(255, 0, 0, 255));
+    // The empty glyph has no layer.
+    collector.outline(GlyphId(0));
+    collector.paint_foreground();
+    collector.outline(GlyphId(2));
+    collector.paint_foreground();
+
+    let brushes: Vec<_> = collector
+      .layers
+      .iter()
+      .map(|l| l.brush.clone())
+      .collect();
+    assert_eq!(brushes, [Some(Color::RED.into()), None]);
+  }
+
+  /// A palette of a red color.
+  const CPAL: [u8; 18] = [0, 0, 0, 1, 0, 1, 0, 1, 0, 0, 0, 14, 0, 0, 0, 0, 255, 255];
+
+  /// A `COLR` table of the version 1, the glyph 5 is the glyph 1 filled by a
+  /// linear gradient from red to the foreground, and the glyph 6 is the glyph
+  /// 1 filled by a sweep gradient of the same colors.
+  fn colr_v1() -> Vec<u8> {
+    fn color_line(data: &mut Vec<u8>) {
+      // Pad, two stops: red at 0 and the foreground at 1, both opaque.
+      data.extend([0, 0, 2]);
+      data.extend([0, 0, 0, 0, 0x40, 0]);
+      data.extend([0x40, 0, 0xFF, 0xFF, 0x40, 0]);
+    }
+    // The header, the base glyph list follows it.
+    let mut data = vec![0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 34];
+    data.extend([0; 16]);
+    let list = data.len();
+    // Two records, their paints follow them.
+    data.extend([0, 0, 0, 2, 0, 5, 0, 0, 0, 16, 0, 6, 0, 0, 0, 53]);
+    // Glyph 5: `PaintGlyph` of the glyph 1 with a `PaintLinearGradient` from
+    // (0, 0) to (100, 0), the third point makes the gradient perpendicular.
+    data.extend([10, 0, 0, 6, 0, 1]);
+    data.extend([4, 0, 0, 16, 0, 0, 0, 0, 0, 100, 0, 0, 0, 0, 0, 100]);
+    color_line(&mut data);
+    assert_eq!(data.len() - list, 53);
+    // Glyph 6: `PaintGlyph` of the glyph 1 with a `PaintSweepGradient` around
+    // (50, 50) from 0 to 360 degrees.
+    data.extend([10, 0, 0, 6, 0, 1]);
+    data.extend([8, 0, 0, 12, 0, 50, 0, 50, 0xC0, 0, 0x40, 0]);
+    color_line(&mut data);
+    data
+  }
+
+  #[test]
+  fn draw_colr_v1_glyph() {
+    let cpal = ttf_parser::cpal::Table::parse(&CPAL).unwrap();
+    let colr = colr_v1();
+    let table = colr_v1::Table::parse(cpal, &colr).unwrap();
+    let layers = |id| color_glyph_v1_layers(&table, GlyphId(id), 0, Color::BLUE, square).unwrap();
+    assert!(color_glyph_v1_layers(&table, GlyphId(1), 0, Color::BLUE, square).is_none());
+
+    let linear = layers(5);
+    assert_eq!(linear.len(), 1);
+    let stops = vec![GradientStop::new(Color::RED, 0.), GradientStop::new(Color::BLUE, 1.)];
+    let expected = LinearGradient {
+      start: Point::zero(),
+      end: Point::new(100., 0.),
+      stops: stops.clone(),
+      spread_method: SpreadMethod::Pad,
+    };
+    assert_eq!(linear[0].brush, Some(Brush::LinearGradient(expected.clone())));
+
+    // The sweep gradient is painted by the wedges from red to blue.
+    let sweep = layers(6);
+    assert_eq!(sweep.len(), (360. / SWEEP_STEP) as usize);
+    let first = sweep[0].brush.clone();
+    let last = sweep[sweep.len() - 1].brush.clone();
+    assert_eq!(first, Some(color_at(&stops, SWEEP_STEP / 720., SpreadMethod::Pad).into()));
+    assert_eq!(last, Some(color_at(&stops, 1. - SWEEP_STEP / 720., SpreadMethod::Pad).into()));
+    assert!(sweep.iter().all(|l| l.clips.len() == 1));
+
+    let mut painter = Painter::new(Rect::from_size(Size::splat(200.)));
+    painter.draw_color_layers(&linear);
+    let cmds = painter.finish();
+    assert!(matches!(
+      &cmds[..],
+      [PaintCommand::Path(cmd)] if matches!(&cmd.action, PaintPathAction::Linear(l) if *l == expected)
+    ));
+  }
+
+  #[test]
+  fn select_palette() {
+    // Two palettes of one color, red and green.
+    let cpal = [0, 0, 0, 1, 0, 2, 0, 2, 0, 0, 0, 16, 0, 0, 0, 1, 0, 0, 255, 255, 0, 255, 0, 255];
+    let cpal = ttf_parser::cpal::Table::parse(&cpal).unwrap();
+    let colr = colr_v1();
+    let table = colr_v1::Table::parse(cpal, &colr).unwrap();
+    let first_stop = |palette| {
+      let layers = color_glyph_v1_layers(&table, GlyphId(5), palette, Color::BLUE, square);
+      match layers.unwrap()[0].brush.clone() {
+        Some(Brush::LinearGradient(g)) => g.stops[0].color,
+        _ => unreachable!(),
+      }
+    };
+    assert_eq!(first_stop(0), Color::RED);
+    assert_eq!(first_stop(1), Color::from_rgb(0, 255, 0));
+  }
+
+  #[test]
+  fn color_of_stops() {
+    let stops = [GradientStop::new(Color::RED, 0.), GradientStop::new(Color::BLUE, 1.)];
+    assert_eq!(color_at(&stops, -1., SpreadMethod::Pad), Color::RED);
+    assert_eq!(
+      color_at(&stops, 1.25, SpreadMethod::Repeat),
+      color_at(&stops, 0.25, SpreadMethod::Pad)
+    );
+    assert_eq!(
+      color_at(&stops, 1.25, SpreadMethod::Reflect),
+      color_at(&stops, 0.75, SpreadMethod::Pad)
+    );
+  }
+}
@@ -8,27 +8,63 @@ use ribir_geom::{Point, Rect, rect};
 use rustybuzz::ttf_parser::{GlyphId, OutlineBuilder};
 
 use crate::{
-  Path, PixelImage, Svg,
+  Color, Path, PixelImage, Svg,
   path_builder::PathBuilder,
-  text::{FontFace, FontFamily, svg_glyph_cache::SvgGlyphCache},
+  text::{
+    ColorLayer, FontFace, FontFamily, Locale, Script,
+    color_glyph::{color_glyph_layers, color_glyph_v1_layers},
+    svg_glyph_cache::SvgGlyphCache,
+  },
 };
+/// Parse the `COLR` table of the version 1 with its `CPAL` table.
+fn colr_v1_table(face: &rustybuzz::Face<'static>) -> Option<ttf_parser::colr::Table<'static>> {
+  use rustybuzz::ttf_parser::Tag;
+
+  let raw = face.raw_face();
+  let colr = raw.table(Tag::from_bytes(b"COLR"))?;
+  // Only the version 1 is not supported by the `rb_face`.
+  if colr.get(..2)? != [0, 1] {
+    return None;
+  }
+  let cpal = ttf_parser::cpal::Table::parse(raw.table(Tag::from_bytes(b"CPAL"))?)?;
+  ttf_parser::colr::Table::parse(cpal, colr)
+}
+
 /// A wrapper of fontdb and cache font data.
 pub struct FontDB {
   default_fonts: Vec<ID>,
   data_base: fontdb::Database,
   cache: HashMap<ID, Option<Face>>,
+  locale: Option<Locale>,
+  fallbacks: Vec<ScriptFallback>,
+  fallback_generation: usize,
+}
+
+/// The fallback families of a script, only used when the `locale` covers the
+/// locale of the `FontDB` if it's not `None`.
+struct ScriptFallback {
+  script: Script,
+  locale: Option<Locale>,
+  families: Box<[FontFamily]>,
 }
 
 type FontGlyphCache<K, V> = Sc<RefCell<HashMap<K, Option<V>>>>;
+/// The glyph, the palette and the foreground color of a color glyph, the
+/// foreground is only used by the `COLR` version 1.
+type ColorGlyphKey = (GlyphId, u16, Option<u32>);
 #[derive(Clone)]
 pub struct Face {
   pub face_id: ID,
   pub source_data: Arc<dyn AsRef<[u8]> + Sync + Send>,
   pub face_data_index: u32,
   pub rb_face: rustybuzz::Face<'static>,
-  raster_image_glyphs: FontGlyphCache<GlyphId, Resource<PixelImage>>,
+  raster_image_glyphs: FontGlyphCache<(GlyphId, u16), Resource<PixelImage>>,
   outline_glyphs: FontGlyphCache<GlyphId, Resource<Path>>,
   svg_glyphs: Sc<RefCell<SvgGlyphCache>>,
+  /// The `COLR` table of the version 1, the version 0 is parsed by the
+  /// `rb_face`.
+  colr_v1: Option<ttf_parser::colr::Table<'static>>,
+  color_glyphs: FontGlyphCache<ColorGlyphKey, Resource<Box<[ColorLayer]>>>,
 }
 
 impl FontDB {
@@ -42,6 +78,93 @@ impl FontDB {
 
   pub fn default_fonts(&self) -> &[ID] { &self.default_fonts }
 
+  /// Sets the locale of the application, it's used to select the fallback
+  /// families of a script.
+  pub fn set_locale(&mut self, locale: Option<Locale>) {
+    if self.locale != locale {
+      self.locale = locale;
+      self.fallback_generation += 1;
+    }
+  }
+
+  pub fn locale(&self) -> Option<&Locale> { self.locale.as_ref() }
+
+  /// Sets the fallback families of the `script` for the `locale`. When the
+  /// text of a script isn't covered by the fonts specified in its text style,
+  /// the fallback families are tried before the default fonts.
+  ///
+  /// If `locale` is `None`, the families are used for all locales, otherwise
+  /// they are only used if the locale covers the locale of the `FontDB`, the
+  /// most specific one is used if multiple locales cover it.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use ribir_painter::{FontFamily, Locale, Script, font_db::FontDB};
+  ///
+  /// let mut db = FontDB::default();
+  /// db.set_locale(Some(Locale::new("ja-JP")));
+  /// db.set_fallback(Script::Han, Locale::new("ja"), [FontFamily::Name("Noto Sans JP".into())]);
+  /// db.set_fallback(Script::Han, Locale::new("zh"), [FontFamily::Name("Noto Sans SC".into())]);
+  /// assert_eq!(db.fallback_families(Script::Han), &[FontFamily::Name("Noto Sans JP".into())]);
+  /// ```
+  pub fn set_fallback(
+    &mut self, script: Script, locale: impl Into<Option<Locale>>,
+    families: impl Into<Box<[FontFamily]>>,
+  ) {
+    let locale = locale.into();
+    let families = families.into();
+    self.fallback_generation += 1;
+    if let Some(f) = self
+      .fallbacks
+      .iter_mut()
+      .find(|f| f.script == script && f.locale == locale)
+    {
+      f.families = families;
+    } else {
+      self
+        .fallbacks
+        .push(ScriptFallback { script, locale, families });
+    }
+  }
+
+  /// Returns a number that changes whenever the locale or the fallback
+  /// families change, the results depend on the fallbacks are stale if it
+  /// differs from the one they were built with.
+  pub fn fallback_generation(&self) -> usize { self.fallback_generation }
+
+  /// Returns the fallback families of the `script` for the current locale.
+  pub fn fallback_families(&self, script: Script) -> &[FontFamily] {
+    self
+      .fallbacks
+      .iter()
+      .filter(|f| f.script == script)
+      .filter_map(|f| match (&f.locale, &self.locale) {
+        (None, _) => Some((0, f)),
+        (Some(l), Some(current)) if l.covers(current) => Some((l.0.len() + 1, f)),
+        _ => None,
+      })
+      .max_by_key(|(specific, _)| *specific)
+      .map_or(&[], |(_, f)| &f.families)
+  }
+
+  /// Returns the faces that the text of the `script` should use, it's the
+  /// faces of the `face` followed by the faces of the fallback families of the
+  /// `script`.
+  pub fn select_script_match(&mut self, face: &FontFace, script: Script) -> Vec<ID> {
+    let mut ids = self.select_all_match(face);
+    let families: Box<[FontFamily]> = self.fallback_families(script).into();
+    if !families.is_empty() {
+      let fallback = FontFace { families, ..face.clone() };
+      for id in self.select_all_match(&fallback) {
+        if !ids.contains(&id) {
+          ids.push(id);
+        }
+      }
+    }
+    ids
+  }
+
   pub fn try_get_face_data(&self, face_id: ID) -> Option<&Face> {
     self.cache.get(&face_id)?.as_ref()
   }
@@ -256,7 +379,14 @@ impl Default for FontDB {
     let mut data_base = fontdb::Database::new();
     data_base.load_font_data(include_bytes!("./Lato-Regular.ttf").to_vec());
     let default_font = data_base.faces().next().map(|f| f.id).unwrap();
-    let mut this = FontDB { default_fonts: vec![default_font], data_base, cache: <_>::default() };
+    let mut this = FontDB {
+      default_fonts: vec![default_font],
+      data_base,
+      cache: <_>::default(),
+      locale: None,
+      fallbacks: vec![],
+      fallback_generation: 0,
+    };
     this.face_data_or_insert(default_font);
     this
   }
@@ -270,6 +400,7 @@ impl Face {
     // Safety: we know the ptr_data has some valid lifetime with source data, and
     // hold them in same struct.
     let rb_face = rustybuzz::Face::from_slice(unsafe { &*ptr_data }, face_index)?;
+    let colr_v1 = colr_v1_table(&rb_face);
     Some(Face {
       source_data,
       face_data_index: face_index,
@@ -278,6 +409,8 @@ impl Face {
       outline_glyphs: <_>::default(),
       raster_image_glyphs: <_>::default(),
       svg_glyphs: <_>::default(),
+      colr_v1,
+      color_glyphs: <_>::default(),
     })
   }
 
@@ -305,24 +438,24 @@ impl Face {
       .cloned()
   }
 
+  /// Returns the bitmap image of the glyph from the best strike for the
+  /// `pixels_per_em`, the images are cached by the strike they come from.
   pub fn glyph_raster_image(
     &self, glyph_id: GlyphId, pixels_per_em: u16,
   ) -> Option<Resource<PixelImage>> {
+    let img = self
+      .rb_face
+      .glyph_raster_image(glyph_id, pixels_per_em)?;
     self
       .raster_image_glyphs
       .borrow_mut()
-      .entry(glyph_id)
-      .or_insert_with(|| {
-        self
-          .rb_face
-          .glyph_raster_image(glyph_id, pixels_per_em)
-          .and_then(|img| match img.format {
-            #[cfg(feature = "png")]
-            rustybuzz::ttf_parser::RasterImageFormat::PNG => {
-              Some(Resource::new(PixelImage::from_png(img.data)))
-            }
-            _ => None,
-          })
+      .entry((glyph_id, img.pixels_per_em))
+      .or_insert_with(|| match img.format {
+        #[cfg(feature = "png")]
+        rustybuzz::ttf_parser::RasterImageFormat::PNG => {
+          Some(Resource::new(PixelImage::from_png(img.data)))
+        }
+        _ => None,
       })
       .clone()
   }
@@ -335,8 +468,57 @@ impl Face {
       .clone()
   }
 
+  /// Returns the layers of the color glyph defined by the `COLR` and `CPAL`
+  /// tables with the `palette`. The `foreground` is the color of the text used
+  /// by the glyphs of the `COLR` version 1, the layers of the version 0 use the
+  /// brush of the text. Only the color glyphs are cached.
+  pub fn glyph_color_layers(
+    &self, glyph_id: GlyphId, palette: u16, foreground: Color,
+  ) -> Option<Resource<Box<[ColorLayer]>>> {
+    let v0 = self.rb_face.is_color_glyph(glyph_id);
+    if !v0
+      && !self
+        .colr_v1
+        .is_some_and(|t| t.contains(ttf_parser::GlyphId(glyph_id.0)))
+    {
+      return None;
+    }
+    let key = (glyph_id, palette, (!v0).then(|| foreground.into_u32()));
+    if let Some(layers) = self.color_glyphs.borrow().get(&key) {
+      return layers.clone();
+    }
+    // Not hold the cache when collecting, the outlines are cached by the face.
+    let outline = |id| self.outline_glyph(id);
+    let layers = match self.colr_v1.as_ref().filter(|_| !v0) {
+      Some(table) => color_glyph_v1_layers(table, glyph_id, palette, foreground, outline),
+      None => color_glyph_layers(&self.rb_face, glyph_id, palette, outline),
+    }
+    .map(Resource::new);
+    self
+      .color_glyphs
+      .borrow_mut()
+      .insert(key, layers.clone());
+    layers
+  }
+
   #[inline]
   pub fn units_per_em(&self) -> u16 { self.rb_face.deref().units_per_em() }
+
+  /// The vertical origin of the glyph in font units, it's the distance from
+  /// the baseline to the top of the glyph cell when setting text vertically.
+  pub fn glyph_v_origin(&self, glyph_id: GlyphId) -> i32 {
+    let face = self.rb_face.deref();
+    face
+      .glyph_y_origin(glyph_id)
+      .map(i32::from)
+      .unwrap_or_else(|| {
+        let top = face
+          .glyph_bounding_box(glyph_id)
+          .map_or(face.ascender(), |b| b.y_max);
+        let bearing = face.glyph_ver_side_bearing(glyph_id).unwrap_or(0);
+        i32::from(top) + i32::from(bearing)
+      })
+  }
 }
 
 fn to_db_family(f: &FontFamily) -> Family {
@@ -492,6 +674,28 @@ mod tests {
     assert!(face_id.is_some());
   }
 
+  #[test]
+  fn outline_glyph_not_in_color_cache() {
+    let mut db = FontDB::default();
+    let bytes = include_bytes!("../../../fonts/GaramondNo8-Reg.ttf");
+    db.load_from_bytes(bytes.to_vec());
+    let face_id = db
+      .select_best_match(&FontFace {
+        families: vec![FontFamily::Name("GaramondNo8".into())].into_boxed_slice(),
+        ..<_>::default()
+      })
+      .unwrap();
+    let face = db.face_data_or_insert(face_id).unwrap();
+    let glyph = face.glyph_index('A').unwrap();
+    assert!(
+      face
+        .glyph_color_layers(glyph, 0, Color::BLACK)
+        .is_none()
+    );
+    assert!(face.outline_glyph(glyph).is_some());
+    assert!(face.color_glyphs.borrow().is_empty());
+  }
+
   #[test]
   fn load_sys_fonts() {
     let mut db = FontDB::default();
@@ -526,4 +730,30 @@ mod tests {
     let info = fonts.face_info(id.unwrap()).unwrap();
     assert_eq!(info.weight, FontWeight::BOLD);
   }
+
+  #[test]
+  fn locale_fallback() {
+    let mut db = FontDB::default();
+    let han = |name: &'static str| [FontFamily::Name(name.into())];
+    db.set_fallback(Script::Han, None, han("Any"));
+    db.set_fallback(Script::Han, Locale::new("zh"), han("SC"));
+    db.set_fallback(Script::Han, Locale::new("zh-Hant"), han("TC"));
+    db.set_fallback(Script::Han, Locale::new("ja"), han("JP"));
+
+    assert_eq!(db.fallback_families(Script::Han), han("Any"));
+    assert!(db.fallback_families(Script::Arabic).is_empty());
+
+    db.set_locale(Some(Locale::new("zh-CN")));
+    assert_eq!(db.fallback_families(Script::Han), han("SC"));
+    db.set_locale(Some(Locale::new("zh-Hant-TW")));
+    assert_eq!(db.fallback_families(Script::Han), han("TC"));
+    db.set_locale(Some(Locale::new("JA")));
+    assert_eq!(db.fallback_families(Script::Han), han("JP"));
+    db.set_locale(Some(Locale::new("jav")));
+    assert_eq!(db.fallback_families(Script::Han), han("Any"));
+
+    // Replace the families of the same script and locale.
+    db.set_fallback(Script::Han, None, han("Other"));
+    assert_eq!(db.fallback_families(Script::Han), han("Other"));
+  }
 }
@@ -5,7 +5,7 @@ use std::{
 
 use ribir_algo::{FrameCache, Sc, Substr};
 pub use rustybuzz::ttf_parser::GlyphId;
-use rustybuzz::{GlyphInfo, UnicodeBuffer};
+use rustybuzz::{Feature, GlyphInfo, UnicodeBuffer, ttf_parser::Tag};
 
 use crate::{
   Glyph, TextDirection,
@@ -13,6 +13,12 @@ use crate::{
 };
 
 pub const NEWLINE_GLYPH_ID: GlyphId = GlyphId(u16::MAX);
+/// The glyph id of an inline placeholder, it occupies the space of the
+/// placeholder and draws nothing.
+pub const PLACEHOLDER_GLYPH_ID: GlyphId = GlyphId(u16::MAX - 1);
+/// The glyph id of a tab character, it draws nothing and advances to the next
+/// tab stop.
+pub const TAB_GLYPH_ID: GlyphId = GlyphId(u16::MAX - 2);
 /// Shaper to shape the `text` using provided font faces, and will do BIDI
 /// reordering before to shape text.
 ///
@@ -58,6 +64,13 @@ impl TextShaper {
         .shape_text_with_fallback(text, direction, face_ids)
         .unwrap_or_default();
 
+      if text.contains('\t') {
+        glyphs
+          .iter_mut()
+          .filter(|g| text.as_bytes()[g.cluster as usize] == b'\t')
+          .for_each(|g| g.glyph_id = TAB_GLYPH_ID);
+      }
+
       if let Some(last_char) = text.bytes().last() {
         if last_char == b'\r' || last_char == b'\n' {
           if let Some(g) = glyphs.last_mut() {
@@ -84,7 +97,7 @@ impl TextShaper {
     buffer.push_str(text);
     buffer.set_direction(dir.into());
 
-    let GlyphsWithoutFallback { mut glyphs, mut buffer } = Self::directly_shape(buffer, &face);
+    let GlyphsWithoutFallback { mut glyphs, mut buffer } = Self::directly_shape(buffer, dir, &face);
     let mut new_part = vec![(0, glyphs.len(), font_fallback.clone())];
     loop {
       if new_part.is_empty() {
@@ -97,8 +110,17 @@ impl TextShaper {
     Some(glyphs)
   }
 
-  fn directly_shape(text: UnicodeBuffer, face: &Face) -> GlyphsWithoutFallback {
-    let output = rustybuzz::shape(face.as_rb_face(), &[], text);
+  fn directly_shape(text: UnicodeBuffer, dir: TextDirection, face: &Face) -> GlyphsWithoutFallback {
+    // The vertical alternates are only meaningful when the text is set vertically.
+    let features: Vec<_> = if dir.is_vertical() {
+      [b"vert", b"vrt2"]
+        .into_iter()
+        .map(|tag| Feature::new(Tag::from_bytes(tag), 1, ..))
+        .collect()
+    } else {
+      vec![]
+    };
+    let output = rustybuzz::shape(face.as_rb_face(), &features, text);
     let mut glyphs = Vec::with_capacity(output.len());
 
     let infos = output.glyph_infos();
@@ -107,7 +129,13 @@ impl TextShaper {
     (0..output.len()).for_each(|idx| {
       let &GlyphInfo { glyph_id, cluster, .. } = &infos[idx];
       let p = &positions[idx];
-      glyphs.push(Glyph::new(GlyphId(glyph_id as u16), cluster, p, face))
+      let glyph_id = GlyphId(glyph_id as u16);
+      let glyph = if dir.is_vertical() {
+        Glyph::new_vertical(glyph_id, cluster, p, face)
+      } else {
+        Glyph::new(glyph_id, cluster, p, face)
+      };
+      glyphs.push(glyph)
     });
 
     GlyphsWithoutFallback { glyphs, buffer: output.clear() }
@@ -195,7 +223,7 @@ fn regen_miss_part<'a>(
     if let Some(face) = helper.next_fallback_face(miss_text) {
       buffer.push_str(miss_text);
       buffer.set_direction(hb_direction);
-      let mut res = TextShaper::directly_shape(buffer, &face);
+      let mut res = TextShaper::directly_shape(buffer, dir, &face);
       buffer = res.buffer;
       for g in res.glyphs.iter_mut() {
         g.cluster += miss_range.start as u32;
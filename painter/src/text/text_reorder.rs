@@ -1,16 +1,26 @@
-use std::ops::Range;
+use std::{ops::Range, rc::Rc};
 
-use ribir_algo::{FrameCache, Sc, Substr};
-use unicode_bidi::{BidiClass, BidiInfo, Level, LevelRun};
+use ribir_algo::{FrameCache, Rope, Sc, Substr, split_paragraphs};
+use unicode_bidi::{BidiInfo, Level, LevelRun};
+use unicode_script::{Script, UnicodeScript};
 
 pub struct Paragraph {
-  pub levels: Vec<Level>,
+  /// The levels of the bytes in the paragraph, use `level` to get the level
+  /// of a byte in the text. They're shared by the results merged from the
+  /// paragraph.
+  pub levels: Rc<[Level]>,
+  /// The runs in visual order, every run has the same bidi level and script.
   pub runs: Vec<LevelRun>,
+  /// The script of every run in `runs`.
+  pub scripts: Vec<Script>,
   pub range: Range<usize>,
 }
+
 pub struct ReorderResult {
-  pub original_classes: Vec<BidiClass>,
   pub paras: Vec<Paragraph>,
+  /// The results of the paragraphs that this result is merged from, empty if
+  /// the text is reordered as one paragraph.
+  pub(crate) sources: Vec<(Substr, Sc<ReorderResult>)>,
 }
 
 // unnecessary cache
@@ -19,38 +29,193 @@ pub struct TextReorder {
   cache: FrameCache<Substr, Sc<ReorderResult>>,
 }
 
+impl Paragraph {
+  /// The level of the byte at `idx` of the text.
+  #[inline]
+  pub fn level(&self, idx: usize) -> Level { self.levels[idx - self.range.start] }
+
+  fn offset(&self, offset: usize) -> Paragraph {
+    let shift = |r: &Range<usize>| r.start + offset..r.end + offset;
+    Paragraph {
+      levels: self.levels.clone(),
+      runs: self.runs.iter().map(shift).collect(),
+      scripts: self.scripts.clone(),
+      range: shift(&self.range),
+    }
+  }
+}
+
+impl ReorderResult {
+  /// The paragraphs of the `text` that the `result` is reordered from.
+  pub(crate) fn paragraphs<'a>(
+    result: &'a Sc<Self>, text: &'a Substr,
+  ) -> impl Iterator<Item = (&'a Substr, &'a Sc<ReorderResult>)> + 'a {
+    let single = result
+      .sources
+      .is_empty()
+      .then_some((text, result));
+    single
+      .into_iter()
+      .chain(result.sources.iter().map(|(t, r)| (t, r)))
+  }
+
+  fn merge(sources: Vec<(Substr, Sc<ReorderResult>)>) -> Sc<ReorderResult> {
+    let mut paras = vec![];
+    let mut start = 0;
+    for (p_text, result) in sources.iter() {
+      paras.extend(result.paras.iter().map(|p| p.offset(start)));
+      start += p_text.len();
+    }
+    Sc::new(ReorderResult { paras, sources })
+  }
+}
+
 impl TextReorder {
   pub fn get_cache(&mut self, text: &Substr) -> Option<Sc<ReorderResult>> {
     self.cache.get(text).cloned()
   }
 
+  /// Reorder the `text` paragraph by paragraph, the paragraphs are cached
+  /// separately, so only the edited paragraphs are reordered again after the
+  /// text is edited.
   pub fn reorder_text(&mut self, text: &Substr) -> &Sc<ReorderResult> {
+    if !self.cache.contains(text) {
+      let mut sources = vec![];
+      let mut start = 0;
+      for p in split_paragraphs(text) {
+        let p_text = text.substr(start..start + p.len());
+        let result = self.reorder_paragraph(&p_text).clone();
+        start += p_text.len();
+        sources.push((p_text, result));
+      }
+      // A text of one paragraph is cached as the paragraph.
+      if sources.len() > 1 {
+        let result = ReorderResult::merge(sources);
+        self.cache.put(text.clone(), result);
+      }
+    }
+    self.cache.get(text).unwrap()
+  }
+
+  /// Reorder the `rope` paragraph by paragraph like `reorder_text`. The
+  /// paragraphs shared with the rope reordered as `prev` keep their results,
+  /// they are found by the identity of the paragraphs, so the text is not
+  /// compared. Return the result and the range of the paragraphs reordered
+  /// again in it.
+  ///
+  /// The result is always merged from the paragraphs, even if the rope has
+  /// only one paragraph.
+  pub fn reorder_rope(
+    &mut self, rope: &Rope, prev: Option<&Sc<ReorderResult>>,
+  ) -> (Sc<ReorderResult>, Range<usize>) {
+    let prev_paras = prev.map_or(&[][..], |prev| &prev.sources[..]);
+    let (count, prev_count) = (rope.paragraph_count(), prev_paras.len());
+    let head = rope
+      .paragraphs()
+      .zip(prev_paras)
+      .take_while(|(p, (prev_p, _))| std::ptr::eq(*p, &**prev_p))
+      .count();
+    if let Some(prev) = prev.filter(|_| head == count && head == prev_count) {
+      return (prev.clone(), head..head);
+    }
+    let tail = (1..=count.min(prev_count) - head)
+      .take_while(|i| {
+        let p = rope.paragraph(count - i).substr(..);
+        p.ptr_eq(&prev_paras[prev_count - i].0)
+      })
+      .count();
+
+    let mut sources = prev_paras[..head].to_vec();
+    for idx in head..count - tail {
+      let p_text = rope.paragraph(idx).substr(..);
+      let result = self.reorder_paragraph(&p_text).clone();
+      sources.push((p_text, result));
+    }
+    let edited = head..sources.len();
+    sources.extend_from_slice(&prev_paras[prev_count - tail..]);
+    (ReorderResult::merge(sources), edited)
+  }
+
+  fn reorder_paragraph(&mut self, text: &Substr) -> &Sc<ReorderResult> {
     self.cache.get_or_insert(text.clone(), || {
       let info = BidiInfo::new(text, None);
       let mut paras: Vec<Paragraph> = info
         .paragraphs
         .iter()
         .map(|p| {
-          let (levels, runs) = info.visual_runs(p, p.range.clone());
-          Paragraph { levels, runs, range: p.range.clone() }
+          let (levels, level_runs) = info.visual_runs(p, p.range.clone());
+          let mut runs = vec![];
+          let mut scripts = vec![];
+          for r in level_runs {
+            let start = runs.len();
+            for (rg, script) in script_runs(text, r.clone()) {
+              runs.push(rg);
+              scripts.push(script);
+            }
+            // The script runs in a right-to-left run are also in visual order.
+            if !r.is_empty() && levels[r.start].is_rtl() {
+              runs[start..].reverse();
+              scripts[start..].reverse();
+            }
+          }
+          let levels = levels[p.range.clone()].into();
+          Paragraph { levels, runs, scripts, range: p.range.clone() }
         })
         .collect();
 
-      if paras.is_empty() || text.ends_with('\r') || text.ends_with('\n') {
+      if paras.is_empty() {
         paras.push(Paragraph {
-          levels: vec![],
+          levels: Rc::new([]),
           runs: vec![Range { start: text.len(), end: text.len() }],
+          scripts: vec![Script::Common],
           range: Range { start: text.len(), end: text.len() },
         })
       }
 
-      Sc::new(ReorderResult { original_classes: info.original_classes, paras })
+      Sc::new(ReorderResult { paras, sources: vec![] })
     })
   }
 
   pub fn end_frame(&mut self) { self.cache.end_frame("Text Reorder"); }
 }
 
+/// Split the `range` of the `text` to runs by the script. The characters of
+/// the common script, like the spaces and punctuation, are merged into the run
+/// before them, or the run after them if they are at the beginning.
+fn script_runs(text: &str, range: Range<usize>) -> Vec<(Range<usize>, Script)> {
+  let mut runs: Vec<(Range<usize>, Script)> = vec![];
+  for (idx, c) in text[range.clone()].char_indices() {
+    let idx = idx + range.start;
+    let script = font_script(c.script());
+    match runs.last_mut() {
+      Some((rg, s)) if *s == script || is_common_script(script) => rg.end = idx + c.len_utf8(),
+      Some((rg, s)) if is_common_script(*s) => {
+        rg.end = idx + c.len_utf8();
+        *s = script;
+      }
+      _ => runs.push((idx..idx + c.len_utf8(), script)),
+    }
+  }
+  if runs.is_empty() {
+    runs.push((range, Script::Common));
+  }
+  runs
+}
+
+fn is_common_script(script: Script) -> bool {
+  matches!(script, Script::Common | Script::Inherited | Script::Unknown)
+}
+
+/// The scripts usually mixed in a text and rendered by the same face are
+/// treated as the same script, so the kana and the ideographs of Japanese will
+/// not be split into different runs.
+fn font_script(script: Script) -> Script {
+  match script {
+    Script::Hiragana | Script::Katakana | Script::Bopomofo => Script::Han,
+    s => s,
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -85,4 +250,97 @@ mod tests {
     reorder.end_frame();
     assert!(reorder.get_cache(&text).is_none());
   }
+
+  #[test]
+  fn split_runs_by_script() {
+    let mut reorder = TextReorder::default();
+    let text: Substr = "abc 日本語のテキスト, 한국어 123".into();
+    let result = reorder.reorder_text(&text);
+    let para = &result.paras[0];
+
+    let runs = para
+      .runs
+      .iter()
+      .map(|r| &text[r.clone()])
+      .collect::<Vec<_>>();
+    assert_eq!(runs, ["abc ", "日本語のテキスト, ", "한국어 123"]);
+    assert_eq!(para.scripts, [Script::Latin, Script::Han, Script::Hangul]);
+  }
+
+  #[test]
+  fn rtl_script_runs_in_visual_order() {
+    let mut reorder = TextReorder::default();
+    let text: Substr = "אבג عربي".into();
+    let result = reorder.reorder_text(&text);
+    let para = &result.paras[0];
+    assert_eq!(para.scripts, [Script::Arabic, Script::Hebrew]);
+    assert_eq!(para.runs, [7..15, 0..7]);
+  }
+
+  #[test]
+  fn reorder_by_paragraph() {
+    let mut reorder = TextReorder::default();
+    let text: Substr = "abc\nאבג".into();
+    let result = reorder.reorder_text(&text).clone();
+    assert_eq!(result.paras.len(), 2);
+    assert_eq!(result.paras[1].range, 4..10);
+    assert_eq!(result.paras[1].runs.len(), 1);
+    assert_eq!(result.paras[1].runs[0], 4..10);
+    assert!(result.paras[1].level(4).is_rtl());
+    assert!(reorder.get_cache(&"abc\n".into()).is_some());
+
+    reorder.end_frame();
+    reorder.end_frame();
+    assert!(reorder.get_cache(&"abc\n".into()).is_none());
+  }
+
+  #[test]
+  fn reorder_rope() {
+    let edits = [
+      ("a\nb\nc", 2..3, "x", 1..2),
+      ("a\nb", 3..3, "\n", 1..3),
+      ("a\n", 0..1, "b", 0..1),
+      ("a\nb", 0..3, "", 0..1),
+      ("", 0..0, "a\nb", 0..2),
+      ("a\nb\nc", 2..4, "", 1..2),
+      ("a\nאבג", 8..8, "ד", 1..2),
+    ];
+    for (prev, range, insert, edited) in edits {
+      let mut reorder = TextReorder::default();
+      let mut rope = Rope::from(prev);
+      let (prev_result, rg) = reorder.reorder_rope(&rope, None);
+      assert_eq!(rg, 0..rope.paragraph_count());
+      rope.replace(range, insert);
+      let (result, rg) = reorder.reorder_rope(&rope, Some(&prev_result));
+      assert_eq!(rg, edited, "{prev:?} to {rope:?}");
+      // The paragraphs not reordered again are kept from the previous result.
+      let (tail, prev_len) = (result.sources.len() - rg.end, prev_result.sources.len());
+      let mut kept = result.sources[..rg.start]
+        .iter()
+        .zip(&prev_result.sources[..rg.start])
+        .chain(
+          result.sources[rg.end..]
+            .iter()
+            .zip(&prev_result.sources[prev_len - tail..]),
+        );
+      assert!(kept.all(|(a, b)| a.0.ptr_eq(&b.0) && Sc::ptr_eq(&a.1, &b.1)));
+
+      let text: Substr = rope.to_string().into();
+      let expected = TextReorder::default().reorder_text(&text).clone();
+      let ranges = |r: &ReorderResult| {
+        r.paras
+          .iter()
+          .map(|p| p.range.clone())
+          .collect::<Vec<_>>()
+      };
+      assert_eq!(ranges(&result), ranges(&expected), "{prev:?} to {rope:?}");
+    }
+
+    let mut reorder = TextReorder::default();
+    let rope = Rope::from("a\nb");
+    let (result, _) = reorder.reorder_rope(&rope, None);
+    let (same, rg) = reorder.reorder_rope(&rope.clone(), Some(&result));
+    assert_eq!(rg, 2..2);
+    assert!(Sc::ptr_eq(&same, &result));
+  }
 }
@@ -1,5 +1,6 @@
 use std::ops::Range;
 
+use ribir_algo::{Rope, is_paragraph_separator, split_paragraphs};
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::GraphemeCursor;
@@ -23,7 +24,7 @@ pub struct TextWriter<T = GraphemeCursor>
 where
   T: CharacterCursor,
 {
-  text: String,
+  text: Rope,
   cursor: T,
 }
 
@@ -31,9 +32,9 @@ impl<T> TextWriter<T>
 where
   T: CharacterCursor,
 {
-  pub fn new(text: String, cursor: T) -> Self { Self { text, cursor } }
+  pub fn new(text: Rope, cursor: T) -> Self { Self { text, cursor } }
 
-  pub fn text(&self) -> &str { &self.text }
+  pub fn text(&self) -> &Rope { &self.text }
 
   pub fn byte_offset(&self) -> usize { self.cursor.byte_offset() }
 
@@ -42,10 +43,17 @@ where
     self.cursor.reset(byte_offset);
   }
 
-  pub fn move_by_char(&mut self, offset: isize) { self.cursor.move_by_char(&self.text, offset); }
+  pub fn move_by_char(&mut self, offset: isize) {
+    for _ in 0..offset.unsigned_abs() {
+      let moved = if offset > 0 { self.move_to_next() } else { self.move_to_prev() };
+      if !moved {
+        return;
+      }
+    }
+  }
 
   pub fn insert_chars(&mut self, s: &str) {
-    self.text.insert_str(self.cursor.byte_offset(), s);
+    self.text.insert(self.cursor.byte_offset(), s);
     self
       .cursor
       .reset(self.cursor.byte_offset() + s.len());
@@ -56,7 +64,11 @@ where
       return;
     }
     let idx = self.cursor.byte_offset();
-    let len = self.cursor.measure_bytes(&self.text, idx, 1);
+    let para = self.text.paragraph_at(idx);
+    let start = self.text.paragraph_range(para).start;
+    let len = self
+      .cursor
+      .measure_bytes(self.text.paragraph(para), idx - start, 1);
 
     self.delete_byte_range(&Range { start: idx, end: idx + len })
   }
@@ -72,29 +84,31 @@ where
       return false;
     }
 
-    self.cursor.next(&self.text)
+    let para = self.text.paragraph_at(self.cursor.byte_offset());
+    self.in_paragraph(para, |cursor, text| cursor.next(text))
   }
 
   pub fn move_to_prev(&mut self) -> bool {
     if self.cursor.byte_offset() == 0 {
       return false;
     }
-    self.cursor.prev(&self.text)
+    let para = self
+      .text
+      .paragraph_at(self.cursor.byte_offset() - 1);
+    self.in_paragraph(para, |cursor, text| cursor.prev(text))
   }
 
   pub fn is_at_last(&self) -> bool { self.text.len() <= self.cursor.byte_offset() }
 
   pub fn insert_str(&mut self, text: &str) {
-    self
-      .text
-      .insert_str(self.cursor.byte_offset(), text);
+    self.text.insert(self.cursor.byte_offset(), text);
     self
       .cursor
       .reset(self.cursor.byte_offset() + text.len());
   }
 
   pub fn delete_byte_range(&mut self, rg: &Range<usize>) {
-    self.text.drain(rg.clone());
+    self.text.remove(rg.clone());
 
     let cursor = self.cursor.byte_offset();
     let new_cursor = match cursor {
@@ -104,6 +118,20 @@ where
     };
     self.cursor.reset(new_cursor);
   }
+
+  /// Move the cursor in the paragraph at `para`, no character crosses the
+  /// paragraphs, so only the paragraph is needed.
+  fn in_paragraph(&mut self, para: usize, f: impl FnOnce(&mut T, &str) -> bool) -> bool {
+    let start = self.text.paragraph_range(para).start;
+    self
+      .cursor
+      .reset(self.cursor.byte_offset() - start);
+    let moved = f(&mut self.cursor, self.text.paragraph(para));
+    self
+      .cursor
+      .reset(self.cursor.byte_offset() + start);
+    moved
+  }
 }
 
 pub fn select_word(text: &str, cluster: usize) -> Range<usize> {
@@ -141,6 +169,35 @@ pub fn select_prev_word(text: &str, cluster: usize, skip_whitespace: bool) -> Ra
   Range { start: 0, end: 0 }
 }
 
+/// The start of the paragraph where the `cluster` is, or the start of the
+/// previous paragraph if the `cluster` is already at the start.
+pub fn prev_paragraph_start(text: &str, cluster: usize) -> usize {
+  let mut start = 0;
+  let mut prev = 0;
+  for p in split_paragraphs(text) {
+    if cluster <= start {
+      break;
+    }
+    prev = start;
+    start += p.len();
+  }
+  prev
+}
+
+/// The end of the paragraph where the `cluster` is, before its separator, or
+/// the end of the next paragraph if the `cluster` is already at the end.
+pub fn next_paragraph_end(text: &str, cluster: usize) -> usize {
+  let mut start = 0;
+  for p in split_paragraphs(text) {
+    let end = start + p.trim_end_matches(is_paragraph_separator).len();
+    if cluster < end {
+      return end;
+    }
+    start += p.len();
+  }
+  text.len()
+}
+
 #[cfg(test)]
 mod tests {
   use crate::text::select_prev_word;
@@ -203,4 +260,39 @@ mod tests {
     // hel
     assert_eq!(select_prev_word("hello,   my number is 123456", 3, false), 0..3);
   }
+
+  #[test]
+  fn write_across_paragraphs() {
+    use super::TextWriter;
+    use crate::GraphemeCursor;
+
+    let mut writer = TextWriter::new("ab\r\ncd".into(), GraphemeCursor(0));
+    writer.move_by_char(3);
+    assert_eq!(writer.byte_offset(), 4);
+    writer.back_space();
+    assert_eq!(writer.byte_offset(), 2);
+    assert_eq!(writer.text().to_string(), "abcd");
+
+    writer.insert_str("\n");
+    assert_eq!(writer.text().paragraph_count(), 2);
+    writer.move_by_char(-2);
+    writer.del_char();
+    assert_eq!(writer.text().to_string(), "a\ncd");
+    assert_eq!(writer.byte_offset(), 1);
+  }
+
+  #[test]
+  fn move_by_paragraph() {
+    use super::{next_paragraph_end, prev_paragraph_start};
+    let text = "ab\ncd\r\nef";
+    assert_eq!(prev_paragraph_start(text, 5), 3);
+    assert_eq!(prev_paragraph_start(text, 3), 0);
+    assert_eq!(prev_paragraph_start(text, 0), 0);
+    assert_eq!(prev_paragraph_start(text, 9), 7);
+
+    assert_eq!(next_paragraph_end(text, 0), 2);
+    assert_eq!(next_paragraph_end(text, 2), 5);
+    assert_eq!(next_paragraph_end(text, 5), 9);
+    assert_eq!(next_paragraph_end("ab\n", 2), 3);
+  }
 }
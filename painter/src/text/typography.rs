@@ -6,7 +6,10 @@ use smallvec::{SmallVec, smallvec};
 use unicode_script::{Script, UnicodeScript};
 use unicode_segmentation::UnicodeSegmentation;
 
-use crate::{Glyph, GlyphUnit, Overflow, TextAlign, shaper::ShapeResult};
+use crate::{
+  Glyph, GlyphOrientation, GlyphUnit, Overflow, TextAlign,
+  shaper::{PLACEHOLDER_GLYPH_ID, ShapeResult, TAB_GLYPH_ID},
+};
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PlaceLineDirection {
@@ -44,6 +47,10 @@ pub struct VisualLine {
   pub y: GlyphUnit,
   pub height: GlyphUnit,
   pub width: GlyphUnit,
+  /// The distance from the start edge of the line to its baseline, it's the
+  /// alphabetic baseline in a horizontal line and the central baseline in a
+  /// vertical line.
+  pub baseline: GlyphUnit,
   /// The glyph position is relative the line x/y
   pub glyphs: Vec<Glyph>,
 }
@@ -235,6 +242,9 @@ where
       let mut word = word.peekable();
       while let Some(g) = word.peek() {
         let mut at = (*g).clone();
+        if at.glyph_id == TAB_GLYPH_ID {
+          self.set_tab_advance(&mut at, &run.tab_stops, cursor.position());
+        }
 
         cursor.advance_glyph(&mut at, line_offset, text);
 
@@ -254,6 +264,19 @@ where
     }
   }
 
+  /// Let the tab glyph at the `position` of the line advance to the next tab
+  /// stop.
+  fn set_tab_advance(&self, g: &mut Glyph, tab_stops: &TabStops, position: GlyphUnit) {
+    let advance = tab_stops.next_stop(position) - position;
+    if self.line_dir.is_horizontal() {
+      g.y_offset = GlyphUnit::ZERO;
+      g.y_advance = advance;
+    } else {
+      g.x_offset = GlyphUnit::ZERO;
+      g.x_advance = advance;
+    }
+  }
+
   fn push_glyph(&mut self, g: Glyph) {
     let line = self.visual_lines.last_mut();
     line.unwrap().glyphs.push(g)
@@ -264,12 +287,34 @@ where
   fn end_line(&mut self) {
     let line = self.visual_lines.last_mut().unwrap();
     // we will reorder the line after consumed all inputs.
-    if self.line_dir.is_horizontal() {
+    // A placeholder may be higher than the line or over the top of the line, the
+    // line grows to contain it.
+    let vertical = self.line_dir.is_horizontal();
+    let (mut start, mut end) = (GlyphUnit::ZERO, self.line_height);
+    for g in line
+      .glyphs
+      .iter()
+      .filter(|g| g.glyph_id == PLACEHOLDER_GLYPH_ID)
+    {
+      let (offset, size) =
+        if vertical { (g.x_offset, g.x_advance) } else { (g.y_offset, g.y_advance) };
+      start = start.min(offset);
+      end = end.max(offset + size);
+    }
+    if start < GlyphUnit::ZERO {
+      line
+        .glyphs
+        .iter_mut()
+        .for_each(|g| if vertical { g.x_offset -= start } else { g.y_offset -= start });
+    }
+    if vertical {
       line.height = self.inline_cursor;
-      line.width = self.line_height;
+      line.width = end - start;
+      line.baseline = GlyphUnit::STANDARD_EM / 2. - start;
     } else {
       line.width = self.inline_cursor;
-      line.height = self.line_height;
+      line.height = end - start;
+      line.baseline = GlyphUnit::STANDARD_EM - start;
     }
 
     self.over_bounds |= self.is_over_line_bound(self.inline_cursor);
@@ -306,12 +351,52 @@ where
   }
 }
 
+/// An inline placeholder in the units relative to the standard em.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlaceholderBox {
+  pub width: GlyphUnit,
+  pub height: GlyphUnit,
+  /// The distance from the top of the placeholder to the baseline.
+  pub baseline_offset: GlyphUnit,
+}
+
+/// The tab stops in the units relative to the standard em.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TabStops {
+  /// The custom tab stops relative to the start of the line in ascending
+  /// order.
+  pub stops: Box<[GlyphUnit]>,
+  /// The distance between the tab stops after the custom ones.
+  pub interval: GlyphUnit,
+}
+
+impl TabStops {
+  /// Return the first tab stop after the `position`, or the `position` itself
+  /// if there is no tab stop after it.
+  pub fn next_stop(&self, position: GlyphUnit) -> GlyphUnit {
+    if let Some(stop) = self.stops.iter().find(|s| position < **s) {
+      return *stop;
+    }
+    let start = self.stops.last().copied().unwrap_or_default();
+    if self.interval <= GlyphUnit::ZERO {
+      return position;
+    }
+    let count = (position - start).0 / self.interval.0 + 1;
+    start + self.interval * count
+  }
+}
+
 pub struct InputRun {
   pub(crate) shape_result: Sc<ShapeResult>,
   /// The factor relative to the standard size.
   pub(crate) font_size_factor: f32,
   pub(crate) letter_space: GlyphUnit,
   pub(crate) range: Range<usize>,
+  /// The run is shaped horizontally, but set sideways in a vertical line.
+  pub(crate) sideways: bool,
+  /// The placeholders in this run, with their cluster relative to the run.
+  pub(crate) placeholders: Vec<(u32, PlaceholderBox)>,
+  pub(crate) tab_stops: TabStops,
   reorder_text: String,
 }
 
@@ -427,8 +512,34 @@ pub(crate) fn text_align_offset(
   }
 }
 
+/// Turn the glyph to a placeholder glyph, in a horizontal line the placeholder
+/// is aligned with the baseline, and in a vertical line it's set at the start
+/// of the line.
+fn placeholder_glyph(mut g: Glyph, p: &PlaceholderBox) -> Glyph {
+  g.glyph_id = PLACEHOLDER_GLYPH_ID;
+  g.x_advance = p.width;
+  g.y_advance = p.height;
+  match g.orientation {
+    GlyphOrientation::Horizontal => {
+      g.x_offset = GlyphUnit::ZERO;
+      // The baseline is one em below the top of the line.
+      g.y_offset = GlyphUnit::STANDARD_EM - p.baseline_offset;
+    }
+    GlyphOrientation::Upright | GlyphOrientation::Sideways => {
+      g.x_offset = GlyphUnit::ZERO;
+      g.y_offset = GlyphUnit::ZERO;
+      g.orientation = GlyphOrientation::Upright;
+    }
+  }
+  g
+}
+
 /// Check if a char support apply letter spacing.
 fn letter_spacing_char(c: char) -> bool {
+  if c == '\t' {
+    // The tab advances to a tab stop, the space after it will break the stop.
+    return false;
+  }
   let script = c.script();
   // The list itself is from: https://github.com/harfbuzz/harfbuzz/issues/64
   !matches!(
@@ -466,14 +577,42 @@ impl InputRun {
       .iter()
       .filter_map(|gh| text[gh.cluster as usize..].chars().next())
       .collect();
-    Self { shape_result, font_size_factor, letter_space, range, reorder_text }
+    Self {
+      shape_result,
+      font_size_factor,
+      letter_space,
+      range,
+      sideways: false,
+      placeholders: vec![],
+      tab_stops: <_>::default(),
+      reorder_text,
+    }
+  }
+
+  /// Replace the glyphs at the clusters with the placeholders.
+  pub(crate) fn with_placeholders(mut self, placeholders: Vec<(u32, PlaceholderBox)>) -> Self {
+    self.placeholders = placeholders;
+    self
+  }
+
+  /// Set the tab stops to advance the tab characters of this run.
+  pub(crate) fn with_tab_stops(mut self, tab_stops: TabStops) -> Self {
+    self.tab_stops = tab_stops;
+    self
+  }
+
+  /// Set the glyphs of this run sideways, the run should be shaped
+  /// horizontally.
+  pub(crate) fn into_sideways(mut self) -> Self {
+    self.sideways = true;
+    self
   }
 
   #[inline]
   fn text(&self) -> &str { &self.shape_result.text }
 
   fn word_glyphs(&self) -> impl Iterator<Item = impl Iterator<Item = Glyph> + Clone + '_> + '_ {
-    let Self { reorder_text, font_size_factor, shape_result, .. } = self;
+    let Self { reorder_text, font_size_factor, shape_result, sideways, placeholders, .. } = self;
     let font_size = *font_size_factor * GlyphUnit::PIXELS_PER_EM as f32;
     reorder_text
       .split_word_bounds()
@@ -484,7 +623,13 @@ impl InputRun {
           w.chars()
             .enumerate()
             .filter_map(move |(idx, _)| shape_result.glyphs.get(base + idx))
-            .map(move |g| g.clone().cast_to(font_size)),
+            .map(move |g| {
+              let mut g = if *sideways { g.clone().into_sideways() } else { g.clone() };
+              if let Some((_, p)) = placeholders.iter().find(|(c, _)| *c == g.cluster) {
+                g = placeholder_glyph(g, p);
+              }
+              g.cast_to(font_size)
+            }),
         )
       })
   }
@@ -1,11 +1,13 @@
 use std::{cell::RefCell, ops::Range};
 
-use ribir_algo::{FrameCache, Sc, Substr};
+use ribir_algo::{FrameCache, Rope, Sc, Substr};
 use ribir_geom::{Point, Rect, Size};
+use smallvec::SmallVec;
+use unicode_script::UnicodeScript;
 
 use crate::{
   font_db::FontDB,
-  shaper::{NEWLINE_GLYPH_ID, TextShaper},
+  shaper::{NEWLINE_GLYPH_ID, PLACEHOLDER_GLYPH_ID, ShapeResult, TextShaper},
   text_reorder::ReorderResult,
   typography::*,
   *,
@@ -16,7 +18,16 @@ struct RunKey {
   pub ids: Box<[ID]>,
   pub line_height: GlyphUnit,
   pub letter_space: GlyphUnit,
-  pub text: Substr,
+  pub tab_stops: TabStops,
+  pub text: LayoutText,
+}
+
+/// The text to lay out. A rope is the same text as its clones, it's compared
+/// by the identity, so the text of a rope is not hashed or compared.
+#[derive(Clone)]
+enum LayoutText {
+  Str(Substr),
+  Rope(Rope),
 }
 
 /// Typography `text` relative to 1em.
@@ -29,6 +40,20 @@ pub struct TypographyKey {
   text_align: TextAlign,
   line_dir: PlaceLineDirection,
   overflow: Overflow,
+  /// The placeholders and the byte index of them in the text.
+  placeholders: Box<[(u32, PlaceholderBox)]>,
+  /// The `FontDB::fallback_generation` the runs are shaped with.
+  fallback_generation: usize,
+}
+
+/// A run shaped by the typography, kept to reuse when the text is edited.
+#[derive(Clone)]
+struct ShapedRun {
+  ids: Box<[ID]>,
+  dir: TextDirection,
+  /// The byte range of the run in the text.
+  range: Range<usize>,
+  result: Sc<ShapeResult>,
 }
 
 /// Do simple text typography and cache it.
@@ -36,7 +61,35 @@ pub struct TypographyStore {
   reorder: TextReorder,
   shaper: TextShaper,
   font_db: Sc<RefCell<FontDB>>,
-  cache: FrameCache<TypographyKey, Sc<VisualInfos>>,
+  cache: FrameCache<TypographyKey, (Sc<VisualInfos>, Sc<Vec<ShapedRun>>)>,
+}
+
+/// The metrics of a laid out text in logical pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextMetrics {
+  /// The size of the text.
+  pub size: Size,
+  /// The metrics of the lines in the order they are placed.
+  pub lines: Vec<LineMetrics>,
+}
+
+/// The metrics of a line of text in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineMetrics {
+  /// The rect of the line relative to the text.
+  pub rect: Rect,
+  /// The position of the baseline relative to the text, it's the y of the
+  /// alphabetic baseline for horizontal text, and the x of the central
+  /// baseline for vertical text.
+  pub baseline: f32,
+}
+
+impl TextMetrics {
+  #[inline]
+  pub fn line_count(&self) -> usize { self.lines.len() }
+
+  /// The baseline of the first placed line, or zero if there is no line.
+  pub fn baseline(&self) -> f32 { self.lines.first().map_or(0., |l| l.baseline) }
 }
 
 #[derive(Clone)]
@@ -46,6 +99,8 @@ pub struct VisualGlyphs {
   y: GlyphUnit,
   visual_info: Sc<VisualInfos>,
   order_info: Sc<ReorderResult>,
+  text: LayoutText,
+  shaped: Sc<Vec<ShapedRun>>,
 }
 
 impl VisualGlyphs {
@@ -67,7 +122,8 @@ impl VisualGlyphs {
     if line_dir == PlaceLineDirection::BottomToTop {
       y += bound_height - visual_info.visual_height
     }
-    Self { font_size, x, y, visual_info, order_info }
+    let text = LayoutText::Str("".into());
+    Self { font_size, x, y, visual_info, order_info, text, shaped: <_>::default() }
   }
 }
 
@@ -89,7 +145,38 @@ impl TypographyStore {
     &mut self, text: Substr, style: &TextStyle, bounds: Size, text_align: TextAlign,
     line_dir: PlaceLineDirection,
   ) -> VisualGlyphs {
-    let TextStyle { font_size, ref font_face, letter_space, line_height, overflow } = *style;
+    self.typography_with_placeholders(text, style, bounds, text_align, line_dir, &[])
+  }
+
+  /// Do a simply typography like `typography` for the text of the `rope`. The
+  /// paragraphs shared with the rope laid out as `prev` keep their reordered
+  /// and shaped results, so only the paragraphs edited from it are reordered
+  /// and shaped again, and the text is never copied out of the rope.
+  pub fn typography_rope(
+    &mut self, rope: &Rope, prev: Option<&VisualGlyphs>, style: &TextStyle, bounds: Size,
+    text_align: TextAlign, line_dir: PlaceLineDirection,
+  ) -> VisualGlyphs {
+    let text = LayoutText::Rope(rope.clone());
+    self.layout(text, style, bounds, text_align, line_dir, &[], prev)
+  }
+
+  /// Do a simply typography like `typography`, and reserve the space of the
+  /// `placeholders` at the `PLACEHOLDER_CHAR`s of the text in order. Use
+  /// `VisualGlyphs::placeholder_rects` to get where they are placed.
+  pub fn typography_with_placeholders(
+    &mut self, text: Substr, style: &TextStyle, bounds: Size, text_align: TextAlign,
+    line_dir: PlaceLineDirection, placeholders: &[InlinePlaceholder],
+  ) -> VisualGlyphs {
+    let text = LayoutText::Str(text);
+    self.layout(text, style, bounds, text_align, line_dir, placeholders, None)
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  fn layout(
+    &mut self, text: LayoutText, style: &TextStyle, bounds: Size, text_align: TextAlign,
+    line_dir: PlaceLineDirection, placeholders: &[InlinePlaceholder], prev: Option<&VisualGlyphs>,
+  ) -> VisualGlyphs {
+    let TextStyle { font_size, ref font_face, letter_space, line_height, overflow, .. } = *style;
     // Since we cache the result of the standard font size, we must ensure that all
     // variables are cast relative to this standard font size.
     let scale = font_size / GlyphUnit::PIXELS_PER_EM as f32;
@@ -102,45 +189,193 @@ impl TypographyStore {
     let line_height =
       GlyphUnit::from_pixel(line_height / font_size * GlyphUnit::PIXELS_PER_EM as f32);
 
-    let info = self.reorder.reorder_text(&text).clone();
-    let ids = self
-      .font_db
-      .borrow_mut()
-      .select_all_match(font_face)
-      .into_boxed_slice();
-    let runs = [RunKey { ids, line_height, letter_space, text }].into();
-    let key = TypographyKey::new(runs, bounds, text_align, line_dir, overflow);
-    let infos = if let Some(infos) = self.cache.get(&key).cloned() {
-      infos
+    let (info, edited) = match &text {
+      LayoutText::Str(text) => (self.reorder.reorder_text(text).clone(), None),
+      LayoutText::Rope(rope) => {
+        let (info, edited) = self
+          .reorder
+          .reorder_rope(rope, prev.map(|p| &p.order_info));
+        (info, prev.map(|prev| (prev, edited)))
+      }
+    };
+    let (ids, fallback_generation) = {
+      let mut font_db = self.font_db.borrow_mut();
+      (font_db.select_all_match(font_face).into_boxed_slice(), font_db.fallback_generation())
+    };
+    let to_unit = |v: f32| GlyphUnit::from_pixel(v / scale);
+    let placeholders = match &text {
+      LayoutText::Str(text) if !placeholders.is_empty() => text
+        .match_indices(PLACEHOLDER_CHAR)
+        .zip(placeholders)
+        .map(|((idx, _), p)| {
+          let InlinePlaceholder { size, baseline_offset } = *p;
+          let width = to_unit(size.width);
+          let height = to_unit(size.height);
+          (idx as u32, PlaceholderBox { width, height, baseline_offset: to_unit(baseline_offset) })
+        })
+        .collect(),
+      _ => Box::default(),
+    };
+    // A rope is not scanned for the tabs, its tab stops are always ready.
+    let has_tab = match &text {
+      LayoutText::Str(text) => text.contains('\t'),
+      LayoutText::Rope(_) => true,
+    };
+    let tab_stops = if has_tab {
+      let interval = match style.tab_size {
+        TabSize::Spaces(count) => {
+          let space = self
+            .shaper
+            .shape_text(&" ".into(), &ids, TextDirection::LeftToRight);
+          let advance = space
+            .glyphs
+            .first()
+            .map_or(GlyphUnit::ZERO, |g| g.x_advance);
+          advance * count as i32
+        }
+        TabSize::Pixels(pixels) => to_unit(pixels),
+      };
+      let stops = style.tab_stops.iter().map(|s| to_unit(*s)).collect();
+      TabStops { stops, interval }
+    } else {
+      TabStops::default()
+    };
+    let glyphs_text = text.clone();
+    let runs = [RunKey { ids, line_height, letter_space, tab_stops, text }].into();
+    let key = TypographyKey::new(
+      runs,
+      bounds,
+      text_align,
+      line_dir,
+      overflow,
+      placeholders,
+      fallback_generation,
+    );
+    let (infos, shaped) = if let Some(cached) = self.cache.get(&key).cloned() {
+      cached
     } else {
+      let mut shaped = vec![];
       let ids = &key.runs[0].ids;
-      let text = &key.runs[0].text;
+      // A rope is always reordered by its paragraphs, the whole text is only
+      // needed by a text reordered as one paragraph.
+      let text = match &key.runs[0].text {
+        LayoutText::Str(text) => text.clone(),
+        LayoutText::Rope(_) => "".into(),
+      };
+      let font_db = self.font_db.clone();
+      let vertical = line_dir.is_horizontal();
+      // The runs of a paragraph are shaped in the text of the paragraph, so the
+      // results kept after an edit only hold the paragraphs not edited.
+      let mut sources = ReorderResult::paragraphs(&info, &text)
+        .enumerate()
+        .scan(0, |start, (idx, (p_text, _))| {
+          let p_start = *start;
+          *start += p_text.len();
+          Some((idx, p_start, p_text))
+        })
+        .peekable();
+      let mut source = sources.next().unwrap();
+      // The cursors of the runs kept from the head and the tail of the text
+      // laid out before the edit.
+      let mut head_cursor = 0;
+      let mut tail_cursor = None;
       let inputs = info.paras.iter().map(|p| {
-        p.runs
-          .iter()
-          .map(|r| {
-            let dir = if r.is_empty() || p.levels[r.start].is_ltr() {
+        while let Some(next) = sources.next_if(|(_, start, _)| *start <= p.range.start) {
+          source = next;
+        }
+        let (p_idx, p_start, p_text) = source;
+        let mut runs = SmallVec::new();
+        for (r, script) in p.runs.iter().zip(p.scripts.iter()) {
+          let is_ltr = r.is_empty() || p.level(r.start).is_ltr();
+          let no_fallback = font_db
+            .borrow()
+            .fallback_families(*script)
+            .is_empty();
+          let fallback_ids;
+          let ids = if no_fallback {
+            &ids[..]
+          } else {
+            fallback_ids = font_db
+              .borrow_mut()
+              .select_script_match(font_face, *script);
+            &fallback_ids[..]
+          };
+
+          let mut sub_runs = orientation_runs(p_text, r.clone(), p_start, vertical);
+          if !is_ltr {
+            sub_runs.reverse();
+          }
+          for (r, upright) in sub_runs {
+            let dir = if upright {
+              TextDirection::TopToBottom
+            } else if is_ltr {
               TextDirection::LeftToRight
             } else {
               TextDirection::RightToLeft
             };
-
-            let shape_result = self
-              .shaper
-              .shape_text(&text.substr(r.clone()), ids, dir);
-            InputRun::new(shape_result, 1., letter_space, r.clone())
-          })
-          .collect()
+            let kept = match &edited {
+              Some((prev, rg)) if p_idx < rg.start => {
+                head_cursor += 1;
+                prev.shaped.get(head_cursor - 1).filter(|s| s.range == r)
+              }
+              Some((prev, rg)) if p_idx >= rg.end => {
+                let prev_start = |start: usize| start + prev.text.len() - key.runs[0].text.len();
+                let cursor = tail_cursor.get_or_insert_with(|| {
+                  let prev_p_start = prev_start(p_start);
+                  prev
+                    .shaped
+                    .partition_point(|s| s.range.start < prev_p_start)
+                });
+                *cursor += 1;
+                let prev_r = prev_start(r.start)..prev_start(r.end);
+                prev
+                  .shaped
+                  .get(*cursor - 1)
+                  .filter(|s| s.range == prev_r)
+              }
+              _ => None,
+            };
+            let shape_result = match kept.filter(|s| s.dir == dir && *s.ids == *ids) {
+              Some(kept) => kept.result.clone(),
+              None => self.shaper.shape_text(
+                &p_text.substr(r.start - p_start..r.end - p_start),
+                ids,
+                dir,
+              ),
+            };
+            shaped.push(ShapedRun {
+              ids: ids.into(),
+              dir,
+              range: r.clone(),
+              result: shape_result.clone(),
+            });
+            let run_placeholders = key
+              .placeholders
+              .iter()
+              .filter(|(idx, _)| r.contains(&(*idx as usize)))
+              .map(|(idx, p)| (*idx - r.start as u32, *p))
+              .collect();
+            let run = InputRun::new(shape_result, 1., letter_space, r)
+              .with_placeholders(run_placeholders)
+              .with_tab_stops(key.runs[0].tab_stops.clone());
+            runs.push(if vertical && !upright { run.into_sideways() } else { run });
+          }
+        }
+        runs
       });
 
       let t_man = TypographyMan::new(inputs, line_dir, text_align, line_height, bounds, overflow);
       let visual_info = t_man.typography_all();
-      let infos = Sc::new(visual_info);
-      self.cache.put(key, infos.clone());
-      infos
+      let cached = (Sc::new(visual_info), Sc::new(shaped));
+      self.cache.put(key, cached.clone());
+      cached
     };
 
-    VisualGlyphs::new(font_size, line_dir, info, bounds.width, bounds.height, infos.clone())
+    let mut glyphs =
+      VisualGlyphs::new(font_size, line_dir, info, bounds.width, bounds.height, infos);
+    glyphs.text = glyphs_text;
+    glyphs.shaped = shaped;
+    glyphs
   }
 
   pub fn font_db(&self) -> &Sc<RefCell<FontDB>> { &self.font_db }
@@ -161,6 +396,9 @@ impl VisualGlyphs {
     let scale = self.font_size / GlyphUnit::PIXELS_PER_EM as f32;
     let x = GlyphUnit::from_pixel(offset_x / scale) - self.x;
     let y = GlyphUnit::from_pixel(offset_y / scale) - self.y;
+    if self.visual_info.line_dir.is_horizontal() {
+      return self.nearest_vertical_glyph(x, y);
+    }
     let mut bottom = self.visual_info.visual_height;
 
     let mut iter = self
@@ -189,6 +427,30 @@ impl VisualGlyphs {
     (0, 0)
   }
 
+  /// Find the nearest glyph in vertical lines, the lines are placed
+  /// horizontally.
+  fn nearest_vertical_glyph(&self, x: GlyphUnit, y: GlyphUnit) -> (usize, usize) {
+    let lines = &self.visual_info.visual_lines;
+    let Some((row, line)) = lines
+      .iter()
+      .enumerate()
+      .rev()
+      .find(|(_, l)| l.x <= x)
+      .or_else(|| lines.iter().enumerate().next())
+    else {
+      return (0, 0);
+    };
+    let idx = line
+      .glyphs
+      .iter()
+      .enumerate()
+      .rev()
+      .find(|(_, g)| GlyphUnit::ZERO < g.y_advance && g.y_offset <= y)
+      .map(|(i, _)| i)
+      .unwrap_or(0);
+    (row, idx)
+  }
+
   pub fn position_by_cluster(&self, cluster: usize) -> (usize, usize) {
     struct RangeLocator<'a> {
       ranges: Vec<(&'a Range<usize>, usize)>,
@@ -230,7 +492,7 @@ impl VisualGlyphs {
     let order_info = &self.order_info.paras[para];
     let locator = RangeLocator::from_unorder_ranges(order_info.runs.iter());
     let dst_run = locator.range_index(cluster);
-    let is_ltr = dst_run.map_or(true, |run| order_info.levels[order_info.runs[run].start].is_ltr());
+    let is_ltr = dst_run.map_or(true, |run| order_info.level(order_info.runs[run].start).is_ltr());
     let is_layout_before = |glyph_cluster: usize| {
       if dst_run.is_none() {
         return true;
@@ -312,18 +574,19 @@ impl VisualGlyphs {
           Size::new(0., self.to_pixel_value(line.height)),
         ),
       },
-      |glyph| {
-        let orign = Point::new(
-          self.to_pixel_value(glyph.x_offset + line.x),
-          self.to_pixel_value(glyph.y_offset + line.y),
-        );
-        let size = match line_dir.is_horizontal() {
-          true => Size::new(self.to_pixel_value(line.width), self.to_pixel_value(glyph.y_advance)),
-          false => {
-            Size::new(self.to_pixel_value(glyph.x_advance), self.to_pixel_value(line.height))
-          }
-        };
-        Rect::new(orign, size)
+      |glyph| match line_dir.is_horizontal() {
+        // The glyph in a vertical line may not start at the line, but its cell does.
+        true => Rect::new(
+          Point::new(self.to_pixel_value(line.x), self.to_pixel_value(glyph.y_offset + line.y)),
+          Size::new(self.to_pixel_value(line.width), self.to_pixel_value(glyph.y_advance)),
+        ),
+        false => Rect::new(
+          Point::new(
+            self.to_pixel_value(glyph.x_offset + line.x),
+            self.to_pixel_value(glyph.y_offset + line.y),
+          ),
+          Size::new(self.to_pixel_value(glyph.x_advance), self.to_pixel_value(line.height)),
+        ),
       },
     );
     rc.origin += Point::new(self.to_pixel_value(self.x), self.to_pixel_value(self.y)).to_vector();
@@ -353,6 +616,13 @@ impl VisualGlyphs {
           self.cur = Some(next);
         }
       }
+      fn join_y(&mut self, next: Rect) {
+        if let Some(rc) = &mut self.cur {
+          rc.size.height = next.max_y() - rc.min_y();
+        } else {
+          self.cur = Some(next);
+        }
+      }
       fn new_rect(&mut self) {
         let cur = self.cur.take();
         if let Some(rc) = cur {
@@ -362,12 +632,24 @@ impl VisualGlyphs {
       fn rects(self) -> Vec<Rect> { self.acc }
     }
     let mut jointer = TypoRectJointer::new();
+    let vertical = self.visual_info.line_dir.is_horizontal();
     for line in &self.visual_info.visual_lines {
       let height = self.to_pixel_value(line.height);
       let offset_x = self.x + line.x;
       let offset_y = self.y + line.y;
       for glyph in &line.glyphs {
         if rg.contains(&(glyph.cluster as usize)) {
+          if vertical {
+            let rc = Rect::new(
+              Point::new(
+                self.to_pixel_value(offset_x),
+                self.to_pixel_value(glyph.y_offset + offset_y),
+              ),
+              Size::new(self.to_pixel_value(line.width), self.to_pixel_value(glyph.y_advance)),
+            );
+            jointer.join_y(rc);
+            continue;
+          }
           let glyph = glyph.clone().cast_to(self.font_size);
           let rc = Rect::new(
             Point::new(
@@ -388,6 +670,41 @@ impl VisualGlyphs {
 
   fn to_pixel_value(&self, v: GlyphUnit) -> f32 { v.cast_to(self.font_size).into_pixel() }
 
+  /// Returns the cluster and the face that actually renders it of every glyph
+  /// in visual order, it's helpful to debug the font fallback.
+  pub fn glyph_faces(&self) -> impl Iterator<Item = (usize, ID)> + '_ {
+    self
+      .visual_info
+      .visual_lines
+      .iter()
+      .flat_map(|l| l.glyphs.iter())
+      .map(|g| (g.cluster as usize, g.face_id))
+  }
+
+  /// Return the rects of the placeholders in the order of the text.
+  pub fn placeholder_rects(&self) -> Vec<Rect> {
+    let mut rects: Vec<_> = self
+      .visual_info
+      .visual_lines
+      .iter()
+      .flat_map(|l| {
+        l.glyphs
+          .iter()
+          .filter(|g| g.glyph_id == PLACEHOLDER_GLYPH_ID)
+          .map(move |g| {
+            let origin = Point::new(
+              self.to_pixel_value(self.x + l.x + g.x_offset),
+              self.to_pixel_value(self.y + l.y + g.y_offset),
+            );
+            let size = Size::new(self.to_pixel_value(g.x_advance), self.to_pixel_value(g.y_advance));
+            (g.cluster, Rect::new(origin, size))
+          })
+      })
+      .collect();
+    rects.sort_by_key(|(cluster, _)| *cluster);
+    rects.into_iter().map(|(_, rc)| rc).collect()
+  }
+
   pub fn glyphs(&self) -> impl Iterator<Item = Glyph> + '_ {
     self
       .visual_info
@@ -459,12 +776,93 @@ impl VisualGlyphs {
   }
 
   pub fn glyph_row_count(&self) -> usize { self.visual_info.visual_lines.len() }
+
+  /// The direction the lines are placed, the text is vertical if the lines
+  /// are placed horizontally.
+  pub fn line_dir(&self) -> PlaceLineDirection { self.visual_info.line_dir }
+
+  /// Return the metrics of the text relative to the origin of the visual
+  /// rect.
+  pub fn metrics(&self) -> TextMetrics {
+    let info = &self.visual_info;
+    let vertical = info.line_dir.is_horizontal();
+    let lines = info
+      .visual_lines
+      .iter()
+      .map(|l| {
+        let origin = Point::new(self.to_pixel_value(l.x), self.to_pixel_value(l.y));
+        let size = Size::new(self.to_pixel_value(l.width), self.to_pixel_value(l.height));
+        let baseline = self.to_pixel_value(l.baseline);
+        let baseline = if vertical { origin.x + baseline } else { origin.y + baseline };
+        LineMetrics { rect: Rect::new(origin, size), baseline }
+      })
+      .collect();
+    TextMetrics { size: self.visual_rect().size, lines }
+  }
+}
+
+/// Split the `range` of the text to the runs that have the same orientation
+/// in vertical text, and return if every run is upright. The whole range is a
+/// sideways run if the text is not vertical. The `text` is the paragraph at
+/// the byte `offset` of the text that contains the `range`.
+fn orientation_runs(
+  text: &str, range: Range<usize>, offset: usize, vertical: bool,
+) -> SmallVec<[(Range<usize>, bool); 1]> {
+  let mut runs: SmallVec<[(Range<usize>, bool); 1]> = SmallVec::new();
+  if vertical {
+    for (idx, c) in text[range.start - offset..range.end - offset].char_indices() {
+      let start = range.start + idx;
+      let end = start + c.len_utf8();
+      let upright = unicode_help::is_upright_in_vertical(c);
+      match runs.last_mut() {
+        // The combining marks always follow their base character.
+        Some((r, last)) if *last == upright || c.script() == Script::Inherited => r.end = end,
+        _ => runs.push((start..end, upright)),
+      }
+    }
+  }
+  if runs.is_empty() {
+    runs.push((range, false));
+  }
+  runs
+}
+
+impl LayoutText {
+  fn len(&self) -> usize {
+    match self {
+      LayoutText::Str(text) => text.len(),
+      LayoutText::Rope(rope) => rope.len(),
+    }
+  }
+}
+
+impl PartialEq for LayoutText {
+  fn eq(&self, other: &Self) -> bool {
+    match (self, other) {
+      (LayoutText::Str(a), LayoutText::Str(b)) => a == b,
+      (LayoutText::Rope(a), LayoutText::Rope(b)) => a.ptr_eq(b),
+      _ => false,
+    }
+  }
+}
+
+impl Eq for LayoutText {}
+
+impl std::hash::Hash for LayoutText {
+  fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    match self {
+      LayoutText::Str(text) => text.hash(state),
+      // The same ropes have the same length and paragraphs.
+      LayoutText::Rope(rope) => (rope.len(), rope.paragraph_count()).hash(state),
+    }
+  }
 }
 
 impl TypographyKey {
   fn new(
     runs: Box<[RunKey]>, bounds: Size<GlyphUnit>, text_align: TextAlign,
-    line_dir: PlaceLineDirection, overflow: Overflow,
+    line_dir: PlaceLineDirection, overflow: Overflow, placeholders: Box<[(u32, PlaceholderBox)]>,
+    fallback_generation: usize,
   ) -> Self {
     let line_width = match overflow {
       // line width is not so important in clip mode, the cache can be use even with difference line
@@ -480,7 +878,7 @@ impl TypographyKey {
       }
     };
 
-    Self { runs, line_width, text_align, line_dir, overflow }
+    Self { runs, line_width, text_align, line_dir, overflow, placeholders, fallback_generation }
   }
 }
 
@@ -502,7 +900,17 @@ mod tests {
     FontFace { families: Box::new([FontFamily::Name("DejaVu Sans".into())]), ..<_>::default() }
   }
   fn text_style(font_size: f32, overflow: Overflow, letter_space: f32) -> TextStyle {
-    TextStyle { font_size, font_face: test_face(), letter_space, line_height: font_size, overflow }
+    TextStyle {
+      font_size,
+      font_face: test_face(),
+      letter_space,
+      line_height: font_size,
+      overflow,
+      writing_mode: WritingMode::HorizontalTb,
+      tab_size: TabSize::Spaces(8),
+      tab_stops: Box::new([]),
+      color_palette: 0,
+    }
   }
   fn zero_letter_space_style(font_size: f32, overflow: Overflow) -> TextStyle {
     text_style(font_size, overflow, 0.)
@@ -742,6 +1150,51 @@ mod tests {
     assert!(store.cache.is_empty());
   }
 
+  #[test]
+  fn keep_paragraphs_not_edited() {
+    let mut store = test_store();
+    let style = zero_letter_space_style(16., Overflow::Clip);
+    let layout = |store: &mut TypographyStore, rope: &Rope, prev: Option<&VisualGlyphs>| {
+      let bounds = Size::new(f32::MAX, f32::MAX);
+      let (align, dir) = (TextAlign::Start, PlaceLineDirection::TopToBottom);
+      store.typography_rope(rope, prev, &style, bounds, align, dir)
+    };
+    let mut rope = Rope::from("hello\nworld\nagain אבג");
+    let prev = layout(&mut store, &rope, None);
+    // The caches are dropped, the paragraphs are kept by the glyphs.
+    for _ in 0..3 {
+      store.end_frame();
+    }
+    rope.insert(6, "wonderful ");
+    let glyphs = layout(&mut store, &rope, Some(&prev));
+
+    let (prev_paras, paras) = (&prev.order_info.sources, &glyphs.order_info.sources);
+    assert_eq!(paras.len(), 3);
+    assert!(Sc::ptr_eq(&prev_paras[0].1, &paras[0].1));
+    assert!(!Sc::ptr_eq(&prev_paras[1].1, &paras[1].1));
+    assert!(Sc::ptr_eq(&prev_paras[2].1, &paras[2].1));
+    let kept = |g: &VisualGlyphs, start| {
+      g.shaped
+        .iter()
+        .filter(|s| s.range.start >= start)
+        .map(|s| s.result.clone())
+        .collect::<Vec<_>>()
+    };
+    let prev_head = prev.shaped.first().unwrap();
+    assert!(Sc::ptr_eq(&prev_head.result, &glyphs.shaped[0].result));
+    let (prev_tail, tail) = (kept(&prev, 12), kept(&glyphs, 22));
+    assert_eq!(tail.len(), 2);
+    assert!(prev_tail.iter().zip(&tail).all(|(a, b)| Sc::ptr_eq(a, b)));
+
+    let fresh = test_store().typography(
+      rope.to_string().into(),
+      &style,
+      Size::new(f32::MAX, f32::MAX),
+      TextAlign::Start,
+      PlaceLineDirection::TopToBottom,
+    );
+    assert_eq!(glyphs.glyphs().collect::<Vec<_>>(), fresh.glyphs().collect::<Vec<_>>());
+  }
   #[test]
   fn cluster_position() {
     let style = zero_letter_space_style(15., Overflow::Clip);
@@ -826,4 +1279,206 @@ mod tests {
     );
     assert_eq!(1, store.cache.len());
   }
+
+  #[test]
+  fn script_fallback() {
+    let mut store = test_store();
+    let path = env!("CARGO_MANIFEST_DIR").to_owned() + "/../fonts/NotoSerifSC-Bold.你好世界.otf";
+    let _ = store.font_db.borrow_mut().load_font_file(path);
+    let style = zero_letter_space_style(16., Overflow::Clip);
+    let bounds = Size::new(f32::MAX, f32::MAX);
+    let face_name = |store: &TypographyStore, id| {
+      let db = store.font_db().borrow();
+      db.face_info(id).unwrap().families[0].0.clone()
+    };
+
+    let text: Substr = "ab你好".into();
+    let glyphs = store.typography(
+      text.clone(),
+      &style,
+      bounds,
+      TextAlign::Start,
+      PlaceLineDirection::TopToBottom,
+    );
+    let han_face = glyphs.glyph_faces().last().unwrap().1;
+    assert_ne!(face_name(&store, han_face), "Noto Serif SC");
+
+    store
+      .font_db
+      .borrow_mut()
+      .set_fallback(Script::Han, None, [FontFamily::Name("Noto Serif SC".into())]);
+    store.end_frame();
+    let glyphs =
+      store.typography(text, &style, bounds, TextAlign::Start, PlaceLineDirection::TopToBottom);
+    let faces: Vec<_> = glyphs
+      .glyph_faces()
+      .map(|(cluster, id)| (cluster, face_name(&store, id)))
+      .collect();
+    assert_eq!(faces, [
+      (0, "DejaVu Sans".to_string()),
+      (1, "DejaVu Sans".to_string()),
+      (2, "Noto Serif SC".to_string()),
+      (5, "Noto Serif SC".to_string())
+    ]);
+  }
+
+  #[test]
+  fn vertical_writing_mode() {
+    let mut store = test_store();
+    let path = env!("CARGO_MANIFEST_DIR").to_owned() + "/../fonts/NotoSerifSC-Bold.你好世界.otf";
+    let _ = store.font_db.borrow_mut().load_font_file(path);
+    let style = TextStyle {
+      font_face: FontFace {
+        families: Box::new([
+          FontFamily::Name("Noto Serif SC".into()),
+          FontFamily::Name("DejaVu Sans".into()),
+        ]),
+        ..<_>::default()
+      },
+      writing_mode: WritingMode::VerticalRl,
+      ..zero_letter_space_style(16., Overflow::Clip)
+    };
+
+    let glyphs = store.typography(
+      "你好ab\n好".into(),
+      &style,
+      Size::new(100., 100.),
+      TextAlign::Start,
+      style.writing_mode.line_dir(),
+    );
+    let orientations: Vec<_> = glyphs
+      .glyphs()
+      .map(|g| (g.cluster, g.orientation))
+      .collect();
+    // The lines are placed from right to left, the first line is the last one.
+    assert_eq!(orientations, [
+      (9, GlyphOrientation::Upright),
+      (0, GlyphOrientation::Upright),
+      (3, GlyphOrientation::Upright),
+      (6, GlyphOrientation::Sideways),
+      (7, GlyphOrientation::Sideways),
+      (8, GlyphOrientation::Sideways),
+    ]);
+
+    // The first line is at the right of the bounds.
+    assert_eq!(glyphs.visual_rect().origin.x, 68.);
+    let second = glyphs.glyph_rect(1, 1).round();
+    assert_eq!(second, Rect::new(Point::new(84., 16.), Size::new(16., 16.)));
+    // The sideways glyph advances with its horizontal advance.
+    let b = glyphs.glyph_rect(1, 3);
+    assert_eq!((b.min_x(), b.width()), (84., 16.));
+    assert!(b.height() < 16.);
+
+    let rects = glyphs.select_range(&(3..8));
+    assert_eq!(rects.len(), 1);
+    assert_eq!(rects[0].min_x(), 84.);
+    assert_eq!(rects[0].min_y().round(), 16.);
+    assert_eq!(rects[0].max_y().round(), b.max_y().round());
+
+    assert_eq!(glyphs.nearest_glyph(90., 20.), (1, 1));
+    assert_eq!(glyphs.nearest_glyph(70., 1.), (0, 0));
+  }
+
+  #[test]
+  fn inline_placeholder() {
+    let mut store = test_store();
+    let style = zero_letter_space_style(16., Overflow::AutoWrap);
+    let placeholder = InlinePlaceholder::new(Size::new(20., 30.));
+
+    let text: Substr = "ab\u{FFFC}cd".into();
+    let glyphs = store.typography_with_placeholders(
+      text,
+      &style,
+      Size::new(f32::MAX, f32::MAX),
+      TextAlign::Start,
+      PlaceLineDirection::TopToBottom,
+      &[placeholder],
+    );
+    let rects = glyphs.placeholder_rects();
+    assert_eq!(rects.len(), 1);
+    // The placeholder is higher than the line, the line grows to contain it, and
+    // the text keeps on the same baseline with it.
+    assert_eq!(rects[0].origin.y, 0.);
+    assert_eq!(rects[0].size, Size::new(20., 30.));
+    assert_eq!(glyphs.visual_rect().height(), 30.);
+    let offsets: Vec<_> = glyphs
+      .glyphs()
+      .map(|g| g.y_offset.into_pixel())
+      .collect();
+    assert_eq!(offsets, [14., 14., 0., 14., 14.]);
+    let c = glyphs.glyph_rect(0, 3);
+    assert_eq!(c.min_x(), rects[0].max_x());
+
+    let glyphs = store.typography_with_placeholders(
+      "a \u{FFFC} b \u{FFFC}".into(),
+      &style,
+      Size::new(40., f32::MAX),
+      TextAlign::Start,
+      PlaceLineDirection::TopToBottom,
+      &[placeholder, InlinePlaceholder { size: Size::new(10., 10.), baseline_offset: 5. }],
+    );
+    let rects = glyphs.placeholder_rects();
+    assert_eq!(rects.len(), 2);
+    // The second placeholder wraps to the next line with the text.
+    assert_eq!(rects[0].origin.y, 0.);
+    assert_eq!(rects[1].origin.y, 30. + 16. - 5.);
+    assert_eq!(glyphs.visual_rect().height(), 30. + 21.);
+  }
+
+  #[test]
+  fn tab_stops() {
+    let mut store = test_store();
+    let style = TextStyle {
+      tab_size: TabSize::Pixels(30.),
+      tab_stops: Box::new([20.]),
+      ..zero_letter_space_style(16., Overflow::Clip)
+    };
+    let glyphs = store.typography(
+      "a\tb\tc\td".into(),
+      &style,
+      Size::new(f32::MAX, f32::MAX),
+      TextAlign::Start,
+      PlaceLineDirection::TopToBottom,
+    );
+    // Tabs advance to the custom stop first, and then every 30 pixels after it.
+    let starts: Vec<_> = [2, 4, 6]
+      .into_iter()
+      .map(|col| glyphs.glyph_rect(0, col).min_x().round())
+      .collect();
+    assert_eq!(starts, [20., 50., 80.]);
+
+    // A point on the tab hits the tab, and the glyph after it hits itself.
+    assert_eq!(glyphs.nearest_glyph(15., 1.), (0, 1));
+    assert_eq!(glyphs.nearest_glyph(45., 1.), (0, 3));
+    assert_eq!(glyphs.nearest_glyph(51., 1.), (0, 4));
+
+    let style = TextStyle { tab_size: TabSize::Spaces(4), ..style };
+    let glyphs = store.typography(
+      "\tb".into(),
+      &style,
+      Size::new(f32::MAX, f32::MAX),
+      TextAlign::Start,
+      PlaceLineDirection::TopToBottom,
+    );
+    let space = store.typography(
+      "    b".into(),
+      &zero_letter_space_style(16., Overflow::Clip),
+      Size::new(f32::MAX, f32::MAX),
+      TextAlign::Start,
+      PlaceLineDirection::TopToBottom,
+    );
+    // The first stop is the custom one.
+    assert_eq!(glyphs.glyph_rect(0, 1).min_x().round(), 20.);
+    let glyphs = store.typography(
+      "abc\tb".into(),
+      &style,
+      Size::new(f32::MAX, f32::MAX),
+      TextAlign::Start,
+      PlaceLineDirection::TopToBottom,
+    );
+    assert_eq!(
+      glyphs.glyph_rect(0, 4).min_x().round(),
+      (20. + space.glyph_rect(0, 4).min_x()).round()
+    );
+  }
 }
@@ -112,3 +112,94 @@ impl CharacterCursor for UnicodeCursor {
 
   fn reset(&mut self, byte_offset: usize) { self.0 = byte_offset; }
 }
+
+/// The ranges of the characters that are set upright in vertical text. It's a
+/// compact version of the `U`, `Tu` and `Tr` values of the
+/// [Vertical_Orientation](https://www.unicode.org/reports/tr50/) property, all
+/// other characters are set sideways.
+const UPRIGHT_RANGES: &[(char, char)] = &[
+  ('\u{00A7}', '\u{00A7}'),
+  ('\u{00A9}', '\u{00A9}'),
+  ('\u{00AE}', '\u{00AE}'),
+  ('\u{00B1}', '\u{00B1}'),
+  ('\u{00BC}', '\u{00BE}'),
+  ('\u{00D7}', '\u{00D7}'),
+  ('\u{00F7}', '\u{00F7}'),
+  ('\u{02EA}', '\u{02EB}'),
+  ('\u{1100}', '\u{11FF}'),
+  ('\u{1401}', '\u{167F}'),
+  ('\u{18B0}', '\u{18FF}'),
+  ('\u{2016}', '\u{2016}'),
+  ('\u{2020}', '\u{2021}'),
+  ('\u{2030}', '\u{2031}'),
+  ('\u{203B}', '\u{203C}'),
+  ('\u{2042}', '\u{2042}'),
+  ('\u{2047}', '\u{2049}'),
+  ('\u{2051}', '\u{2051}'),
+  ('\u{2100}', '\u{2101}'),
+  ('\u{2103}', '\u{2109}'),
+  ('\u{210F}', '\u{210F}'),
+  ('\u{2113}', '\u{2114}'),
+  ('\u{2116}', '\u{2117}'),
+  ('\u{211E}', '\u{2123}'),
+  ('\u{2125}', '\u{2125}'),
+  ('\u{2127}', '\u{2127}'),
+  ('\u{2129}', '\u{2129}'),
+  ('\u{212E}', '\u{212E}'),
+  ('\u{2135}', '\u{213F}'),
+  ('\u{2145}', '\u{214A}'),
+  ('\u{214C}', '\u{214D}'),
+  ('\u{214F}', '\u{2189}'),
+  ('\u{218C}', '\u{218F}'),
+  ('\u{221E}', '\u{221E}'),
+  ('\u{2234}', '\u{2235}'),
+  ('\u{2300}', '\u{2307}'),
+  ('\u{230C}', '\u{231F}'),
+  ('\u{2322}', '\u{2328}'),
+  ('\u{232B}', '\u{237D}'),
+  ('\u{237F}', '\u{238A}'),
+  ('\u{23BE}', '\u{23CD}'),
+  ('\u{23CF}', '\u{23CF}'),
+  ('\u{23D1}', '\u{23DB}'),
+  ('\u{23E2}', '\u{2422}'),
+  ('\u{2424}', '\u{24FF}'),
+  ('\u{25A0}', '\u{2619}'),
+  ('\u{2620}', '\u{2767}'),
+  ('\u{2776}', '\u{2793}'),
+  ('\u{2B12}', '\u{2B2F}'),
+  ('\u{2B50}', '\u{2B59}'),
+  ('\u{2BB8}', '\u{2BFF}'),
+  ('\u{2E80}', '\u{A4CF}'),
+  ('\u{A960}', '\u{A97F}'),
+  ('\u{AC00}', '\u{D7FF}'),
+  ('\u{E000}', '\u{FAFF}'),
+  ('\u{FE10}', '\u{FE1F}'),
+  ('\u{FE30}', '\u{FE48}'),
+  ('\u{FE50}', '\u{FE57}'),
+  ('\u{FE59}', '\u{FE62}'),
+  ('\u{FE67}', '\u{FE6F}'),
+  ('\u{FF01}', '\u{FF0C}'),
+  ('\u{FF0E}', '\u{FF1B}'),
+  ('\u{FF1F}', '\u{FF60}'),
+  ('\u{FFE0}', '\u{FFE7}'),
+  ('\u{FFF0}', '\u{FFF8}'),
+  ('\u{FFFC}', '\u{FFFD}'),
+  ('\u{1F000}', '\u{1FAFF}'),
+  ('\u{20000}', '\u{3FFFD}'),
+];
+
+/// Return if the character is set upright in vertical text, otherwise it's set
+/// sideways.
+pub fn is_upright_in_vertical(c: char) -> bool {
+  UPRIGHT_RANGES
+    .binary_search_by(|&(start, end)| {
+      if end < c {
+        std::cmp::Ordering::Less
+      } else if c < start {
+        std::cmp::Ordering::Greater
+      } else {
+        std::cmp::Ordering::Equal
+      }
+    })
+    .is_ok()
+}
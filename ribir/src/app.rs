@@ -120,6 +120,9 @@ impl App {
             request_redraw(&wnd)
           }
           WindowEvent::Focused(focused) => {
+            if !focused {
+              wnd.process_pointer_cancel();
+            }
             let mut event = AppEvent::WndFocusChanged(wnd_id, focused);
 
             App::shared()
@@ -68,6 +68,12 @@ impl Render for Divider {
     }
   }
 
+  /// The divider takes the `extent` across its direction, and fills the space
+  /// along it.
+  fn intrinsic_size(&self, kind: IntrinsicSize, _: f32, _: &mut LayoutCtx) -> f32 {
+    if kind.is_width() == self.direction.is_horizontal() { 0. } else { self.extent }
+  }
+
   fn paint(&self, ctx: &mut PaintingCtx) {
     let mut size = ctx.box_size().unwrap();
     let (origin, size) = if self.direction.is_horizontal() {
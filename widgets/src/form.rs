@@ -0,0 +1,698 @@
+use std::{fmt, future::Future, pin::Pin, rc::Rc};
+
+use ribir_core::prelude::*;
+use serde::de::DeserializeOwned;
+
+use crate::prelude::*;
+
+/// The value of a field in a `Form`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+  Text(CowArc<str>),
+  Bool(bool),
+  /// No radio of the radio group is checked.
+  None,
+}
+
+pub type ValidateResult = Result<(), CowArc<str>>;
+
+/// The validating function of `Validator::Async`.
+pub type AsyncValidateFn = dyn Fn(FieldValue) -> Pin<Box<dyn Future<Output = ValidateResult>>>;
+
+/// A validator of a form field, it returns the error message if the value is
+/// invalid.
+#[derive(Clone)]
+pub enum Validator {
+  Sync(Rc<dyn Fn(&FieldValue) -> ValidateResult>),
+  /// The validator runs in a future spawned by `AppCtx::spawn_local`, for
+  /// example, to check the value on a server.
+  Async(Rc<AsyncValidateFn>),
+}
+
+/// When a field validates its value, every field validates on submit, and a
+/// field with an error validates on every change.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ValidateOn {
+  /// Validate when the value changes or the field loses focus.
+  #[default]
+  Change,
+  /// Validate when the field loses focus.
+  Blur,
+  /// Only validate on submit.
+  Submit,
+}
+
+/// The status of a field in a `Form`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FieldStatus {
+  /// The value is changed from the initial value.
+  pub dirty: bool,
+  /// The field has lost focus once, or the form has been submitted.
+  pub touched: bool,
+  /// The field has no error, a field not validated yet is valid.
+  pub valid: bool,
+  /// The async validators of the field are running.
+  pub validating: bool,
+}
+
+#[derive(Debug)]
+pub enum FormError {
+  /// A field is invalid, it's the name of the first one.
+  Invalid(CowArc<str>),
+  /// The values of the form can't be deserialized to the submit type.
+  Deserialize(serde_json::Error),
+}
+
+impl fmt::Display for FormError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      FormError::Invalid(name) => write!(f, "the field `{}` is invalid", &**name),
+      FormError::Deserialize(err) => write!(f, "failed to deserialize the form values: {err}"),
+    }
+  }
+}
+
+impl std::error::Error for FormError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      FormError::Invalid(_) => None,
+      FormError::Deserialize(err) => Some(err),
+    }
+  }
+}
+
+/// The model of a form, it keeps the values and the status of the fields by
+/// name.
+///
+/// Provide it in a `Stateful` by a `Provider`, and wrap the `TextField`,
+/// `Checkbox` and `Radio` in it with the `FormField` to register them. The
+/// submit value is deserialized from the values of the fields by their names,
+/// a text is a string, a checkbox is a bool, and a radio group is the option
+/// of the checked radio or null.
+///
+/// # Example
+///
+/// ```no_run
+/// use ribir_core::prelude::*;
+/// use ribir_widgets::prelude::*;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct SignUp {
+///   email: String,
+///   agree: bool,
+/// }
+///
+/// let form = Stateful::new(Form::default());
+/// let _w = Provider::new(Box::new(form.clone_writer())).with_child(fn_widget! {
+///   @Column {
+///     @FormField {
+///       name: "email",
+///       validators: vec![Validator::required("Email is required")],
+///       @TextField {}
+///     }
+///     @FormField { name: "agree", @Checkbox {} }
+///     @FilledButton {
+///       on_tap: move |_| {
+///         let submit = Form::submit::<SignUp>(&form);
+///         let _ = AppCtx::spawn_local(async move {
+///           if let Ok(sign_up) = submit.await {
+///             println!("{} signs up.", sign_up.email);
+///           }
+///         });
+///       },
+///       @{ Label::new("Sign up") }
+///     }
+///   }
+/// });
+/// ```
+#[derive(Default)]
+pub struct Form {
+  fields: Vec<Field>,
+}
+
+struct Field {
+  name: CowArc<str>,
+  value: FieldValue,
+  initial: FieldValue,
+  touched: bool,
+  error: Option<CowArc<str>>,
+  validating: bool,
+  /// Increased by every validation, so the result of an outdated async
+  /// validation is dropped.
+  generation: usize,
+  validators: Vec<Validator>,
+  validate_on: ValidateOn,
+  /// Request the focus to the widget of the field.
+  focus: Option<Box<dyn Fn()>>,
+  /// The count of the widgets of the field, a radio group has many.
+  widgets: usize,
+}
+
+impl Validator {
+  pub fn new(f: impl Fn(&FieldValue) -> ValidateResult + 'static) -> Self { Self::Sync(Rc::new(f)) }
+
+  pub fn new_async<F>(f: impl Fn(FieldValue) -> F + 'static) -> Self
+  where
+    F: Future<Output = ValidateResult> + 'static,
+  {
+    Self::Async(Rc::new(move |v| Box::pin(f(v))))
+  }
+
+  /// The text must not be empty, the checkbox must be checked and a radio of
+  /// the group must be checked.
+  pub fn required(message: impl Into<CowArc<str>>) -> Self {
+    let message = message.into();
+    Self::new(move |v| match v {
+      FieldValue::Text(t) if !t.is_empty() => Ok(()),
+      FieldValue::Bool(true) => Ok(()),
+      _ => Err(message.clone()),
+    })
+  }
+
+  /// The count of the chars of the text must be at least `len`.
+  pub fn min_length(len: usize, message: impl Into<CowArc<str>>) -> Self {
+    let message = message.into();
+    Self::new(move |v| match v {
+      FieldValue::Text(t) if t.chars().count() < len => Err(message.clone()),
+      _ => Ok(()),
+    })
+  }
+
+  /// The text must match the `regex`.
+  pub fn pattern(regex: regex::Regex, message: impl Into<CowArc<str>>) -> Self {
+    let message = message.into();
+    Self::new(move |v| match v {
+      FieldValue::Text(t) if !regex.is_match(t) => Err(message.clone()),
+      _ => Ok(()),
+    })
+  }
+}
+
+impl FieldValue {
+  pub fn as_text(&self) -> Option<&CowArc<str>> {
+    if let FieldValue::Text(t) = self { Some(t) } else { None }
+  }
+
+  pub fn as_bool(&self) -> Option<bool> {
+    if let FieldValue::Bool(b) = self { Some(*b) } else { None }
+  }
+
+  fn to_json(&self) -> serde_json::Value {
+    match self {
+      FieldValue::Text(t) => serde_json::Value::String(t.to_string()),
+      FieldValue::Bool(b) => serde_json::Value::Bool(*b),
+      FieldValue::None => serde_json::Value::Null,
+    }
+  }
+}
+
+impl Form {
+  /// The value of the field `name`.
+  pub fn value(&self, name: &str) -> Option<&FieldValue> { self.field(name).map(|f| &f.value) }
+
+  /// Set the value of the field `name`, the widget of the field is updated,
+  /// but the value is not validated until the next validation.
+  pub fn set_value(&mut self, name: &str, value: FieldValue) {
+    if let Some(f) = self.field_mut(name) {
+      f.value = value;
+    }
+  }
+
+  /// The error message of the field `name`.
+  pub fn error(&self, name: &str) -> Option<&CowArc<str>> {
+    self.field(name).and_then(|f| f.error.as_ref())
+  }
+
+  pub fn status(&self, name: &str) -> Option<FieldStatus> {
+    self.field(name).map(|f| FieldStatus {
+      dirty: f.value != f.initial,
+      touched: f.touched,
+      valid: f.error.is_none(),
+      validating: f.validating,
+    })
+  }
+
+  /// Any field is changed from its initial value.
+  pub fn is_dirty(&self) -> bool { self.fields.iter().any(|f| f.value != f.initial) }
+
+  /// Any field has lost focus once, or the form has been submitted.
+  pub fn is_touched(&self) -> bool { self.fields.iter().any(|f| f.touched) }
+
+  /// No field has an error or is validating.
+  pub fn is_valid(&self) -> bool {
+    self
+      .fields
+      .iter()
+      .all(|f| f.error.is_none() && !f.validating)
+  }
+
+  /// Reset the fields to their initial values and clear their status.
+  pub fn reset(&mut self) {
+    for f in self.fields.iter_mut() {
+      f.value = f.initial.clone();
+      f.touched = false;
+      f.error = None;
+      f.validating = false;
+      f.generation += 1;
+    }
+  }
+
+  /// Deserialize the values of the fields by their names, without validation.
+  pub fn values<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+    let map = self
+      .fields
+      .iter()
+      .map(|f| (f.name.to_string(), f.value.to_json()))
+      .collect();
+    serde_json::from_value(serde_json::Value::Object(map))
+  }
+
+  /// Run the validators of the field `name`, the async validators are spawned
+  /// by `AppCtx::spawn_local`.
+  pub fn validate(this: &impl StateWriter<Value = Self>, name: &str) {
+    if let Some(validating) = Self::start_validate(this, name) {
+      let _ = AppCtx::spawn_local(validating);
+    }
+  }
+
+  /// Validate all the fields and mark them touched, then deserialize the
+  /// values of the fields by their names if all are valid, otherwise the focus
+  /// moves to the first invalid field.
+  ///
+  /// The sync validators run at once, and the returned future waits for the
+  /// async validators.
+  pub fn submit<T: DeserializeOwned>(
+    this: &impl StateWriter<Value = Self>,
+  ) -> impl Future<Output = Result<T, FormError>> + 'static {
+    let names: Vec<_> = {
+      let mut form = this.write();
+      form
+        .fields
+        .iter_mut()
+        .for_each(|f| f.touched = true);
+      form
+        .fields
+        .iter()
+        .map(|f| f.name.clone())
+        .collect()
+    };
+    let validating: Vec<_> = names
+      .iter()
+      .filter_map(|name| Self::start_validate(this, name))
+      .collect();
+    let this = this.clone_writer();
+    async move {
+      for v in validating {
+        v.await;
+      }
+      let form = this.read();
+      if let Some(f) = form.fields.iter().find(|f| f.error.is_some()) {
+        if let Some(focus) = f.focus.as_ref() {
+          focus();
+        }
+        return Err(FormError::Invalid(f.name.clone()));
+      }
+      form.values().map_err(FormError::Deserialize)
+    }
+  }
+
+  /// Run the sync validators of the field `name`, and return the future of the
+  /// async validators if any.
+  fn start_validate(
+    this: &impl StateWriter<Value = Self>, name: &str,
+  ) -> Option<impl Future<Output = ()> + 'static> {
+    let mut form = this.write();
+    let field = form.field_mut(name)?;
+    field.generation += 1;
+    let value = field.value.clone();
+    let error = field.validators.iter().find_map(|v| match v {
+      Validator::Sync(f) => f(&value).err(),
+      Validator::Async(_) => None,
+    });
+    let validating: Vec<_> = field
+      .validators
+      .iter()
+      .filter_map(|v| match v {
+        Validator::Async(f) if error.is_none() => Some(f(value.clone())),
+        _ => None,
+      })
+      .collect();
+    field.error = error;
+    field.validating = !validating.is_empty();
+    if validating.is_empty() {
+      return None;
+    }
+
+    let generation = field.generation;
+    let name = field.name.clone();
+    let this = this.clone_writer();
+    Some(async move {
+      let mut error = None;
+      for v in validating {
+        if let Err(e) = v.await {
+          error = Some(e);
+          break;
+        }
+      }
+      let mut form = this.write();
+      if let Some(field) = form
+        .field_mut(&name)
+        .filter(|f| f.generation == generation)
+      {
+        field.error = error;
+        field.validating = false;
+      }
+    })
+  }
+
+  /// The value of the field `name` is changed by its widget.
+  fn change(this: &impl StateWriter<Value = Self>, name: &str, value: FieldValue) {
+    let validate = {
+      let mut form = this.write();
+      let Some(field) = form.field_mut(name) else { return };
+      field.value = value;
+      field.validate_on == ValidateOn::Change || field.error.is_some()
+    };
+    if validate {
+      Self::validate(this, name);
+    }
+  }
+
+  /// The field `name` loses focus.
+  fn blur(this: &impl StateWriter<Value = Self>, name: &str) {
+    let validate = {
+      let mut form = this.write();
+      let Some(field) = form.field_mut(name) else { return };
+      field.touched = true;
+      field.validate_on <= ValidateOn::Blur
+    };
+    if validate {
+      Self::validate(this, name);
+    }
+  }
+
+  /// Register a widget of the field `name`, the widgets of a radio group
+  /// register to the same field, and the first one configures it.
+  fn register(
+    &mut self, name: CowArc<str>, value: Option<FieldValue>, validators: Vec<Validator>,
+    validate_on: ValidateOn, focus: Box<dyn Fn()>,
+  ) {
+    if let Some(field) = self.field_mut(&name) {
+      field.widgets += 1;
+      if let Some(value) = value {
+        field.initial = value.clone();
+        field.value = value;
+      }
+      return;
+    }
+
+    let value = value.unwrap_or(FieldValue::None);
+    self.fields.push(Field {
+      name,
+      initial: value.clone(),
+      value,
+      touched: false,
+      error: None,
+      validating: false,
+      generation: 0,
+      validators,
+      validate_on,
+      focus: Some(focus),
+      widgets: 1,
+    });
+  }
+
+  fn unregister(&mut self, name: &str) {
+    if let Some(idx) = self.fields.iter().position(|f| &*f.name == name) {
+      self.fields[idx].widgets -= 1;
+      if self.fields[idx].widgets == 0 {
+        self.fields.remove(idx);
+      }
+    }
+  }
+
+  fn field(&self, name: &str) -> Option<&Field> { self.fields.iter().find(|f| &*f.name == name) }
+
+  fn field_mut(&mut self, name: &str) -> Option<&mut Field> {
+    self.fields.iter_mut().find(|f| &*f.name == name)
+  }
+}
+
+/// Register its child to the `Form` provided by the ancestors as the field
+/// `name`, and keep the value and the error of the field in sync with the
+/// child.
+#[derive(Declare)]
+pub struct FormField {
+  /// The name of the field in the form and in the submit value.
+  pub name: CowArc<str>,
+  /// The option of a radio, the radios of the same name are a radio group,
+  /// and the value of the group is the option of the checked radio.
+  #[declare(default)]
+  pub option: Option<CowArc<str>>,
+  #[declare(default)]
+  pub validators: Vec<Validator>,
+  #[declare(default)]
+  pub validate_on: ValidateOn,
+}
+
+#[derive(Template)]
+pub enum FormControl {
+  TextField(FatObj<State<TextField>>),
+  Checkbox(FatObj<State<Checkbox>>),
+  Radio(FatObj<State<Radio>>),
+}
+
+/// A widget that can be a field of a `Form`.
+trait FieldControl: 'static {
+  /// The value of the control, `None` if the control does not decide the
+  /// value, like an unchecked radio of a group.
+  fn field_value(&self, option: Option<&CowArc<str>>) -> Option<FieldValue>;
+
+  fn set_field_value(&mut self, value: &FieldValue, option: Option<&CowArc<str>>);
+
+  fn field_error(&self) -> Option<&CowArc<str>> { None }
+
+  fn set_field_error(&mut self, _: Option<CowArc<str>>) {}
+}
+
+impl FieldControl for TextField {
+  fn field_value(&self, _: Option<&CowArc<str>>) -> Option<FieldValue> {
+    Some(FieldValue::Text(self.text()))
+  }
+
+  fn set_field_value(&mut self, value: &FieldValue, _: Option<&CowArc<str>>) {
+    if let FieldValue::Text(text) = value {
+      self.set_text(text.clone());
+    }
+  }
+
+  fn field_error(&self) -> Option<&CowArc<str>> { self.error.as_ref() }
+
+  fn set_field_error(&mut self, error: Option<CowArc<str>>) { self.error = error; }
+}
+
+impl FieldControl for Checkbox {
+  fn field_value(&self, _: Option<&CowArc<str>>) -> Option<FieldValue> {
+    Some(FieldValue::Bool(self.checked))
+  }
+
+  fn set_field_value(&mut self, value: &FieldValue, _: Option<&CowArc<str>>) {
+    self.checked = value == &FieldValue::Bool(true);
+    self.indeterminate = false;
+  }
+}
+
+impl FieldControl for Radio {
+  fn field_value(&self, option: Option<&CowArc<str>>) -> Option<FieldValue> {
+    match option {
+      Some(option) => self
+        .checked
+        .then(|| FieldValue::Text(option.clone())),
+      None => Some(FieldValue::Bool(self.checked)),
+    }
+  }
+
+  fn set_field_value(&mut self, value: &FieldValue, option: Option<&CowArc<str>>) {
+    self.checked = match option {
+      Some(option) => value.as_text() == Some(option),
+      None => value == &FieldValue::Bool(true),
+    };
+  }
+}
+
+impl ComposeChild<'static> for FormField {
+  type Child = FormControl;
+  fn compose_child(this: impl StateWriter<Value = Self>, child: Self::Child) -> Widget<'static> {
+    let field = this
+      .try_into_value()
+      .unwrap_or_else(|_| panic!("`FormField` does not support to be a shared state."));
+    match child {
+      FormControl::TextField(c) => bind_field(field, c.clone_writer(), c.into_widget()),
+      FormControl::Checkbox(c) => bind_field(field, c.clone_writer(), c.into_widget()),
+      FormControl::Radio(c) => bind_field(field, c.clone_writer(), c.into_widget()),
+    }
+  }
+}
+
+fn bind_field<C: FieldControl>(
+  field: FormField, control: impl StateWriter<Value = C>, widget: Widget<'static>,
+) -> Widget<'static> {
+  fn_widget! {
+    let form = Provider::of::<Stateful<Form>>(BuildCtx::get())
+      .expect("`FormField` requires a `Stateful<Form>` provided by the ancestors.")
+      .clone_writer();
+    let FormField { name, option, validators, validate_on } = field;
+
+    let mut host = @FocusScope {};
+    let request_focus = host.get_request_focus_widget().clone_writer();
+    let value = $control.field_value(option.as_ref());
+    form.write().register(
+      name.clone(),
+      value,
+      validators,
+      validate_on,
+      Box::new(move || request_focus.read().request_focus()),
+    );
+
+    let (form2, name2, option2) = (form.clone_writer(), name.clone(), option.clone());
+    let to_form = watch!($control.field_value(option2.as_ref()))
+      .distinct_until_changed()
+      .subscribe(move |value| {
+        if let Some(value) = value.filter(|v| form2.read().value(&name2) != Some(v)) {
+          Form::change(&form2, &name2, value);
+        }
+      });
+
+    let name2 = name.clone();
+    let to_control = watch!(($form.value(&name2).cloned(), $form.error(&name2).cloned()))
+      .subscribe(move |(value, error)| {
+        let mut c = $control.write();
+        if let Some(value) = value.filter(|v| c.field_value(option.as_ref()).as_ref() != Some(v)) {
+          c.set_field_value(&value, option.as_ref());
+        }
+        if c.field_error() != error.as_ref() {
+          c.set_field_error(error);
+        }
+      });
+
+    let (form2, name2) = (form.clone_writer(), name.clone());
+    @ $host {
+      on_focus_out: move |_| Form::blur(&form2, &name2),
+      on_disposed: move |_| {
+        to_form.unsubscribe();
+        to_control.unsubscribe();
+        form.write().unregister(&name);
+      },
+      @ { widget }
+    }
+  }
+  .into_widget()
+}
+
+#[cfg(test)]
+mod tests {
+  use ribir_core::{reset_test_env, test_helper::*};
+  use serde::Deserialize;
+
+  use super::*;
+
+  #[derive(Deserialize, Debug, PartialEq)]
+  struct SignUp {
+    name: String,
+    agree: bool,
+    plan: Option<String>,
+  }
+
+  #[test]
+  fn submit_form() {
+    reset_test_env!();
+    let form = Stateful::new(Form::default());
+    let name = Stateful::new(TextField::default());
+    let agree = Stateful::new(Checkbox { checked: false, indeterminate: false, color: Color::RED });
+    let pro = Stateful::new(Radio { checked: false });
+
+    let (c_form, c_name) = (form.clone_writer(), name.clone_writer());
+    let (c_agree, c_pro) = (agree.clone_writer(), pro.clone_writer());
+    let w = fn_widget! {
+      let form = c_form.clone_writer();
+      let (c_name, c_agree) = (c_name.clone_writer(), c_agree.clone_writer());
+      let c_pro = c_pro.clone_writer();
+      Provider::new(Box::new(form)).with_child(fn_widget! {
+        @Column {
+          @FormField {
+            name: "name",
+            validators: vec![Validator::required("Name is required")],
+            @ { FatObj::new(State::stateful(c_name.clone_writer())) }
+          }
+          @FormField {
+            name: "agree",
+            @ { FatObj::new(State::stateful(c_agree.clone_writer())) }
+          }
+          @FormField {
+            name: "plan",
+            option: Some("free".into()),
+            @Radio { checked: false }
+          }
+          @FormField {
+            name: "plan",
+            option: Some("pro".into()),
+            @ { FatObj::new(State::stateful(c_pro.clone_writer())) }
+          }
+        }
+      })
+    };
+    let mut wnd = TestWindow::new(w);
+    wnd.draw_frame();
+    assert_eq!(form.read().value("plan"), Some(&FieldValue::None));
+
+    let res = AppCtx::wait_future(Form::submit::<SignUp>(&form));
+    let err = res.unwrap_err();
+    assert_eq!(err.to_string(), "the field `name` is invalid");
+    assert!(matches!(err, FormError::Invalid(name) if &*name == "name"));
+    wnd.draw_frame();
+    assert_eq!(name.read().error.as_deref(), Some("Name is required"));
+    assert!(wnd.focusing().is_some());
+
+    name.write().set_text("Ann".into());
+    agree.write().checked = true;
+    pro.write().checked = true;
+    wnd.draw_frame();
+    let status = form.read().status("name").unwrap();
+    assert!(status.dirty && status.touched && status.valid);
+    assert_eq!(name.read().error, None);
+
+    let res = AppCtx::wait_future(Form::submit::<SignUp>(&form));
+    assert_eq!(res.unwrap(), SignUp { name: "Ann".into(), agree: true, plan: Some("pro".into()) });
+
+    form.write().reset();
+    wnd.draw_frame();
+    assert_eq!(&*name.read().text(), "");
+    assert!(!agree.read().checked);
+    assert!(!pro.read().checked);
+  }
+
+  #[test]
+  fn async_validator() {
+    reset_test_env!();
+    let form = Stateful::new(Form::default());
+    let taken = Validator::new_async(|v: FieldValue| async move {
+      if v.as_text().is_some_and(|t| &**t == "admin") { Err("Taken".into()) } else { Ok(()) }
+    });
+    let value = Some(FieldValue::Text("admin".into()));
+    form
+      .write()
+      .register("user".into(), value, vec![taken], ValidateOn::Change, Box::new(|| {}));
+
+    Form::validate(&form, "user");
+    assert!(form.read().status("user").unwrap().validating);
+    AppCtx::run_until_stalled();
+    assert_eq!(form.read().error("user").map(|e| &**e), Some("Taken"));
+
+    // The outdated result is dropped.
+    Form::validate(&form, "user");
+    Form::change(&form, "user", FieldValue::Text("ann".into()));
+    AppCtx::run_until_stalled();
+    assert!(form.read().is_valid());
+  }
+}
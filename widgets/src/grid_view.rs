@@ -69,6 +69,10 @@ impl Render for GridView {
     self.bound_size(idx)
   }
 
+  fn intrinsic_size(&self, kind: IntrinsicSize, _: f32, ctx: &mut LayoutCtx) -> f32 {
+    kind.axis_of(self.bound_size(ctx.children().count() as u32))
+  }
+
   #[inline]
   fn only_sized_by_parent(&self) -> bool { false }
 
@@ -1,21 +1,49 @@
 use ribir_core::{prelude::*, ticker::FrameMsg};
 mod caret;
 mod caret_state;
+mod code_editor;
+mod edit_history;
 mod glyphs_helper;
 mod handle;
+mod input_filter;
+mod masked_text;
+mod pre_edit;
 mod selected_text;
+mod selection_area;
+mod spell_check;
+mod text_key_map;
 mod text_selectable;
-use std::ops::Range;
+use std::{
+  cell::{Cell, OnceCell},
+  convert::Infallible,
+  ops::Range,
+  rc::Rc,
+};
 
 pub use caret_state::{CaretPosition, CaretState};
+pub use code_editor::{
+  CodeEditor, CodeEditorStyle, Highlight, HighlightSpan, Highlighter, SimpleHighlighter, TokenKind,
+};
+pub use edit_history::EditHistory;
+pub(crate) use glyphs_helper::GlyphsText;
+pub use input_filter::*;
+pub use masked_text::MaskedText;
+pub use pre_edit::PreEditStyle;
 pub use selected_text::SelectedHighLightStyle;
+pub use selection_area::SelectionArea;
+pub(crate) use selection_area::paint_selection;
+pub use spell_check::{DictionaryChecker, SpellCheck, SpellCheckStyle, SpellChecker};
+pub use text_key_map::{CaretMove, KeyBinding, TextAction, TextKeyMap};
 pub use text_selectable::TextSelectable;
 
 use crate::{
   input::{
     caret::Caret,
-    handle::{TextCaretWriter, edit_handle, edit_key_handle},
+    handle::{TextCaretWriter, apply_edit, delete_edit, edit_handle, edit_key_handle},
+    masked_text::zeroize,
+    pre_edit::{PreEditRange, PreEditUnderline},
     selected_text::SelectedHighLight,
+    spell_check::{Misspelled, SpellCheckState, SpellCheckUnderline, show_suggestions},
     text_selectable::{SelectableText, bind_point_listener, select_key_handle},
   },
   layout::{OnlySizedByParent, Stack, StackFit},
@@ -55,6 +83,19 @@ impl CustomStyle for InputStyle {
   fn default_style(_: &impl ProviderCtx) -> Self { InputStyle { size: Some(20.) } }
 }
 
+#[derive(Clone, PartialEq)]
+pub struct PasswordStyle {
+  /// The char shown for every grapheme of the password.
+  pub mask: char,
+  /// How long the grapheme typed last is shown before it's masked, `None` to
+  /// mask it at once.
+  pub reveal: Option<Duration>,
+}
+
+impl CustomStyle for PasswordStyle {
+  fn default_style(_: &impl ProviderCtx) -> Self { PasswordStyle { mask: '•', reveal: None } }
+}
+
 #[derive(Clone, PartialEq)]
 pub struct TextAreaStyle {
   pub rows: Option<f32>,
@@ -68,13 +109,61 @@ impl CustomStyle for TextAreaStyle {
 }
 
 pub trait EditableText: Sized {
+  /// The text of the `rope`, it's only built when it's read after an edit.
   fn text(&self) -> &CowArc<str>;
 
   fn caret(&self) -> CaretState;
 
-  fn set_text_with_caret(&mut self, text: &str, caret: CaretState);
+  fn set_text_with_caret(&mut self, text: &str, caret: CaretState) {
+    self.set_rope_with_caret(Rope::from(text), caret);
+  }
+
+  /// The text in a `Rope`, the edits are done on it.
+  fn rope(&self) -> &Rope;
+
+  /// Set the text edited in a `Rope` and the caret.
+  fn set_rope_with_caret(&mut self, rope: Rope, caret: CaretState);
 
   fn writer(&mut self) -> TextCaretWriter<Self> { TextCaretWriter::new(self) }
+
+  fn edit_history(&self) -> &EditHistory;
+
+  fn edit_history_mut(&mut self) -> &mut EditHistory;
+
+  /// The filters check the text typed, pasted, committed by the IME and
+  /// deleted.
+  fn input_filters(&self) -> &InputFilters;
+
+  /// The masked text shown in place of the text, the text is not cut and its
+  /// edits are not recorded in the edit history if it's masked.
+  fn text_mask(&self) -> Option<MaskedText> { None }
+
+  /// The spell checker checks the text when the window is idle.
+  fn spell_checker(&self) -> Option<&Rc<dyn SpellChecker>> { None }
+
+  /// Undo the last edit, return if any edit is undone.
+  fn undo(&mut self) -> bool {
+    let text = self.rope().clone();
+    let Some((edit, caret)) = self.edit_history_mut().undo(&text) else { return false };
+    let caret = caret.valid(text.len() - edit.range.len() + edit.insert.len());
+    apply_edit(self, &edit, caret);
+    true
+  }
+
+  /// Redo the last undone edit, return if any edit is redone.
+  fn redo(&mut self) -> bool {
+    let text = self.rope().clone();
+    let Some((edit, caret)) = self.edit_history_mut().redo(&text) else { return false };
+    let caret = caret.valid(text.len() - edit.range.len() + edit.insert.len());
+    apply_edit(self, &edit, caret);
+    true
+  }
+
+  #[inline]
+  fn can_undo(&self) -> bool { self.edit_history().can_undo() }
+
+  #[inline]
+  fn can_redo(&self) -> bool { self.edit_history().can_redo() }
 }
 
 #[derive(Declare)]
@@ -82,11 +171,33 @@ pub struct Input {
   #[declare(default = TypographyTheme::of(BuildCtx::get()).body_large.text.clone())]
   pub style: TextStyle,
   #[declare(skip)]
-  text: CowArc<str>,
+  rope: Rope,
+  /// The text of the `rope`, built when it's read.
+  #[declare(skip)]
+  text: OnceCell<CowArc<str>>,
   #[declare(skip)]
   caret: CaretState,
+  #[declare(skip)]
+  history: EditHistory,
+  /// The filters check the text typed, pasted, committed by the IME and
+  /// deleted, the text set by `set_text` is not checked.
+  #[declare(default)]
+  pub filters: InputFilters,
   #[declare(default = InputStyle::of(BuildCtx::get()).size)]
   size: Option<f32>,
+  /// Show the text as a password, it's masked by the `PasswordStyle`, can't be
+  /// copied or cut, and the text buffers dropped by the input are zeroed if
+  /// they are not shared.
+  ///
+  /// The text read by `text()` is shared, so a clone of it kept by a pipe or a
+  /// `FormField` is not zeroed by the input, the holder should drop it soon.
+  #[declare(default)]
+  pub password: bool,
+  #[declare(default = PasswordStyle::of(BuildCtx::get()))]
+  password_style: PasswordStyle,
+  /// The byte range of the grapheme typed last and shown as it is.
+  #[declare(skip)]
+  revealed: Option<Range<usize>>,
 }
 
 #[derive(Declare)]
@@ -95,10 +206,25 @@ pub struct TextArea {
   pub style: TextStyle,
   #[declare(default = true)]
   pub auto_wrap: bool,
+  /// The text is edited in the rope, so an edit only rebuilds the paragraphs
+  /// it touches.
+  #[declare(skip)]
+  rope: Rope,
+  /// The text of the `rope`, built when it's read.
   #[declare(skip)]
-  text: CowArc<str>,
+  text: OnceCell<CowArc<str>>,
   #[declare(skip)]
   caret: CaretState,
+  #[declare(skip)]
+  history: EditHistory,
+  /// The filters check the text typed, pasted, committed by the IME and
+  /// deleted, the text set by `set_text` is not checked.
+  #[declare(default)]
+  pub filters: InputFilters,
+  /// The misspelled words are underlined, and the suggestions to replace a
+  /// misspelled word are shown by a right click on it.
+  #[declare(default)]
+  pub spell_checker: SpellCheck,
   #[declare(default = TextAreaStyle::of(BuildCtx::get()).rows)]
   rows: Option<f32>,
   #[declare(default = TextAreaStyle::of(BuildCtx::get()).cols)]
@@ -106,39 +232,117 @@ pub struct TextArea {
 }
 
 impl Input {
-  /// set the text and the caret selection will be reset to the start.
-  pub fn set_text(&mut self, text: &str) { self.set_text_with_caret(text, CaretState::default()); }
+  /// set the text and the caret selection will be reset to the start, the
+  /// edit history is cleared.
+  pub fn set_text(&mut self, text: &str) {
+    self.set_text_with_caret(text, CaretState::default());
+    self.history.clear();
+  }
+
+  /// The masked text of the password.
+  fn masked_text(&self) -> Option<MaskedText> {
+    let mask = self.password_style.mask;
+    self
+      .password
+      .then(|| MaskedText::new(EditableText::text(self), mask, self.revealed.clone()))
+  }
+
+  /// The text to show, it's masked if the input is a password.
+  fn display_text(&self) -> CowArc<str> {
+    match self.masked_text() {
+      Some(masked) => masked.display().clone(),
+      None => EditableText::text(self).clone(),
+    }
+  }
+}
+
+impl Drop for Input {
+  fn drop(&mut self) {
+    if let Some(text) = self.text.take().filter(|_| self.password) {
+      zeroize(text);
+    }
+    self.rope.set_secret(self.password);
+  }
 }
 
 impl TextArea {
-  /// set the text and the caret selection will be reset to the start.
-  pub fn set_text(&mut self, text: &str) { self.set_text_with_caret(text, CaretState::default()); }
+  /// set the text and the caret selection will be reset to the start, the
+  /// edit history is cleared.
+  pub fn set_text(&mut self, text: &str) {
+    self.set_text_with_caret(text, CaretState::default());
+    self.history.clear();
+  }
 }
 
 impl SelectableText for Input {
   fn select_range(&self) -> Range<usize> { self.caret.select_range() }
 
-  fn text(&self) -> &CowArc<str> { &self.text }
+  fn text(&self) -> &CowArc<str> { EditableText::text(self) }
 
   fn caret(&self) -> CaretState { self.caret }
 
   fn set_caret(&mut self, caret: CaretState) { self.caret = caret; }
+
+  fn text_mask(&self) -> Option<MaskedText> { self.masked_text() }
 }
 
 impl EditableText for Input {
-  fn text(&self) -> &CowArc<str> { &self.text }
+  fn text(&self) -> &CowArc<str> {
+    // Copy in one buffer of the exact size, a growing buffer would leave the
+    // copies of a password behind.
+    self
+      .text
+      .get_or_init(|| self.rope.slice(0..self.rope.len()).into())
+  }
+
   fn caret(&self) -> CaretState { self.caret }
+
   fn set_text_with_caret(&mut self, text: &str, caret: CaretState) {
-    let new_text = text.replace(['\r', '\n'], " ");
-    self.text = new_text.into();
+    let mut rope = Rope::new();
+    rope.set_secret(self.password);
+    rope.insert(0, text);
+    self.set_rope_with_caret(rope, caret);
+  }
+
+  fn rope(&self) -> &Rope { &self.rope }
+
+  fn set_rope_with_caret(&mut self, mut rope: Rope, caret: CaretState) {
+    rope.set_secret(self.password);
+    // The input is a single line, the separators are replaced in the rope, so
+    // the text of a password is not copied.
+    for idx in (0..rope.paragraph_count() - 1).rev() {
+      let para = rope.paragraph(idx);
+      let (sep_len, spaces) = if para.ends_with("\r\n") {
+        (2, "  ")
+      } else {
+        (para.chars().next_back().map_or(0, char::len_utf8), " ")
+      };
+      let end = rope.paragraph_range(idx).end;
+      rope.replace(end - sep_len..end, spaces);
+    }
+    self.rope = rope;
+    if let Some(old) = self.text.take().filter(|_| self.password) {
+      zeroize(old);
+    }
+    self.revealed = None;
     self.caret = caret;
   }
+
+  fn edit_history(&self) -> &EditHistory { &self.history }
+
+  fn edit_history_mut(&mut self) -> &mut EditHistory { &mut self.history }
+
+  fn input_filters(&self) -> &InputFilters { &self.filters }
+
+  fn text_mask(&self) -> Option<MaskedText> { self.masked_text() }
 }
 
 impl SelectableText for TextArea {
   fn select_range(&self) -> Range<usize> { self.caret.select_range() }
 
-  fn text(&self) -> &CowArc<str> { &self.text }
+  fn text(&self) -> &CowArc<str> { EditableText::text(self) }
+
+  fn text_rope(&self) -> Option<&Rope> { Some(&self.rope) }
 
   fn caret(&self) -> CaretState { self.caret }
 
@@ -146,20 +350,36 @@ impl SelectableText for TextArea {
 }
 
 impl EditableText for TextArea {
-  fn text(&self) -> &CowArc<str> { &self.text }
+  fn text(&self) -> &CowArc<str> {
+    self
+      .text
+      .get_or_init(|| self.rope.slice(0..self.rope.len()).into())
+  }
 
   fn caret(&self) -> CaretState { self.caret }
 
-  fn set_text_with_caret(&mut self, text: &str, caret: CaretState) {
-    self.text = text.to_string().into();
+  fn rope(&self) -> &Rope { &self.rope }
+
+  fn set_rope_with_caret(&mut self, rope: Rope, caret: CaretState) {
+    self.rope = rope;
+    self.text.take();
     self.caret = caret;
   }
+
+  fn edit_history(&self) -> &EditHistory { &self.history }
+
+  fn edit_history_mut(&mut self) -> &mut EditHistory { &mut self.history }
+
+  fn input_filters(&self) -> &InputFilters { &self.filters }
+
+  fn spell_checker(&self) -> Option<&Rc<dyn SpellChecker>> { self.spell_checker.checker() }
 }
 
 #[derive(Debug)]
 struct PreEditState {
   position: usize,
   value: Option<String>,
+  cursor: Option<(usize, usize)>,
 }
 
 struct ImeHandle<H> {
@@ -167,16 +387,17 @@ struct ImeHandle<H> {
   pre_edit: Option<PreEditState>,
   guard: Option<SubscriptionGuard<BoxSubscription<'static>>>,
   window: Sc<Window>,
-  caret_id: LazyWidgetId,
+  text: Reader<Text>,
+  text_id: LazyWidgetId,
 }
 
 impl<E, H> ImeHandle<H>
 where
-  E: EditableText + 'static,
+  E: EditableText + SelectableText + 'static,
   H: StateWriter<Value = E>,
 {
-  fn new(window: Sc<Window>, host: H, caret_id: LazyWidgetId) -> Self {
-    Self { window, host, pre_edit: None, guard: None, caret_id }
+  fn new(window: Sc<Window>, host: H, text: Reader<Text>, text_id: LazyWidgetId) -> Self {
+    Self { window, host, pre_edit: None, guard: None, text, text_id }
   }
   fn ime_allowed(&mut self) {
     self.window.set_ime_allowed(true);
@@ -192,12 +413,22 @@ where
     match &e.pre_edit {
       ImePreEdit::Begin => {
         let mut host = self.host.write();
-        let rg = host.caret().select_range();
-        host.writer().delete_byte_range(&rg);
-        self.pre_edit = Some(PreEditState { position: rg.start, value: None });
+        let caret = EditableText::caret(&*host);
+        let edit = delete_edit(&*host, caret.select_range());
+        if EditableText::text_mask(&*host).is_none() {
+          let removed = host.rope().slice(edit.range.clone());
+          host
+            .edit_history_mut()
+            .start_composing(&removed, &edit, caret);
+        }
+        let position = edit.caret;
+        apply_edit(&mut *host, &edit, CaretPosition { cluster: position, position: None }.into());
+        self.pre_edit = Some(PreEditState { position, value: None, cursor: None });
       }
       ImePreEdit::PreEdit { value, cursor } => {
-        let Some(PreEditState { position, value: edit_value }) = self.pre_edit.as_mut() else {
+        let Some(PreEditState { position, value: edit_value, cursor: edit_cursor }) =
+          self.pre_edit.as_mut()
+        else {
           return;
         };
         let mut host = self.host.write();
@@ -208,6 +439,7 @@ where
         writer.insert_str(value);
         writer.set_to(*position + cursor.map_or(0, |(start, _)| start));
         *edit_value = Some(value.clone());
+        *edit_cursor = *cursor;
       }
       ImePreEdit::End => {
         if let Some(PreEditState { value: Some(txt), position, .. }) = self.pre_edit.take() {
@@ -215,13 +447,35 @@ where
           let mut writer = host.writer();
           writer.delete_byte_range(&(position..position + txt.len()));
         }
+        // The IME commits its text after ending the composition, so close the
+        // composition when the frame ends, or a canceled one takes the next
+        // edit as its text.
+        let host = self.host.clone_writer();
+        self
+          .window
+          .frame_tick_stream()
+          .filter(|msg| matches!(msg, FrameMsg::Finish(_)))
+          .take(1)
+          .subscribe(move |_| host.silent().edit_history_mut().end_composing());
       }
     }
-    if self.pre_edit.is_none() {
-      self.track_cursor();
-    } else {
-      self.guard = None;
-    }
+  }
+
+  /// The text composing by the IME and the clause it's converting.
+  fn pre_edit_range(&self) -> Option<PreEditRange> {
+    let PreEditState { position, value: Some(value), cursor } = self.pre_edit.as_ref()? else {
+      return None;
+    };
+    let host = self.host.read();
+    let clause = cursor
+      .filter(|(start, end)| start < end)
+      .map(|(start, end)| position + start..position + end);
+    Some(PreEditRange {
+      text: GlyphsText::of(&*host),
+      mask: EditableText::text_mask(&*host),
+      range: *position..position + value.len(),
+      clause,
+    })
   }
 
   fn track_cursor(&mut self) {
@@ -229,16 +483,12 @@ where
       return;
     }
 
-    let window = self.window.clone();
-    let wid = self.caret_id.clone();
-
-    let pos = window.map_to_global(Point::zero(), wid.assert_id());
-    let size = window
-      .widget_size(wid.assert_id())
-      .unwrap_or_default();
-    window.set_ime_cursor_area(&Rect::new(pos, size));
+    let update_cursor_area = self.cursor_area_updater();
+    update_cursor_area();
 
-    let tick_of_layout_ready = window
+    // Update the area after the layout, the glyphs of the text are ready then.
+    let tick_of_layout_ready = self
+      .window
       .frame_tick_stream()
       .filter(|msg| matches!(msg, FrameMsg::LayoutReady(_)));
     self.guard = Some(
@@ -247,16 +497,28 @@ where
         .modifies()
         .sample(tick_of_layout_ready)
         .box_it()
-        .subscribe(move |_| {
-          let pos = window.map_to_global(Point::zero(), wid.assert_id());
-          let size = window
-            .widget_size(wid.assert_id())
-            .unwrap_or_default();
-          window.set_ime_cursor_area(&Rect::new(pos, size));
-        })
+        .subscribe(move |_| update_cursor_area())
         .unsubscribe_when_dropped(),
     );
   }
+
+  /// Return a function that sets the caret area of the text as the IME cursor
+  /// area, the IME shows its candidates by it.
+  fn cursor_area_updater(&self) -> impl Fn() + 'static {
+    let window = self.window.clone();
+    let host = self.host.clone_reader();
+    let text = self.text.clone_reader();
+    let text_id = self.text_id.clone();
+    move || {
+      let (host, text) = (host.read(), text.read());
+      let pos = host.caret_position(&text).unwrap_or_default();
+      let height = host
+        .current_line_height(&text)
+        .unwrap_or_default();
+      let pos = window.map_to_global(pos, text_id.assert_id());
+      window.set_ime_cursor_area(&Rect::new(pos, Size::new(1., height)));
+    }
+  }
 }
 
 impl ComposeChild<'static> for Input {
@@ -266,11 +528,35 @@ impl ComposeChild<'static> for Input {
   ) -> Widget<'static> {
     fn_widget! {
       let text = @Text {
-        text: pipe!($this.text.clone()),
+        text: pipe!($this.display_text()),
         text_style: pipe!($this.style.clone()),
       };
+      // The length of the text before the chars are typed, to reveal the
+      // grapheme typed last of the password.
+      let len_before_typing = Rc::new(Cell::new(0));
+      let len_before = len_before_typing.clone();
       @FocusScope {
         can_focus: true,
+        on_chars_capture: move |_| len_before_typing.set($this.rope.len()),
+        on_chars: move |_| {
+          let Some(dur) = $this.password_style.reveal.filter(|_| $this.password) else {
+            return;
+          };
+          let caret = $this.caret.cluster();
+          let mut cursor = GraphemeCursor(caret);
+          let typed = {
+            let host = $this;
+            host.rope.len() > len_before.get() && cursor.prev(EditableText::text(&*host))
+          };
+          if !typed {
+            return;
+          }
+          let revealed = cursor.byte_offset()..caret;
+          $this.write().revealed = Some(revealed.clone());
+          observable::timer((), dur, AppCtx::scheduler())
+            .filter(move |_| $this.revealed.as_ref() == Some(&revealed))
+            .subscribe(move |_| $this.write().revealed = None);
+        },
         @ConstrainedBox {
           clamp: pipe!(size_clamp(&$this.style, Some(1.), $this.size)),
           @ {
@@ -278,7 +564,8 @@ impl ComposeChild<'static> for Input {
               this.clone_writer(),
               text,
               BoxPipe::value(Scrollable::X).into_pipe(),
-              placeholder
+              placeholder,
+              TextDecorations::default(),
             )
           }
         }
@@ -294,8 +581,11 @@ impl ComposeChild<'static> for TextArea {
     this: impl StateWriter<Value = Self>, placeholder: Self::Child,
   ) -> Widget<'static> {
     fn_widget! {
+      // The rope is laid out, so an edit only lays out the paragraphs it
+      // touches, and the text is not copied out of the rope.
       let text = @Text {
-        text: pipe!($this.text.clone()),
+        text: "",
+        rope: pipe!(Some($this.rope.clone())),
         text_style: pipe!{
           let this = $this;
           let mut style = this.style.clone();
@@ -322,7 +612,8 @@ impl ComposeChild<'static> for TextArea {
             this.clone_writer(),
             text,
             scroll_dir,
-            placeholder
+            placeholder,
+            TextDecorations::default(),
           )
         }
       }
@@ -331,16 +622,40 @@ impl ComposeChild<'static> for TextArea {
   }
 }
 
+/// The widgets painted with the text of an edit area, they are stacked in the
+/// scroll view of the text.
+#[derive(Default)]
+struct TextDecorations {
+  /// The widgets under the selection and the text.
+  under: Vec<Widget<'static>>,
+  /// The widgets above the text.
+  over: Vec<Widget<'static>>,
+}
+
 trait EditableTextExtraWidget: EditableText + SelectableText
 where
   Self: 'static,
 {
+  /// Handle the key down before the edit area, return `true` if the key is
+  /// handled and the edit area should ignore it.
+  fn intercept_key(_this: &impl StateWriter<Value = Self>, _event: &mut KeyboardEvent) -> bool {
+    false
+  }
+
+  /// Handle the chars typed before the edit area, return `true` if the chars
+  /// are handled and should not be inserted.
+  fn intercept_chars(_this: &impl StateWriter<Value = Self>, _event: &mut CharsEvent) -> bool {
+    false
+  }
+
   fn edit_area(
     this: impl StateWriter<Value = Self>, text: FatObj<State<Text>>,
     scroll_dir: impl Pipe<Value = Scrollable>, placeholder: Option<Placeholder>,
+    decorations: TextDecorations,
   ) -> Widget<'static> {
     fn_widget! {
       let only_text = text.clone_reader();
+      let text_id = text.lazy_host_id();
 
       let mut stack = @Stack {
         fit: StackFit::Passthrough,
@@ -354,7 +669,6 @@ where
           ).map(BoxClamp::fixed_height),
       };
 
-      let caret_box_id = caret_box.lazy_host_id();
       let mut caret_box = @$caret_box {
         anchor: pipe!(
           let pos = $this.caret_position(&$text).unwrap_or_default();
@@ -363,6 +677,8 @@ where
       };
       let scrollable = stack.get_scrollable_widget();
       let wnd = BuildCtx::get().window();
+      let wnd_id = wnd.id();
+      let caret_text_id = text_id.clone();
       let tick_of_layout_ready = wnd
         .frame_tick_stream()
         .filter(|msg| matches!(msg, FrameMsg::LayoutReady(_)));
@@ -372,36 +688,82 @@ where
         .map(move |_| $this.caret_position(&$text).unwrap_or_default())
         .scan_initial((Point::zero(), Point::zero()), |pair, v| (pair.1, v))
         .subscribe(move |(before, after)| {
-          let mut scrollable = $scrollable.silent();
-          let pos = auto_scroll_pos(&scrollable, before, after, $caret_box.layout_size());
-          scrollable.jump_to(pos);
+          let caret_size = $caret_box.layout_size();
+          {
+            let mut scrollable = $scrollable.silent();
+            let pos = auto_scroll_pos(&scrollable, before, after, caret_size);
+            scrollable.jump_to(pos);
+          }
+          // Reveal the caret in the outer scroll views too.
+          if let (Some(wnd), Some(id)) = (AppCtx::get_window(wnd_id), caret_text_id.id()) {
+            let caret = Rect::new(after, caret_size);
+            ScrollableWidget::scroll_rect_into_view(&wnd, id, caret, ScrollAlign::Nearest);
+          }
       });
 
       let placeholder = @ {
         placeholder.map(move |holder| @Text {
-          visible: pipe!(SelectableText::text(&*$this).is_empty()),
+          visible: pipe!(EditableText::rope(&*$this).is_empty()),
           text: holder.0,
         })
       };
 
+      // Check the spelling when a frame is finished, the window is idle then. A
+      // batch only checks a part of a long text, the next batch is checked when
+      // the window is idle again.
+      let misspelled = Stateful::new(Misspelled::default());
+      let tick_of_finish = wnd
+        .frame_tick_stream()
+        .filter(|msg| matches!(msg, FrameMsg::Finish(_)));
+      let next_batch = Subject::<'static, (), Infallible>::default();
+      let request_batch = next_batch.clone();
+      let mut spell_state = SpellCheckState::default();
+      this
+        .modifies()
+        .map(|_| ())
+        .start_with(vec![()])
+        .sample(tick_of_finish)
+        .merge(next_batch)
+        .subscribe(move |_| {
+          let host = $this;
+          if spell_state.update(host.spell_checker(), host.rope()) {
+            *$misspelled.write() = spell_state.misspelled().clone();
+          }
+          if spell_state.is_checking() {
+            let mut request_batch = request_batch.clone();
+            observable::timer((), Duration::ZERO, AppCtx::scheduler())
+              .subscribe(move |_| request_batch.next(()));
+          }
+        });
+
       let ime_handle = Stateful::new(
-        ImeHandle::new(wnd, this.clone_writer(), caret_box_id)
+        ImeHandle::new(wnd, this.clone_writer(), only_text.clone_reader(), text_id.clone())
       );
       let mut stack = @ $stack {
         on_focus: move |_| $ime_handle.write().ime_allowed(),
         on_blur: move |_| $ime_handle.write().ime_disallowed(),
         on_chars: move |c| {
           let _hint_capture_writer = || $this.write();
-          edit_handle(&this, c);
+          if !Self::intercept_chars(&this, c) {
+            edit_handle(&this, c);
+          }
         },
         on_key_down: move |k| {
           let _hint_capture_writer = || $this.write();
-          select_key_handle(&this, &$only_text, k);
-          edit_key_handle(&this, k);
+          if !Self::intercept_key(&this, k) {
+            select_key_handle(&this, &$only_text, k);
+            edit_key_handle(&this, &$only_text, k);
+          }
         },
         on_ime_pre_edit: move |e| {
           $ime_handle.write().update_pre_edit(e);
         },
+        on_pointer_down: move |e| {
+          let _hint_capture_writer = || $this.write();
+          if e.mouse_buttons() == MouseButtons::SECONDARY {
+            show_suggestions(&this, &$only_text, text_id.assert_id(), &$misspelled, e);
+          }
+        },
       };
 
       let high_light_rect = @UnconstrainedBox {
@@ -414,6 +776,16 @@ where
         }
       };
 
+      let pre_edit_underline = @PreEditUnderline {
+        pre_edit: pipe!($ime_handle.pre_edit_range()),
+        text: only_text.clone_reader(),
+      };
+
+      let spell_check_underline = @SpellCheckUnderline {
+        misspelled: pipe!($misspelled.clone()),
+        text: only_text.clone_reader(),
+      };
+
       let caret = @UnconstrainedBox {
         clamp_dim: ClampDim::MIN_SIZE,
         @OnlySizedByParent { @ {caret_box } }
@@ -426,12 +798,17 @@ where
         only_text
       );
 
+      let TextDecorations { under, over } = decorations;
       @ $stack {
         padding: EdgeInsets::horizontal(2.),
         @ { placeholder }
+        @ { under }
         @ { high_light_rect }
         @ { caret }
         @ { text_widget }
+        @ { over }
+        @ { spell_check_underline }
+        @ { pre_edit_underline }
       }
     }
     .into_widget()
@@ -490,10 +867,19 @@ mod tests {
     reset_test_env,
     test_helper::{TestWindow, split_value},
   };
-  use winit::event::{DeviceId, ElementState, MouseButton, WindowEvent};
+  use winit::{
+    event::{DeviceId, ElementState, MouseButton, WindowEvent},
+    keyboard::{ModifiersState, NativeKeyCode},
+  };
 
-  use super::{EditableText, Input};
-  use crate::layout::SizedBox;
+  use super::{
+    CaretPosition, CaretState, DictionaryChecker, EditableText, Input, InputFilters, InputMask,
+    MaxLength, TextArea,
+  };
+  use crate::{
+    layout::{Column, SizedBox},
+    text::Text,
+  };
 
   #[test]
   fn input_edit() {
@@ -546,4 +932,497 @@ mod tests {
     wnd.draw_frame();
     assert_eq!(*value.read(), "hello");
   }
+
+  #[test]
+  fn input_undo_redo() {
+    reset_test_env!();
+    let (value, w_value) = split_value(String::default());
+    let w = fn_widget! {
+      let input = @Input { auto_focus: true };
+      watch!($input.text().clone())
+        .subscribe(move |text| *$w_value.write() = text.to_string());
+      input
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 200.));
+    wnd.draw_frame();
+    for c in "hello world".chars() {
+      wnd.processes_receive_chars(c.to_string());
+    }
+    wnd.draw_frame();
+    assert_eq!(*value.read(), "hello world");
+
+    let command =
+      if cfg!(target_os = "macos") { ModifiersState::SUPER } else { ModifiersState::CONTROL };
+    let press = |wnd: &mut TestWindow, modifiers: ModifiersState, key: KeyCode, c: &str| {
+      #[allow(deprecated)]
+      wnd.processes_native_event(WindowEvent::ModifiersChanged(modifiers.into()));
+      wnd.processes_keyboard_event(
+        PhysicalKey::Code(key),
+        VirtualKey::Character(c.into()),
+        false,
+        KeyLocation::Standard,
+        ElementState::Pressed,
+      );
+      wnd.draw_frame();
+    };
+
+    press(&mut wnd, command, KeyCode::KeyZ, "z");
+    assert_eq!(*value.read(), "hello ");
+    press(&mut wnd, command, KeyCode::KeyZ, "z");
+    assert_eq!(*value.read(), "");
+    press(&mut wnd, command | ModifiersState::SHIFT, KeyCode::KeyZ, "Z");
+    assert_eq!(*value.read(), "hello ");
+    press(&mut wnd, command, KeyCode::KeyZ, "z");
+    assert_eq!(*value.read(), "");
+    press(&mut wnd, command, KeyCode::KeyY, "y");
+    if cfg!(target_os = "macos") {
+      assert_eq!(*value.read(), "");
+    } else {
+      assert_eq!(*value.read(), "hello ");
+    }
+  }
+
+  #[test]
+  fn undo_after_ime_cancel() {
+    reset_test_env!();
+    let (value, w_value) = split_value(String::default());
+    let w = fn_widget! {
+      let input = @Input { auto_focus: true };
+      watch!($input.text().clone())
+        .subscribe(move |text| *$w_value.write() = text.to_string());
+      input
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 200.));
+    wnd.draw_frame();
+    wnd.processes_receive_chars("ab ".into());
+    wnd.draw_frame();
+
+    wnd.processes_ime_pre_edit(ImePreEdit::Begin);
+    wnd.processes_ime_pre_edit(ImePreEdit::PreEdit { value: "x".into(), cursor: None });
+    wnd.processes_ime_pre_edit(ImePreEdit::End);
+    wnd.draw_frame();
+    assert_eq!(*value.read(), "ab ");
+
+    for c in "cd".chars() {
+      wnd.processes_receive_chars(c.to_string());
+    }
+    wnd.draw_frame();
+    assert_eq!(*value.read(), "ab cd");
+
+    let command =
+      if cfg!(target_os = "macos") { ModifiersState::SUPER } else { ModifiersState::CONTROL };
+    #[allow(deprecated)]
+    wnd.processes_native_event(WindowEvent::ModifiersChanged(command.into()));
+    wnd.processes_keyboard_event(
+      PhysicalKey::Code(KeyCode::KeyZ),
+      VirtualKey::Character("z".into()),
+      false,
+      KeyLocation::Standard,
+      ElementState::Pressed,
+    );
+    wnd.draw_frame();
+    assert_eq!(*value.read(), "ab ");
+  }
+
+  #[test]
+  fn input_filters() {
+    reset_test_env!();
+    let (value, w_value) = split_value(String::default());
+    let w = fn_widget! {
+      let input = @Input {
+        auto_focus: true,
+        filters: InputFilters::default()
+          .with(InputMask::new("(###) ###-####"))
+          .with(MaxLength(14)),
+      };
+      watch!($input.text().clone())
+        .subscribe(move |text| *$w_value.write() = text.to_string());
+      input
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 200.));
+    wnd.draw_frame();
+    for c in "555x1234".chars() {
+      wnd.processes_receive_chars(c.to_string());
+    }
+    wnd.draw_frame();
+    assert_eq!(*value.read(), "(555) 123-4");
+
+    wnd.processes_receive_chars("5678".into());
+    wnd.draw_frame();
+    assert_eq!(*value.read(), "(555) 123-4567");
+  }
+
+  #[test]
+  fn mask_on_delete_and_ime_commit() {
+    reset_test_env!();
+    let (value, w_value) = split_value(String::default());
+    let w = fn_widget! {
+      let input = @Input {
+        auto_focus: true,
+        filters: InputFilters::default().with(InputMask::new("(###) ###-####")),
+      };
+      watch!($input.text().clone())
+        .subscribe(move |text| *$w_value.write() = text.to_string());
+      input
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 200.));
+    wnd.draw_frame();
+    wnd.processes_receive_chars("5551234".into());
+    wnd.draw_frame();
+    assert_eq!(*value.read(), "(555) 123-4");
+
+    let back_space = |wnd: &mut TestWindow| {
+      wnd.processes_keyboard_event(
+        PhysicalKey::Unidentified(NativeKeyCode::Unidentified),
+        VirtualKey::Named(NamedKey::Backspace),
+        false,
+        KeyLocation::Standard,
+        ElementState::Pressed,
+      );
+      wnd.draw_frame();
+    };
+    // The literal before the deleted char is dropped with it.
+    back_space(&mut wnd);
+    assert_eq!(*value.read(), "(555) 123");
+    back_space(&mut wnd);
+    assert_eq!(*value.read(), "(555) 12");
+
+    // The text committed by the IME is filtered by the mask.
+    wnd.processes_ime_pre_edit(ImePreEdit::Begin);
+    wnd.processes_ime_pre_edit(ImePreEdit::PreEdit { value: "9x".into(), cursor: None });
+    wnd.draw_frame();
+    wnd.processes_ime_pre_edit(ImePreEdit::End);
+    wnd.processes_receive_chars("9x".into());
+    wnd.draw_frame();
+    assert_eq!(*value.read(), "(555) 129");
+
+    wnd.processes_ime_pre_edit(ImePreEdit::Begin);
+    wnd.processes_ime_pre_edit(ImePreEdit::PreEdit { value: "x".into(), cursor: None });
+    wnd.processes_ime_pre_edit(ImePreEdit::End);
+    wnd.processes_receive_chars("x".into());
+    wnd.draw_frame();
+    assert_eq!(*value.read(), "(555) 129");
+  }
+
+  #[test]
+  fn password_input() {
+    reset_test_env!();
+    let (value, w_value) = split_value((String::default(), String::default(), false));
+    let w = fn_widget! {
+      let input = @Input { auto_focus: true, password: true };
+      watch!(($input.text().clone(), $input.display_text(), $input.can_undo()))
+        .subscribe(move |(text, display, undo)| {
+          *$w_value.write() = (text.to_string(), display.to_string(), undo)
+        });
+      input
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 200.));
+    wnd.draw_frame();
+    wnd.processes_receive_chars("pa".into());
+    wnd.processes_receive_chars("e\u{301}".into());
+    wnd.draw_frame();
+    assert_eq!(*value.read(), ("pae\u{301}".into(), "•••".into(), false));
+  }
+
+  #[test]
+  fn shared_password_not_zeroed() {
+    reset_test_env!();
+    let (text, w_text) = split_value(CowArc::<str>::default());
+    let w = fn_widget! {
+      let input = @Input { password: true };
+      $input.write().set_text("secret");
+      *$w_text.write() = EditableText::text(&*$input).clone();
+      input
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 200.));
+    wnd.draw_frame();
+    drop(wnd);
+    assert_eq!(&**text.read(), "secret");
+  }
+
+  #[test]
+  fn password_rope_is_secret() {
+    reset_test_env!();
+    let (rope, w_rope) = split_value(Rope::new());
+    let w = fn_widget! {
+      let input = @Input { auto_focus: true, password: true };
+      $input.write().set_text("a\r\nb\nc");
+      watch!($input.rope().clone()).subscribe(move |rope| *$w_rope.write() = rope);
+      input
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 200.));
+    wnd.draw_frame();
+    wnd.processes_receive_chars("x".into());
+    wnd.draw_frame();
+
+    let rope = rope.read();
+    assert!(rope.is_secret());
+    assert_eq!(rope.paragraph_count(), 1);
+    assert_eq!(*rope, *"xa  b c");
+  }
+
+  #[test]
+  fn page_by_scroll_view_height() {
+    reset_test_env!();
+    let (caret, w_caret) = split_value(0);
+    let (line_height, w_line_height) = split_value(0.);
+    let w = fn_widget! {
+      let mut text = @Text { text: "0" };
+      watch!($text.layout_height()).subscribe(move |h| *$w_line_height.write() = h);
+      let area = @TextArea { auto_focus: true };
+      $area.write().set_text("0\n1\n2\n3\n4\n5\n6\n7\n8\n9");
+      super::SelectableText::set_caret(&mut *$area.write(), CaretState::Caret(CaretPosition { cluster: 0, position: None }));
+      watch!(EditableText::caret(&*$area).cluster())
+        .subscribe(move |c| *$w_caret.write() = c);
+      @Column {
+        @SizedBox { size: Size::new(200., 50.), @ { area } }
+        @ { text }
+      }
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 400.));
+    wnd.draw_frame();
+    wnd.processes_keyboard_event(
+      PhysicalKey::Unidentified(NativeKeyCode::Unidentified),
+      VirtualKey::Named(NamedKey::PageDown),
+      false,
+      KeyLocation::Standard,
+      ElementState::Pressed,
+    );
+    wnd.draw_frame();
+
+    // The caret moves by the lines in the view, not in the window.
+    let lines = (50. / *line_height.read()).floor() as usize;
+    assert!(lines < 9);
+    assert_eq!(*caret.read(), lines * 2);
+  }
+
+  #[test]
+  fn edit_keeps_other_paragraphs() {
+    reset_test_env!();
+    let (rope, w_rope) = split_value(Rope::new());
+    let (reader, w_reader) = split_value(None);
+    let w = fn_widget! {
+      let area = @TextArea { auto_focus: true };
+      $area.write().set_text("one\ntwo\nthree");
+      watch!($area.rope().clone()).subscribe(move |rope| *$w_rope.write() = rope);
+      *$w_reader.write() = Some(area.clone_reader());
+      area
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 200.));
+    wnd.draw_frame();
+    let before = rope.read().clone();
+    wnd.processes_receive_chars("x".into());
+    wnd.draw_frame();
+
+    let after = rope.read().clone();
+    assert_eq!(after, *"xone\ntwo\nthree");
+    assert!(!after.paragraph(0).ptr_eq(before.paragraph(0)));
+    assert!(after.paragraph(1).ptr_eq(before.paragraph(1)));
+    assert!(after.paragraph(2).ptr_eq(before.paragraph(2)));
+
+    // The rope is laid out, the text is not copied out of it.
+    let area = reader.read();
+    assert!(area.as_ref().unwrap().read().text.get().is_none());
+  }
+
+  #[test]
+  fn text_area_key_navigation() {
+    reset_test_env!();
+    let (value, w_value) = split_value((String::default(), 0..0));
+    let w = fn_widget! {
+      let area = @TextArea { auto_focus: true };
+      $area.write().set_text("ab cd\nef gh");
+      watch!(($area.text().clone(), EditableText::caret(&*$area).select_range()))
+        .subscribe(move |(text, rg)| *$w_value.write() = (text.to_string(), rg));
+      area
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 200.));
+    wnd.draw_frame();
+
+    let word =
+      if cfg!(target_os = "macos") { ModifiersState::ALT } else { ModifiersState::CONTROL };
+    let press = |wnd: &mut TestWindow, modifiers: ModifiersState, key: NamedKey| {
+      #[allow(deprecated)]
+      wnd.processes_native_event(WindowEvent::ModifiersChanged(modifiers.into()));
+      wnd.processes_keyboard_event(
+        PhysicalKey::Unidentified(NativeKeyCode::Unidentified),
+        VirtualKey::Named(key),
+        false,
+        KeyLocation::Standard,
+        ElementState::Pressed,
+      );
+      wnd.draw_frame();
+    };
+
+    press(&mut wnd, word, NamedKey::ArrowRight);
+    assert_eq!(value.read().1, 2..2);
+    press(&mut wnd, word | ModifiersState::SHIFT, NamedKey::ArrowDown);
+    assert_eq!(value.read().1, 2..5);
+    press(&mut wnd, word, NamedKey::Backspace);
+    assert_eq!(value.read().0, "ab\nef gh");
+
+    press(&mut wnd, ModifiersState::empty(), NamedKey::PageDown);
+    assert_eq!(value.read().1, 5..5);
+    let text_end =
+      if cfg!(target_os = "macos") { ModifiersState::empty() } else { ModifiersState::CONTROL };
+    press(&mut wnd, text_end, NamedKey::End);
+    assert_eq!(value.read().1, 8..8);
+    press(&mut wnd, word, NamedKey::Backspace);
+    assert_eq!(*value.read(), ("ab\nef ".to_string(), 6..6));
+    press(&mut wnd, word, NamedKey::ArrowUp);
+    assert_eq!(value.read().1, 3..3);
+  }
+
+  #[test]
+  fn reveal_caret_in_outer_scroll() {
+    reset_test_env!();
+    let (scroll_y, w_scroll_y) = split_value(0.);
+    let (to_top, w_to_top) = split_value(false);
+    let w = fn_widget! {
+      let mut column = @Column { scrollable: Scrollable::Y };
+      let view_scroll = column.get_scrollable_widget();
+      watch!({
+        let view_scroll = $view_scroll;
+        view_scroll.get_scroll_pos().y
+      })
+      .subscribe(move |y| *$w_scroll_y.write() = y);
+      watch!(*$to_top)
+        .filter(|v| *v)
+        .subscribe(move |_| {
+          let mut view_scroll = $view_scroll.write();
+          view_scroll.jump_to(Point::zero());
+        });
+      @ $column {
+        @SizedBox { size: Size::new(200., 500.) }
+        @TextArea { auto_focus: true }
+      }
+    };
+
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 100.));
+    wnd.draw_frame();
+    wnd.draw_frame();
+    // The focused text area is scrolled into view.
+    let revealed = *scroll_y.read();
+    assert!(revealed > 400.);
+
+    *w_to_top.write() = true;
+    wnd.draw_frame();
+    assert_eq!(*scroll_y.read(), 0.);
+
+    wnd.processes_receive_chars("a".into());
+    wnd.draw_frame();
+    wnd.draw_frame();
+    assert!(*scroll_y.read() > 400.);
+  }
+
+  #[test]
+  fn ime_pre_edit() {
+    reset_test_env!();
+    let (value, w_value) = split_value(String::default());
+    let w = fn_widget! {
+      let input = @Input { auto_focus: true };
+      watch!($input.text().clone())
+        .subscribe(move |text| *$w_value.write() = text.to_string());
+      input
+    };
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 200.));
+    wnd.draw_frame();
+    wnd.processes_receive_chars("ab".into());
+    wnd.draw_frame();
+
+    // The heights of the underlines painted in the last frame.
+    let underlines = |wnd: &mut TestWindow| {
+      fn collect(cmds: &[PaintCommand], heights: &mut Vec<f32>) {
+        for cmd in cmds {
+          match cmd {
+            PaintCommand::Path(path) => {
+              let h = path.paint_bounds.height();
+              if h == 1. || h == 2. {
+                heights.push(h);
+              }
+            }
+            PaintCommand::Bundle { cmds, .. } => collect(cmds, heights),
+            PaintCommand::PopClip => {}
+          }
+        }
+      }
+      let mut heights = vec![];
+      collect(&wnd.take_last_frame().unwrap().commands, &mut heights);
+      heights
+    };
+
+    wnd.processes_ime_pre_edit(ImePreEdit::Begin);
+    wnd
+      .processes_ime_pre_edit(ImePreEdit::PreEdit { value: "ni hao".into(), cursor: Some((6, 6)) });
+    wnd.draw_frame();
+    assert_eq!(*value.read(), "abni hao");
+    assert_eq!(underlines(&mut wnd), vec![1.]);
+    let typing_area = wnd.ime_cursor_area().unwrap();
+
+    // The IME converts the first clause, the caret moves to its start.
+    wnd
+      .processes_ime_pre_edit(ImePreEdit::PreEdit { value: "你好".into(), cursor: Some((0, 3)) });
+    wnd.draw_frame();
+    assert_eq!(*value.read(), "ab你好");
+    assert_eq!(underlines(&mut wnd), vec![1., 2.]);
+    let converting_area = wnd.ime_cursor_area().unwrap();
+    assert!(converting_area.min_x() < typing_area.min_x());
+
+    wnd.processes_ime_pre_edit(ImePreEdit::End);
+    wnd.processes_receive_chars("你好".into());
+    wnd.draw_frame();
+    assert_eq!(*value.read(), "ab你好");
+    assert!(underlines(&mut wnd).is_empty());
+  }
+
+  #[test]
+  fn spell_check_suggestions() {
+    reset_test_env!();
+    let (value, w_value) = split_value(String::default());
+    let w = fn_widget! {
+      let area = @TextArea {
+        auto_focus: true,
+        spell_checker: DictionaryChecker::new(["hello", "world"]),
+      };
+      watch!(EditableText::text(&*$area).clone())
+        .subscribe(move |text| *$w_value.write() = text.to_string());
+      area
+    };
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 200.));
+    wnd.draw_frame();
+    wnd.processes_receive_chars("helo world".into());
+    // The text is checked after the frame, and the underline is painted in
+    // the next frame.
+    wnd.draw_frame();
+    wnd.draw_frame();
+
+    let device_id = unsafe { DeviceId::dummy() };
+    let click = |wnd: &mut TestWindow, pos: Point, btn: MouseButton| {
+      #[allow(deprecated)]
+      wnd.processes_native_event(WindowEvent::CursorMoved {
+        device_id,
+        position: pos.to_tuple().into(),
+      });
+      wnd.process_mouse_input(device_id, ElementState::Pressed, btn);
+      wnd.draw_frame();
+      wnd.process_mouse_input(device_id, ElementState::Released, btn);
+      wnd.draw_frame();
+    };
+
+    // Show the suggestions of the misspelled word, and replace it with the
+    // first suggestion.
+    click(&mut wnd, Point::new(8., 8.), MouseButton::Right);
+    click(&mut wnd, Point::new(20., 16.), MouseButton::Left);
+    assert_eq!(*value.read(), "hello world");
+  }
 }
@@ -0,0 +1,923 @@
+use std::{
+  cell::{OnceCell, RefCell},
+  ops::Range,
+  rc::Rc,
+};
+
+use ribir_core::prelude::*;
+
+mod highlighter;
+pub use highlighter::*;
+
+use super::{
+  EditableText, EditableTextExtraWidget, Placeholder, TextDecorations,
+  caret_state::{CaretPosition, CaretState},
+  edit_history::{EditHistory, EditKind},
+  glyphs_helper::GlyphsHelper,
+  handle::{edit_with_history, edit_with_history_to},
+  input_filter::{InputFilters, TextEdit},
+  size_clamp,
+  text_selectable::SelectableText,
+};
+use crate::prelude::*;
+
+/// The style of the `CodeEditor`, the text style should use a monospace font.
+#[derive(Clone)]
+pub struct CodeEditorStyle {
+  pub text_style: TextStyle,
+  pub rows: Option<f32>,
+  pub cols: Option<f32>,
+  pub keyword: Brush,
+  pub ty: Brush,
+  pub string: Brush,
+  pub number: Brush,
+  pub comment: Brush,
+  pub function: Brush,
+  pub punctuation: Brush,
+  pub line_number: Brush,
+  /// The brush of the line number of the line with the caret.
+  pub current_line_number: Brush,
+  pub gutter_background: Brush,
+  /// The background of the line with the caret.
+  pub current_line: Brush,
+  /// The background of the bracket matching the bracket at the caret.
+  pub bracket_match: Brush,
+}
+
+impl CustomStyle for CodeEditorStyle {
+  fn default_style(ctx: &impl ProviderCtx) -> Self {
+    let palette = Palette::of(ctx);
+    let mut text_style = TypographyTheme::of(ctx).body_medium.text.clone();
+    text_style.font_face.families = Box::new([FontFamily::Monospace]);
+    CodeEditorStyle {
+      text_style,
+      rows: Some(10.),
+      cols: Some(40.),
+      keyword: palette.primary().into(),
+      ty: palette.tertiary().into(),
+      string: palette.success().into(),
+      number: palette.warning().into(),
+      comment: palette.outline().into(),
+      function: palette.secondary().into(),
+      punctuation: palette.on_surface_variant().into(),
+      line_number: palette.outline().into(),
+      current_line_number: palette.on_surface().into(),
+      gutter_background: palette.surface_container().into(),
+      current_line: palette.surface_container_low().into(),
+      bracket_match: palette.primary_container().into(),
+    }
+  }
+}
+
+impl CodeEditorStyle {
+  pub fn token_brush(&self, kind: TokenKind) -> &Brush {
+    match kind {
+      TokenKind::Keyword => &self.keyword,
+      TokenKind::Type => &self.ty,
+      TokenKind::String => &self.string,
+      TokenKind::Number => &self.number,
+      TokenKind::Comment => &self.comment,
+      TokenKind::Function => &self.function,
+      TokenKind::Punctuation => &self.punctuation,
+    }
+  }
+}
+
+/// An editor of the code, it edits like a `TextArea` without wrapping the
+/// lines, and shows the line numbers in a gutter, highlights the code by its
+/// `highlighter`, the line with the caret and the bracket matching the one at
+/// the caret.
+///
+/// The tab key indents the line by the `tab_size`, and a new line keeps the
+/// indent of the line before it, the indent is increased after an open
+/// bracket.
+///
+/// # Example
+///
+/// ```
+/// use ribir_core::prelude::*;
+/// use ribir_widgets::prelude::*;
+///
+/// let _editor = fn_widget! {
+///   let editor = @CodeEditor { highlighter: SimpleHighlighter::javascript() };
+///   $editor.write().set_text("function add(a, b) {\n  return a + b;\n}");
+///   editor
+/// };
+/// ```
+#[derive(Declare)]
+pub struct CodeEditor {
+  #[declare(default = CodeEditorStyle::of(BuildCtx::get()).text_style)]
+  pub style: TextStyle,
+  #[declare(default)]
+  pub highlighter: Highlight,
+  /// The count of the spaces of an indent, and the width of a tab char in
+  /// spaces.
+  #[declare(default = 4usize)]
+  pub tab_size: usize,
+  /// Indent by spaces or by a tab char.
+  #[declare(default = true)]
+  pub insert_spaces: bool,
+  #[declare(default = true)]
+  pub line_numbers: bool,
+  /// The filters check the text typed, pasted, committed by the IME and
+  /// deleted, the text set by `set_text` is not checked.
+  #[declare(default)]
+  pub filters: InputFilters,
+  #[declare(default = CodeEditorStyle::of(BuildCtx::get()).rows)]
+  rows: Option<f32>,
+  #[declare(default = CodeEditorStyle::of(BuildCtx::get()).cols)]
+  cols: Option<f32>,
+  #[declare(skip)]
+  rope: Rope,
+  /// The text of the `rope`, built when it's read.
+  #[declare(skip)]
+  text: OnceCell<CowArc<str>>,
+  #[declare(skip)]
+  caret: CaretState,
+  #[declare(skip)]
+  history: EditHistory,
+  /// The spans of the text highlighted last time, and the highlighter of them.
+  #[declare(skip)]
+  spans: RefCell<Option<HighlightCache>>,
+}
+
+struct HighlightCache {
+  text: CowArc<str>,
+  highlighter: Option<Rc<dyn Highlighter>>,
+  spans: Rc<[HighlightSpan]>,
+}
+
+const BRACKETS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+impl CodeEditor {
+  /// set the text and the caret selection will be reset to the start, the
+  /// edit history is cleared.
+  pub fn set_text(&mut self, text: &str) {
+    self.set_text_with_caret(text, CaretState::default());
+    self.history.clear();
+  }
+
+  /// The highlighted spans of the text.
+  pub fn highlight_spans(&self) -> Rc<[HighlightSpan]> {
+    let highlighter = self.highlighter.highlighter();
+    let mut cache = self.spans.borrow_mut();
+    let valid = cache.as_ref().is_some_and(|c| {
+      let same_highlighter = match (&c.highlighter, highlighter) {
+        (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+        (a, b) => a.is_none() && b.is_none(),
+      };
+      same_highlighter && c.text == *EditableText::text(self)
+    });
+    if !valid {
+      let text = EditableText::text(self);
+      let spans = highlighter.map_or_else(Vec::new, |h| h.highlight(text));
+      *cache = Some(HighlightCache {
+        text: text.clone(),
+        highlighter: highlighter.cloned(),
+        spans: spans.into(),
+      });
+    }
+    cache.as_ref().unwrap().spans.clone()
+  }
+
+  /// The positions of the bracket at the caret and the bracket matching it.
+  fn matching_brackets(&self) -> Option<[usize; 2]> {
+    let rg = self.caret.select_range();
+    if !rg.is_empty() {
+      return None;
+    }
+    matching_brackets(EditableText::text(self), rg.start, &self.highlight_spans())
+  }
+
+  fn line_count(&self) -> usize { self.rope.paragraph_count() }
+
+  fn current_line(&self) -> usize { self.rope.paragraph_at(self.caret.cluster()) }
+
+  fn indent_unit(&self) -> Indent {
+    Indent { size: self.tab_size.max(1), spaces: self.insert_spaces }
+  }
+
+  /// Insert a new line at the caret.
+  fn new_line(&mut self) {
+    let text = EditableText::text(self);
+    let edit = new_line_edit(text, self.caret.select_range(), self.indent_unit());
+    let Some(edit) = self.filters.filter(text, edit) else { return };
+    edit_with_history(self, EditKind::Typing, &edit);
+  }
+
+  /// Indent or outdent the lines of the selection.
+  fn indent(&mut self, outdent: bool) {
+    let text = EditableText::text(self);
+    let Some((edit, caret)) = indent_edit(text, self.caret, self.indent_unit(), outdent) else {
+      return;
+    };
+    edit_with_history_to(self, EditKind::Replace, &edit, caret);
+  }
+}
+
+/// An indent level of the code.
+#[derive(Clone, Copy)]
+struct Indent {
+  /// The count of the spaces of an indent.
+  size: usize,
+  /// Indent by spaces or by a tab char.
+  spaces: bool,
+}
+
+impl Indent {
+  fn unit(&self) -> String { if self.spaces { " ".repeat(self.size) } else { "\t".to_string() } }
+
+  /// The column at the end of the `line`, a tab char advances to the next tab
+  /// stop.
+  fn column(&self, line: &str) -> usize {
+    line.chars().fold(
+      0,
+      |col, c| {
+        if c == '\t' { col + self.size - col % self.size } else { col + 1 }
+      },
+    )
+  }
+}
+
+fn line_start(text: &str, idx: usize) -> usize { text[..idx].rfind('\n').map_or(0, |i| i + 1) }
+
+/// The edit of a new line replacing the `rg`, the new line keeps the indent of
+/// the current line, and increases the indent after an open bracket.
+fn new_line_edit(text: &str, rg: Range<usize>, indent: Indent) -> TextEdit {
+  let line = &text[line_start(text, rg.start)..rg.start];
+  let line_indent = &line[..line.len() - line.trim_start().len()];
+
+  let mut insert = format!("\n{line_indent}");
+  let mut caret = rg.start + insert.len();
+  let open = line
+    .trim_end()
+    .chars()
+    .next_back()
+    .and_then(|c| BRACKETS.iter().find(|(open, _)| *open == c));
+  if let Some((_, close)) = open {
+    insert.push_str(&indent.unit());
+    caret = rg.start + insert.len();
+    // Move the close bracket after the caret to its own line.
+    if text[rg.end..]
+      .trim_start_matches([' ', '\t'])
+      .starts_with(*close)
+    {
+      insert.push('\n');
+      insert.push_str(line_indent);
+    }
+  }
+  TextEdit { range: rg, insert, caret }
+}
+
+/// The edit of the tab key, it inserts an indent at the caret if the selection
+/// is in a line, or indents the lines of the selection. It outdents the lines
+/// if `outdent` is true. Return the edit and the caret after it.
+fn indent_edit(
+  text: &str, caret: CaretState, indent: Indent, outdent: bool,
+) -> Option<(TextEdit, CaretState)> {
+  let rg = caret.select_range();
+  let multi_lines = text[rg.clone()].contains('\n');
+  if !outdent && !multi_lines {
+    let insert = if indent.spaces {
+      let column = indent.column(&text[line_start(text, rg.start)..rg.start]);
+      " ".repeat(indent.size - column % indent.size)
+    } else {
+      "\t".to_string()
+    };
+    let edit = TextEdit::new(rg, insert);
+    let caret = CaretPosition { cluster: edit.caret, position: None }.into();
+    return Some((edit, caret));
+  }
+
+  let start = line_start(text, rg.start);
+  let mut end = text[rg.end..]
+    .find('\n')
+    .map_or(text.len(), |idx| rg.end + idx);
+  // The line the selection ends at its start is not selected.
+  if multi_lines && rg.end == line_start(text, rg.end) {
+    end = rg.end - 1;
+  }
+  let unit = indent.unit();
+  let lines = text[start..end]
+    .split('\n')
+    .map(|line| {
+      if !outdent {
+        return format!("{unit}{line}");
+      }
+      let spaces = line.len() - line.trim_start_matches(' ').len();
+      let remove = if line.starts_with('\t') { 1 } else { spaces.min(indent.size) };
+      line[remove..].to_string()
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+  if lines == text[start..end] {
+    return None;
+  }
+
+  let caret = match caret {
+    CaretState::Caret(_) => {
+      let removed = (end - start).saturating_sub(lines.len());
+      CaretPosition { cluster: rg.start.saturating_sub(removed).max(start), position: None }.into()
+    }
+    _ => CaretState::Select(
+      CaretPosition { cluster: start, position: None },
+      CaretPosition { cluster: start + lines.len(), position: None },
+    ),
+  };
+  let edit = TextEdit::new(start..end, lines);
+  Some((edit, caret))
+}
+
+/// The positions of the bracket at the `caret` and the bracket matching it,
+/// the bracket before the caret first, and the brackets in the strings and the
+/// comments are ignored.
+fn matching_brackets(text: &str, caret: usize, spans: &[HighlightSpan]) -> Option<[usize; 2]> {
+  let in_literal = |idx: usize| {
+    let i = spans.partition_point(|s| s.range.end <= idx);
+    spans.get(i).is_some_and(|s| {
+      s.range.start <= idx && matches!(s.kind, TokenKind::String | TokenKind::Comment)
+    })
+  };
+  let before = text[..caret]
+    .chars()
+    .next_back()
+    .map(|c| caret - c.len_utf8());
+  [before, Some(caret)]
+    .into_iter()
+    .flatten()
+    .filter(|pos| !in_literal(*pos))
+    .find_map(|pos| match_bracket(text, pos, &in_literal).map(|m| [pos, m]))
+}
+
+/// The position of the bracket matching the bracket at `pos`.
+fn match_bracket(text: &str, pos: usize, skip: &impl Fn(usize) -> bool) -> Option<usize> {
+  let c = text[pos..].chars().next()?;
+  let mut depth = 0;
+  let mut step = |idx: usize, ch: char, open: char, close: char| {
+    if skip(idx) {
+      return None;
+    }
+    if ch == open {
+      depth += 1;
+    } else if ch == close {
+      depth -= 1;
+      if depth == 0 {
+        return Some(idx);
+      }
+    }
+    None
+  };
+  if let Some(&(open, close)) = BRACKETS.iter().find(|(open, _)| *open == c) {
+    text[pos..]
+      .char_indices()
+      .find_map(|(idx, ch)| step(pos + idx, ch, open, close))
+  } else if let Some(&(open, close)) = BRACKETS.iter().find(|(_, close)| *close == c) {
+    text[..pos + c.len_utf8()]
+      .char_indices()
+      .rev()
+      .find_map(|(idx, ch)| step(idx, ch, close, open))
+  } else {
+    None
+  }
+}
+
+impl SelectableText for CodeEditor {
+  fn select_range(&self) -> Range<usize> { self.caret.select_range() }
+
+  fn text(&self) -> &CowArc<str> { EditableText::text(self) }
+
+  fn text_rope(&self) -> Option<&Rope> { Some(&self.rope) }
+
+  fn caret(&self) -> CaretState { self.caret }
+
+  fn set_caret(&mut self, caret: CaretState) { self.caret = caret; }
+}
+
+impl EditableText for CodeEditor {
+  fn text(&self) -> &CowArc<str> {
+    self
+      .text
+      .get_or_init(|| self.rope.slice(0..self.rope.len()).into())
+  }
+
+  fn caret(&self) -> CaretState { self.caret }
+
+  fn rope(&self) -> &Rope { &self.rope }
+
+  fn set_rope_with_caret(&mut self, rope: Rope, caret: CaretState) {
+    self.rope = rope;
+    self.text.take();
+    self.caret = caret;
+  }
+
+  fn edit_history(&self) -> &EditHistory { &self.history }
+
+  fn edit_history_mut(&mut self) -> &mut EditHistory { &mut self.history }
+
+  fn input_filters(&self) -> &InputFilters { &self.filters }
+}
+
+impl EditableTextExtraWidget for CodeEditor {
+  fn intercept_key(this: &impl StateWriter<Value = Self>, event: &mut KeyboardEvent) -> bool {
+    let tab = *event.key() == VirtualKey::Named(NamedKey::Tab) && !event.with_command_key();
+    if tab {
+      // Keep the focus in the editor.
+      event.prevent_default();
+      this.write().indent(event.with_shift_key());
+    }
+    tab
+  }
+
+  fn intercept_chars(this: &impl StateWriter<Value = Self>, event: &mut CharsEvent) -> bool {
+    match event.chars.as_str() {
+      "\r" | "\n" | "\r\n" => this.write().new_line(),
+      // The tab is indented by the key down.
+      "\t" => {}
+      _ => return false,
+    }
+    true
+  }
+}
+
+impl ComposeChild<'static> for CodeEditor {
+  type Child = Option<Placeholder>;
+  fn compose_child(
+    this: impl StateWriter<Value = Self>, placeholder: Self::Child,
+  ) -> Widget<'static> {
+    fn_widget! {
+      // The glyphs are painted by the `CodeHighlight` in the colors of their
+      // tokens.
+      let text = @Text { text: "", rope: pipe!(Some($this.rope.clone())) };
+      $text.silent().glyphs_painted_by_others = true;
+      let only_text = text.clone_reader();
+      let text_id = text.lazy_host_id();
+
+      let current_line = @CurrentLine {
+        caret: pipe!(SelectableText::caret(&*$this).caret_position()),
+        brackets: pipe!($this.matching_brackets()),
+        text: only_text.clone_reader(),
+      };
+      let highlight = @CodeHighlight {
+        spans: pipe!($this.highlight_spans()),
+        text: only_text.clone_reader(),
+      };
+      let gutter = @LineNumbers {
+        visible: pipe!($this.line_numbers),
+        lines: pipe!($this.line_count()),
+        current: pipe!($this.current_line()),
+        text: only_text.clone_reader(),
+        text_id,
+      };
+      let decorations = TextDecorations {
+        under: vec![current_line.into_widget()],
+        over: vec![highlight.into_widget()],
+      };
+
+      // The focus of the editor goes to its edit area.
+      @FocusScope {
+        text_style: pipe!{
+          let this = $this;
+          let mut style = this.style.clone();
+          style.overflow = Overflow::Clip;
+          style.tab_size = TabSize::Spaces(this.indent_unit().size as u16);
+          style
+        },
+        @ConstrainedBox {
+          clamp: pipe!(size_clamp(&$this.style, $this.rows, $this.cols)),
+          @Row {
+            align_items: Align::Stretch,
+            @ { gutter }
+            @Expanded {
+              flex: 1.,
+              @EditableTextExtraWidget::edit_area(
+                this.clone_writer(),
+                text,
+                BoxPipe::value(Scrollable::Both).into_pipe(),
+                placeholder,
+                decorations,
+              )
+            }
+          }
+        }
+      }
+    }
+    .into_widget()
+  }
+}
+
+/// The horizontal padding of the line numbers in the gutter.
+const GUTTER_PADDING: f32 = 8.;
+
+/// The gutter of the line numbers, the numbers are placed at the lines of the
+/// text when painting, so they follow the text scrolled in this frame.
+#[derive(Declare)]
+struct LineNumbers {
+  lines: usize,
+  /// The line with the caret.
+  current: usize,
+  text: Reader<Text>,
+  text_id: LazyWidgetId,
+  #[declare(skip)]
+  shaped: RefCell<ShapedNumbers>,
+}
+
+/// The line numbers shaped when they're painted the first time, they're shaped
+/// again only if the text style is changed.
+#[derive(Default)]
+struct ShapedNumbers {
+  style: Option<TextStyle>,
+  /// The glyphs of the number `n` at `n - 1`.
+  numbers: Vec<Option<VisualGlyphs>>,
+  /// The count of the digits and the width of the widest number of them.
+  widest: Option<(usize, f32)>,
+}
+
+impl ShapedNumbers {
+  fn set_style(&mut self, style: &TextStyle) {
+    if self.style.as_ref() != Some(style) {
+      *self = Self { style: Some(style.clone()), ..<_>::default() };
+    }
+  }
+
+  /// The width of the widest number of the `digits`.
+  fn widest_width(&mut self, digits: usize) -> f32 {
+    match self.widest {
+      Some((d, width)) if d == digits => width,
+      _ => {
+        let width = self
+          .shape(&"0".repeat(digits))
+          .visual_rect()
+          .width();
+        self.widest = Some((digits, width));
+        width
+      }
+    }
+  }
+
+  fn number(&mut self, number: usize) -> &VisualGlyphs {
+    if self.numbers.len() < number {
+      self.numbers.resize_with(number, || None);
+    }
+    if self.numbers[number - 1].is_none() {
+      self.numbers[number - 1] = Some(self.shape(&number.to_string()));
+    }
+    self.numbers[number - 1].as_ref().unwrap()
+  }
+
+  fn shape(&self, text: &str) -> VisualGlyphs {
+    let style = self.style.as_ref().unwrap();
+    let text: CowArc<str> = text.to_string().into();
+    AppCtx::typography_store()
+      .borrow_mut()
+      .typography(
+        text.substr(..),
+        style,
+        Size::splat(f32::INFINITY),
+        TextAlign::Start,
+        style.writing_mode.line_dir(),
+      )
+  }
+}
+
+impl Render for LineNumbers {
+  fn perform_layout(&self, clamp: BoxClamp, ctx: &mut LayoutCtx) -> Size {
+    let style = Provider::of::<TextStyle>(&ctx).unwrap();
+    let mut shaped = self.shaped.borrow_mut();
+    shaped.set_style(&style);
+    shaped.numbers.truncate(self.lines);
+    let width = shaped.widest_width(self.lines.max(1).to_string().len());
+    clamp.clamp(Size::new(width + GUTTER_PADDING * 2., clamp.min.height))
+  }
+
+  fn paint(&self, ctx: &mut PaintingCtx) {
+    let size = ctx.box_size().unwrap();
+    let lines = {
+      let text = self.text.read();
+      let Some(glyphs) = text.glyphs() else { return };
+      let top = glyphs.visual_rect().min_y();
+      glyphs
+        .metrics()
+        .lines
+        .iter()
+        .map(|l| (top + l.rect.min_y(), l.rect.height()))
+        .collect::<Vec<_>>()
+    };
+    // The position of the text in the gutter.
+    let wnd = ctx.window();
+    let offset = wnd.map_to_global(Point::zero(), self.text_id.assert_id())
+      - wnd.map_to_global(Point::zero(), ctx.widget_id());
+
+    let style = CodeEditorStyle::of(&ctx);
+    let mut shaped = self.shaped.borrow_mut();
+    shaped.set_style(&Provider::of::<TextStyle>(&ctx).unwrap());
+    let font_db = AppCtx::font_db().clone();
+    let painter = ctx.painter();
+    let rect = Rect::from_size(size);
+    painter.save();
+    painter
+      .set_fill_brush(style.gutter_background.clone())
+      .rect(&rect)
+      .fill();
+    painter.clip(Path::rect(&rect).into());
+    for (idx, (y, height)) in lines.into_iter().enumerate() {
+      let y = y + offset.y;
+      if y + height < 0. || size.height < y {
+        continue;
+      }
+      let number = shaped.number(idx + 1);
+      // The numbers are aligned to the end of the gutter.
+      let x = size.width - GUTTER_PADDING - number.visual_rect().width();
+      let bounds = Rect::from_size(Size::new(size.width, height));
+      let brush = if idx == self.current { &style.current_line_number } else { &style.line_number };
+      painter
+        .save()
+        .set_fill_brush(brush.clone())
+        .translate(x, y)
+        .draw_glyphs_in_rect(number, bounds, &font_db.borrow());
+      painter.restore();
+    }
+    painter.restore();
+  }
+}
+
+/// The background of the line with the caret and the matching brackets.
+#[derive(Declare)]
+struct CurrentLine {
+  caret: CaretPosition,
+  brackets: Option<[usize; 2]>,
+  text: Reader<Text>,
+}
+
+impl Render for CurrentLine {
+  fn perform_layout(&self, clamp: BoxClamp, _: &mut LayoutCtx) -> Size { clamp.min }
+
+  #[inline]
+  fn only_sized_by_parent(&self) -> bool { true }
+
+  fn paint(&self, ctx: &mut PaintingCtx) {
+    let (line, brackets) = {
+      let text = self.text.read();
+      let Some(glyphs) = text.glyphs() else { return };
+      let pos = glyphs.cursor(self.caret);
+      let width = ctx
+        .box_size()
+        .unwrap()
+        .width
+        .max(glyphs.visual_rect().max_x());
+      let height = glyphs.line_height_by_caret(self.caret);
+      let line = Rect::new(Point::new(0., pos.y), Size::new(width, height));
+      let brackets = self
+        .brackets
+        .iter()
+        .flatten()
+        .flat_map(|pos| glyphs.selection(&(*pos..pos + 1)))
+        .collect::<Vec<_>>();
+      (line, brackets)
+    };
+
+    let style = CodeEditorStyle::of(&ctx);
+    let painter = ctx.painter();
+    painter.save();
+    painter
+      .set_fill_brush(style.current_line.clone())
+      .rect(&line)
+      .fill();
+    if !brackets.is_empty() {
+      painter.set_fill_brush(style.bracket_match.clone());
+      for rect in brackets.iter() {
+        painter.rect(rect);
+      }
+      painter.fill();
+    }
+    painter.restore();
+  }
+}
+
+/// Paint the glyphs of the text in the brushes of their tokens, the glyphs
+/// not in any token are painted by the foreground.
+#[derive(Declare)]
+struct CodeHighlight {
+  spans: Rc<[HighlightSpan]>,
+  text: Reader<Text>,
+}
+
+impl Render for CodeHighlight {
+  fn perform_layout(&self, clamp: BoxClamp, _: &mut LayoutCtx) -> Size { clamp.min }
+
+  #[inline]
+  fn only_sized_by_parent(&self) -> bool { true }
+
+  fn paint(&self, ctx: &mut PaintingCtx) {
+    let text = self.text.read();
+    let Some(glyphs) = text.glyphs() else { return };
+    let style = CodeEditorStyle::of(&ctx);
+    let font_db = AppCtx::font_db().clone();
+    let font_db = font_db.borrow();
+    let painter = ctx.painter();
+    let foreground = painter.fill_brush().clone();
+    let visual_rect = glyphs.visual_rect();
+    let Some(paint_rect) = painter.intersection_paint_bounds(&visual_rect) else { return };
+
+    // The spans may be highlighted for an old text.
+    let len = text.glyphs_text().len();
+    let brush_of = |cluster: usize| {
+      let idx = self
+        .spans
+        .partition_point(|s| s.range.end <= cluster);
+      match self.spans.get(idx) {
+        Some(s) if s.range.start <= cluster && s.range.end <= len => style.token_brush(s.kind),
+        _ => &foreground,
+      }
+    };
+
+    // Only the glyphs in the paint bounds are painted, in one pass.
+    painter.save();
+    painter.translate(visual_rect.origin.x, visual_rect.origin.y);
+    let mut current = None;
+    for g in glyphs.glyphs_in_bounds(&paint_rect) {
+      if g.glyph_id == shaper::PLACEHOLDER_GLYPH_ID {
+        continue;
+      }
+      let brush = brush_of(g.cluster as usize);
+      if current.is_none_or(|c| !std::ptr::eq(c, brush)) {
+        painter.set_fill_brush(brush.clone());
+        current = Some(brush);
+      }
+      painter.draw_glyph(&g, &font_db);
+    }
+    painter.restore();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ribir_core::{
+    reset_test_env,
+    test_helper::{TestWindow, split_value},
+  };
+  use winit::event::ElementState;
+
+  use super::*;
+
+  const SPACES: Indent = Indent { size: 4, spaces: true };
+
+  fn caret(cluster: usize) -> CaretPosition { CaretPosition { cluster, position: None } }
+
+  #[test]
+  fn shape_line_numbers_once() {
+    reset_test_env!();
+    let mut shaped = ShapedNumbers::default();
+    shaped.set_style(&TextStyle::default());
+    let first: *const VisualGlyphs = shaped.number(3);
+    assert!(std::ptr::eq(first, shaped.number(3)));
+    assert_eq!(shaped.numbers.len(), 3);
+    let width = shaped.widest_width(2);
+    assert_eq!(shaped.widest, Some((2, width)));
+
+    shaped.set_style(&TextStyle { font_size: 30., ..<_>::default() });
+    assert!(shaped.numbers.is_empty());
+    assert!(shaped.widest.is_none());
+  }
+
+  #[test]
+  fn bracket_matching() {
+    let code = "fn f(a: [u8; 2]) { \"(\" }";
+    let spans = SimpleHighlighter::rust().highlight(code);
+    assert_eq!(matching_brackets(code, 4, &spans), Some([4, 15]));
+    // The bracket before the caret first.
+    assert_eq!(matching_brackets(code, 16, &spans), Some([15, 4]));
+    assert_eq!(matching_brackets(code, 17, &spans), Some([17, 23]));
+    // The bracket in the string is ignored.
+    assert_eq!(matching_brackets(code, 20, &spans), None);
+  }
+
+  #[test]
+  fn auto_indent() {
+    let edit = new_line_edit("  if x {}", 8..8, SPACES);
+    assert_eq!(edit.apply("  if x {}"), "  if x {\n      \n  }");
+    assert_eq!(edit.caret, 15);
+
+    let edit = new_line_edit("  let a = 1;", 12..12, SPACES);
+    assert_eq!(edit.apply("  let a = 1;"), "  let a = 1;\n  ");
+
+    let tab = Indent { size: 4, spaces: false };
+    let edit = new_line_edit("\tf(", 3..3, tab);
+    assert_eq!(edit.apply("\tf("), "\tf(\n\t\t");
+  }
+
+  #[test]
+  fn indent_lines() {
+    // Tab to the next tab stop.
+    let (edit, _) = indent_edit("ab\ncd", caret(2).into(), SPACES, false).unwrap();
+    assert_eq!(edit.apply("ab\ncd"), "ab  \ncd");
+    // A tab char is counted to its tab stop.
+    let (edit, _) = indent_edit("\tab", caret(3).into(), SPACES, false).unwrap();
+    assert_eq!(edit.apply("\tab"), "\tab  ");
+
+    let text = "ab\ncd\nef";
+    let select = CaretState::Select(caret(1), caret(4));
+    let (edit, caret) = indent_edit(text, select, SPACES, false).unwrap();
+    let indented = edit.apply(text);
+    assert_eq!(indented, "    ab\n    cd\nef");
+    assert_eq!(caret.select_range(), 0..13);
+
+    let (edit, _) = indent_edit(&indented, caret, SPACES, true).unwrap();
+    assert_eq!(edit.apply(&indented), text);
+    assert!(indent_edit(text, select, SPACES, true).is_none());
+  }
+
+  #[test]
+  fn tab_size_of_text() {
+    reset_test_env!();
+    let w = fn_widget! {
+      let editor = @CodeEditor { tab_size: 2usize };
+      $editor.write().set_text("\tx\n  x");
+      editor
+    };
+    let mut wnd = TestWindow::new_with_size(w, Size::new(400., 300.));
+    wnd.draw_frame();
+
+    fn glyph_x(cmds: &[PaintCommand], xs: &mut Vec<f32>) {
+      for cmd in cmds {
+        match cmd {
+          PaintCommand::Path(p) if p.paint_bounds.width() < 16. => xs.push(p.paint_bounds.min_x()),
+          PaintCommand::Bundle { cmds, .. } => glyph_x(cmds, xs),
+          _ => {}
+        }
+      }
+    }
+    let mut xs = vec![];
+    glyph_x(&wnd.take_last_frame().unwrap().commands, &mut xs);
+    xs.sort_by(|a, b| b.total_cmp(a));
+    // The `x` after the tab is aligned with the `x` after two spaces.
+    assert!((xs[0] - xs[1]).abs() < 1., "{xs:?}");
+  }
+
+  #[test]
+  fn highlight_visible_lines() {
+    reset_test_env!();
+    let keyword = Rc::new(RefCell::new(Brush::Color(Color::BLACK)));
+    let c_keyword = keyword.clone();
+    let w = fn_widget! {
+      *c_keyword.borrow_mut() = CodeEditorStyle::of(BuildCtx::get()).keyword;
+      let editor = @CodeEditor { highlighter: SimpleHighlighter::rust() };
+      $editor.write().set_text(&"let a = 1;\n".repeat(100));
+      editor
+    };
+    let mut wnd = TestWindow::new_with_size(w, Size::new(400., 300.));
+    wnd.draw_frame();
+
+    fn count(cmds: &[PaintCommand], color: Color) -> usize {
+      cmds
+        .iter()
+        .map(|cmd| match cmd {
+          PaintCommand::Path(p) => {
+            matches!(p.action, PaintPathAction::Color(c) if c == color) as usize
+          }
+          PaintCommand::Bundle { cmds, .. } => count(cmds, color),
+          _ => 0,
+        })
+        .sum()
+    }
+    let Brush::Color(color) = *keyword.borrow() else { panic!() };
+    let lets = count(&wnd.take_last_frame().unwrap().commands, color);
+    // The `let` of the lines in the view of ten rows.
+    assert!(lets > 0 && lets <= 3 * 11, "{lets}");
+  }
+
+  #[test]
+  fn edit_in_window() {
+    reset_test_env!();
+    let (value, w_value) = split_value(String::default());
+    let w = fn_widget! {
+      let editor = @CodeEditor {
+        auto_focus: true,
+        highlighter: SimpleHighlighter::rust(),
+      };
+      watch!(EditableText::text(&*$editor).clone())
+        .subscribe(move |text| *$w_value.write() = text.to_string());
+      editor
+    };
+    let mut wnd = TestWindow::new_with_size(w, Size::new(400., 300.));
+    wnd.draw_frame();
+    wnd.processes_receive_chars("fn main() {".into());
+    wnd.draw_frame();
+    wnd.processes_receive_chars("\r".into());
+    wnd.draw_frame();
+    assert_eq!(*value.read(), "fn main() {\n    ");
+
+    // The tab is indented by the editor, not moving the focus.
+    wnd.processes_keyboard_event(
+      PhysicalKey::Code(KeyCode::Tab),
+      VirtualKey::Named(NamedKey::Tab),
+      false,
+      KeyLocation::Standard,
+      ElementState::Pressed,
+    );
+    wnd.processes_receive_chars("\t".into());
+    wnd.draw_frame();
+    wnd.processes_receive_chars("}".into());
+    wnd.draw_frame();
+    assert_eq!(*value.read(), "fn main() {\n        }");
+  }
+}
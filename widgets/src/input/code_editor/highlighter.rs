@@ -0,0 +1,244 @@
+use std::{ops::Range, rc::Rc};
+
+/// The kind of a token of the code, the `CodeEditorStyle` gives every kind a
+/// brush.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+  Keyword,
+  Type,
+  String,
+  Number,
+  Comment,
+  Function,
+  Punctuation,
+}
+
+/// A range of the code styled as the `kind`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightSpan {
+  pub range: Range<usize>,
+  pub kind: TokenKind,
+}
+
+/// A highlighter of the `CodeEditor`, it returns the styled ranges of the
+/// code, the ranges are in order and not overlapped, the code not in any range
+/// is styled as the plain text.
+pub trait Highlighter {
+  fn highlight(&self, code: &str) -> Vec<HighlightSpan>;
+}
+
+/// The highlighter of a `CodeEditor`, no highlight by default.
+///
+/// # Example
+///
+/// ```
+/// use ribir_core::prelude::*;
+/// use ribir_widgets::prelude::*;
+///
+/// let _editor = fn_widget! {
+///   @CodeEditor { highlighter: SimpleHighlighter::rust() }
+/// };
+/// ```
+#[derive(Clone, Default)]
+pub struct Highlight(Option<Rc<dyn Highlighter>>);
+
+impl Highlight {
+  #[inline]
+  pub fn highlighter(&self) -> Option<&Rc<dyn Highlighter>> { self.0.as_ref() }
+}
+
+impl<T: Highlighter + 'static> From<T> for Highlight {
+  fn from(highlighter: T) -> Self { Self(Some(Rc::new(highlighter))) }
+}
+
+/// A highlighter by a simple tokenizer, it knows the keywords, the types, the
+/// comments and the strings of a language, the identifiers followed by a `(`
+/// are functions.
+#[derive(Debug, Clone, Default)]
+pub struct SimpleHighlighter {
+  pub keywords: Vec<&'static str>,
+  /// The words highlighted as types, the words start with a uppercase letter
+  /// are types too.
+  pub types: Vec<&'static str>,
+  pub line_comment: Option<&'static str>,
+  pub block_comment: Option<(&'static str, &'static str)>,
+  /// The quotes of the strings, the chars escaped by `\` in the strings are
+  /// skipped.
+  pub quotes: Vec<char>,
+}
+
+impl SimpleHighlighter {
+  pub fn rust() -> Self {
+    Self {
+      keywords: vec![
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while",
+      ],
+      types: vec![
+        "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32",
+        "i64", "i128", "isize", "f32", "f64",
+      ],
+      line_comment: Some("//"),
+      block_comment: Some(("/*", "*/")),
+      quotes: vec!['"'],
+    }
+  }
+
+  pub fn javascript() -> Self {
+    Self {
+      keywords: vec![
+        "async", "await", "break", "case", "catch", "class", "const", "continue", "default",
+        "delete", "do", "else", "export", "extends", "false", "finally", "for", "function", "if",
+        "import", "in", "instanceof", "let", "new", "null", "of", "return", "static", "super",
+        "switch", "this", "throw", "true", "try", "typeof", "undefined", "var", "void", "while",
+        "yield",
+      ],
+      types: vec![],
+      line_comment: Some("//"),
+      block_comment: Some(("/*", "*/")),
+      quotes: vec!['"', '\'', '`'],
+    }
+  }
+
+  pub fn python() -> Self {
+    Self {
+      keywords: vec![
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+        "elif", "else", "except", "False", "finally", "for", "from", "global", "if", "import",
+        "in", "is", "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "True",
+        "try", "while", "with", "yield",
+      ],
+      types: vec!["int", "float", "str", "bool", "list", "dict", "set", "tuple"],
+      line_comment: Some("#"),
+      block_comment: None,
+      quotes: vec!['"', '\''],
+    }
+  }
+
+  fn word_kind(&self, word: &str, rest: &str) -> Option<TokenKind> {
+    if self.keywords.contains(&word) {
+      Some(TokenKind::Keyword)
+    } else if self.types.contains(&word) || word.starts_with(char::is_uppercase) {
+      Some(TokenKind::Type)
+    } else if rest.trim_start().starts_with('(') {
+      Some(TokenKind::Function)
+    } else {
+      None
+    }
+  }
+}
+
+impl Highlighter for SimpleHighlighter {
+  fn highlight(&self, code: &str) -> Vec<HighlightSpan> {
+    let mut spans = vec![];
+    let mut idx = 0;
+    while let Some(c) = code[idx..].chars().next() {
+      let rest = &code[idx..];
+      let (len, kind) = if let Some(prefix) = self.line_comment.filter(|p| rest.starts_with(p)) {
+        let len = rest.find('\n').unwrap_or(rest.len());
+        (len.max(prefix.len()), Some(TokenKind::Comment))
+      } else if let Some((open, close)) = self
+        .block_comment
+        .filter(|(open, _)| rest.starts_with(open))
+      {
+        let len = rest[open.len()..]
+          .find(close)
+          .map_or(rest.len(), |end| open.len() + end + close.len());
+        (len, Some(TokenKind::Comment))
+      } else if self.quotes.contains(&c) {
+        (string_len(rest, c), Some(TokenKind::String))
+      } else if c.is_ascii_digit() {
+        let len = rest
+          .find(|c: char| !c.is_alphanumeric() && c != '_' && c != '.')
+          .unwrap_or(rest.len());
+        (len, Some(TokenKind::Number))
+      } else if c.is_alphabetic() || c == '_' {
+        let len = rest
+          .find(|c: char| !c.is_alphanumeric() && c != '_')
+          .unwrap_or(rest.len());
+        (len, self.word_kind(&rest[..len], &rest[len..]))
+      } else if c.is_ascii_punctuation() {
+        (c.len_utf8(), Some(TokenKind::Punctuation))
+      } else {
+        (c.len_utf8(), None)
+      };
+
+      if let Some(kind) = kind {
+        match spans.last_mut() {
+          Some(HighlightSpan { range, kind: last }) if *last == kind && range.end == idx => {
+            range.end = idx + len
+          }
+          _ => spans.push(HighlightSpan { range: idx..idx + len, kind }),
+        }
+      }
+      idx += len;
+    }
+    spans
+  }
+}
+
+/// The length of the string starts with the `quote` at the start of the
+/// `code`, the string not closed goes to the end of the line.
+fn string_len(code: &str, quote: char) -> usize {
+  let mut chars = code.char_indices().skip(1);
+  while let Some((idx, c)) = chars.next() {
+    if c == '\\' {
+      chars.next();
+    } else if c == quote {
+      return idx + c.len_utf8();
+    } else if c == '\n' && quote != '`' {
+      return idx;
+    }
+  }
+  code.len()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn rust_tokens() {
+    let code = "fn main() {\n  let s: String = \"a\\\"b\"; // hi\n  foo(1.5);\n}";
+    let spans = SimpleHighlighter::rust().highlight(code);
+    let tokens = spans
+      .iter()
+      .map(|s| (&code[s.range.clone()], s.kind))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      tokens,
+      vec![
+        ("fn", TokenKind::Keyword),
+        ("main", TokenKind::Function),
+        ("()", TokenKind::Punctuation),
+        ("{", TokenKind::Punctuation),
+        ("let", TokenKind::Keyword),
+        (":", TokenKind::Punctuation),
+        ("String", TokenKind::Type),
+        ("=", TokenKind::Punctuation),
+        ("\"a\\\"b\"", TokenKind::String),
+        (";", TokenKind::Punctuation),
+        ("// hi", TokenKind::Comment),
+        ("foo", TokenKind::Function),
+        ("(", TokenKind::Punctuation),
+        ("1.5", TokenKind::Number),
+        (");", TokenKind::Punctuation),
+        ("}", TokenKind::Punctuation),
+      ]
+    );
+  }
+
+  #[test]
+  fn unclosed_comment_and_string() {
+    let highlighter = SimpleHighlighter::javascript();
+    let code = "/* open\n 'str\nx";
+    let spans = highlighter.highlight(code);
+    assert_eq!(spans, vec![HighlightSpan { range: 0..code.len(), kind: TokenKind::Comment }]);
+
+    let code = "'str\nx";
+    let spans = highlighter.highlight(code);
+    assert_eq!(spans, vec![HighlightSpan { range: 0..4, kind: TokenKind::String }]);
+  }
+}
@@ -0,0 +1,329 @@
+use ribir_core::prelude::Rope;
+
+use super::{CaretState, TextEdit};
+
+/// The kind of an edit, the continuous typing is merged into word-sized
+/// groups, and the others are recorded one by one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum EditKind {
+  Typing,
+  Delete,
+  Paste,
+  Cut,
+  Ime,
+  Replace,
+}
+
+/// An edit that replaced the `removed` text at `start` with the `inserted`
+/// text.
+#[derive(Debug)]
+struct Edit {
+  kind: EditKind,
+  start: usize,
+  removed: String,
+  inserted: String,
+  caret_before: CaretState,
+  caret_after: CaretState,
+}
+
+/// The undo and redo stacks of an editable text.
+#[derive(Debug)]
+pub struct EditHistory {
+  undo: Vec<Edit>,
+  redo: Vec<Edit>,
+  limit: usize,
+  /// Do not merge the next typing into the last group.
+  sealed: bool,
+  /// The edit of the IME composition, it's recorded when the composition ends.
+  composing: Option<Edit>,
+}
+
+impl Default for EditHistory {
+  fn default() -> Self {
+    Self { undo: vec![], redo: vec![], limit: 100, sealed: false, composing: None }
+  }
+}
+
+impl EditHistory {
+  #[inline]
+  pub fn can_undo(&self) -> bool { !self.undo.is_empty() }
+
+  #[inline]
+  pub fn can_redo(&self) -> bool { !self.redo.is_empty() }
+
+  /// Set the max count of the edits that can be undone.
+  pub fn set_limit(&mut self, limit: usize) {
+    self.limit = limit;
+    self.trim();
+  }
+
+  pub fn clear(&mut self) {
+    self.undo.clear();
+    self.redo.clear();
+    self.composing = None;
+  }
+
+  /// Record the `edit` that replaced the `removed` text.
+  pub(crate) fn record(
+    &mut self, kind: EditKind, removed: &str, edit: &TextEdit, caret_before: CaretState,
+    caret_after: CaretState,
+  ) {
+    let edit = Edit {
+      kind,
+      start: edit.range.start,
+      removed: removed.to_string(),
+      inserted: edit.insert.clone(),
+      caret_before,
+      caret_after,
+    };
+    match self.composing.take() {
+      // The text committed by the IME.
+      Some(mut composing) if composing.can_join(&edit) => {
+        composing.inserted.push_str(&edit.inserted);
+        composing.caret_after = edit.caret_after;
+        self.push(composing);
+      }
+      Some(composing) => {
+        self.push(composing);
+        self.push(edit);
+      }
+      None => self.push(edit),
+    }
+  }
+
+  /// Start an IME composition by the `edit` that replaced the `removed` text,
+  /// the edits before the composition ends are recorded as one edit.
+  pub(crate) fn start_composing(&mut self, removed: &str, edit: &TextEdit, caret: CaretState) {
+    if self.composing.is_some() {
+      return;
+    }
+    self.composing = Some(Edit {
+      kind: EditKind::Ime,
+      start: edit.range.start,
+      removed: removed.to_string(),
+      inserted: edit.insert.clone(),
+      caret_before: caret,
+      caret_after: caret,
+    });
+  }
+
+  /// End the IME composition, the composition canceled without committing is
+  /// recorded if it removed the selected text.
+  pub(crate) fn end_composing(&mut self) {
+    if let Some(composing) = self.composing.take() {
+      self.push(composing);
+    }
+  }
+
+  /// Undo the last edit of the `text`, and return the edit that restores the
+  /// text and the caret before it.
+  pub(crate) fn undo(&mut self, text: &Rope) -> Option<(TextEdit, CaretState)> {
+    self.end_composing();
+    let edit = self.undo.pop()?;
+    let Some(restore) = edit.revert(text) else {
+      // The text was changed out of the history, the history is invalid now.
+      self.clear();
+      return None;
+    };
+    let caret = edit.caret_before;
+    self.redo.push(edit);
+    self.sealed = true;
+    Some((restore, caret))
+  }
+
+  /// Redo the last undone edit of the `text`, and return the edit that
+  /// restores the text and the caret after it.
+  pub(crate) fn redo(&mut self, text: &Rope) -> Option<(TextEdit, CaretState)> {
+    self.end_composing();
+    let edit = self.redo.pop()?;
+    let Some(restore) = edit.apply(text) else {
+      self.clear();
+      return None;
+    };
+    let caret = edit.caret_after;
+    self.undo.push(edit);
+    self.sealed = true;
+    Some((restore, caret))
+  }
+
+  fn push(&mut self, edit: Edit) {
+    if edit.removed == edit.inserted {
+      return;
+    }
+    self.redo.clear();
+    let sealed = std::mem::take(&mut self.sealed);
+    match self.undo.last_mut() {
+      Some(last) if !sealed && last.can_merge(&edit) => {
+        last.inserted.push_str(&edit.inserted);
+        last.caret_after = edit.caret_after;
+      }
+      _ => {
+        self.undo.push(edit);
+        self.trim();
+      }
+    }
+  }
+
+  fn trim(&mut self) {
+    if self.undo.len() > self.limit {
+      let over = self.undo.len() - self.limit;
+      self.undo.drain(..over);
+    }
+  }
+}
+
+impl Edit {
+  /// Typing is merged until a word ends, the word ends when typing a
+  /// non-whitespace after whitespace.
+  fn can_merge(&self, next: &Edit) -> bool {
+    let word_end = self.inserted.ends_with(char::is_whitespace)
+      && !next.inserted.starts_with(char::is_whitespace);
+    self.kind == EditKind::Typing
+      && next.kind == EditKind::Typing
+      && next.removed.is_empty()
+      && self.start + self.inserted.len() == next.start
+      && !word_end
+  }
+
+  /// The IME commits its text by inserting it at the end of the composition.
+  fn can_join(&self, next: &Edit) -> bool {
+    next.removed.is_empty() && self.start + self.inserted.len() == next.start
+  }
+
+  fn revert(&self, text: &Rope) -> Option<TextEdit> {
+    replace(text, self.start, &self.inserted, &self.removed)
+  }
+
+  fn apply(&self, text: &Rope) -> Option<TextEdit> {
+    replace(text, self.start, &self.removed, &self.inserted)
+  }
+}
+
+/// The edit replaces the `from` at `start` of the `text` with `to`, return
+/// `None` if the `text` has no `from` at `start`.
+fn replace(text: &Rope, start: usize, from: &str, to: &str) -> Option<TextEdit> {
+  let end = start + from.len();
+  if text.get(start..end)? != from {
+    return None;
+  }
+  Some(TextEdit::new(start..end, to.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::input::CaretPosition;
+
+  fn caret(cluster: usize) -> CaretState { CaretPosition { cluster, position: None }.into() }
+
+  /// Do the `edit` on the `text` and record it.
+  fn edit(history: &mut EditHistory, kind: EditKind, text: &mut String, edit: TextEdit) {
+    let before = caret(edit.range.end);
+    history.record(kind, &text[edit.range.clone()], &edit, before, caret(edit.caret));
+    *text = edit.apply(text);
+  }
+
+  fn insert(history: &mut EditHistory, kind: EditKind, text: &mut String, at: usize, s: &str) {
+    edit(history, kind, text, TextEdit::new(at..at, s.to_string()));
+  }
+
+  fn type_str(history: &mut EditHistory, text: &mut String, s: &str) {
+    for c in s.chars() {
+      let at = text.len();
+      insert(history, EditKind::Typing, text, at, &c.to_string());
+    }
+  }
+
+  fn undo(history: &mut EditHistory, text: &str) -> Option<(String, CaretState)> {
+    history
+      .undo(&text.into())
+      .map(|(edit, caret)| (edit.apply(text), caret))
+  }
+
+  fn redo(history: &mut EditHistory, text: &str) -> Option<(String, CaretState)> {
+    history
+      .redo(&text.into())
+      .map(|(edit, caret)| (edit.apply(text), caret))
+  }
+
+  #[test]
+  fn typing_groups_by_word() {
+    let mut history = EditHistory::default();
+    let mut text = String::new();
+    type_str(&mut history, &mut text, "hello world");
+
+    let (text, c) = undo(&mut history, &text).unwrap();
+    assert_eq!(text, "hello ");
+    assert_eq!(c, caret(6));
+    let (text, c) = undo(&mut history, &text).unwrap();
+    assert_eq!(text, "");
+    assert_eq!(c, caret(0));
+    assert!(!history.can_undo());
+
+    let (text, _) = redo(&mut history, &text).unwrap();
+    let (text, c) = redo(&mut history, &text).unwrap();
+    assert_eq!(text, "hello world");
+    assert_eq!(c, caret(11));
+    assert!(!history.can_redo());
+  }
+
+  #[test]
+  fn record_separately() {
+    let mut history = EditHistory::default();
+    let mut text = String::new();
+    type_str(&mut history, &mut text, "ab");
+    insert(&mut history, EditKind::Paste, &mut text, 2, "cd");
+    edit(&mut history, EditKind::Delete, &mut text, TextEdit::new(3..4, String::new()));
+    edit(&mut history, EditKind::Delete, &mut text, TextEdit::new(2..3, String::new()));
+    assert_eq!(text, "ab");
+
+    let (text, _) = undo(&mut history, "ab").unwrap();
+    assert_eq!(text, "abc");
+    let (text, _) = undo(&mut history, &text).unwrap();
+    assert_eq!(text, "abcd");
+    let (mut text, _) = undo(&mut history, &text).unwrap();
+    assert_eq!(text, "ab");
+
+    // A new edit clears the redo stack.
+    edit(&mut history, EditKind::Cut, &mut text, TextEdit::new(0..2, String::new()));
+    assert!(!history.can_redo());
+    assert_eq!(undo(&mut history, "").unwrap().0, "ab");
+    // The history is dropped if the text is not what it recorded.
+    assert!(undo(&mut history, "xy").is_none());
+    assert!(!history.can_undo());
+  }
+
+  #[test]
+  fn ime_composition() {
+    let mut history = EditHistory::default();
+    let select = CaretState::Select(caret(2).caret_position(), caret(5).caret_position());
+    let mut text = "a 你".to_string();
+    let removed = TextEdit::new(2..5, String::new());
+    history.start_composing(&text[2..5], &removed, select);
+    text = removed.apply(&text);
+    // The text committed by the IME.
+    insert(&mut history, EditKind::Typing, &mut text, 2, "好的");
+    history.end_composing();
+
+    let (text, c) = undo(&mut history, &text).unwrap();
+    assert_eq!(text, "a 你");
+    assert_eq!(c.select_range(), 2..5);
+  }
+
+  #[test]
+  fn ime_cancel() {
+    let mut history = EditHistory::default();
+    let mut text = "ab".to_string();
+    let removed = TextEdit::new(2..2, String::new());
+    history.start_composing("", &removed, caret(2));
+    // Canceled without committing.
+    history.end_composing();
+
+    type_str(&mut history, &mut text, "c");
+    type_str(&mut history, &mut text, "d");
+    // The typing is not taken as the text of the canceled composition.
+    assert_eq!(history.undo.len(), 1);
+    assert_eq!(history.undo[0].kind, EditKind::Typing);
+    assert_eq!(undo(&mut history, &text).unwrap().0, "ab");
+  }
+}
@@ -2,7 +2,7 @@ use std::ops::Range;
 
 use ribir_core::prelude::*;
 
-use super::caret_state::CaretPosition;
+use super::{caret_state::CaretPosition, masked_text::MaskedText, text_selectable::SelectableText};
 
 impl<K, V> SingleKeyMap<K, V>
 where
@@ -23,54 +23,256 @@ impl<K, V> Default for SingleKeyMap<K, V> {
   fn default() -> Self { Self(None) }
 }
 
+/// The text the glyphs are laid out from. A rope is the same text as its
+/// clones, it's compared by the identity, so its text is not copied out.
+#[derive(Clone, Debug)]
+pub(crate) enum GlyphsText {
+  Str(CowArc<str>),
+  Rope(Rope),
+}
+
+impl GlyphsText {
+  /// The text of the `host`, it's the rope of the host if the host has one.
+  pub(crate) fn of(host: &(impl SelectableText + ?Sized)) -> Self {
+    match host.text_rope() {
+      Some(rope) => GlyphsText::Rope(rope.clone()),
+      None => GlyphsText::Str(host.text().clone()),
+    }
+  }
+
+  pub(crate) fn len(&self) -> usize {
+    match self {
+      GlyphsText::Str(text) => text.len(),
+      GlyphsText::Rope(rope) => rope.len(),
+    }
+  }
+
+  /// Return if the byte `range` is in the text and on the char boundaries.
+  pub(crate) fn has_range(&self, range: &Range<usize>) -> bool {
+    match self {
+      GlyphsText::Str(text) => text.get(range.clone()).is_some(),
+      GlyphsText::Rope(rope) => rope.get(range.clone()).is_some(),
+    }
+  }
+
+  /// Do `f` with the paragraph at `idx` and its byte offset in the text, the
+  /// words never cross the paragraphs, so the words of a rope are found in
+  /// its paragraphs. A text not in a rope is one paragraph.
+  fn with_paragraph<R>(&self, idx: usize, f: impl FnOnce(&str, usize) -> R) -> R {
+    match self {
+      GlyphsText::Str(text) => f(text, 0),
+      GlyphsText::Rope(rope) => f(rope.paragraph(idx), rope.paragraph_range(idx).start),
+    }
+  }
+
+  /// The index of the paragraph that contains the byte at `cluster`.
+  fn paragraph_at(&self, cluster: usize) -> usize {
+    match self {
+      GlyphsText::Str(_) => 0,
+      GlyphsText::Rope(rope) => rope.paragraph_at(cluster),
+    }
+  }
+
+  fn paragraph_count(&self) -> usize {
+    match self {
+      GlyphsText::Str(_) => 1,
+      GlyphsText::Rope(rope) => rope.paragraph_count(),
+    }
+  }
+
+  /// The range of the word at the `cluster`.
+  pub(crate) fn word_at(&self, cluster: usize) -> Range<usize> {
+    self.with_paragraph(self.paragraph_at(cluster), |p, start| {
+      let rg = select_word(p, cluster - start);
+      rg.start + start..rg.end + start
+    })
+  }
+
+  /// The start of the word before the `cluster`.
+  pub(crate) fn prev_word_start(&self, cluster: usize) -> usize {
+    if cluster == 0 {
+      return 0;
+    }
+    self.with_paragraph(self.paragraph_at(cluster - 1), |p, start| {
+      start + select_prev_word(p, cluster - start, false).start
+    })
+  }
+
+  /// The end of the first word after the `cluster` that is not a whitespace.
+  pub(crate) fn next_word_end(&self, cluster: usize) -> usize {
+    let first = self.paragraph_at(cluster);
+    (first..self.paragraph_count())
+      .find_map(|idx| {
+        self.with_paragraph(idx, |p, start| {
+          let from = cluster.saturating_sub(start);
+          let word = select_next_word(p, from, true);
+          (word.start < p.len()).then_some(start + word.end)
+        })
+      })
+      .unwrap_or(self.len())
+  }
+
+  /// The start of the paragraph where the `cluster` is, or the start of the
+  /// previous paragraph if the `cluster` is already at the start.
+  pub(crate) fn prev_paragraph_start(&self, cluster: usize) -> usize {
+    match self {
+      GlyphsText::Str(text) => prev_paragraph_start(text, cluster),
+      GlyphsText::Rope(_) if cluster == 0 => 0,
+      GlyphsText::Rope(rope) => {
+        rope
+          .paragraph_range(rope.paragraph_at(cluster - 1))
+          .start
+      }
+    }
+  }
+
+  /// The end of the paragraph where the `cluster` is, before its separator, or
+  /// the end of the next paragraph if the `cluster` is already at the end.
+  pub(crate) fn next_paragraph_end(&self, cluster: usize) -> usize {
+    let GlyphsText::Rope(rope) = self else {
+      return self.with_paragraph(0, |text, _| next_paragraph_end(text, cluster));
+    };
+    let end = |idx: usize| {
+      let content = rope
+        .paragraph(idx)
+        .trim_end_matches(is_paragraph_separator);
+      rope.paragraph_range(idx).start + content.len()
+    };
+    let idx = rope.paragraph_at(cluster);
+    if cluster < end(idx) {
+      end(idx)
+    } else if idx + 1 < rope.paragraph_count() {
+      end(idx + 1)
+    } else {
+      rope.len()
+    }
+  }
+}
+
+impl PartialEq for GlyphsText {
+  fn eq(&self, other: &Self) -> bool {
+    match (self, other) {
+      (GlyphsText::Str(a), GlyphsText::Str(b)) => a == b,
+      (GlyphsText::Rope(a), GlyphsText::Rope(b)) => a.ptr_eq(b),
+      _ => false,
+    }
+  }
+}
+
+impl Eq for GlyphsText {}
+
 #[derive(Default)]
 pub(crate) struct TextGlyphsHelper {
-  helper: SingleKeyMap<CowArc<str>, VisualGlyphs>,
+  helper: SingleKeyMap<GlyphsText, VisualGlyphs>,
+  /// The glyphs are of the masked text if the text is masked, the carets are
+  /// mapped between the text and the masked text.
+  mask: Option<MaskedText>,
 }
 
 impl TextGlyphsHelper {
-  pub(crate) fn new(text: CowArc<str>, glyphs: VisualGlyphs) -> Self {
-    Self { helper: SingleKeyMap(Some((text, glyphs))) }
+  pub(crate) fn new(text: GlyphsText, glyphs: VisualGlyphs) -> Self {
+    Self { helper: SingleKeyMap(Some((text, glyphs))), mask: None }
   }
 
-  pub(crate) fn line_end(&self, text: &CowArc<str>, caret: CaretPosition) -> Option<CaretPosition> {
-    self.helper.get(text)?.line_end(caret).into()
+  /// The glyphs are of the `mask` text.
+  pub(crate) fn with_mask(mut self, mask: Option<MaskedText>) -> Self {
+    self.mask = mask;
+    self
+  }
+
+  #[inline]
+  pub(crate) fn is_masked(&self) -> bool { self.mask.is_some() }
+
+  /// Do `f` with the glyphs of the `text`, and map the carets of `f` between
+  /// the text and the masked text.
+  fn with_glyphs<R>(
+    &self, text: &GlyphsText, caret: CaretPosition,
+    f: impl FnOnce(&VisualGlyphs, CaretPosition) -> R,
+  ) -> Option<R> {
+    match &self.mask {
+      Some(mask) if mask.secret_len() == text.len() => {
+        let glyphs = self
+          .helper
+          .get(&GlyphsText::Str(mask.display().clone()))?;
+        let cluster = mask.to_display(caret.cluster);
+        Some(f(glyphs, CaretPosition { cluster, ..caret }))
+      }
+      Some(_) => None,
+      None => Some(f(self.helper.get(text)?, caret)),
+    }
+  }
+
+  fn move_caret(
+    &self, text: &GlyphsText, caret: CaretPosition,
+    f: impl FnOnce(&VisualGlyphs, CaretPosition) -> CaretPosition,
+  ) -> Option<CaretPosition> {
+    let caret = self.with_glyphs(text, caret, f)?;
+    Some(self.to_secret(caret))
+  }
+
+  /// Map the `caret` in the glyphs to the caret in the text.
+  pub(crate) fn to_secret(&self, caret: CaretPosition) -> CaretPosition {
+    match &self.mask {
+      Some(mask) => CaretPosition { cluster: mask.to_secret(caret.cluster), ..caret },
+      None => caret,
+    }
+  }
+
+  pub(crate) fn line_end(&self, text: &GlyphsText, caret: CaretPosition) -> Option<CaretPosition> {
+    self.move_caret(text, caret, |g, c| g.line_end(c))
   }
 
   pub(crate) fn line_begin(
-    &self, text: &CowArc<str>, caret: CaretPosition,
+    &self, text: &GlyphsText, caret: CaretPosition,
   ) -> Option<CaretPosition> {
-    self.helper.get(text)?.line_begin(caret).into()
+    self.move_caret(text, caret, |g, c| g.line_begin(c))
+  }
+
+  pub(crate) fn prev(&self, text: &GlyphsText, caret: CaretPosition) -> Option<CaretPosition> {
+    self.move_caret(text, caret, |g, c| g.prev(c))
+  }
+
+  pub(crate) fn next(&self, text: &GlyphsText, caret: CaretPosition) -> Option<CaretPosition> {
+    self.move_caret(text, caret, |g, c| g.next(c))
   }
 
-  pub(crate) fn prev(&self, text: &CowArc<str>, caret: CaretPosition) -> Option<CaretPosition> {
-    self.helper.get(text)?.prev(caret).into()
+  pub(crate) fn up(&self, text: &GlyphsText, caret: CaretPosition) -> Option<CaretPosition> {
+    self.move_caret(text, caret, |g, c| g.up(c))
   }
 
-  pub(crate) fn next(&self, text: &CowArc<str>, caret: CaretPosition) -> Option<CaretPosition> {
-    self.helper.get(text)?.next(caret).into()
+  pub(crate) fn down(&self, text: &GlyphsText, caret: CaretPosition) -> Option<CaretPosition> {
+    self.move_caret(text, caret, |g, c| g.down(c))
   }
 
-  pub(crate) fn up(&self, text: &CowArc<str>, caret: CaretPosition) -> Option<CaretPosition> {
-    self.helper.get(text)?.up(caret).into()
+  pub(crate) fn page_up(
+    &self, text: &GlyphsText, caret: CaretPosition, page_height: f32,
+  ) -> Option<CaretPosition> {
+    self.move_caret(text, caret, |g, c| g.up_lines(c, g.page_lines(c, page_height)))
   }
 
-  pub(crate) fn down(&self, text: &CowArc<str>, caret: CaretPosition) -> Option<CaretPosition> {
-    self.helper.get(text)?.down(caret).into()
+  pub(crate) fn page_down(
+    &self, text: &GlyphsText, caret: CaretPosition, page_height: f32,
+  ) -> Option<CaretPosition> {
+    self.move_caret(text, caret, |g, c| g.down_lines(c, g.page_lines(c, page_height)))
   }
 
-  pub(crate) fn cursor(&self, text: &CowArc<str>, caret: CaretPosition) -> Option<Point> {
-    let this = self.helper.get(text)?;
-    this.cursor(caret).into()
+  pub(crate) fn cursor(&self, text: &GlyphsText, caret: CaretPosition) -> Option<Point> {
+    self.with_glyphs(text, caret, |g, c| g.cursor(c))
   }
 
-  pub(crate) fn line_height(&self, text: &CowArc<str>, caret: CaretPosition) -> Option<f32> {
-    let this = self.helper.get(text)?;
-    this.line_height_by_caret(caret).into()
+  pub(crate) fn line_height(&self, text: &GlyphsText, caret: CaretPosition) -> Option<f32> {
+    self.with_glyphs(text, caret, |g, c| g.line_height_by_caret(c))
   }
 
-  pub(crate) fn selection(&self, text: &CowArc<str>, rg: &Range<usize>) -> Option<Vec<Rect>> {
-    self.helper.get(text)?.selection(rg).into()
+  pub(crate) fn selection(&self, text: &GlyphsText, rg: &Range<usize>) -> Option<Vec<Rect>> {
+    let start = CaretPosition { cluster: rg.start, position: None };
+    self.with_glyphs(text, start, |g, start| {
+      let end = self
+        .mask
+        .as_ref()
+        .map_or(rg.end, |m| m.to_display(rg.end));
+      g.selection(&(start.cluster..end))
+    })
   }
 }
 
@@ -91,6 +293,13 @@ pub(crate) trait GlyphsHelper {
 
   fn down(&self, caret: CaretPosition) -> CaretPosition;
 
+  fn up_lines(&self, caret: CaretPosition, lines: usize) -> CaretPosition;
+
+  fn down_lines(&self, caret: CaretPosition, lines: usize) -> CaretPosition;
+
+  /// The count of the lines in a page of `page_height`, at least one line.
+  fn page_lines(&self, caret: CaretPosition, page_height: f32) -> usize;
+
   fn cursor(&self, caret: CaretPosition) -> Point;
 
   fn line_height_by_caret(&self, caret: CaretPosition) -> f32;
@@ -153,27 +362,42 @@ impl GlyphsHelper for VisualGlyphs {
     CaretPosition { cluster, position: Some((row, col)) }
   }
 
-  fn up(&self, caret: CaretPosition) -> CaretPosition {
+  fn up(&self, caret: CaretPosition) -> CaretPosition { self.up_lines(caret, 1) }
+
+  fn down(&self, caret: CaretPosition) -> CaretPosition { self.down_lines(caret, 1) }
+
+  fn up_lines(&self, caret: CaretPosition, lines: usize) -> CaretPosition {
     let (mut row, mut col) = self.caret_position(caret);
 
     (row, col) = match row > 0 {
-      true => (row - 1, col.min(self.glyph_count(row - 1, true))),
+      true => {
+        let row = row.saturating_sub(lines);
+        (row, col.min(self.glyph_count(row, true)))
+      }
       false => (row, col),
     };
     let cluster = self.position_to_cluster(row, col);
     CaretPosition { cluster, position: Some((row, col)) }
   }
 
-  fn down(&self, caret: CaretPosition) -> CaretPosition {
+  fn down_lines(&self, caret: CaretPosition, lines: usize) -> CaretPosition {
     let (mut row, mut col) = self.caret_position(caret);
     (row, col) = match row + 1 < self.glyph_row_count() {
-      true => (row + 1, col.min(self.glyph_count(row + 1, true))),
+      true => {
+        let row = (row + lines).min(self.glyph_row_count() - 1);
+        (row, col.min(self.glyph_count(row, true)))
+      }
       false => (row, col),
     };
     let cluster = self.position_to_cluster(row, col);
     CaretPosition { cluster, position: Some((row, col)) }
   }
 
+  fn page_lines(&self, caret: CaretPosition, page_height: f32) -> usize {
+    let line_height = self.line_height_by_caret(caret);
+    if line_height > 0. { ((page_height / line_height) as usize).max(1) } else { 1 }
+  }
+
   fn cursor(&self, caret: CaretPosition) -> Point {
     let (row, col) = self.caret_position(caret);
     if col == 0 {
@@ -233,6 +457,10 @@ mod tests {
       letter_space: 0.,
       line_height: 16.,
       overflow: Overflow::AutoWrap,
+      writing_mode: WritingMode::HorizontalTb,
+      tab_size: TabSize::Spaces(8),
+      tab_stops: Box::new([]),
+      color_palette: 0,
     };
     let glyphs = store.typography(
       "1 23 456 7890\n12345".into(),
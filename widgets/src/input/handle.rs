@@ -1,12 +1,19 @@
 #![allow(clippy::needless_lifetimes)]
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Range};
 
 use ribir_core::prelude::{
-  AppCtx, CharsEvent, GraphemeCursor, KeyCode, KeyboardEvent, NamedKey, PhysicalKey, StateWriter,
-  TextWriter, VirtualKey,
+  AppCtx, CharsEvent, CustomStyle, GraphemeCursor, KeyCode, KeyboardEvent, PhysicalKey,
+  StateWriter, TextWriter,
 };
 
-use super::EditableText;
+use super::{
+  CaretMove, CaretPosition, CaretState, EditableText, TextAction, TextEdit, TextKeyMap,
+  edit_history::EditKind,
+  glyphs_helper::{GlyphsText, TextGlyphsHelper},
+  masked_text::SecretString,
+  text_selectable::{SelectableText, caret_move_target, page_height},
+};
+use crate::text::Text;
 
 pub struct TextCaretWriter<'a, H>
 where
@@ -22,8 +29,8 @@ where
 {
   pub fn new(host: &'a mut H) -> Self {
     let cursor = GraphemeCursor(host.caret().cluster());
-    let string = host.text().to_string();
-    Self { host, writer: TextWriter::new(string, cursor) }
+    let rope = host.rope().clone();
+    Self { host, writer: TextWriter::new(rope, cursor) }
   }
 }
 
@@ -34,10 +41,10 @@ where
   fn drop(&mut self) {
     use crate::input::caret_state::CaretPosition;
     let Self { host, writer } = self;
-    let text = writer.text().to_string();
+    let rope = writer.text().clone();
     let caret = CaretPosition { cluster: writer.byte_offset(), position: None };
 
-    host.set_text_with_caret(&text, caret.into());
+    host.set_rope_with_caret(rope, caret.into());
   }
 }
 
@@ -56,33 +63,110 @@ where
   fn deref_mut(&mut self) -> &mut Self::Target { &mut self.writer }
 }
 
+/// Do the `edit` on the host and record it in the edit history.
+pub(crate) fn edit_with_history<F: EditableText>(host: &mut F, kind: EditKind, edit: &TextEdit) {
+  let caret = CaretPosition { cluster: edit.caret, position: None };
+  edit_with_history_to(host, kind, edit, caret.into());
+}
+
+/// Do the `edit` on the host, move the caret to `caret`, and record it in the
+/// edit history. Only the replaced range of the text is kept by the history.
+pub(crate) fn edit_with_history_to<F: EditableText>(
+  host: &mut F, kind: EditKind, edit: &TextEdit, caret: CaretState,
+) {
+  // The masked text is not recorded, the history keeps the text.
+  if host.text_mask().is_some() {
+    apply_edit(host, edit, caret);
+    return;
+  }
+  let Some(removed) = host.rope().get(edit.range.clone()) else { return };
+  let caret_before = host.caret();
+  apply_edit(host, edit, caret);
+  host
+    .edit_history_mut()
+    .record(kind, &removed, edit, caret_before, caret);
+}
+
+/// Do the `edit` on the host and move the caret to `caret`.
+pub(crate) fn apply_edit<F: EditableText>(host: &mut F, edit: &TextEdit, caret: CaretState) {
+  let mut writer = TextWriter::new(host.rope().clone(), GraphemeCursor(edit.range.start));
+  writer.delete_byte_range(&edit.range);
+  writer.insert_str(&edit.insert);
+  host.set_rope_with_caret(writer.text().clone(), caret);
+}
+
+/// The edit replacing the selection of the host with the `text`, or `None` if
+/// the input filters of the host reject it.
+fn insert_edit<F: EditableText>(host: &F, text: &str) -> Option<TextEdit> {
+  let edit = TextEdit::new(host.caret().select_range(), text.to_string());
+  if host.input_filters().is_empty() {
+    Some(edit)
+  } else {
+    host.input_filters().filter(host.text(), edit)
+  }
+}
+
+/// The edit deleting the `range` of the host, the input filters can transform
+/// it, such as the `InputMask` formatting the rest of the text.
+pub(crate) fn delete_edit<F: EditableText>(host: &F, range: Range<usize>) -> TextEdit {
+  let edit = TextEdit::new(range, String::new());
+  if host.input_filters().is_empty() {
+    edit
+  } else {
+    host
+      .input_filters()
+      .filter_delete(host.text(), edit)
+  }
+}
+
+/// The range of the grapheme before or after the caret of the host.
+fn grapheme_at_caret<F: EditableText>(host: &F, before: bool) -> Range<usize> {
+  let mut writer = TextWriter::new(host.rope().clone(), GraphemeCursor(host.caret().cluster()));
+  let len = writer.text().len();
+  if before {
+    writer.back_space();
+  } else {
+    writer.del_char();
+  }
+  let start = writer.byte_offset();
+  start..start + len - writer.text().len()
+}
+
+/// Replace the `range` of the host with the `text`, and record it in the edit
+/// history.
+pub(crate) fn replace_with_history<F: EditableText>(host: &mut F, range: Range<usize>, text: &str) {
+  edit_with_history(host, EditKind::Replace, &TextEdit::new(range, text.to_string()));
+}
+
 pub(crate) fn edit_handle<F: EditableText>(this: &impl StateWriter<Value = F>, event: &CharsEvent) {
   if event.common.with_command_key() {
     return;
   }
-  let chars = event
-    .chars
-    .chars()
-    .filter(|c| !c.is_control() || c.is_ascii_whitespace())
-    .collect::<String>();
+  // The chars may be typed in a password, so they're dropped with zeros.
+  let mut chars = SecretString(String::with_capacity(event.chars.len()));
+  chars.extend(
+    event
+      .chars
+      .chars()
+      .filter(|c| !c.is_control() || c.is_ascii_whitespace()),
+  );
   if !chars.is_empty() {
-    let mut this = this.write();
-    let rg = this.caret().select_range();
-    let mut writer = TextCaretWriter::new(&mut *this);
-    writer.delete_byte_range(&rg);
-    writer.insert_str(&chars);
+    let mut host = this.write();
+    if let Some(edit) = insert_edit(&*host, &chars) {
+      edit_with_history(&mut *host, EditKind::Typing, &edit);
+    }
   }
 }
 
-pub(crate) fn edit_key_handle<F: EditableText>(
-  this: &impl StateWriter<Value = F>, event: &KeyboardEvent,
+pub(crate) fn edit_key_handle<F: EditableText + SelectableText>(
+  this: &impl StateWriter<Value = F>, text: &Text, event: &KeyboardEvent,
 ) {
   let mut deal = false;
   if event.with_command_key() {
     deal = key_with_command(this, event)
   }
   if !deal {
-    single_key(this, event);
+    delete_by_key(this, text, event);
   }
 }
 
@@ -99,54 +183,71 @@ fn key_with_command<F: EditableText>(
     PhysicalKey::Code(KeyCode::KeyV) => {
       let clipboard = AppCtx::clipboard();
       let txt = clipboard.borrow_mut().read_text();
-      if let Ok(txt) = txt {
-        let mut this = this.write();
-        let rg = this.caret().select_range();
-        let mut writer = TextCaretWriter::new(&mut *this);
-        if !rg.is_empty() {
-          writer.delete_byte_range(&rg);
+      if let Ok(txt) = txt.map(SecretString) {
+        let mut host = this.write();
+        if let Some(edit) = insert_edit(&*host, &txt) {
+          edit_with_history(&mut *host, EditKind::Paste, &edit);
         }
-        writer.insert_chars(&txt);
       }
       true
     }
     PhysicalKey::Code(KeyCode::KeyX) => {
       let rg = this.read().caret().select_range();
-      if !rg.is_empty() {
+      if !rg.is_empty() && this.read().text_mask().is_none() {
         let mut this = this.write();
-        let txt = this.text().substr(rg.clone()).to_string();
-        TextCaretWriter::new(&mut *this).delete_byte_range(&rg);
+        let txt = this.rope().slice(rg.clone());
+        let edit = delete_edit(&*this, rg);
+        edit_with_history(&mut *this, EditKind::Cut, &edit);
         let clipboard = AppCtx::clipboard();
         let _ = clipboard.borrow_mut().clear();
         let _ = clipboard.borrow_mut().write_text(&txt);
       }
       true
     }
+    PhysicalKey::Code(KeyCode::KeyZ) => {
+      if event.with_shift_key() {
+        this.write().redo();
+      } else {
+        this.write().undo();
+      }
+      true
+    }
+    // The redo of macOS is only `Cmd+Shift+Z`.
+    PhysicalKey::Code(KeyCode::KeyY) if !cfg!(target_os = "macos") => {
+      this.write().redo();
+      true
+    }
     _ => false,
   }
 }
 
-fn single_key<F: EditableText>(this: &impl StateWriter<Value = F>, key: &KeyboardEvent) -> bool {
-  match key.key() {
-    VirtualKey::Named(NamedKey::Backspace) => {
-      let mut this = this.write();
-      let rg = this.caret().select_range();
-      if rg.is_empty() {
-        TextCaretWriter::new(&mut *this).back_space();
-      } else {
-        TextCaretWriter::new(&mut *this).delete_byte_range(&rg);
-      }
-    }
-    VirtualKey::Named(NamedKey::Delete) => {
-      let mut this = this.write();
-      let rg = this.caret().select_range();
-      if rg.is_empty() {
-        TextCaretWriter::new(&mut *this).del_char();
-      } else {
-        TextCaretWriter::new(&mut *this).delete_byte_range(&rg);
+fn delete_by_key<F: EditableText + SelectableText>(
+  this: &impl StateWriter<Value = F>, text: &Text, event: &KeyboardEvent,
+) {
+  let Some(TextAction::Delete(mv)) = TextKeyMap::of(event).action(event) else { return };
+  let page_height = page_height(event);
+  let mut host = this.write();
+  let mut rg = EditableText::caret(&*host).select_range();
+  if rg.is_empty() {
+    rg = match mv {
+      // Delete by the grapheme, not the glyph.
+      CaretMove::PrevChar => grapheme_at_caret(&*host, true),
+      CaretMove::NextChar => grapheme_at_caret(&*host, false),
+      mv => {
+        let Some(glyphs) = text.glyphs() else { return };
+        let mask = EditableText::text_mask(&*host);
+        let helper = TextGlyphsHelper::new(text.glyphs_text(), glyphs.clone()).with_mask(mask);
+        let caret = EditableText::caret(&*host).caret_position();
+        let host_text = GlyphsText::of(&*host);
+        let Some(target) = caret_move_target(&helper, &host_text, caret, mv, page_height) else {
+          return;
+        };
+        caret.cluster.min(target.cluster)..caret.cluster.max(target.cluster)
       }
-    }
-    _ => (),
-  };
-  true
+    };
+  }
+  if !rg.is_empty() {
+    let edit = delete_edit(&*host, rg);
+    edit_with_history(&mut *host, EditKind::Delete, &edit);
+  }
 }
@@ -0,0 +1,392 @@
+use std::{ops::Range, rc::Rc};
+
+use regex::Regex;
+
+use super::masked_text::{SecretString, zeroize_str};
+
+/// An edit that replaces the `range` of the text with the `insert`.
+///
+/// The `insert` is filled with zeros when the edit is dropped, since it may be
+/// a part of a password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+  pub range: Range<usize>,
+  pub insert: String,
+  /// The byte offset of the caret in the text after the edit.
+  pub caret: usize,
+}
+
+/// What an `InputFilter` does with an edit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterResult {
+  Accept,
+  Reject,
+  /// Do the given edit instead.
+  Transform(TextEdit),
+}
+
+/// A filter that checks the text typed, pasted or committed by the IME before
+/// it's inserted into an editable text. The edits of deleting are filtered too,
+/// their `insert` is empty and they can be transformed but not rejected. The
+/// text set by the code is not filtered.
+pub trait InputFilter {
+  /// Check the `edit` on the `text`.
+  fn filter(&self, text: &str, edit: &TextEdit) -> FilterResult;
+}
+
+/// The filters of an editable text, they filter an edit in order, and every
+/// filter checks the edit transformed by the filters before it.
+///
+/// # Example
+///
+/// ```
+/// use ribir_core::prelude::*;
+/// use ribir_widgets::prelude::*;
+///
+/// let _phone = fn_widget! {
+///   @Input {
+///     filters: InputFilters::default().with(InputMask::new("(###) ###-####")),
+///   }
+/// };
+///
+/// let _code = fn_widget! {
+///   @Input {
+///     filters: InputFilters::default()
+///       .with(CharFilter::digits())
+///       .with(MaxLength(6)),
+///   }
+/// };
+/// ```
+#[derive(Clone, Default)]
+pub struct InputFilters(Vec<Rc<dyn InputFilter>>);
+
+impl TextEdit {
+  /// The edit that replaces the `range` with the `insert`, and the caret is
+  /// at the end of the `insert`.
+  pub fn new(range: Range<usize>, insert: String) -> Self {
+    let caret = range.start + insert.len();
+    Self { range, insert, caret }
+  }
+
+  /// Return the text after the edit.
+  pub fn apply(&self, text: &str) -> String {
+    [&text[..self.range.start], &self.insert, &text[self.range.end..]].concat()
+  }
+
+  fn is_valid(&self, text: &str) -> bool {
+    let Range { start, end } = self.range;
+    let caret = self.caret;
+    text.get(start..end).is_some() && {
+      let insert_end = start + self.insert.len();
+      if caret < start {
+        text.is_char_boundary(caret)
+      } else if caret <= insert_end {
+        self.insert.is_char_boundary(caret - start)
+      } else {
+        caret - insert_end + end <= text.len() && text.is_char_boundary(caret - insert_end + end)
+      }
+    }
+  }
+}
+
+impl Drop for TextEdit {
+  fn drop(&mut self) { zeroize_str(&mut self.insert); }
+}
+
+impl InputFilters {
+  /// Append the `filter` to the filters.
+  pub fn with(mut self, filter: impl InputFilter + 'static) -> Self {
+    self.0.push(Rc::new(filter));
+    self
+  }
+
+  #[inline]
+  pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+  /// Run the `edit` through the filters, return the edit to do or `None` if
+  /// it's rejected.
+  pub fn filter(&self, text: &str, mut edit: TextEdit) -> Option<TextEdit> {
+    for f in self.0.iter() {
+      match f.filter(text, &edit) {
+        FilterResult::Accept => {}
+        FilterResult::Reject => return None,
+        FilterResult::Transform(new_edit) => edit = new_edit,
+      }
+    }
+    edit.is_valid(text).then_some(edit)
+  }
+
+  /// Run the deleting `edit` through the filters and return the edit to do. A
+  /// deletion can't be rejected, so the filters rejecting it are skipped.
+  pub fn filter_delete(&self, text: &str, mut edit: TextEdit) -> TextEdit {
+    for f in self.0.iter() {
+      if let FilterResult::Transform(new_edit) = f.filter(text, &edit) {
+        if new_edit.is_valid(text) {
+          edit = new_edit;
+        }
+      }
+    }
+    edit
+  }
+}
+
+/// Limit the count of the chars of the text, the inserted text is cut to fit
+/// the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxLength(pub usize);
+
+impl InputFilter for MaxLength {
+  fn filter(&self, text: &str, edit: &TextEdit) -> FilterResult {
+    let kept = text.chars().count() - text[edit.range.clone()].chars().count();
+    let room = self.0.saturating_sub(kept);
+    match edit.insert.char_indices().nth(room) {
+      None => FilterResult::Accept,
+      Some((0, _)) => FilterResult::Reject,
+      Some((idx, _)) => {
+        FilterResult::Transform(TextEdit::new(edit.range.clone(), edit.insert[..idx].to_string()))
+      }
+    }
+  }
+}
+
+/// Only allow the chars accepted by a function, the other chars are removed
+/// from the inserted text.
+pub struct CharFilter(Box<dyn Fn(char) -> bool>);
+
+impl CharFilter {
+  pub fn new(accept: impl Fn(char) -> bool + 'static) -> Self { Self(Box::new(accept)) }
+
+  /// Only allow the ASCII digits.
+  pub fn digits() -> Self { Self::new(|c| c.is_ascii_digit()) }
+
+  /// Only allow the letters and the numbers.
+  pub fn alphanumeric() -> Self { Self::new(char::is_alphanumeric) }
+}
+
+impl InputFilter for CharFilter {
+  fn filter(&self, _: &str, edit: &TextEdit) -> FilterResult {
+    if edit.insert.chars().all(&self.0) {
+      return FilterResult::Accept;
+    }
+    let mut insert = String::with_capacity(edit.insert.len());
+    insert.extend(edit.insert.chars().filter(|c| (self.0)(*c)));
+    if insert.is_empty() {
+      FilterResult::Reject
+    } else {
+      FilterResult::Transform(TextEdit::new(edit.range.clone(), insert))
+    }
+  }
+}
+
+/// Reject the edit if the whole text after it does not match the regex.
+///
+/// The text is checked after every edit, so the regex should match the
+/// unfinished text too, for example, use `^\d{0,3}(\.\d{0,2})?$` rather than
+/// `^\d{1,3}\.\d{2}$` for a price.
+pub struct RegexFilter(Regex);
+
+impl RegexFilter {
+  /// Create the filter with the `pattern`, return the error if the pattern is
+  /// invalid.
+  pub fn new(pattern: &str) -> Result<Self, regex::Error> { Regex::new(pattern).map(Self) }
+}
+
+impl From<Regex> for RegexFilter {
+  fn from(regex: Regex) -> Self { Self(regex) }
+}
+
+impl InputFilter for RegexFilter {
+  fn filter(&self, text: &str, edit: &TextEdit) -> FilterResult {
+    if self.0.is_match(&SecretString(edit.apply(text))) {
+      FilterResult::Accept
+    } else {
+      FilterResult::Reject
+    }
+  }
+}
+
+/// Format the text with a template, the template has placeholders for the
+/// chars of the user and literal chars filled by the mask.
+///
+/// The placeholders are:
+/// - `#` for an ASCII digit.
+/// - `A` for a letter.
+/// - `*` for a letter or a number.
+///
+/// Any other char is a literal, use `\` to make a placeholder char a literal.
+/// For example, `(###) ###-####` for a phone number and `####-##-##` for a
+/// date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputMask {
+  slots: Vec<MaskSlot>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MaskSlot {
+  Digit,
+  Letter,
+  Alphanumeric,
+  Literal(char),
+}
+
+impl InputMask {
+  pub fn new(template: &str) -> Self {
+    let mut slots = vec![];
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+      let slot = match c {
+        '#' => MaskSlot::Digit,
+        'A' => MaskSlot::Letter,
+        '*' => MaskSlot::Alphanumeric,
+        '\\' => MaskSlot::Literal(chars.next().unwrap_or('\\')),
+        c => MaskSlot::Literal(c),
+      };
+      slots.push(slot);
+    }
+    Self { slots }
+  }
+
+  /// Format the chars of the `text` by the mask, the chars not matching the
+  /// placeholders are dropped. Return the formatted text and where the
+  /// `caret` of the `text` is in it.
+  pub fn format(&self, text: &str, caret: usize) -> (String, usize) {
+    // A char is 4 bytes at most, so the buffer never grows and leaves a copy of
+    // the text behind.
+    let mut formatted = String::with_capacity(self.slots.len() * 4);
+    let mut new_caret = 0;
+    // The end of the last placeholder, the literals after it are not kept.
+    let mut end = 0;
+    let mut chars = text.char_indices().peekable();
+    for slot in self.slots.iter() {
+      if chars.peek().is_none() {
+        break;
+      }
+      match slot {
+        MaskSlot::Literal(c) => {
+          // Skip the literal if the user types it.
+          if chars.peek().is_some_and(|(_, next)| next == c) {
+            chars.next();
+          }
+          formatted.push(*c);
+        }
+        slot => {
+          let Some((idx, c)) = chars.find(|(_, c)| slot.accept(*c)) else { break };
+          formatted.push(c);
+          end = formatted.len();
+          if idx < caret {
+            new_caret = end;
+          }
+        }
+      }
+    }
+    formatted.truncate(end);
+    (formatted, new_caret)
+  }
+}
+
+impl InputMask {
+  /// Extend a deletion of only the literals to the placeholder char before
+  /// them, or the mask fills the literals back.
+  fn delete_over_literals(&self, text: &str, edit: &TextEdit) -> Option<TextEdit> {
+    if !edit.insert.is_empty() || edit.range.is_empty() {
+      return None;
+    }
+    let slots = || text.char_indices().zip(self.slots.iter());
+    let only_literals = slots()
+      .filter(|((idx, _), _)| edit.range.contains(idx))
+      .all(|(_, slot)| matches!(slot, MaskSlot::Literal(_)));
+    if !only_literals {
+      return None;
+    }
+    let ((start, _), _) = slots()
+      .take_while(|((idx, _), _)| *idx < edit.range.start)
+      .filter(|(_, slot)| !matches!(slot, MaskSlot::Literal(_)))
+      .last()?;
+    Some(TextEdit::new(start..edit.range.end, String::new()))
+  }
+}
+
+impl MaskSlot {
+  fn accept(&self, c: char) -> bool {
+    match self {
+      MaskSlot::Digit => c.is_ascii_digit(),
+      MaskSlot::Letter => c.is_alphabetic(),
+      MaskSlot::Alphanumeric => c.is_alphanumeric(),
+      MaskSlot::Literal(l) => *l == c,
+    }
+  }
+}
+
+impl InputFilter for InputMask {
+  fn filter(&self, text: &str, edit: &TextEdit) -> FilterResult {
+    let extended = self.delete_over_literals(text, edit);
+    let edit = extended.as_ref().unwrap_or(edit);
+    let edited = SecretString(edit.apply(text));
+    let (formatted, caret) = self.format(&edited, edit.caret);
+    if formatted.len() <= text.len() - edit.range.len() && !edit.insert.is_empty() {
+      // Nothing of the insert is accepted.
+      return FilterResult::Reject;
+    }
+    FilterResult::Transform(TextEdit { range: 0..text.len(), insert: formatted, caret })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn type_in(filters: &InputFilters, text: &str, caret: usize, insert: &str) -> (String, usize) {
+    let edit = TextEdit::new(caret..caret, insert.to_string());
+    filters
+      .filter(text, edit)
+      .map(|e| (e.apply(text), e.caret))
+      .unwrap_or((text.to_string(), caret))
+  }
+
+  #[test]
+  fn max_length_and_chars() {
+    let filters = InputFilters::default()
+      .with(CharFilter::digits())
+      .with(MaxLength(4));
+    assert_eq!(type_in(&filters, "12", 2, "a3b45"), ("1234".into(), 4));
+    assert_eq!(type_in(&filters, "1234", 4, "5"), ("1234".into(), 4));
+    assert_eq!(type_in(&filters, "12", 1, "x"), ("12".into(), 1));
+
+    let regex = InputFilters::default().with(RegexFilter::new(r"^\d{0,3}(\.\d{0,2})?$").unwrap());
+    assert_eq!(type_in(&regex, "12", 2, ".5").0, "12.5");
+    assert_eq!(type_in(&regex, "12.5", 4, "67").0, "12.5");
+  }
+
+  #[test]
+  fn mask() {
+    let filters = InputFilters::default().with(InputMask::new("(###) ###-####"));
+    let (text, caret) = type_in(&filters, "", 0, "1");
+    assert_eq!((text.as_str(), caret), ("(1", 2));
+    let (text, caret) = type_in(&filters, &text, caret, "2345");
+    assert_eq!((text.as_str(), caret), ("(123) 45", 8));
+    // Paste a formatted number.
+    assert_eq!(type_in(&filters, "", 0, "(555) 123-4567").0, "(555) 123-4567");
+    // Insert in the middle.
+    let (text, caret) = type_in(&filters, "(123) 45", 2, "9");
+    assert_eq!((text.as_str(), caret), ("(192) 345", 3));
+    // The letters are rejected.
+    assert_eq!(type_in(&filters, "(12", 3, "x"), ("(12".into(), 3));
+    assert_eq!(type_in(&filters, "(123", 4, "x"), ("(123".into(), 4));
+  }
+
+  fn delete(filters: &InputFilters, text: &str, range: Range<usize>) -> (String, usize) {
+    let edit = filters.filter_delete(text, TextEdit::new(range, String::new()));
+    (edit.apply(text), edit.caret)
+  }
+
+  #[test]
+  fn mask_delete() {
+    let filters = InputFilters::default().with(InputMask::new("(###) ###-####"));
+    // The chars after the deleted one move forward.
+    assert_eq!(delete(&filters, "(123) 456", 2..3), ("(134) 56".into(), 2));
+    // Deleting a literal deletes the placeholder char before it too.
+    assert_eq!(delete(&filters, "(123) 4", 5..6), ("(124".into(), 3));
+    // The literals are dropped with the last char.
+    assert_eq!(delete(&filters, "(1", 1..2), ("".into(), 0));
+  }
+}
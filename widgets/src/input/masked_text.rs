@@ -0,0 +1,135 @@
+use std::ops::{Deref, DerefMut, Range};
+
+use ribir_core::prelude::*;
+
+/// The text shown in place of a secret text, every grapheme of the secret text
+/// is shown as a mask char, but the revealed ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaskedText {
+  display: CowArc<str>,
+  /// The byte offsets of the grapheme boundaries in the secret text and in the
+  /// display text.
+  bounds: Vec<(usize, usize)>,
+}
+
+impl MaskedText {
+  /// Mask the `secret` by the `mask`, the graphemes in the `reveal` range are
+  /// shown as they are.
+  pub fn new(secret: &str, mask: char, reveal: Option<Range<usize>>) -> Self {
+    // Reserve the most bytes the display may take, so the revealed graphemes are
+    // not left in a buffer freed by growing.
+    let mut display = String::with_capacity(secret.len() * (mask.len_utf8() + 1));
+    let mut bounds = vec![(0, 0)];
+    let mut cursor = GraphemeCursor(0);
+    let mut start = 0;
+    while cursor.next(secret) {
+      let end = cursor.byte_offset();
+      if reveal
+        .as_ref()
+        .is_some_and(|rg| rg.start <= start && end <= rg.end)
+      {
+        display.push_str(&secret[start..end]);
+      } else {
+        display.push(mask);
+      }
+      bounds.push((end, display.len()));
+      start = end;
+    }
+    Self { display: display.into(), bounds }
+  }
+
+  /// The text to show.
+  #[inline]
+  pub fn display(&self) -> &CowArc<str> { &self.display }
+
+  /// The length of the secret text in bytes.
+  pub fn secret_len(&self) -> usize { self.bounds[self.bounds.len() - 1].0 }
+
+  /// The byte offset in the display text of the byte `offset` in the secret
+  /// text, an offset inside a grapheme maps to the start of the grapheme.
+  pub fn to_display(&self, offset: usize) -> usize {
+    let idx = self.bounds.partition_point(|(s, _)| *s <= offset) - 1;
+    self.bounds[idx].1
+  }
+
+  /// The byte offset in the secret text of the byte `offset` in the display
+  /// text, an offset inside a grapheme maps to the start of the grapheme.
+  pub fn to_secret(&self, offset: usize) -> usize {
+    let idx = self.bounds.partition_point(|(_, d)| *d <= offset) - 1;
+    self.bounds[idx].0
+  }
+}
+
+impl Drop for MaskedText {
+  fn drop(&mut self) { zeroize(std::mem::replace(&mut self.display, CowArc::borrowed(""))); }
+}
+
+/// A copy of a text may be a secret, its buffer is filled with zeros when it's
+/// dropped.
+#[derive(Default)]
+pub(crate) struct SecretString(pub(crate) String);
+
+impl Deref for SecretString {
+  type Target = String;
+
+  fn deref(&self) -> &String { &self.0 }
+}
+
+impl DerefMut for SecretString {
+  fn deref_mut(&mut self) -> &mut String { &mut self.0 }
+}
+
+impl Drop for SecretString {
+  fn drop(&mut self) { zeroize_str(&mut self.0) }
+}
+
+/// Fill the buffer of the `text` with zeros before it's dropped, if no one else
+/// shares it.
+pub(crate) fn zeroize(mut text: CowArc<str>) {
+  if let Some(text) = text.get_mut() {
+    zeroize_str(text);
+  }
+}
+
+/// Fill the `text` with zeros.
+pub(crate) fn zeroize_str(text: &mut str) {
+  // Safety: zeros are valid UTF-8.
+  for b in unsafe { text.as_bytes_mut() } {
+    // Write by volatile, so the compiler does not remove the writes to a buffer
+    // going to be dropped.
+    unsafe { std::ptr::write_volatile(b, 0) };
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn map_offsets() {
+    // "é" in two chars is one grapheme.
+    let secret = "ae\u{301}b";
+    let masked = MaskedText::new(secret, '•', None);
+    assert_eq!(&**masked.display(), "•••");
+    assert_eq!(masked.secret_len(), secret.len());
+    assert_eq!(masked.to_display(1), 3);
+    assert_eq!(masked.to_display(2), 3);
+    assert_eq!(masked.to_display(4), 6);
+    assert_eq!(masked.to_secret(6), 4);
+    assert_eq!(masked.to_secret(9), 5);
+
+    let masked = MaskedText::new(secret, '*', Some(4..5));
+    assert_eq!(&**masked.display(), "**b");
+    assert_eq!(masked.to_secret(2), 4);
+  }
+
+  #[test]
+  fn zero_dropped_text() {
+    let text = CowArc::<str>::from(String::from("secret"));
+    let ptr = text.as_ptr();
+    let shared = text.clone();
+    zeroize(text);
+    assert_eq!(&*shared, "secret");
+    assert_eq!(shared.as_ptr(), ptr);
+  }
+}
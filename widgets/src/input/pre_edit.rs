@@ -0,0 +1,99 @@
+use std::ops::Range;
+
+use ribir_core::prelude::*;
+
+use super::{
+  glyphs_helper::{GlyphsText, TextGlyphsHelper},
+  masked_text::MaskedText,
+};
+use crate::prelude::*;
+
+/// The style of the underlines of the text composing by the IME, the text is
+/// underlined by a thin line and the clause converting by the IME is
+/// underlined by a thick line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreEditStyle {
+  /// The brush of the underlines, use the inherited foreground if it's
+  /// `None`.
+  pub brush: Option<Brush>,
+  pub thickness: f32,
+  /// The thickness of the underline of the active clause.
+  pub active_thickness: f32,
+}
+
+impl CustomStyle for PreEditStyle {
+  fn default_style(_: &impl ProviderCtx) -> Self {
+    PreEditStyle { brush: None, thickness: 1., active_thickness: 2. }
+  }
+}
+
+/// The text composing by the IME in an editable text.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PreEditRange {
+  /// The text of the editable text, not the masked text shown.
+  pub(crate) text: GlyphsText,
+  pub(crate) mask: Option<MaskedText>,
+  pub(crate) range: Range<usize>,
+  /// The clause the IME is converting.
+  pub(crate) clause: Option<Range<usize>>,
+}
+
+/// Draw the underlines of the pre-edit text, the underlines are computed when
+/// painting, so they follow the glyphs of the text laid out in this frame.
+#[derive(Declare)]
+pub(crate) struct PreEditUnderline {
+  pub(crate) pre_edit: Option<PreEditRange>,
+  pub(crate) text: Reader<Text>,
+}
+
+impl Render for PreEditUnderline {
+  fn perform_layout(&self, clamp: BoxClamp, _: &mut LayoutCtx) -> Size { clamp.min }
+
+  #[inline]
+  fn only_sized_by_parent(&self) -> bool { true }
+
+  fn paint(&self, ctx: &mut PaintingCtx) {
+    let Some(pre_edit) = self.pre_edit.as_ref() else { return };
+    let style = PreEditStyle::of(&ctx);
+    let underlines = {
+      let text = self.text.read();
+      let Some(glyphs) = text.glyphs() else { return };
+      let helper =
+        TextGlyphsHelper::new(text.glyphs_text(), glyphs.clone()).with_mask(pre_edit.mask.clone());
+      let lines = |rg: &Range<usize>, thickness: f32| {
+        pre_edit
+          .text
+          .has_range(rg)
+          .then(|| helper.selection(&pre_edit.text, rg))
+          .flatten()
+          .unwrap_or_default()
+          .into_iter()
+          .map(move |rc| {
+            let origin = Point::new(rc.min_x(), rc.max_y() - thickness);
+            Rect::new(origin, Size::new(rc.width(), thickness))
+          })
+      };
+      let mut underlines = vec![lines(&pre_edit.range, style.thickness).collect::<Vec<_>>()];
+      if let Some(clause) = pre_edit.clause.as_ref() {
+        underlines.push(lines(clause, style.active_thickness).collect());
+      }
+      underlines
+    };
+
+    let painter = ctx.painter();
+    painter.save();
+    if let Some(brush) = style.brush {
+      painter.set_fill_brush(brush);
+    }
+    for rects in underlines
+      .iter()
+      .filter(|rects| !rects.is_empty())
+    {
+      for rect in rects {
+        painter.rect(rect);
+      }
+      painter.fill();
+    }
+    painter.restore();
+  }
+}
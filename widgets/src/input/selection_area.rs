@@ -0,0 +1,452 @@
+use std::{cell::RefCell, collections::HashMap, ops::Range};
+
+use ribir_core::{prelude::*, ticker::FrameMsg};
+
+use super::{glyphs_helper::GlyphsHelper, selected_text::SelectedHighLightStyle};
+
+/// A container that lets the user select the text across all the `Text`
+/// widgets in it, the selection goes through the texts in the document order.
+///
+/// The selected part of every text is highlighted, and the copy shortcut
+/// copies the selected text with a line break between the texts.
+///
+/// # Example
+///
+/// ```
+/// use ribir_core::prelude::*;
+/// use ribir_widgets::prelude::*;
+///
+/// let _message = fn_widget! {
+///   @SelectionArea {
+///     @Column {
+///       @Text { text: "The first paragraph." }
+///       @Text { text: "The second paragraph." }
+///     }
+///   }
+/// };
+/// ```
+#[derive(Declare, Default)]
+pub struct SelectionArea {
+  /// The texts laid out in the area by their widget ids, the texts register
+  /// themselves in their layout.
+  #[declare(skip)]
+  texts: RefCell<HashMap<WidgetId, AreaText>>,
+  /// The texts in the document order.
+  #[declare(skip)]
+  order: Vec<WidgetId>,
+  /// The anchor and the focus of the selection.
+  #[declare(skip)]
+  selection: Option<(SelectionPoint, SelectionPoint)>,
+  #[declare(skip)]
+  selecting: bool,
+}
+
+struct AreaText {
+  text: CowArc<str>,
+  glyphs: VisualGlyphs,
+}
+
+/// A caret in the text at the `index` of the document order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct SelectionPoint {
+  index: usize,
+  cluster: usize,
+}
+
+impl SelectionArea {
+  /// Return if any text is selected.
+  pub fn has_selection(&self) -> bool { matches!(self.selection, Some((a, f)) if a != f) }
+
+  /// The selected text, the parts of the different texts are joined by a line
+  /// break.
+  pub fn selected_text(&self) -> String {
+    let Some((start, end)) = self.ordered_selection() else { return String::new() };
+    let texts = self.texts.borrow();
+    (start.index..=end.index)
+      .filter_map(|idx| {
+        let text = &texts.get(self.order.get(idx)?)?.text;
+        self.range_of(idx, text).map(|rg| &text[rg])
+      })
+      .collect::<Vec<_>>()
+      .join("\n")
+  }
+
+  pub fn clear_selection(&mut self) {
+    self.selection = None;
+    self.selecting = false;
+  }
+
+  pub(crate) fn register(&self, id: WidgetId, text: CowArc<str>, glyphs: VisualGlyphs) {
+    self
+      .texts
+      .borrow_mut()
+      .insert(id, AreaText { text, glyphs });
+  }
+
+  /// The range selected in the `text` of the widget `id`.
+  pub(crate) fn selected_range(&self, id: WidgetId, text: &str) -> Option<Range<usize>> {
+    let idx = self.order.iter().position(|w| *w == id)?;
+    self.range_of(idx, text)
+  }
+
+  fn range_of(&self, idx: usize, text: &str) -> Option<Range<usize>> {
+    let (start, end) = self.ordered_selection()?;
+    if idx < start.index || end.index < idx {
+      return None;
+    }
+    let from = if idx == start.index { start.cluster } else { 0 };
+    let to = if idx == end.index { end.cluster } else { text.len() };
+    // The text may be changed after it's selected.
+    (from < to && text.get(from..to).is_some()).then_some(from..to)
+  }
+
+  fn ordered_selection(&self) -> Option<(SelectionPoint, SelectionPoint)> {
+    let (anchor, focus) = self.selection?;
+    Some(if anchor <= focus { (anchor, focus) } else { (focus, anchor) })
+  }
+
+  /// Sort the texts by the `descendants` of the area in the tree order, and
+  /// drop the texts not in the area anymore.
+  fn update_order(&mut self, descendants: impl Iterator<Item = WidgetId>) {
+    let texts = self.texts.get_mut();
+    let mut old = std::mem::take(texts);
+    self.order = descendants
+      .filter(|id| old.contains_key(id))
+      .collect();
+    *texts = self
+      .order
+      .iter()
+      .filter_map(|id| old.remove_entry(id))
+      .collect();
+  }
+
+  /// Drop the texts disposed, they are not in the tree anymore.
+  fn drop_disposed(&self, wnd: &Window) {
+    self
+      .texts
+      .borrow_mut()
+      .retain(|id, _| wnd.widget_size(*id).is_some());
+  }
+
+  /// The selection point of the global position `pos`, a position out of the
+  /// texts goes to the last text before it in the reading order, that is the
+  /// text above it, or on the same line and on its left.
+  fn point_at(&self, wnd: &Window, pos: Point) -> Option<SelectionPoint> {
+    let texts = self.texts.borrow();
+    let mut hit = None;
+    for (index, id) in self.order.iter().enumerate() {
+      if !texts.contains_key(id) {
+        continue;
+      }
+      let Some(size) = wnd.widget_size(*id) else { continue };
+      let rect = Rect::new(wnd.map_to_global(Point::zero(), *id), size);
+      if rect.contains(pos) {
+        hit = Some((index, *id, rect));
+        break;
+      } else if rect.max_y() < pos.y || (rect.min_y() <= pos.y && rect.min_x() <= pos.x) {
+        hit = Some((index, *id, rect));
+      }
+    }
+
+    let Some((index, id, rect)) = hit else {
+      return (!self.order.is_empty()).then_some(SelectionPoint { index: 0, cluster: 0 });
+    };
+    let text = texts.get(&id)?;
+    let cluster = if rect.max_y() < pos.y || rect.max_x() < pos.x {
+      text.text.len()
+    } else {
+      let pos = wnd.map_from_global(pos, id);
+      text
+        .glyphs
+        .caret_position_from_pos(pos.x, pos.y)
+        .cluster
+    };
+    Some(SelectionPoint { index, cluster })
+  }
+
+  fn select_all(&mut self) {
+    let last = self.order.len().checked_sub(1).map(|index| {
+      let text = &self.texts.borrow()[&self.order[index]].text;
+      SelectionPoint { index, cluster: text.len() }
+    });
+    self.selection = last.map(|end| (SelectionPoint { index: 0, cluster: 0 }, end));
+  }
+
+  fn select_word(&mut self, point: SelectionPoint) {
+    let rg = {
+      let texts = self.texts.borrow();
+      let Some(text) = texts.get(&self.order[point.index]) else { return };
+      select_word(&text.text, point.cluster)
+    };
+    let anchor = SelectionPoint { index: point.index, cluster: rg.start };
+    let focus = SelectionPoint { index: point.index, cluster: rg.end };
+    self.selection = Some((anchor, focus));
+  }
+}
+
+/// Paint the selection highlight of the text, if the text is in a
+/// `SelectionArea`.
+pub(crate) fn paint_selection(ctx: &mut PaintingCtx, text: &str, glyphs: &VisualGlyphs) {
+  let rects = {
+    let Some(area) = Provider::of::<SelectionArea>(&ctx) else { return };
+    let Some(rg) = area.selected_range(ctx.widget_id(), text) else { return };
+    glyphs.selection(&rg)
+  };
+  if rects.is_empty() {
+    return;
+  }
+
+  let brush = SelectedHighLightStyle::of(&ctx).brush;
+  let painter = ctx.painter();
+  painter.save().set_fill_brush(brush);
+  for rect in rects.iter() {
+    painter.rect(rect);
+  }
+  painter.fill().restore();
+}
+
+impl ComposeChild<'static> for SelectionArea {
+  type Child = Widget<'static>;
+  fn compose_child(this: impl StateWriter<Value = Self>, child: Self::Child) -> Widget<'static> {
+    // The texts repaint their highlights when the selection changes.
+    let modifies = this.raw_modifies();
+    let child = child.on_build(move |id| id.dirty_on(modifies));
+    let provider = Provider::new(Box::new(this.clone_writer()));
+
+    provider
+      .with_child(fn_widget! {
+        // The texts register themselves in their layout, drop the disposed
+        // ones after the layout to not keep their glyphs.
+        let wnd = BuildCtx::get().window();
+        let c_wnd = wnd.clone();
+        let u = wnd
+          .frame_tick_stream()
+          .filter(|msg| matches!(msg, FrameMsg::LayoutReady(_)))
+          .subscribe(move |_| $this.drop_disposed(&c_wnd));
+        let child = FatObj::new(child);
+        @ $child {
+          on_disposed: move |_| u.unsubscribe(),
+          tab_index: -1_i16,
+          on_pointer_down: move |e| {
+            let mut this = $this.write();
+            this.update_order(e.descendants());
+            let Some(point) = this.point_at(&e.window(), e.global_pos()) else {
+              this.clear_selection();
+              return;
+            };
+            let anchor = match this.selection {
+              Some((anchor, _)) if e.with_shift_key() => anchor,
+              _ => point,
+            };
+            this.selection = Some((anchor, point));
+            this.selecting = true;
+          },
+          on_pointer_move: move |e| {
+            if !$this.selecting
+              || e.point_type != PointerType::Mouse
+              || e.mouse_buttons() != MouseButtons::PRIMARY {
+              return;
+            }
+            let point = $this.point_at(&e.window(), e.global_pos());
+            let selection = $this.selection;
+            if let (Some(point), Some((anchor, focus))) = (point, selection) {
+              if point != focus {
+                $this.write().selection = Some((anchor, point));
+              }
+            }
+          },
+          on_pointer_up: move |_| {
+            if $this.selecting {
+              $this.write().selecting = false;
+            }
+          },
+          on_double_tap: move |e| {
+            let point = $this.point_at(&e.window(), e.global_pos());
+            if let Some(point) = point {
+              $this.write().select_word(point);
+            }
+          },
+          on_blur: move |_| {
+            if $this.selection.is_some() {
+              $this.write().clear_selection();
+            }
+          },
+          on_key_down: move |e| {
+            if !e.with_command_key() {
+              return;
+            }
+            // use the physical key to make sure the keyboard with different
+            // layout use the same key as shortcut.
+            match e.key_code() {
+              PhysicalKey::Code(KeyCode::KeyC) => {
+                let text = $this.selected_text();
+                if !text.is_empty() {
+                  let clipboard = AppCtx::clipboard();
+                  let _ = clipboard.borrow_mut().clear();
+                  let _ = clipboard.borrow_mut().write_text(&text);
+                }
+              }
+              PhysicalKey::Code(KeyCode::KeyA) => {
+                let mut this = $this.write();
+                this.update_order(e.descendants());
+                this.select_all();
+              }
+              _ => {}
+            }
+          }
+        }
+      })
+      .into_widget()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ribir_core::{reset_test_env, test_helper::*};
+  use winit::{
+    dpi::LogicalPosition,
+    event::{DeviceId, ElementState, MouseButton, WindowEvent},
+    keyboard::ModifiersState,
+  };
+
+  use super::*;
+  use crate::prelude::*;
+
+  #[test]
+  fn select_across_texts() {
+    reset_test_env!();
+    let area = Stateful::new(SelectionArea::default());
+    let c_area = area.clone_writer();
+    let w = fn_widget! {
+      let area = c_area.clone_writer();
+      @ $area {
+        @Column {
+          @Text { text: "Hello world" }
+          @Text { text: "Second line" }
+        }
+      }
+    };
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 200.));
+    wnd.draw_frame();
+
+    let device_id = unsafe { DeviceId::dummy() };
+    let cursor_move = |wnd: &mut TestWindow, x: f32, y: f32| {
+      #[allow(deprecated)]
+      wnd.processes_native_event(WindowEvent::CursorMoved {
+        device_id,
+        position: LogicalPosition::new(x, y).to_physical(1.),
+      });
+      wnd.draw_frame();
+    };
+    let click = |wnd: &mut TestWindow, state: ElementState| {
+      wnd.process_mouse_input(device_id, state, MouseButton::Left);
+      wnd.draw_frame();
+    };
+
+    // Drag from the start of the first text to the end of the second text.
+    cursor_move(&mut wnd, 0., 2.);
+    click(&mut wnd, ElementState::Pressed);
+    cursor_move(&mut wnd, 72., 35.);
+    click(&mut wnd, ElementState::Released);
+    assert!(area.read().has_selection());
+    assert_eq!(area.read().selected_text(), "Hello world\nSecond line");
+
+    // A click clears the selection.
+    click(&mut wnd, ElementState::Pressed);
+    click(&mut wnd, ElementState::Released);
+    assert!(!area.read().has_selection());
+
+    // Select all by the keyboard.
+    let command =
+      if cfg!(target_os = "macos") { ModifiersState::SUPER } else { ModifiersState::CONTROL };
+    #[allow(deprecated)]
+    wnd.processes_native_event(WindowEvent::ModifiersChanged(command.into()));
+    wnd.processes_keyboard_event(
+      PhysicalKey::Code(KeyCode::KeyA),
+      VirtualKey::Character("a".into()),
+      false,
+      KeyLocation::Standard,
+      ElementState::Pressed,
+    );
+    wnd.draw_frame();
+    assert_eq!(area.read().selected_text(), "Hello world\nSecond line");
+  }
+
+  #[test]
+  fn drop_disposed_texts() {
+    reset_test_env!();
+    let area = Stateful::new(SelectionArea::default());
+    let (show, w_show) = split_value(true);
+    let c_area = area.clone_writer();
+    let w = fn_widget! {
+      let area = c_area.clone_writer();
+      @ $area {
+        @Column {
+          @Text { text: "Kept" }
+          @ { pipe!(*$show).map(move |show| show.then(|| @Text { text: "Disposed" })) }
+        }
+      }
+    };
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 200.));
+    wnd.draw_frame();
+    assert_eq!(area.read().texts.borrow().len(), 2);
+
+    *w_show.write() = false;
+    wnd.draw_frame();
+    assert_eq!(area.read().texts.borrow().len(), 1);
+  }
+
+  #[test]
+  fn select_in_row() {
+    reset_test_env!();
+    let area = Stateful::new(SelectionArea::default());
+    let c_area = area.clone_writer();
+    let w = fn_widget! {
+      let area = c_area.clone_writer();
+      @ $area {
+        @SizedBox {
+          size: Size::new(200., 20.),
+          @Row {
+            padding: EdgeInsets::only_left(20.),
+            item_gap: 20.,
+            @Text { text: "Hello" }
+            @Text { text: "World" }
+          }
+        }
+      }
+    };
+    let mut wnd = TestWindow::new_with_size(w, Size::new(200., 200.));
+    wnd.draw_frame();
+
+    let device_id = unsafe { DeviceId::dummy() };
+    let cursor_move = |wnd: &mut TestWindow, x: f32, y: f32| {
+      #[allow(deprecated)]
+      wnd.processes_native_event(WindowEvent::CursorMoved {
+        device_id,
+        position: LogicalPosition::new(x, y).to_physical(1.),
+      });
+      wnd.draw_frame();
+    };
+
+    // Drag from the left of the first text into it, the selection starts at
+    // the first text, not the last text of the row.
+    cursor_move(&mut wnd, 5., 5.);
+    wnd.process_mouse_input(device_id, ElementState::Pressed, MouseButton::Left);
+    wnd.draw_frame();
+    cursor_move(&mut wnd, 32., 5.);
+    wnd.process_mouse_input(device_id, ElementState::Released, MouseButton::Left);
+    wnd.draw_frame();
+    let selected = area.read().selected_text();
+    assert!(!selected.is_empty() && "Hello".starts_with(&selected), "{selected}");
+
+    // Drag from the right of the row back into the second text.
+    cursor_move(&mut wnd, 190., 5.);
+    wnd.process_mouse_input(device_id, ElementState::Pressed, MouseButton::Left);
+    wnd.draw_frame();
+    cursor_move(&mut wnd, 100., 5.);
+    wnd.process_mouse_input(device_id, ElementState::Released, MouseButton::Left);
+    wnd.draw_frame();
+    let selected = area.read().selected_text();
+    assert!(!selected.is_empty() && "World".ends_with(&selected), "{selected}");
+  }
+}
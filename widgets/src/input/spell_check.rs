@@ -0,0 +1,562 @@
+use std::{
+  collections::{BTreeSet, HashSet},
+  ops::Range,
+  path::Path,
+  rc::Rc,
+};
+
+use ribir_core::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{EditableText, glyphs_helper::GlyphsHelper, handle::replace_with_history};
+use crate::prelude::*;
+
+/// A spell checker of the editable texts, the texts are checked by
+/// paragraphs when the window is idle, and only the changed paragraphs are
+/// checked again.
+pub trait SpellChecker {
+  /// Check the words in the byte `range` of the `text`, return the byte
+  /// ranges of the misspelled words in the `text`.
+  fn check(&self, text: &str, range: Range<usize>) -> Vec<Range<usize>>;
+
+  /// The words to replace the misspelled `word`, the better one first.
+  fn suggest(&self, word: &str) -> Vec<String>;
+}
+
+/// The spell checker of an editable text, no spell check by default.
+///
+/// # Example
+///
+/// ```
+/// use ribir_core::prelude::*;
+/// use ribir_widgets::prelude::*;
+///
+/// let _editor = fn_widget! {
+///   @TextArea {
+///     spell_checker: DictionaryChecker::new(["hello", "world"]),
+///   }
+/// };
+/// ```
+#[derive(Clone, Default)]
+pub struct SpellCheck(Option<Rc<dyn SpellChecker>>);
+
+impl SpellCheck {
+  #[inline]
+  pub fn checker(&self) -> Option<&Rc<dyn SpellChecker>> { self.0.as_ref() }
+}
+
+impl<T: SpellChecker + 'static> From<T> for SpellCheck {
+  fn from(checker: T) -> Self { Self(Some(Rc::new(checker))) }
+}
+
+/// A spell checker by the words of a dictionary, the words are matched case
+/// insensitively.
+///
+/// The dictionary file has a word in every line, the flags after a `/` of a
+/// word, a count of the words in the first line and the lines starting with
+/// `#` are ignored, so the `.dic` files of Hunspell can be used.
+#[derive(Debug, Clone, Default)]
+pub struct DictionaryChecker {
+  words: HashSet<String>,
+}
+
+/// The max count of the suggestions of a word.
+const MAX_SUGGESTIONS: usize = 5;
+/// The max edit distance from a word to its suggestions.
+const MAX_SUGGEST_DISTANCE: usize = 2;
+
+impl DictionaryChecker {
+  pub fn new<S: AsRef<str>>(words: impl IntoIterator<Item = S>) -> Self {
+    let mut checker = Self::default();
+    for w in words {
+      checker.add_word(w.as_ref());
+    }
+    checker
+  }
+
+  /// Create a checker from the content of a dictionary file.
+  pub fn from_dic(content: &str) -> Self {
+    let mut lines = content.lines().map(str::trim).peekable();
+    if lines
+      .peek()
+      .is_some_and(|l| l.parse::<usize>().is_ok())
+    {
+      lines.next();
+    }
+    let words = lines
+      .filter(|l| !l.is_empty() && !l.starts_with('#'))
+      .map(|l| l.split('/').next().unwrap_or(l));
+    Self::new(words)
+  }
+
+  /// Create a checker from a dictionary file.
+  pub fn from_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+    std::fs::read_to_string(path).map(|content| Self::from_dic(&content))
+  }
+
+  pub fn add_word(&mut self, word: &str) {
+    let word = word.trim();
+    if !word.is_empty() {
+      self.words.insert(word.to_lowercase());
+    }
+  }
+
+  pub fn contains(&self, word: &str) -> bool { self.words.contains(&word.to_lowercase()) }
+}
+
+impl SpellChecker for DictionaryChecker {
+  fn check(&self, text: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    text[range.clone()]
+      .split_word_bound_indices()
+      .filter(|(_, w)| is_checked_word(w) && !self.contains(w))
+      .map(|(idx, w)| range.start + idx..range.start + idx + w.len())
+      .collect()
+  }
+
+  fn suggest(&self, word: &str) -> Vec<String> {
+    let lower = word.to_lowercase().chars().collect::<Vec<_>>();
+    let mut candidates = self
+      .words
+      .iter()
+      .filter(|w| w.chars().count().abs_diff(lower.len()) <= MAX_SUGGEST_DISTANCE)
+      .filter_map(|w| {
+        let dis = edit_distance(&lower, &w.chars().collect::<Vec<_>>());
+        (0 < dis && dis <= MAX_SUGGEST_DISTANCE).then_some((dis, w))
+      })
+      .collect::<Vec<_>>();
+    candidates.sort();
+
+    let capitalized = word
+      .chars()
+      .next()
+      .is_some_and(char::is_uppercase);
+    candidates
+      .into_iter()
+      .take(MAX_SUGGESTIONS)
+      .map(|(_, w)| if capitalized { capitalize(w) } else { w.clone() })
+      .collect()
+  }
+}
+
+/// The words with a digit or without any letter are not checked.
+fn is_checked_word(word: &str) -> bool {
+  word.chars().any(char::is_alphabetic) && !word.chars().any(|c| c.is_numeric())
+}
+
+fn capitalize(word: &str) -> String {
+  let mut chars = word.chars();
+  chars
+    .next()
+    .map(|c| c.to_uppercase().chain(chars).collect())
+    .unwrap_or_default()
+}
+
+/// The optimal string alignment distance, a transposition of two adjacent
+/// chars is one edit.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+  let mut rows = vec![(0..=b.len()).collect::<Vec<_>>(); a.len() + 1];
+  for (i, row) in rows.iter_mut().enumerate() {
+    row[0] = i;
+  }
+  for i in 1..=a.len() {
+    for j in 1..=b.len() {
+      let cost = usize::from(a[i - 1] != b[j - 1]);
+      let mut dis = (rows[i - 1][j] + 1)
+        .min(rows[i][j - 1] + 1)
+        .min(rows[i - 1][j - 1] + cost);
+      if 1 < i && 1 < j && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+        dis = dis.min(rows[i - 2][j - 2] + 1);
+      }
+      rows[i][j] = dis;
+    }
+  }
+  rows[a.len()][b.len()]
+}
+
+/// The misspelled words of a text.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Misspelled {
+  /// The text checked, the text may be edited after it's checked.
+  text: Rope,
+  ranges: Vec<Range<usize>>,
+}
+
+impl Misspelled {
+  /// The misspelled ranges not edited after the check in the `text`, only the
+  /// words are compared if the text is not the one checked.
+  fn ranges_in<'a>(&'a self, text: &'a Rope) -> impl Iterator<Item = &'a Range<usize>> + 'a {
+    let same = self.text.ptr_eq(text);
+    self.ranges.iter().filter(move |rg| {
+      same || {
+        let checked = self.text.get((*rg).clone());
+        checked.is_some() && text.get((*rg).clone()) == checked
+      }
+    })
+  }
+
+  /// The misspelled range in the `text` at the byte offset `cluster`.
+  pub(crate) fn range_at(&self, text: &Rope, cluster: usize) -> Option<Range<usize>> {
+    self
+      .ranges_in(text)
+      .find(|rg| rg.start <= cluster && cluster <= rg.end)
+      .cloned()
+  }
+}
+
+/// The bytes of the paragraphs checked at most in a batch, the rest of the
+/// changed paragraphs are checked in the next batches, so a long text does not
+/// block the window.
+const CHECK_BUDGET: usize = 4096;
+
+/// The spell check state of an editable text, it keeps the result of every
+/// paragraph of the rope, so only the paragraphs changed are checked again.
+pub(crate) struct SpellCheckState {
+  checker: Option<Rc<dyn SpellChecker>>,
+  misspelled: Misspelled,
+  /// The rope the `paragraphs` are of.
+  rope: Rope,
+  /// The misspelled ranges of every paragraph relative to the paragraph, `None`
+  /// if the paragraph is not checked yet.
+  paragraphs: Vec<Option<Vec<Range<usize>>>>,
+  /// The indices of the paragraphs not checked yet.
+  dirty: BTreeSet<usize>,
+}
+
+impl Default for SpellCheckState {
+  fn default() -> Self {
+    Self {
+      checker: None,
+      misspelled: Misspelled::default(),
+      rope: Rope::default(),
+      paragraphs: vec![None],
+      dirty: BTreeSet::from([0]),
+    }
+  }
+}
+
+impl SpellCheckState {
+  pub(crate) fn misspelled(&self) -> &Misspelled { &self.misspelled }
+
+  /// Return true if there are changed paragraphs not checked yet.
+  pub(crate) fn is_checking(&self) -> bool { !self.dirty.is_empty() }
+
+  /// Check a batch of the changed paragraphs of the `rope` by the `checker`.
+  /// Return if the misspelled words are changed.
+  pub(crate) fn update(&mut self, checker: Option<&Rc<dyn SpellChecker>>, rope: &Rope) -> bool {
+    let same_checker = match (self.checker.as_ref(), checker) {
+      (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+      (a, b) => a.is_none() && b.is_none(),
+    };
+    if same_checker && self.rope.ptr_eq(rope) && self.dirty.is_empty() {
+      return false;
+    }
+
+    self.sync_paragraphs(rope);
+    if !same_checker {
+      self.checker = checker.cloned();
+      self.paragraphs.fill(None);
+      self.dirty = (0..self.paragraphs.len()).collect();
+    }
+    let ranges = match checker {
+      Some(checker) => {
+        self.check_batch(&**checker);
+        self.misspelled_ranges()
+      }
+      None => {
+        self.dirty.clear();
+        vec![]
+      }
+    };
+
+    let changed = self.misspelled.ranges != ranges;
+    self.misspelled = Misspelled { text: rope.clone(), ranges };
+    changed
+  }
+
+  /// Keep the results of the paragraphs shared by the `rope` and the rope
+  /// checked before, the other paragraphs are marked as not checked.
+  fn sync_paragraphs(&mut self, rope: &Rope) {
+    if self.rope.ptr_eq(rope) {
+      return;
+    }
+    let (old, new) = (&self.rope, rope);
+    let (old_count, new_count) = (old.paragraph_count(), new.paragraph_count());
+    let head = old
+      .paragraphs()
+      .zip(new.paragraphs())
+      .take_while(|(a, b)| std::ptr::eq(*a, *b))
+      .count();
+    let tail = (1..=old_count.min(new_count) - head)
+      .take_while(|i| {
+        old
+          .paragraph(old_count - i)
+          .ptr_eq(new.paragraph(new_count - i))
+      })
+      .count();
+
+    let (old_end, new_end) = (old_count - tail, new_count - tail);
+    self
+      .paragraphs
+      .splice(head..old_end, std::iter::repeat_n(None, new_end - head));
+    self.dirty = self
+      .dirty
+      .iter()
+      .filter_map(|&idx| match idx {
+        _ if idx < head => Some(idx),
+        _ if idx >= old_end => Some(idx + new_end - old_end),
+        _ => None,
+      })
+      .chain(head..new_end)
+      .collect();
+    self.rope = rope.clone();
+  }
+
+  /// Check the paragraphs not checked yet in order, until the bytes checked
+  /// exceed the budget.
+  /// The paragraphs are checked in themselves, so the text is not copied out
+  /// of the rope.
+  fn check_batch(&mut self, checker: &dyn SpellChecker) {
+    let mut budget = CHECK_BUDGET;
+    while budget > 0 {
+      let Some(idx) = self.dirty.pop_first() else { break };
+      let para = self.rope.paragraph(idx);
+      budget = budget.saturating_sub(para.len());
+      self.paragraphs[idx] = Some(checker.check(para, 0..para.len()));
+    }
+  }
+
+  /// The misspelled ranges of the checked paragraphs in the text.
+  fn misspelled_ranges(&self) -> Vec<Range<usize>> {
+    let mut ranges = vec![];
+    let mut start = 0;
+    for (para, relative) in self.rope.paragraphs().zip(&self.paragraphs) {
+      ranges.extend(
+        relative
+          .iter()
+          .flatten()
+          .map(|rg| rg.start + start..rg.end + start),
+      );
+      start += para.len();
+    }
+    ranges
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpellCheckStyle {
+  pub brush: Brush,
+  pub thickness: f32,
+  /// The height of the waves of the underline.
+  pub wave_height: f32,
+  /// The length of a wave of the underline.
+  pub wave_length: f32,
+}
+
+impl CustomStyle for SpellCheckStyle {
+  fn default_style(ctx: &impl ProviderCtx) -> Self {
+    SpellCheckStyle {
+      brush: Palette::of(ctx).error().into(),
+      thickness: 1.,
+      wave_height: 2.,
+      wave_length: 4.,
+    }
+  }
+}
+
+/// Draw the wavy underlines of the misspelled words, the underlines are
+/// computed when painting, so they follow the glyphs of the text laid out in
+/// this frame.
+#[derive(Declare)]
+pub(crate) struct SpellCheckUnderline {
+  pub(crate) misspelled: Misspelled,
+  pub(crate) text: Reader<Text>,
+}
+
+impl Render for SpellCheckUnderline {
+  fn perform_layout(&self, clamp: BoxClamp, _: &mut LayoutCtx) -> Size { clamp.min }
+
+  #[inline]
+  fn only_sized_by_parent(&self) -> bool { true }
+
+  fn paint(&self, ctx: &mut PaintingCtx) {
+    let rects = {
+      let text = self.text.read();
+      let Some(glyphs) = text.glyphs() else { return };
+      // Only the texts edited in a rope are checked.
+      let Some(rope) = text.rope.as_ref() else { return };
+      self
+        .misspelled
+        .ranges_in(rope)
+        .flat_map(|rg| glyphs.selection(rg))
+        .collect::<Vec<_>>()
+    };
+    if rects.is_empty() {
+      return;
+    }
+
+    let style = SpellCheckStyle::of(&ctx);
+    let painter = ctx.painter();
+    painter.save();
+    painter
+      .set_stroke_brush(style.brush)
+      .set_line_width(style.thickness);
+    let half = style.wave_length.max(1.) / 2.;
+    for rc in rects {
+      let base = rc.max_y() - style.wave_height;
+      let mut x = rc.min_x();
+      let mut up = true;
+      painter.begin_path(Point::new(x, base));
+      while x < rc.max_x() {
+        let to = (x + half).min(rc.max_x());
+        let peak = if up { base - style.wave_height } else { base + style.wave_height };
+        painter.quadratic_curve_to(Point::new((x + to) / 2., peak), Point::new(to, base));
+        x = to;
+        up = !up;
+      }
+      painter.end_path(false);
+    }
+    painter.stroke();
+    painter.restore();
+  }
+}
+
+/// Show the suggestions to replace the misspelled word at the pointer in a
+/// context menu.
+pub(crate) fn show_suggestions<E: EditableText + 'static>(
+  host: &impl StateWriter<Value = E>, text: &Text, text_id: WidgetId, misspelled: &Misspelled,
+  e: &PointerEvent,
+) {
+  let Some(checker) = host.read().spell_checker().cloned() else { return };
+  let Some(glyphs) = text.glyphs() else { return };
+  let wnd = e.window();
+  let pos = wnd.map_from_global(e.global_pos(), text_id);
+  let cluster = glyphs
+    .caret_position_from_pos(pos.x, pos.y)
+    .cluster;
+  let rope = host.read().rope().clone();
+  let Some(range) = misspelled.range_at(&rope, cluster) else { return };
+  let word = rope.slice(range.clone());
+  let suggestions = checker.suggest(&word);
+  if suggestions.is_empty() {
+    return;
+  }
+
+  let host = host.clone_writer();
+  let menu = fn_widget! {
+    let items = suggestions.iter().map(|suggestion| {
+      let (host, range, word) = (host.clone_writer(), range.clone(), word.clone());
+      let suggestion = suggestion.clone();
+      @Text {
+        text: suggestion.clone(),
+        padding: EdgeInsets::new(4., 12., 4., 12.),
+        cursor: CursorIcon::Pointer,
+        on_tap: move |e| {
+          let mut host = host.write();
+          // The text may be edited after the menu shown.
+          if host.rope().get(range.clone()).as_ref() == Some(&word) {
+            replace_with_history(&mut *host, range.clone(), &suggestion);
+          }
+          drop(host);
+          if let Some(overlay) = Overlay::of(&**e) {
+            overlay.close();
+          }
+        },
+      }
+    }).collect::<Vec<_>>();
+    @Column {
+      padding: EdgeInsets::vertical(4.),
+      background: Palette::of(BuildCtx::get()).surface_container(),
+      @ { items }
+    }
+  };
+  Overlay::new(menu).show_at(e.global_pos(), wnd);
+}
+
+#[cfg(test)]
+mod tests {
+  use std::cell::RefCell;
+
+  use super::*;
+
+  #[test]
+  fn dictionary_check() {
+    let dic = DictionaryChecker::from_dic("3\nhello\nworld/M\n# comment\nthe\n");
+    let text = "Helo wrold, the World 2nd";
+    assert_eq!(dic.check(text, 0..text.len()), vec![0..4, 5..10]);
+    assert_eq!(dic.check(text, 5..text.len()), vec![5..10]);
+
+    assert_eq!(dic.suggest("Helo"), vec!["Hello".to_string()]);
+    assert_eq!(dic.suggest("wrold"), vec!["world".to_string()]);
+    assert!(dic.suggest("xyz").is_empty());
+  }
+
+  #[test]
+  fn check_changed_paragraphs() {
+    struct Recorder(DictionaryChecker, RefCell<Vec<String>>);
+    impl SpellChecker for Recorder {
+      fn check(&self, text: &str, range: Range<usize>) -> Vec<Range<usize>> {
+        self
+          .1
+          .borrow_mut()
+          .push(text[range.clone()].to_string());
+        self.0.check(text, range)
+      }
+      fn suggest(&self, word: &str) -> Vec<String> { self.0.suggest(word) }
+    }
+
+    let checker = Rc::new(Recorder(DictionaryChecker::new(["a", "good", "day"]), <_>::default()));
+    let dyn_checker: Rc<dyn SpellChecker> = checker.clone();
+    let mut state = SpellCheckState::default();
+    let mut rope = Rope::from("a good day\na bad day");
+    assert!(state.update(Some(&dyn_checker), &rope));
+    assert_eq!(state.misspelled().ranges, vec![13..16]);
+    assert_eq!(checker.1.take(), vec!["a good day\n", "a bad day"]);
+
+    rope.replace(13..20, "good day\nbad");
+    assert!(state.update(Some(&dyn_checker), &rope));
+    assert_eq!(state.misspelled().ranges, vec![22..25]);
+    assert_eq!(checker.1.take(), vec!["a good day\n", "bad"]);
+
+    assert!(!state.update(Some(&dyn_checker), &rope));
+    assert!(checker.1.take().is_empty());
+
+    // The misspelled word edited after the check is not marked.
+    assert_eq!(state.misspelled().range_at(&rope, 23), Some(22..25));
+    let mut edited = rope.clone();
+    edited.replace(22..25, "bed");
+    assert_eq!(state.misspelled().range_at(&edited, 23), None);
+    assert_eq!(
+      state
+        .misspelled()
+        .range_at(&Rope::from(rope.to_string()), 23),
+      Some(22..25)
+    );
+
+    assert!(state.update(None, &rope));
+    assert!(state.misspelled().ranges.is_empty());
+  }
+
+  #[test]
+  fn check_long_text_in_batches() {
+    let checker: Rc<dyn SpellChecker> = Rc::new(DictionaryChecker::new(["a", "good", "day"]));
+    let mut state = SpellCheckState::default();
+    let mut rope = Rope::from("a good day\n".repeat(1000).as_str());
+    rope.replace(0..0, "bad\n");
+
+    assert!(state.update(Some(&checker), &rope));
+    assert_eq!(state.misspelled().ranges, vec![0..3]);
+    let mut batches = 1;
+    while state.is_checking() {
+      state.update(Some(&checker), &rope);
+      batches += 1;
+    }
+    assert!(batches > 1);
+
+    // Only the edited paragraph is checked again.
+    let idx = rope.paragraph_range(500).start;
+    rope.replace(idx..idx + 1, "an");
+    assert!(state.update(Some(&checker), &rope));
+    assert!(!state.is_checking());
+    assert_eq!(state.misspelled().ranges, vec![0..3, idx..idx + 2]);
+  }
+}
@@ -0,0 +1,166 @@
+use ribir_core::prelude::*;
+
+/// Where a keyboard shortcut moves the caret in text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaretMove {
+  PrevChar,
+  NextChar,
+  PrevWord,
+  NextWord,
+  LineStart,
+  LineEnd,
+  PrevLine,
+  NextLine,
+  /// To the start of the paragraph, or the start of the previous paragraph if
+  /// the caret is already at the start.
+  PrevParagraph,
+  /// To the end of the paragraph, or the end of the next paragraph if the
+  /// caret is already at the end.
+  NextParagraph,
+  PageUp,
+  PageDown,
+  TextStart,
+  TextEnd,
+}
+
+/// What a keyboard shortcut does in text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextAction {
+  /// Move the caret, or extend the selection if the shift key is pressed.
+  Move(CaretMove),
+  /// Delete the text from the caret to where it moves, or delete the
+  /// selection if any. Only works in the editable text.
+  Delete(CaretMove),
+}
+
+/// A key with the modifiers pressed, the shift key is not a part of it, since
+/// it extends the selection of any caret move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyBinding {
+  pub key: NamedKey,
+  pub modifiers: ModifiersState,
+}
+
+/// The keyboard shortcuts to move the caret and delete in `Input`, `TextArea`
+/// and the selectable `Text`.
+///
+/// The default key map follows the convention of the platform, and it's a
+/// custom style, so you can provide another one by the theme or a `Provider`.
+///
+/// # Example
+///
+/// ```
+/// use ribir_core::prelude::*;
+/// use ribir_widgets::prelude::*;
+///
+/// // Use `Alt+ArrowUp` and `Alt+ArrowDown` to move to the start and end of the
+/// // text.
+/// let key_map = TextKeyMap::standard()
+///   .bind(NamedKey::ArrowUp, ModifiersState::ALT, TextAction::Move(CaretMove::TextStart))
+///   .bind(NamedKey::ArrowDown, ModifiersState::ALT, TextAction::Move(CaretMove::TextEnd));
+/// let mut styles = CustomStyles::default();
+/// styles.set_custom_style(key_map);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextKeyMap {
+  bindings: Vec<(KeyBinding, TextAction)>,
+}
+
+impl TextKeyMap {
+  /// The key map of Windows and Linux.
+  pub fn standard() -> Self {
+    use CaretMove::*;
+    use NamedKey::*;
+    use TextAction::Move;
+
+    let none = ModifiersState::empty();
+    let ctrl = ModifiersState::CONTROL;
+    Self::default()
+      .bind(ArrowLeft, none, Move(PrevChar))
+      .bind(ArrowRight, none, Move(NextChar))
+      .bind(ArrowLeft, ctrl, Move(PrevWord))
+      .bind(ArrowRight, ctrl, Move(NextWord))
+      .bind(ArrowUp, none, Move(PrevLine))
+      .bind(ArrowDown, none, Move(NextLine))
+      .bind(ArrowUp, ctrl, Move(PrevParagraph))
+      .bind(ArrowDown, ctrl, Move(NextParagraph))
+      .bind(Home, none, Move(LineStart))
+      .bind(End, none, Move(LineEnd))
+      .bind(Home, ctrl, Move(TextStart))
+      .bind(End, ctrl, Move(TextEnd))
+      .bind(NamedKey::PageUp, none, Move(CaretMove::PageUp))
+      .bind(NamedKey::PageDown, none, Move(CaretMove::PageDown))
+      .bind(Backspace, none, TextAction::Delete(PrevChar))
+      .bind(Delete, none, TextAction::Delete(NextChar))
+      .bind(Backspace, ctrl, TextAction::Delete(PrevWord))
+      .bind(Delete, ctrl, TextAction::Delete(NextWord))
+  }
+
+  /// The key map of macOS.
+  pub fn mac() -> Self {
+    use CaretMove::*;
+    use NamedKey::*;
+    use TextAction::Move;
+
+    let none = ModifiersState::empty();
+    let alt = ModifiersState::ALT;
+    let cmd = ModifiersState::SUPER;
+    Self::default()
+      .bind(ArrowLeft, none, Move(PrevChar))
+      .bind(ArrowRight, none, Move(NextChar))
+      .bind(ArrowLeft, alt, Move(PrevWord))
+      .bind(ArrowRight, alt, Move(NextWord))
+      .bind(ArrowLeft, cmd, Move(LineStart))
+      .bind(ArrowRight, cmd, Move(LineEnd))
+      .bind(ArrowUp, none, Move(PrevLine))
+      .bind(ArrowDown, none, Move(NextLine))
+      .bind(ArrowUp, alt, Move(PrevParagraph))
+      .bind(ArrowDown, alt, Move(NextParagraph))
+      .bind(ArrowUp, cmd, Move(TextStart))
+      .bind(ArrowDown, cmd, Move(TextEnd))
+      .bind(Home, none, Move(TextStart))
+      .bind(End, none, Move(TextEnd))
+      .bind(NamedKey::PageUp, none, Move(CaretMove::PageUp))
+      .bind(NamedKey::PageDown, none, Move(CaretMove::PageDown))
+      .bind(Backspace, none, TextAction::Delete(PrevChar))
+      .bind(Delete, none, TextAction::Delete(NextChar))
+      .bind(Backspace, alt, TextAction::Delete(PrevWord))
+      .bind(Delete, alt, TextAction::Delete(NextWord))
+      .bind(Backspace, cmd, TextAction::Delete(LineStart))
+  }
+
+  /// Bind the `key` with the `modifiers` to the `action`, it replaces the
+  /// action bound to the same keys before.
+  pub fn bind(mut self, key: NamedKey, modifiers: ModifiersState, action: TextAction) -> Self {
+    let binding = KeyBinding { key, modifiers: modifiers - ModifiersState::SHIFT };
+    self.bindings.retain(|(b, _)| *b != binding);
+    self.bindings.push((binding, action));
+    self
+  }
+
+  /// Remove the action bound to the `key` with the `modifiers`.
+  pub fn unbind(mut self, key: NamedKey, modifiers: ModifiersState) -> Self {
+    let binding = KeyBinding { key, modifiers: modifiers - ModifiersState::SHIFT };
+    self.bindings.retain(|(b, _)| *b != binding);
+    self
+  }
+
+  /// The action bound to the keys of the `event`.
+  pub fn action(&self, event: &KeyboardEvent) -> Option<TextAction> {
+    let VirtualKey::Named(key) = event.key() else { return None };
+    let modifiers = event.modifiers() - ModifiersState::SHIFT;
+    self
+      .bindings
+      .iter()
+      .find(|(b, _)| b.key == *key && b.modifiers == modifiers)
+      .map(|(_, action)| *action)
+  }
+
+  pub fn bindings(&self) -> impl Iterator<Item = &(KeyBinding, TextAction)> { self.bindings.iter() }
+}
+
+impl CustomStyle for TextKeyMap {
+  fn default_style(_: &impl ProviderCtx) -> Self {
+    if cfg!(target_os = "macos") { Self::mac() } else { Self::standard() }
+  }
+}
@@ -2,7 +2,10 @@ use std::ops::Range;
 
 use ribir_core::prelude::*;
 
-use super::glyphs_helper::TextGlyphsHelper;
+use super::{
+  glyphs_helper::{GlyphsText, TextGlyphsHelper},
+  masked_text::MaskedText,
+};
 use crate::{
   input::{glyphs_helper::GlyphsHelper, selected_text::SelectedHighLight},
   prelude::*,
@@ -27,31 +30,42 @@ pub trait SelectableText {
 
   fn text(&self) -> &CowArc<str>;
 
+  /// The rope the text is edited in, if the text is shown by a `Text` laying
+  /// out the rope. The glyphs are checked with it, so the `text` is not built.
+  fn text_rope(&self) -> Option<&Rope> { None }
+
   fn caret(&self) -> CaretState;
 
   fn set_caret(&mut self, caret: CaretState);
 
+  /// The masked text shown in place of the text, the text is not copied if
+  /// it's masked.
+  fn text_mask(&self) -> Option<MaskedText> { None }
+
   fn select_text_rect(&self, text: &Text) -> Vec<Rect> {
     text
       .glyphs()
       .and_then(|glyphs| {
-        let helper = TextGlyphsHelper::new(text.text.clone(), glyphs.clone());
-        helper.selection(self.text(), &self.select_range())
+        let helper =
+          TextGlyphsHelper::new(text.glyphs_text(), glyphs.clone()).with_mask(self.text_mask());
+        helper.selection(&GlyphsText::of(self), &self.select_range())
       })
       .unwrap_or_default()
   }
 
   fn caret_position(&self, text: &Text) -> Option<Point> {
     text.glyphs().and_then(|glyphs| {
-      let helper = TextGlyphsHelper::new(text.text.clone(), glyphs.clone());
-      helper.cursor(self.text(), self.caret().caret_position())
+      let helper =
+        TextGlyphsHelper::new(text.glyphs_text(), glyphs.clone()).with_mask(self.text_mask());
+      helper.cursor(&GlyphsText::of(self), self.caret().caret_position())
     })
   }
 
   fn current_line_height(&self, text: &Text) -> Option<f32> {
     text.glyphs().and_then(|glyphs| {
-      let helper = TextGlyphsHelper::new(text.text.clone(), glyphs.clone());
-      helper.line_height(self.text(), self.caret().caret_position())
+      let helper =
+        TextGlyphsHelper::new(text.glyphs_text(), glyphs.clone()).with_mask(self.text_mask());
+      helper.line_height(&GlyphsText::of(self), self.caret().caret_position())
     })
   }
 }
@@ -79,8 +93,9 @@ pub(crate) fn bind_point_listener<T: SelectableText>(
       on_pointer_down: move |e| {
         let mut this = $this.write();
         let position = e.position();
-        if let Some(helper) = $text.glyphs() {
-          let end = helper.caret_position_from_pos(position.x, position.y);
+        if let Some(glyphs) = $text.glyphs() {
+          let end = glyphs.caret_position_from_pos(position.x, position.y);
+          let end = unmask_caret(&*this, end);
           let begin = if e.with_shift_key() {
             match this.caret() {
               CaretState::Caret(begin) |
@@ -101,6 +116,7 @@ pub(crate) fn bind_point_listener<T: SelectableText>(
               if let Some(glyphs) = $text.glyphs() {
                 let position = e.position();
                 let end = glyphs.caret_position_from_pos(position.x, position.y);
+                let end = unmask_caret(&*this, end);
                 this.set_caret(CaretState::Selecting(begin, end));
               }
           }
@@ -122,8 +138,13 @@ pub(crate) fn bind_point_listener<T: SelectableText>(
         if let Some(glyphs) = $text.glyphs() {
           let position = e.position();
           let caret = glyphs.caret_position_from_pos(position.x, position.y);
-          let rg = select_word(&$text.text, caret.cluster);
-          $this.write().set_caret(CaretState::Select(
+          let mut this = $this.write();
+          // Select all the masked text, not leak the words of it.
+          let rg = match this.text_mask() {
+            Some(mask) => 0..mask.secret_len(),
+            None => $text.glyphs_text().word_at(caret.cluster),
+          };
+          this.set_caret(CaretState::Select(
             CaretPosition { cluster: rg.start, position: None },
             CaretPosition { cluster: rg.end, position: None }
           ));
@@ -134,6 +155,14 @@ pub(crate) fn bind_point_listener<T: SelectableText>(
   .into_widget()
 }
 
+/// Map the `caret` in the glyphs of the text shown to the caret in the text.
+fn unmask_caret(this: &impl SelectableText, caret: CaretPosition) -> CaretPosition {
+  match this.text_mask() {
+    Some(mask) => CaretPosition { cluster: mask.to_secret(caret.cluster), ..caret },
+    None => caret,
+  }
+}
+
 impl ComposeChild<'static> for TextSelectable {
   type Child = FatObj<State<Text>>;
   fn compose_child(this: impl StateWriter<Value = Self>, text: Self::Child) -> Widget<'static> {
@@ -201,15 +230,16 @@ fn deal_with_command<F: SelectableText>(
   // layout use the same key as shortcut.
   match event.key_code() {
     PhysicalKey::Code(KeyCode::KeyC) => {
-      let text = this.read().selected_text();
-      if !text.is_empty() {
+      let this = this.read();
+      let text = this.selected_text();
+      if !text.is_empty() && this.text_mask().is_none() {
         let clipboard = AppCtx::clipboard();
         let _ = clipboard.borrow_mut().clear();
         let _ = clipboard.borrow_mut().write_text(&text);
       }
     }
     PhysicalKey::Code(KeyCode::KeyA) => {
-      let len = this.read().text().len();
+      let len = GlyphsText::of(&*this.read()).len();
       this.write().set_caret(CaretState::Select(
         CaretPosition { cluster: 0, position: None },
         CaretPosition { cluster: len, position: None },
@@ -220,60 +250,72 @@ fn deal_with_command<F: SelectableText>(
   true
 }
 
-fn is_move_by_word(event: &KeyboardEvent) -> bool {
-  #[cfg(target_os = "macos")]
-  return event.with_alt_key();
-  #[cfg(not(target_os = "macos"))]
-  return event.with_ctrl_key();
-}
-
 fn deal_with_selection<F: SelectableText>(
   this: &impl StateWriter<Value = F>, text: &Text, event: &KeyboardEvent,
 ) {
+  let Some(TextAction::Move(mv)) = TextKeyMap::of(event).action(event) else { return };
   let Some(glyphs) = text.glyphs() else { return };
-  let helper = TextGlyphsHelper::new(text.text.clone(), glyphs.clone());
+  let helper =
+    TextGlyphsHelper::new(text.glyphs_text(), glyphs.clone()).with_mask(this.read().text_mask());
 
   let old_caret = this.read().caret();
-  let text = this.read().text().clone();
-  let new_caret_position = match event.key() {
-    VirtualKey::Named(NamedKey::ArrowLeft) => {
-      if is_move_by_word(event) {
-        let cluster = select_prev_word(&text, old_caret.cluster(), false).start;
-        Some(CaretPosition { cluster, position: None })
-      } else if event.with_command_key() {
-        helper.line_begin(&text, old_caret.caret_position())
-      } else {
-        helper.prev(&text, old_caret.caret_position())
-      }
-    }
-    VirtualKey::Named(NamedKey::ArrowRight) => {
-      if is_move_by_word(event) {
-        let cluster = select_next_word(&text, old_caret.cluster(), true).end;
-        Some(CaretPosition { cluster, position: None })
-      } else if event.with_command_key() {
-        helper.line_end(&text, old_caret.caret_position())
-      } else {
-        helper.next(&text, old_caret.caret_position())
-      }
-    }
-    VirtualKey::Named(NamedKey::ArrowUp) => helper.up(&text, old_caret.caret_position()),
-    VirtualKey::Named(NamedKey::ArrowDown) => helper.down(&text, old_caret.caret_position()),
-    VirtualKey::Named(NamedKey::Home) => helper.line_begin(&text, old_caret.caret_position()),
-    VirtualKey::Named(NamedKey::End) => helper.line_end(&text, old_caret.caret_position()),
-    _ => None,
-  };
-
-  if new_caret_position.is_some() {
+  let text = GlyphsText::of(&*this.read());
+  let page_height = page_height(event);
+  let new_caret_position =
+    caret_move_target(&helper, &text, old_caret.caret_position(), mv, page_height);
+
+  if let Some(new_caret_position) = new_caret_position {
     if event.with_shift_key() {
       this.write().set_caret(match old_caret {
         CaretState::Caret(begin)
         | CaretState::Select(begin, _)
-        | CaretState::Selecting(begin, _) => CaretState::Select(begin, new_caret_position.unwrap()),
+        | CaretState::Selecting(begin, _) => CaretState::Select(begin, new_caret_position),
       })
     } else {
-      this
-        .write()
-        .set_caret(new_caret_position.unwrap().into())
+      this.write().set_caret(new_caret_position.into())
     }
   }
 }
+
+/// The distance of a page move, the view height of the scroll view the text is
+/// in, or the height of the widget handling the `event` if it isn't scrolled.
+pub(crate) fn page_height(event: &KeyboardEvent) -> f32 {
+  Provider::of::<ScrollableWidget>(event)
+    .map(|s| s.scroll_view_size().height)
+    .filter(|h| *h > 0.)
+    .or_else(|| {
+      event
+        .window()
+        .widget_size(event.current_target())
+        .map(|s| s.height)
+    })
+    .unwrap_or_default()
+}
+
+/// Where the caret moves to from the `caret` by the `mv`, the page moves go
+/// through the lines in the `page_height`.
+pub(crate) fn caret_move_target(
+  helper: &TextGlyphsHelper, text: &GlyphsText, caret: CaretPosition, mv: CaretMove,
+  page_height: f32,
+) -> Option<CaretPosition> {
+  let to = |cluster| Some(CaretPosition { cluster, position: None });
+  match mv {
+    // Not leak the words of the masked text.
+    CaretMove::PrevWord if helper.is_masked() => to(0),
+    CaretMove::NextWord if helper.is_masked() => to(text.len()),
+    CaretMove::PrevChar => helper.prev(text, caret),
+    CaretMove::NextChar => helper.next(text, caret),
+    CaretMove::PrevWord => to(text.prev_word_start(caret.cluster)),
+    CaretMove::NextWord => to(text.next_word_end(caret.cluster)),
+    CaretMove::LineStart => helper.line_begin(text, caret),
+    CaretMove::LineEnd => helper.line_end(text, caret),
+    CaretMove::PrevLine => helper.up(text, caret),
+    CaretMove::NextLine => helper.down(text, caret),
+    CaretMove::PrevParagraph => to(text.prev_paragraph_start(caret.cluster)),
+    CaretMove::NextParagraph => to(text.next_paragraph_end(caret.cluster)),
+    CaretMove::PageUp => helper.page_up(text, caret, page_height),
+    CaretMove::PageDown => helper.page_down(text, caret, page_height),
+    CaretMove::TextStart => to(0),
+    CaretMove::TextEnd => to(text.len()),
+  }
+}
@@ -34,3 +34,7 @@ pub use only_sized_by_parent::OnlySizedByParent;
 pub use ribir_core::builtin_widgets::container::Container;
 mod fractionally;
 pub use fractionally::*;
+mod grid;
+pub use grid::*;
+mod intrinsic_width;
+pub use intrinsic_width::IntrinsicWidth;
@@ -114,6 +114,80 @@ impl Render for Flex {
 
   #[inline]
   fn paint(&self, _: &mut PaintingCtx) {}
+
+  /// The baselines of the first and the last child that have baselines.
+  fn baselines(&self, ctx: &LayoutCtx) -> Option<Baselines> {
+    let baseline_of = |c: WidgetId| {
+      let b = ctx.child_baselines(c)?;
+      Some(b.offset(ctx.widget_box_pos(c)?.y))
+    };
+    let mut children = ctx.children().filter_map(baseline_of);
+    let first = children.next()?;
+    let last = children.last().unwrap_or(first);
+    Some(Baselines::new(first.first, last.last))
+  }
+
+  /// The main axis sums the children and the gaps, or takes the largest child
+  /// for the minimum size of a wrapped flex, the `Expanded` children share the
+  /// space by their flex factors, so the flex unit fits the largest of them.
+  /// The cross axis takes the largest child when every child is given its
+  /// maximum main size, or its share of the `extent` if it's expanded.
+  fn intrinsic_size(&self, kind: IntrinsicSize, extent: f32, ctx: &mut LayoutCtx) -> f32 {
+    let (ctx, children) = ctx.split_children();
+    let children: Vec<_> = children
+      .map(|c| {
+        let flex = ctx
+          .query_of_widget::<Expanded>(c)
+          .map(|e| e.flex)
+          .filter(|f| *f > 0.);
+        (c, flex)
+      })
+      .collect();
+    let gaps = self.item_gap * children.len().saturating_sub(1) as f32;
+    let total_flex: f32 = children.iter().filter_map(|(_, f)| *f).sum();
+
+    if kind.is_width() == self.direction.is_horizontal() {
+      let (mut fixed, mut unit, mut max) = (0f32, 0f32, 0f32);
+      for (c, flex) in children {
+        let size = ctx.child_intrinsic_size(c, kind, extent);
+        max = max.max(size);
+        match flex {
+          Some(flex) => unit = unit.max(size / flex),
+          None => fixed += size,
+        }
+      }
+      if self.wrap && kind.is_min() { max } else { fixed + unit * total_flex + gaps }
+    } else {
+      let main_kind =
+        if kind.is_width() { IntrinsicSize::MaxHeight } else { IntrinsicSize::MaxWidth };
+      let mains: Vec<_> = children
+        .iter()
+        .map(|(c, flex)| {
+          flex.is_none().then(|| {
+            ctx
+              .child_intrinsic_size(*c, main_kind, f32::INFINITY)
+              .min(extent)
+          })
+        })
+        .collect();
+      // The expanded children share the space left by the others.
+      let unit = (extent.is_finite() && total_flex > 0.).then(|| {
+        let fixed: f32 = mains.iter().flatten().sum();
+        (extent - fixed - gaps).max(0.) / total_flex
+      });
+      children
+        .iter()
+        .zip(mains)
+        .fold(0., |size, ((c, flex), main)| {
+          let main = match (main, flex, unit) {
+            (Some(main), ..) => main,
+            (None, Some(flex), Some(unit)) => unit * flex,
+            _ => ctx.child_intrinsic_size(*c, main_kind, f32::INFINITY),
+          };
+          size.max(ctx.child_intrinsic_size(*c, kind, main))
+        })
+    }
+  }
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -165,6 +239,9 @@ impl FlexLayouter {
   fn layout(&mut self, ctx: &mut LayoutCtx) -> Size {
     self.perform_children_layout(ctx);
     self.flex_children_layout(ctx);
+    if self.is_baseline_align() {
+      self.align_baselines(ctx);
+    }
 
     // cross direction need calculate cross_axis_gap but last line don't need.
     let cross = self
@@ -212,6 +289,7 @@ impl FlexLayouter {
           .map(|expanded| expanded.flex),
         pos: <_>::default(),
         size: <_>::default(),
+        baseline: None,
       };
 
       let gap = if children.peek().is_some() && !FlexLayouter::is_space_layout(self.justify_content)
@@ -249,6 +327,30 @@ impl FlexLayouter {
     self.place_line();
   }
 
+  fn is_baseline_align(&self) -> bool {
+    self.align_items == Align::Baseline && self.dir == Direction::Horizontal
+  }
+
+  /// Place the baseline of every line below the highest part above the
+  /// baseline of its items, and grow the line to contain the items.
+  fn align_baselines(&mut self, ctx: &mut LayoutCtx) {
+    let (ctx, mut children) = ctx.split_children();
+    self.lines.iter_mut().for_each(|line| {
+      let (mut ascent, mut descent) = (0f32, 0f32);
+      line.items_info.iter_mut().for_each(|info| {
+        let child = children.next().unwrap();
+        let baseline = ctx
+          .child_baselines(child)
+          .map_or(info.size.cross, |b| b.first);
+        info.baseline = Some(baseline);
+        ascent = ascent.max(baseline);
+        descent = descent.max(info.size.cross - baseline);
+      });
+      line.baseline = ascent;
+      line.cross_line_height = line.cross_line_height.max(ascent + descent);
+    });
+  }
+
   fn is_space_layout(justify_content: JustifyContent) -> bool {
     matches!(
       justify_content,
@@ -297,8 +399,10 @@ impl FlexLayouter {
         lines.iter_mut()$(.$rev())?.for_each(|line| {
           let (mut main, step) = line.place_args(bound.main, *justify_content, self.main_axis_gap);
           line.items_info.iter_mut()$(.$rev())?.for_each(|item| {
-            let item_cross_offset =
-              align_items.align_value(item.size.cross, line.cross_line_height);
+            let item_cross_offset = match item.baseline {
+              Some(baseline) => Align::baseline_offset(item.size.cross, Some(baseline), line.baseline),
+              None => align_items.align_value(item.size.cross, line.cross_line_height),
+            };
 
             item.pos.cross = cross + item_cross_offset + self.cross_axis_gap;
             item.pos.main = main;
@@ -347,12 +451,16 @@ struct MainLineInfo {
   items_info: Vec<FlexLayoutInfo>,
   flex_sum: f32,
   cross_line_height: f32,
+  /// The baseline of the line when the items are aligned by baseline.
+  baseline: f32,
 }
 
 struct FlexLayoutInfo {
   pos: FlexSize,
   size: FlexSize,
   flex: Option<f32>,
+  /// The baseline of the item when the items are aligned by baseline.
+  baseline: Option<f32>,
 }
 
 impl MainLineInfo {
@@ -573,6 +681,30 @@ mod tests {
     LayoutCase::new(&[0, 2]).with_rect(ribir_geom::rect(200., 0., 100., 40.))
   );
 
+  widget_layout_test!(
+    baseline_cross_align,
+    WidgetTester::new(fn_widget! {
+      let style = |font_size: f32| TextStyle { font_size, line_height: font_size, ..<_>::default() };
+      @Row {
+        align_items: Align::Baseline,
+        @Text {
+          text: "small",
+          text_style: style(16.),
+          margin: EdgeInsets::only_top(4.),
+        }
+        @Text { text: "big", text_style: style(32.) }
+        @SizedBox { size: Size::new(10., 10.) }
+      }
+    })
+    .with_wnd_size(Size::new(500., 500.)),
+    // The baseline of the text is an em below its top, and the box without a
+    // baseline sits on the baseline by its bottom.
+    LayoutCase::default().with_height(32.),
+    LayoutCase::new(&[0, 0]).with_y(12.),
+    LayoutCase::new(&[0, 1]).with_y(0.),
+    LayoutCase::new(&[0, 2]).with_y(22.)
+  );
+
   fn main_align(justify_content: JustifyContent) -> WidgetTester {
     WidgetTester::new(fn_widget! {
       let item_size = Size::new(100., 20.);
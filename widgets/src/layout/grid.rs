@@ -0,0 +1,770 @@
+use std::ops::Range;
+
+use ribir_core::prelude::*;
+
+use super::Direction;
+
+/// The sizing function of a row or a column track in a [`Grid`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GridTrack {
+  /// The track has a fixed size in pixels.
+  Fixed(f32),
+  /// The track takes a fraction of the free space that is left after the
+  /// other tracks are sized, but not less than the minimum content of its
+  /// children. If the free space is unbounded, the track is sized by its
+  /// content like `Auto`.
+  Fr(f32),
+  /// The track is sized by the largest content of the children placed in it.
+  Auto,
+  /// The track is sized by the largest minimum content of the children placed
+  /// in it, e.g. the longest word of a text.
+  MinContent,
+  /// The track is sized by the largest maximum content of the children placed
+  /// in it, e.g. a text without wrapping.
+  MaxContent,
+  /// The track is sized by its content, but not smaller than the first value
+  /// and not larger than the second value.
+  MinMax(f32, f32),
+}
+
+/// A layout widget that places its children in a two-dimensional grid of
+/// rows and columns, the size of every track is defined by a [`GridTrack`].
+///
+/// The children are placed by [`GridItem`] if they have one, otherwise they
+/// are placed automatically in the next free cell along the `flow` direction.
+/// The grid creates implicit tracks that are sized by `auto_rows` or
+/// `auto_columns` if the children are placed out of the explicit tracks.
+///
+/// # Example
+///
+/// ```
+/// use ribir_core::prelude::*;
+/// use ribir_widgets::prelude::*;
+///
+/// let _dashboard = fn_widget! {
+///   @Grid {
+///     columns: vec![GridTrack::Fixed(120.), GridTrack::Fr(1.), GridTrack::Fr(2.)],
+///     row_gap: 8.,
+///     column_gap: 8.,
+///     @GridItem {
+///       column_span: 3usize,
+///       @Text { text: "Dashboard" }
+///     }
+///     @Text { text: "Sidebar" }
+///     @Text { text: "Chart" }
+///     @Text { text: "Table" }
+///   }
+/// };
+/// ```
+#[derive(MultiChild, Declare, Clone, PartialEq)]
+pub struct Grid {
+  /// The explicit row tracks.
+  #[declare(default)]
+  pub rows: Vec<GridTrack>,
+  /// The explicit column tracks.
+  #[declare(default)]
+  pub columns: Vec<GridTrack>,
+  /// The size of the implicit row tracks.
+  #[declare(default = GridTrack::Auto)]
+  pub auto_rows: GridTrack,
+  /// The size of the implicit column tracks.
+  #[declare(default = GridTrack::Auto)]
+  pub auto_columns: GridTrack,
+  /// The gap between rows.
+  #[declare(default)]
+  pub row_gap: f32,
+  /// The gap between columns.
+  #[declare(default)]
+  pub column_gap: f32,
+  /// The direction in which the children without a position are placed,
+  /// `Horizontal` fills the grid row by row and `Vertical` fills the grid
+  /// column by column.
+  #[declare(default)]
+  pub flow: Direction,
+  /// How the children are aligned within their cells in the x-axis.
+  #[declare(default = Align::Stretch)]
+  pub justify_items: Align,
+  /// How the children are aligned within their cells in the y-axis.
+  #[declare(default = Align::Stretch)]
+  pub align_items: Align,
+}
+
+/// A widget that places its child in a [`Grid`] at the given row and column,
+/// spanning the given number of tracks.
+///
+/// If the `row` or the `column` isn't specified, the grid finds the next free
+/// cell for the child.
+#[derive(Clone, PartialEq)]
+#[simple_declare]
+pub struct GridItem {
+  #[declare(default)]
+  pub row: Option<usize>,
+  #[declare(default)]
+  pub column: Option<usize>,
+  #[declare(default = 1usize)]
+  pub row_span: usize,
+  #[declare(default = 1usize)]
+  pub column_span: usize,
+}
+
+impl<'c> ComposeChild<'c> for GridItem {
+  type Child = Widget<'c>;
+  #[inline]
+  fn compose_child(this: impl StateWriter<Value = Self>, mut child: Self::Child) -> Widget<'c> {
+    let data: Box<dyn Query> = match this.try_into_value() {
+      Ok(this) => Box::new(Queryable(this)),
+      Err(this) => {
+        let modifies = this.raw_modifies();
+        child = child.on_build(|id| id.dirty_on(modifies));
+        Box::new(this)
+      }
+    };
+
+    child.attach_data(data)
+  }
+}
+
+/// The tracks a child occupies.
+struct Area {
+  rows: Range<usize>,
+  columns: Range<usize>,
+}
+
+/// The children of a grid with their areas, and the definitions of the tracks.
+struct GridCells {
+  children: Vec<(WidgetId, Area)>,
+  row_defs: Vec<GridTrack>,
+  col_defs: Vec<GridTrack>,
+}
+
+impl Render for Grid {
+  fn perform_layout(&self, clamp: BoxClamp, ctx: &mut LayoutCtx) -> Size {
+    // The tracks are sized by the intrinsic sizes of the children, they are
+    // cached in the layout pass, so every child is only laid out once in its
+    // cell even if the grids are nested.
+    let cells = self.cells(ctx);
+    let col_sizes = self.column_sizes(&cells, IntrinsicSize::MaxWidth, clamp.max.width, ctx);
+    let row_sizes =
+      self.row_sizes(&cells, &col_sizes, IntrinsicSize::MaxHeight, clamp.max.height, ctx);
+
+    for (c, area) in cells.children.iter() {
+      let cell = Rect::new(
+        Point::new(
+          track_offset(&col_sizes, area.columns.start, self.column_gap),
+          track_offset(&row_sizes, area.rows.start, self.row_gap),
+        ),
+        Size::new(
+          span_extent(&col_sizes, &area.columns, self.column_gap),
+          span_extent(&row_sizes, &area.rows, self.row_gap),
+        ),
+      );
+      let min_width = if self.justify_items == Align::Stretch { cell.width() } else { 0. };
+      let min_height = if self.align_items == Align::Stretch { cell.height() } else { 0. };
+      let clamp = BoxClamp { min: Size::new(min_width, min_height), max: cell.size };
+      let size = ctx.perform_child_layout(*c, clamp);
+      let x = align_offset(self.justify_items, cell.width(), size.width);
+      let y = align_offset(self.align_items, cell.height(), size.height);
+      ctx.update_position(*c, cell.origin + Vector::new(x, y));
+    }
+
+    let size = Size::new(
+      span_extent(&col_sizes, &(0..col_sizes.len()), self.column_gap),
+      span_extent(&row_sizes, &(0..row_sizes.len()), self.row_gap),
+    );
+    clamp.clamp(size)
+  }
+
+  #[inline]
+  fn only_sized_by_parent(&self) -> bool { false }
+
+  #[inline]
+  fn paint(&self, _: &mut PaintingCtx) {}
+
+  /// The tracks are sized by the intrinsic sizes of the children without the
+  /// limit of the axis, so the fractional tracks are sized by their content.
+  fn intrinsic_size(&self, kind: IntrinsicSize, extent: f32, ctx: &mut LayoutCtx) -> f32 {
+    let cells = self.cells(ctx);
+    if kind.is_width() {
+      let columns = self.column_sizes(&cells, kind, f32::INFINITY, ctx);
+      span_extent(&columns, &(0..columns.len()), self.column_gap)
+    } else {
+      let columns = self.column_sizes(&cells, IntrinsicSize::MaxWidth, extent, ctx);
+      let rows = self.row_sizes(&cells, &columns, kind, f32::INFINITY, ctx);
+      span_extent(&rows, &(0..rows.len()), self.row_gap)
+    }
+  }
+}
+
+impl Grid {
+  fn cells(&self, ctx: &LayoutCtx) -> GridCells {
+    let children: Vec<_> = ctx.children().collect();
+    let areas = self.place(children.iter().map(|c| {
+      ctx
+        .query_of_widget::<GridItem>(*c)
+        .map(|i| i.clone())
+    }));
+    let row_cnt = self.rows.len().max(
+      areas
+        .iter()
+        .map(|a| a.rows.end)
+        .max()
+        .unwrap_or(0),
+    );
+    let col_cnt = self.columns.len().max(
+      areas
+        .iter()
+        .map(|a| a.columns.end)
+        .max()
+        .unwrap_or(0),
+    );
+    GridCells {
+      children: children.into_iter().zip(areas).collect(),
+      row_defs: track_defs(&self.rows, self.auto_rows, row_cnt),
+      col_defs: track_defs(&self.columns, self.auto_columns, col_cnt),
+    }
+  }
+
+  /// Size the columns by the intrinsic widths of the `kind` of the children
+  /// within the `available` width.
+  fn column_sizes(
+    &self, cells: &GridCells, kind: IntrinsicSize, available: f32, ctx: &mut LayoutCtx,
+  ) -> Vec<f32> {
+    let items = cells.children.iter().map(|(c, area)| {
+      let defs = &cells.col_defs[area.columns.clone()];
+      let extents = content_extents(defs, kind, IntrinsicSize::MinWidth, |kind| {
+        ctx
+          .child_intrinsic_size(*c, kind, f32::INFINITY)
+          .min(available)
+      });
+      (area.columns.clone(), extents)
+    });
+    size_tracks(&cells.col_defs, items, self.column_gap, available)
+  }
+
+  /// Size the rows by the intrinsic heights of the `kind` of the children at
+  /// the widths of their cells within the `available` height.
+  fn row_sizes(
+    &self, cells: &GridCells, columns: &[f32], kind: IntrinsicSize, available: f32,
+    ctx: &mut LayoutCtx,
+  ) -> Vec<f32> {
+    let items = cells.children.iter().map(|(c, area)| {
+      let defs = &cells.row_defs[area.rows.clone()];
+      let width = span_extent(columns, &area.columns, self.column_gap);
+      let extents = content_extents(defs, kind, IntrinsicSize::MinHeight, |kind| {
+        ctx.child_intrinsic_size(*c, kind, width)
+      });
+      (area.rows.clone(), extents)
+    });
+    size_tracks(&cells.row_defs, items, self.row_gap, available)
+  }
+
+  /// Place the children in the grid. The children with both row and column
+  /// are placed first, then the children with only the track of the flow
+  /// direction, and the rest are auto-placed in order by a cursor that only
+  /// moves forward.
+  fn place(&self, items: impl Iterator<Item = Option<GridItem>>) -> Vec<Area> {
+    let by_row = self.flow.is_horizontal();
+    // Work in the (major, minor) coordinates, the minor axis is the one the
+    // cursor moves along.
+    let items: Vec<_> = items
+      .map(|item| {
+        let (row, column, row_span, column_span) = item.map_or((None, None, 1, 1), |i| {
+          (i.row, i.column, i.row_span.max(1), i.column_span.max(1))
+        });
+        if by_row {
+          (row, column, row_span, column_span)
+        } else {
+          (column, row, column_span, row_span)
+        }
+      })
+      .collect();
+    let explicit_minor = if by_row { self.columns.len() } else { self.rows.len() };
+    let minor_cnt = items
+      .iter()
+      .map(|(_, minor, _, minor_span)| minor.map_or(*minor_span, |m| m + minor_span))
+      .fold(explicit_minor.max(1), usize::max);
+
+    let mut occupied = Occupied { minor_cnt, cells: vec![] };
+    let mut placed: Vec<Option<(usize, usize)>> = vec![None; items.len()];
+
+    for (i, item) in items.iter().enumerate() {
+      if let (Some(major), Some(minor), major_span, minor_span) = *item {
+        occupied.mark(major, minor, major_span, minor_span);
+        placed[i] = Some((major, minor));
+      }
+    }
+
+    for (i, item) in items.iter().enumerate() {
+      if let (Some(major), None, major_span, minor_span) = *item {
+        let minor = (0..=minor_cnt - minor_span)
+          .find(|m| occupied.fits(major, *m, major_span, minor_span))
+          .unwrap_or(0);
+        occupied.mark(major, minor, major_span, minor_span);
+        placed[i] = Some((major, minor));
+      }
+    }
+
+    let mut cursor = (0, 0);
+    for (i, item) in items.iter().enumerate() {
+      let (None, minor, major_span, minor_span) = *item else { continue };
+      let pos = match minor {
+        Some(minor) => {
+          let mut major = if minor < cursor.1 { cursor.0 + 1 } else { cursor.0 };
+          while !occupied.fits(major, minor, major_span, minor_span) {
+            major += 1;
+          }
+          (major, minor)
+        }
+        None => {
+          let (mut major, mut minor) = cursor;
+          loop {
+            if minor + minor_span > minor_cnt {
+              major += 1;
+              minor = 0;
+            } else if occupied.fits(major, minor, major_span, minor_span) {
+              break;
+            } else {
+              minor += 1;
+            }
+          }
+          (major, minor)
+        }
+      };
+      occupied.mark(pos.0, pos.1, major_span, minor_span);
+      cursor = (pos.0, pos.1 + minor_span);
+      placed[i] = Some(pos);
+    }
+
+    items
+      .iter()
+      .zip(placed)
+      .map(|((.., major_span, minor_span), pos)| {
+        let (major, minor) = pos.unwrap();
+        let major = major..major + major_span;
+        let minor = minor..minor + minor_span;
+        if by_row {
+          Area { rows: major, columns: minor }
+        } else {
+          Area { rows: minor, columns: major }
+        }
+      })
+      .collect()
+  }
+}
+
+/// The occupied cells of the grid, indexed by the major and the minor track.
+struct Occupied {
+  minor_cnt: usize,
+  cells: Vec<Vec<bool>>,
+}
+
+impl Occupied {
+  fn fits(&self, major: usize, minor: usize, major_span: usize, minor_span: usize) -> bool {
+    (major..major + major_span).all(|r| {
+      self.cells.get(r).is_none_or(|line| {
+        line
+          .iter()
+          .skip(minor)
+          .take(minor_span)
+          .all(|c| !c)
+      })
+    })
+  }
+
+  fn mark(&mut self, major: usize, minor: usize, major_span: usize, minor_span: usize) {
+    let minor_cnt = self.minor_cnt.max(minor + minor_span);
+    if self.cells.len() < major + major_span {
+      self.cells.resize(major + major_span, vec![]);
+    }
+    for line in &mut self.cells[major..major + major_span] {
+      if line.len() < minor_cnt {
+        line.resize(minor_cnt, false);
+      }
+      line[minor..minor + minor_span].fill(true);
+    }
+  }
+}
+
+fn track_defs(explicit: &[GridTrack], auto: GridTrack, cnt: usize) -> Vec<GridTrack> {
+  (0..cnt)
+    .map(|i| explicit.get(i).copied().unwrap_or(auto))
+    .collect()
+}
+
+/// The minimum and the maximum content extents of a child spanning the
+/// tracks, the maximum is measured by `kind` and the minimum by `min_kind`.
+/// The fixed tracks are not sized by their content, and the minimum is only
+/// measured if a track needs it.
+fn content_extents(
+  defs: &[GridTrack], kind: IntrinsicSize, min_kind: IntrinsicSize,
+  mut measure: impl FnMut(IntrinsicSize) -> f32,
+) -> ContentExtents {
+  if fixed_extent(defs, 0.).is_some() {
+    return ContentExtents { min: 0., max: 0. };
+  }
+  let max = measure(kind);
+  let need_min = defs
+    .iter()
+    .any(|t| matches!(t, GridTrack::MinContent | GridTrack::Fr(_)));
+  let min = if kind.is_min() || !need_min { max } else { measure(min_kind).min(max) };
+  ContentExtents { min, max }
+}
+
+#[derive(Clone, Copy)]
+struct ContentExtents {
+  min: f32,
+  max: f32,
+}
+
+/// The extent of the tracks if all of them are fixed.
+fn fixed_extent(tracks: &[GridTrack], gap: f32) -> Option<f32> {
+  tracks
+    .iter()
+    .try_fold(0., |sum, t| match t {
+      GridTrack::Fixed(v) => Some(sum + v + gap),
+      _ => None,
+    })
+    .map(|sum| (sum - gap).max(0.))
+}
+
+fn track_offset(sizes: &[f32], idx: usize, gap: f32) -> f32 {
+  sizes[..idx].iter().map(|s| s + gap).sum()
+}
+
+fn span_extent(sizes: &[f32], span: &Range<usize>, gap: f32) -> f32 {
+  let sum: f32 = sizes[span.clone()].iter().sum();
+  sum + gap * span.len().saturating_sub(1) as f32
+}
+
+fn align_offset(align: Align, cell: f32, child: f32) -> f32 {
+  match align {
+    Align::Center => (cell - child) / 2.,
+    Align::End => cell - child,
+    Align::Start | Align::Stretch | Align::Baseline => 0.,
+  }
+}
+
+/// Resolve the size of the tracks by the extents of the children that span
+/// them and the available space.
+fn size_tracks(
+  defs: &[GridTrack], items: impl Iterator<Item = (Range<usize>, ContentExtents)>, gap: f32,
+  available: f32,
+) -> Vec<f32> {
+  let bounded = available.is_finite();
+  let by_content = |t: &GridTrack| match t {
+    GridTrack::Auto | GridTrack::MinContent | GridTrack::MaxContent | GridTrack::MinMax(..) => true,
+    GridTrack::Fr(_) => !bounded,
+    GridTrack::Fixed(_) => false,
+  };
+  let content = |t: &GridTrack, e: &ContentExtents| match t {
+    GridTrack::MinContent => e.min,
+    _ => e.max,
+  };
+  let limit = |t: &GridTrack| match t {
+    GridTrack::MinMax(_, max) => *max,
+    _ => f32::INFINITY,
+  };
+  let mut sizes: Vec<f32> = defs
+    .iter()
+    .map(|t| match t {
+      GridTrack::Fixed(v) => *v,
+      GridTrack::MinMax(min, _) => *min,
+      GridTrack::Auto | GridTrack::MinContent | GridTrack::MaxContent | GridTrack::Fr(_) => 0.,
+    })
+    .collect();
+  // The minimum content of the fractional tracks, they never shrink below it.
+  let mut fr_floors = vec![0.; defs.len()];
+
+  // The children spanning a single track first, then grow the content-sized
+  // tracks evenly for the children spanning multiple tracks.
+  let (single, multi): (Vec<_>, Vec<_>) = items.partition(|(span, _)| span.len() == 1);
+  for (span, extents) in single {
+    let t = &defs[span.start];
+    if by_content(t) {
+      sizes[span.start] = sizes[span.start].max(content(t, &extents).min(limit(t)));
+    } else if matches!(t, GridTrack::Fr(_)) {
+      fr_floors[span.start] = f32::max(fr_floors[span.start], extents.min);
+    }
+  }
+  for (span, extents) in multi {
+    let growable: Vec<_> = span
+      .clone()
+      .filter(|i| by_content(&defs[*i]))
+      .collect();
+    // Grow to the maximum content unless all the growable tracks only want
+    // the minimum content.
+    let extent = if growable
+      .iter()
+      .all(|i| defs[*i] == GridTrack::MinContent)
+    {
+      extents.min
+    } else {
+      extents.max
+    };
+    let need = extent - span_extent(&sizes, &span, gap);
+    if need > 0. && !growable.is_empty() {
+      let each = need / growable.len() as f32;
+      for i in growable {
+        sizes[i] = (sizes[i] + each).min(limit(&defs[i]).max(sizes[i]));
+      }
+    }
+  }
+
+  if bounded {
+    share_free_space(defs, &mut sizes, &fr_floors, gap, available);
+  }
+  sizes
+}
+
+/// Share the free space to the fractional tracks by their fractions. A track
+/// whose share is less than its floor keeps its floor, and the rest of the
+/// space is shared by the other fractional tracks.
+fn share_free_space(
+  defs: &[GridTrack], sizes: &mut [f32], floors: &[f32], gap: f32, available: f32,
+) {
+  let mut flexible: Vec<(usize, f32)> = defs
+    .iter()
+    .enumerate()
+    .filter_map(|(i, t)| if let GridTrack::Fr(f) = t { Some((i, *f)) } else { None })
+    .collect();
+  for (i, _) in &flexible {
+    sizes[*i] = floors[*i];
+  }
+  let others: f32 = {
+    let all = span_extent(sizes, &(0..sizes.len()), gap);
+    all
+      - flexible
+        .iter()
+        .map(|(i, _)| sizes[*i])
+        .sum::<f32>()
+  };
+
+  let mut frozen = 0.;
+  loop {
+    let fr_sum: f32 = flexible.iter().map(|(_, f)| f).sum();
+    if fr_sum <= 0. {
+      break;
+    }
+    let unit = (available - others - frozen).max(0.) / fr_sum;
+    let (keep, floored): (Vec<_>, Vec<_>) = flexible
+      .into_iter()
+      .partition(|(i, f)| floors[*i] <= unit * f);
+    if floored.is_empty() {
+      for (i, f) in keep {
+        sizes[i] = unit * f;
+      }
+      break;
+    }
+    frozen += floored
+      .iter()
+      .map(|(i, _)| floors[*i])
+      .sum::<f32>();
+    flexible = keep;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{cell::Cell, rc::Rc};
+
+  use ribir_core::{reset_test_env, test_helper::*};
+  use ribir_dev_helper::*;
+
+  use super::*;
+  use crate::prelude::*;
+
+  widget_layout_test!(
+    track_sizing,
+    WidgetTester::new(fn_widget! {
+      @Grid {
+        columns: vec![
+          GridTrack::Fixed(50.), GridTrack::Fr(1.), GridTrack::Fr(3.), GridTrack::Auto
+        ],
+        rows: vec![GridTrack::Auto, GridTrack::MinMax(30., 60.)],
+        column_gap: 10.,
+        row_gap: 5.,
+        @SizedBox { size: Size::new(20., 20.) }
+        @SizedBox { size: Size::new(20., 40.) }
+        @SizedBox { size: Size::new(20., 20.) }
+        @SizedBox { size: Size::new(40., 20.) }
+        @SizedBox { size: Size::new(20., 100.) }
+      }
+    })
+    .with_wnd_size(Size::new(400., 400.)),
+    LayoutCase::default().with_size(Size::new(400., 105.)),
+    LayoutCase::new(&[0, 0]).with_rect(ribir_geom::rect(0., 0., 50., 40.)),
+    LayoutCase::new(&[0, 1]).with_rect(ribir_geom::rect(60., 0., 70., 40.)),
+    LayoutCase::new(&[0, 2]).with_rect(ribir_geom::rect(140., 0., 210., 40.)),
+    LayoutCase::new(&[0, 3]).with_rect(ribir_geom::rect(360., 0., 40., 40.)),
+    LayoutCase::new(&[0, 4]).with_rect(ribir_geom::rect(0., 45., 50., 60.))
+  );
+
+  widget_layout_test!(
+    span_and_auto_place,
+    WidgetTester::new(fn_widget! {
+      @Grid {
+        columns: vec![GridTrack::Fixed(50.); 3],
+        auto_rows: GridTrack::Fixed(20.),
+        @GridItem {
+          column_span: 2usize,
+          @SizedBox { size: Size::zero() }
+        }
+        @GridItem {
+          row: 1usize,
+          column: 2usize,
+          row_span: 2usize,
+          @SizedBox { size: Size::zero() }
+        }
+        @SizedBox { size: Size::zero() }
+        @SizedBox { size: Size::zero() }
+        @SizedBox { size: Size::zero() }
+        @SizedBox { size: Size::zero() }
+      }
+    })
+    .with_wnd_size(Size::new(400., 400.)),
+    LayoutCase::default().with_size(Size::new(150., 60.)),
+    LayoutCase::new(&[0, 0]).with_rect(ribir_geom::rect(0., 0., 100., 20.)),
+    LayoutCase::new(&[0, 1]).with_rect(ribir_geom::rect(100., 20., 50., 40.)),
+    LayoutCase::new(&[0, 2]).with_rect(ribir_geom::rect(100., 0., 50., 20.)),
+    LayoutCase::new(&[0, 3]).with_rect(ribir_geom::rect(0., 20., 50., 20.)),
+    LayoutCase::new(&[0, 4]).with_rect(ribir_geom::rect(50., 20., 50., 20.)),
+    LayoutCase::new(&[0, 5]).with_rect(ribir_geom::rect(0., 40., 50., 20.))
+  );
+
+  widget_layout_test!(
+    column_flow_and_align,
+    WidgetTester::new(fn_widget! {
+      @Grid {
+        rows: vec![GridTrack::Fixed(40.); 2],
+        auto_columns: GridTrack::Fixed(40.),
+        flow: Direction::Vertical,
+        justify_items: Align::Center,
+        align_items: Align::End,
+        @SizedBox { size: Size::new(10., 10.) }
+        @SizedBox { size: Size::new(20., 20.) }
+        @SizedBox { size: Size::new(30., 30.) }
+      }
+    })
+    .with_wnd_size(Size::new(400., 400.)),
+    LayoutCase::default().with_size(Size::new(80., 80.)),
+    LayoutCase::new(&[0, 0]).with_rect(ribir_geom::rect(15., 30., 10., 10.)),
+    LayoutCase::new(&[0, 1]).with_rect(ribir_geom::rect(10., 60., 20., 20.)),
+    LayoutCase::new(&[0, 2]).with_rect(ribir_geom::rect(45., 10., 30., 30.))
+  );
+
+  #[test]
+  fn grid_item_follows_state() {
+    reset_test_env!();
+
+    let item = Stateful::new(GridItem { row: Some(0), column: None, row_span: 1, column_span: 1 });
+    let w_item = item.clone_writer();
+    let mut wnd = TestWindow::new(fn_widget! {
+      let item = item.clone_writer();
+      @Grid {
+        auto_rows: GridTrack::Fixed(20.),
+        auto_columns: GridTrack::Fixed(20.),
+        @ $item { @SizedBox { size: Size::zero() } }
+      }
+    });
+    wnd.draw_frame();
+    wnd.assert_root_size(Size::new(20., 20.));
+
+    w_item.write().row = Some(2);
+    wnd.draw_frame();
+    wnd.assert_root_size(Size::new(20., 60.));
+  }
+
+  /// A leaf that can be wrapped to a width between `min` and `max`.
+  struct Wrappable {
+    min: f32,
+    max: f32,
+  }
+
+  impl Render for Wrappable {
+    fn perform_layout(&self, clamp: BoxClamp, _: &mut LayoutCtx) -> Size {
+      clamp.clamp(Size::new(self.max, 10.))
+    }
+
+    fn intrinsic_size(&self, kind: IntrinsicSize, _: f32, _: &mut LayoutCtx) -> f32 {
+      match kind {
+        IntrinsicSize::MinWidth => self.min,
+        IntrinsicSize::MaxWidth => self.max,
+        _ => 10.,
+      }
+    }
+  }
+
+  widget_layout_test!(
+    content_tracks,
+    WidgetTester::new(fn_widget! {
+      @Grid {
+        columns: vec![GridTrack::MinContent, GridTrack::MaxContent, GridTrack::Fr(1.)],
+        @ { Wrappable { min: 20., max: 80. } }
+        @ { Wrappable { min: 20., max: 80. } }
+        @ { Wrappable { min: 20., max: 80. } }
+      }
+    })
+    .with_wnd_size(Size::new(400., 400.)),
+    LayoutCase::default().with_size(Size::new(400., 10.)),
+    LayoutCase::new(&[0, 0]).with_rect(ribir_geom::rect(0., 0., 20., 10.)),
+    LayoutCase::new(&[0, 1]).with_rect(ribir_geom::rect(20., 0., 80., 10.)),
+    LayoutCase::new(&[0, 2]).with_rect(ribir_geom::rect(100., 0., 300., 10.))
+  );
+
+  widget_layout_test!(
+    fr_floored_by_min_content,
+    WidgetTester::new(fn_widget! {
+      @Grid {
+        columns: vec![GridTrack::Fr(1.), GridTrack::Fr(1.)],
+        @ { Wrappable { min: 70., max: 200. } }
+        @ { Wrappable { min: 0., max: 200. } }
+      }
+    })
+    .with_wnd_size(Size::new(100., 100.)),
+    LayoutCase::default().with_size(Size::new(100., 10.)),
+    LayoutCase::new(&[0, 0]).with_rect(ribir_geom::rect(0., 0., 70., 10.)),
+    LayoutCase::new(&[0, 1]).with_rect(ribir_geom::rect(70., 0., 30., 10.))
+  );
+
+  /// A leaf that counts how many times it's laid out.
+  struct LayoutCounter(Rc<Cell<usize>>);
+
+  impl Render for LayoutCounter {
+    fn perform_layout(&self, clamp: BoxClamp, _: &mut LayoutCtx) -> Size {
+      self.0.set(self.0.get() + 1);
+      clamp.clamp(Size::new(30., 10.))
+    }
+
+    fn intrinsic_size(&self, kind: IntrinsicSize, _: f32, _: &mut LayoutCtx) -> f32 {
+      kind.axis_of(Size::new(30., 10.))
+    }
+  }
+
+  #[test]
+  fn nested_grids_layout_once() {
+    reset_test_env!();
+
+    let cnt = Rc::new(Cell::new(0));
+    let c_cnt = cnt.clone();
+    let mut wnd = TestWindow::new(fn_widget! {
+      @Grid {
+        columns: vec![GridTrack::Auto, GridTrack::Auto],
+        column_gap: 5.,
+        @Text { text: "Label" }
+        @Grid {
+          @Grid {
+            @ { LayoutCounter(c_cnt.clone()) }
+          }
+        }
+      }
+    });
+    wnd.draw_frame();
+    assert_eq!(cnt.get(), 1);
+    let label = wnd
+      .layout_info_by_path(&[0, 0])
+      .unwrap()
+      .size
+      .unwrap();
+    wnd.assert_root_size(Size::new(label.width + 35., label.height.max(10.)));
+  }
+}
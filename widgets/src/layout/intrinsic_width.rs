@@ -0,0 +1,246 @@
+use ribir_core::prelude::*;
+
+/// A widget that sizes its child to the maximum intrinsic width of the child.
+///
+/// It's useful when the available width is unbounded or too large, and the
+/// child should be as wide as its content wants. For example, the buttons in a
+/// stretched `Column` share the width of the widest one.
+///
+/// This is relatively expensive, because the intrinsic width of the subtree is
+/// queried before the layout.
+#[derive(SingleChild, Declare)]
+pub struct IntrinsicWidth {}
+
+impl Render for IntrinsicWidth {
+  fn perform_layout(&self, clamp: BoxClamp, ctx: &mut LayoutCtx) -> Size {
+    let height =
+      if clamp.min.height == clamp.max.height { clamp.max.height } else { f32::INFINITY };
+    let width = ctx
+      .single_child_intrinsic_size(IntrinsicSize::MaxWidth, height)
+      .clamp(clamp.min.width, clamp.max.width);
+    let clamp =
+      BoxClamp { min: Size::new(width, clamp.min.height), max: Size::new(width, clamp.max.height) };
+    ctx
+      .perform_single_child_layout(clamp)
+      .unwrap_or(Size::new(width, clamp.min.height))
+  }
+
+  fn intrinsic_size(&self, kind: IntrinsicSize, extent: f32, ctx: &mut LayoutCtx) -> f32 {
+    let kind = if kind == IntrinsicSize::MinWidth { IntrinsicSize::MaxWidth } else { kind };
+    ctx.single_child_intrinsic_size(kind, extent)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::cell::Cell;
+
+  use ribir_core::{reset_test_env, test_helper::*};
+  use ribir_dev_helper::*;
+
+  use super::*;
+  use crate::prelude::*;
+
+  widget_layout_test!(
+    share_widest_width,
+    WidgetTester::new(fn_widget! {
+      @IntrinsicWidth {
+        @Column {
+          align_items: Align::Stretch,
+          @SizedBox { size: Size::new(50., 10.) }
+          @SizedBox { size: Size::new(80., 10.) }
+        }
+      }
+    })
+    .with_wnd_size(Size::new(400., 400.)),
+    LayoutCase::default().with_size(Size::new(80., 20.)),
+    LayoutCase::new(&[0, 0, 0]).with_size(Size::new(80., 10.))
+  );
+
+  widget_layout_test!(
+    padding_and_gap,
+    WidgetTester::new(fn_widget! {
+      @IntrinsicWidth {
+        @Row {
+          item_gap: 5.,
+          padding: EdgeInsets::all(10.),
+          @Container { size: Size::new(30., 10.) }
+          @Container { size: Size::new(20., 10.) }
+        }
+      }
+    })
+    .with_wnd_size(Size::new(400., 400.)),
+    LayoutCase::default().with_size(Size::new(75., 30.))
+  );
+
+  widget_layout_test!(
+    expanded_flex_factors,
+    WidgetTester::new(fn_widget! {
+      @IntrinsicWidth {
+        @Row {
+          @Expanded { flex: 1., @SizedBox { size: Size::new(30., 10.) } }
+          @Expanded { flex: 2., @SizedBox { size: Size::new(40., 10.) } }
+          @SizedBox { size: Size::new(10., 10.) }
+        }
+      }
+    })
+    .with_wnd_size(Size::new(400., 400.)),
+    LayoutCase::default().with_size(Size::new(100., 10.)),
+    LayoutCase::new(&[0, 0, 1]).with_x(30.),
+    LayoutCase::new(&[0, 0, 2]).with_x(90.)
+  );
+
+  widget_layout_test!(
+    grid_tracks,
+    WidgetTester::new(fn_widget! {
+      @IntrinsicWidth {
+        @Grid {
+          columns: vec![GridTrack::Fixed(50.), GridTrack::Auto, GridTrack::Fr(1.)],
+          column_gap: 10.,
+          @SizedBox { size: Size::new(20., 10.) }
+          @SizedBox { size: Size::new(30., 10.) }
+          @SizedBox { size: Size::new(20., 10.) }
+        }
+      }
+    })
+    .with_wnd_size(Size::new(400., 400.)),
+    LayoutCase::default().with_size(Size::new(120., 10.))
+  );
+
+  widget_layout_test!(
+    sticky_section,
+    WidgetTester::new(fn_widget! {
+      @IntrinsicWidth {
+        @Sticky {
+          @StickyHeader { @SizedBox { size: Size::new(60., 10.) } }
+          @SizedBox { size: Size::new(40., 50.) }
+        }
+      }
+    })
+    .with_wnd_size(Size::new(400., 400.)),
+    LayoutCase::default().with_size(Size::new(60., 60.))
+  );
+
+  #[test]
+  fn lazy_list_built_items() {
+    reset_test_env!();
+
+    let mut wnd = TestWindow::new_with_size(
+      fn_widget! {
+        @IntrinsicWidth {
+          @LazyList {
+            count: 10usize,
+            estimated_extent: 20.,
+            item_builder: |idx: usize| {
+              @SizedBox { size: Size::new(40. + idx as f32, 20.) }.into_widget()
+            },
+          }
+        }
+      },
+      Size::new(400., 400.),
+    );
+    wnd.draw_frame();
+    // The items are built after the first layout.
+    wnd.draw_frame();
+    let size = wnd
+      .layout_info_by_path(&[0])
+      .unwrap()
+      .size
+      .unwrap();
+    assert_eq!(size.width, 49.);
+  }
+
+  #[derive(SingleChild, Declare)]
+  struct Probe {
+    #[declare(default)]
+    min: Cell<f32>,
+    #[declare(default)]
+    max: Cell<f32>,
+  }
+
+  impl Render for Probe {
+    fn perform_layout(&self, clamp: BoxClamp, ctx: &mut LayoutCtx) -> Size {
+      let min = ctx.single_child_intrinsic_size(IntrinsicSize::MinWidth, f32::INFINITY);
+      let max = ctx.single_child_intrinsic_size(IntrinsicSize::MaxWidth, f32::INFINITY);
+      self.min.set(min);
+      self.max.set(max);
+      ctx
+        .perform_single_child_layout(clamp.loose())
+        .unwrap_or_default()
+    }
+  }
+
+  /// Only query the intrinsic width of the child, without laying it out.
+  #[derive(SingleChild, Declare)]
+  struct QueryOnly {
+    #[declare(default)]
+    max: Cell<f32>,
+  }
+
+  impl Render for QueryOnly {
+    fn perform_layout(&self, clamp: BoxClamp, ctx: &mut LayoutCtx) -> Size {
+      let max = ctx.single_child_intrinsic_size(IntrinsicSize::MaxWidth, f32::INFINITY);
+      self.max.set(max);
+      clamp.min
+    }
+  }
+
+  #[test]
+  fn query_without_layout() {
+    reset_test_env!();
+
+    let query = Stateful::new(QueryOnly { max: Cell::new(0.) });
+    let q = query.clone_writer();
+    let mut wnd = TestWindow::new_with_size(
+      fn_widget! {
+        let q = q.clone_writer();
+        @ $q {
+          @MockMulti {
+            @SizedBox { size: Size::new(20., 10.) }
+            @SizedBox { size: Size::new(30., 10.) }
+          }
+        }
+      },
+      Size::new(400., 400.),
+    );
+    wnd.draw_frame();
+
+    // The default takes the largest child, and the children are not laid out.
+    assert_eq!(query.read().max.get(), 30.);
+    let info = wnd.layout_info_by_path(&[0, 0]);
+    assert!(info.and_then(|i| i.size).is_none());
+  }
+
+  #[test]
+  fn text_intrinsic_width() {
+    reset_test_env!();
+
+    let probe = Stateful::new(Probe { min: Cell::new(0.), max: Cell::new(0.) });
+    let (line_width, w_line_width) = split_value(0.);
+    let p = probe.clone_writer();
+    let mut wnd = TestWindow::new_with_size(
+      fn_widget! {
+        let p = p.clone_writer();
+        let mut wrap = Provider::of::<TextStyle>(BuildCtx::get()).unwrap().clone();
+        wrap.overflow = Overflow::AutoWrap;
+        @Column {
+          @ $p { @Text { text: "Hello world", text_style: wrap } }
+          @Text {
+            text: "Hello world",
+            on_performed_layout: move |e| *$w_line_width.write() = e.box_size().unwrap().width,
+          }
+        }
+      },
+      Size::new(400., 400.),
+    );
+    wnd.draw_frame();
+
+    let probe = probe.read();
+    let line_width = *line_width.read();
+    assert!(line_width > 0.);
+    assert_eq!(probe.max.get(), line_width);
+    assert!(probe.min.get() > 0.);
+    // The text wraps, so the minimum width is the width of the widest word.
+    assert!(probe.min.get() < line_width / 2.);
+  }
+}
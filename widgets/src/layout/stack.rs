@@ -56,6 +56,11 @@ impl Render for Stack {
   fn paint(&self, _: &mut PaintingCtx) {
     // nothing to paint.
   }
+
+  fn intrinsic_size(&self, kind: IntrinsicSize, extent: f32, ctx: &mut LayoutCtx) -> f32 {
+    let (ctx, children) = ctx.split_children();
+    children.fold(0., |size, c| size.max(ctx.child_intrinsic_size(c, kind, extent)))
+  }
 }
 
 #[cfg(test)]
@@ -0,0 +1,757 @@
+use std::{cell::RefCell, collections::HashMap, ops::Range, rc::Rc};
+
+use ribir_core::{pipe::ValueStream, prelude::*, ticker::FrameMsg};
+
+/// The builder of the items of the `LazyList` and the `LazyGrid`, it builds
+/// the item of an index.
+#[derive(Clone)]
+pub struct ItemBuilder(Rc<dyn Fn(usize) -> Widget<'static>>);
+
+impl ItemBuilder {
+  #[inline]
+  pub fn build(&self, index: usize) -> Widget<'static> { (self.0)(index) }
+}
+
+impl<F: Fn(usize) -> Widget<'static> + 'static> From<F> for ItemBuilder {
+  #[inline]
+  fn from(f: F) -> Self { Self(Rc::new(f)) }
+}
+
+/// A vertical list that only builds the items in the viewport of its scroll
+/// view and the `overscan` around it, the items are built by the
+/// `item_builder` with their indexes.
+///
+/// The items scrolled out are disposed, and their slots are recycled by the
+/// items scrolled in. An item disposed is still painted if it's kept alive by
+/// `keep_alive`, until it's not.
+///
+/// The items can have the different heights, an item is treated as
+/// `estimated_extent` high until it's built, and the scroll position is kept
+/// when the items above the viewport change their heights.
+///
+/// # Example
+///
+/// ```
+/// use ribir_core::prelude::*;
+/// use ribir_widgets::prelude::*;
+///
+/// let _logs = fn_widget! {
+///   @LazyList {
+///     count: 50_000usize,
+///     estimated_extent: 20.,
+///     item_builder: |idx: usize| @Text { text: format!("log {idx}") }.into_widget(),
+///   }
+/// };
+/// ```
+#[derive(Declare)]
+pub struct LazyList {
+  pub count: usize,
+  pub item_builder: ItemBuilder,
+  /// The height of the items not built yet.
+  #[declare(default = 48.)]
+  pub estimated_extent: f32,
+  /// The space between two items.
+  #[declare(default)]
+  pub spacing: f32,
+  /// The pixels out of the viewport that the items in it are built too, on
+  /// both the top and the bottom.
+  #[declare(default = 100.)]
+  pub overscan: f32,
+  #[declare(skip)]
+  built: BuiltItems,
+}
+
+/// A vertical grid that only builds the items in the viewport of its scroll
+/// view and the `overscan` around it, like the `LazyList`, the items are laid
+/// out in `columns` columns with the same width.
+///
+/// The height of a row is the height of its highest item, and a row is treated
+/// as `estimated_extent` high until it's built.
+///
+/// # Example
+///
+/// ```
+/// use ribir_core::prelude::*;
+/// use ribir_widgets::prelude::*;
+///
+/// let _photos = fn_widget! {
+///   @LazyGrid {
+///     count: 1000usize,
+///     columns: 4usize,
+///     estimated_extent: 80.,
+///     item_builder: |idx: usize| @Text { text: format!("photo {idx}") }.into_widget(),
+///   }
+/// };
+/// ```
+#[derive(Declare)]
+pub struct LazyGrid {
+  pub count: usize,
+  pub item_builder: ItemBuilder,
+  #[declare(default = 1usize)]
+  pub columns: usize,
+  /// The height of the rows not built yet.
+  #[declare(default = 48.)]
+  pub estimated_extent: f32,
+  #[declare(default)]
+  pub row_spacing: f32,
+  #[declare(default)]
+  pub column_spacing: f32,
+  /// The pixels out of the viewport that the items in it are built too, on
+  /// both the top and the bottom.
+  #[declare(default = 100.)]
+  pub overscan: f32,
+  #[declare(skip)]
+  built: BuiltItems,
+}
+
+impl LazyList {
+  /// The range of the items built.
+  pub fn built_range(&self) -> Range<usize> { self.built.range.clone() }
+}
+
+impl LazyGrid {
+  /// The range of the items built.
+  pub fn built_range(&self) -> Range<usize> { self.built.range.clone() }
+}
+
+/// The items built by a lazy widget. Every slot keeps its item until the item
+/// is out of the range, then the slot is recycled by an item scrolled in, so
+/// the items still in the range are not rebuilt.
+#[derive(Default)]
+struct BuiltItems {
+  range: Range<usize>,
+  /// The item of every slot, the slots grow by chunks, see [`slot_chunk`].
+  slots: Vec<Option<usize>>,
+}
+
+impl BuiltItems {
+  fn capacity(&self) -> usize { self.slots.len() }
+
+  fn slot_item(&self, slot: usize) -> Option<usize> { self.slots.get(slot).copied().flatten() }
+
+  fn set_range(&mut self, range: Range<usize>) {
+    for slot in self.slots.iter_mut() {
+      if slot.is_some_and(|item| !range.contains(&item)) {
+        *slot = None;
+      }
+    }
+    let mut free = 0;
+    for item in range.clone() {
+      if self.range.contains(&item) {
+        continue;
+      }
+      while free < self.slots.len() && self.slots[free].is_some() {
+        free += 1;
+      }
+      if free == self.slots.len() {
+        // Grow to the end of the next chunk.
+        self.slots.resize(free * 2 + 1, None);
+      }
+      self.slots[free] = Some(item);
+    }
+    self.range = range;
+  }
+}
+
+/// The slots of the chunk `k`, the chunks double their sizes, so the slots
+/// grow by adding a chunk without touching the slots of the others.
+fn slot_chunk(k: u32) -> Range<usize> {
+  let end = |k: u32| {
+    usize::MAX
+      .checked_shr(usize::BITS - k)
+      .unwrap_or(0)
+  };
+  end(k)..end(k + 1)
+}
+
+/// The count of the chunks of the slots.
+const SLOT_CHUNKS: u32 = 32;
+
+/// The grid arguments of a lazy widget, a list is a grid with one column.
+#[derive(Clone, Copy, PartialEq)]
+struct GridArgs {
+  count: usize,
+  columns: usize,
+  estimated_extent: f32,
+  row_spacing: f32,
+  column_spacing: f32,
+}
+
+impl GridArgs {
+  fn row_count(&self) -> usize { self.count.div_ceil(self.columns) }
+}
+
+trait LazyItems: 'static {
+  fn grid_args(&self) -> GridArgs;
+
+  fn item_builder(&self) -> &ItemBuilder;
+
+  fn overscan(&self) -> f32;
+
+  fn built(&self) -> &BuiltItems;
+
+  fn built_mut(&mut self) -> &mut BuiltItems;
+}
+
+impl LazyItems for LazyList {
+  fn grid_args(&self) -> GridArgs {
+    GridArgs {
+      count: self.count,
+      columns: 1,
+      estimated_extent: self.estimated_extent,
+      row_spacing: self.spacing,
+      column_spacing: 0.,
+    }
+  }
+
+  fn item_builder(&self) -> &ItemBuilder { &self.item_builder }
+
+  fn overscan(&self) -> f32 { self.overscan }
+
+  fn built(&self) -> &BuiltItems { &self.built }
+
+  fn built_mut(&mut self) -> &mut BuiltItems { &mut self.built }
+}
+
+impl LazyItems for LazyGrid {
+  fn grid_args(&self) -> GridArgs {
+    GridArgs {
+      count: self.count,
+      columns: self.columns.max(1),
+      estimated_extent: self.estimated_extent,
+      row_spacing: self.row_spacing,
+      column_spacing: self.column_spacing,
+    }
+  }
+
+  fn item_builder(&self) -> &ItemBuilder { &self.item_builder }
+
+  fn overscan(&self) -> f32 { self.overscan }
+
+  fn built(&self) -> &BuiltItems { &self.built }
+
+  fn built_mut(&mut self) -> &mut BuiltItems { &mut self.built }
+}
+
+impl Compose for LazyList {
+  fn compose(this: impl StateWriter<Value = Self>) -> Widget<'static> { lazy_widget(this) }
+}
+
+impl Compose for LazyGrid {
+  fn compose(this: impl StateWriter<Value = Self>) -> Widget<'static> { lazy_widget(this) }
+}
+
+fn lazy_widget<T: LazyItems>(this: impl StateWriter<Value = T>) -> Widget<'static> {
+  fn_widget! {
+    let rows = Rc::new(RefCell::new(RowExtents::default()));
+    let items: Rc<RefCell<HashMap<WidgetId, usize>>> = <_>::default();
+    let mut view = @LazyView {
+      range: pipe!($this.built().range.clone()),
+      args: pipe!($this.grid_args()),
+      rows: rows.clone(),
+      items: items.clone(),
+    };
+    let scrollable = view.get_scrollable_widget().clone_writer();
+
+    // The range is updated when the scroll view moves before the layout, so the
+    // items scrolled in are built in the same frame, and it's updated again
+    // after the layout if the rows or the viewport change.
+    let c_this = this.clone_writer();
+    let c_rows = rows.clone();
+    let c_scrollable = scrollable.clone_watcher();
+    let u1 = watch!(ScrollableWidget::get_scroll_pos(&$scrollable))
+      .subscribe(move |_| update_range(&c_this, &c_scrollable, &c_rows));
+
+    let c_this = this.clone_writer();
+    let c_scrollable = scrollable.clone_writer();
+    let wnd = BuildCtx::get().window();
+    let tick_of_layout_ready = wnd
+      .frame_tick_stream()
+      .filter(|msg| matches!(msg, FrameMsg::LayoutReady(_)));
+    let u2 = tick_of_layout_ready.subscribe(move |_| {
+      let shift = std::mem::take(&mut rows.borrow_mut().shift);
+      if shift != 0. {
+        let mut scrollable = c_scrollable.write();
+        let pos = scrollable.get_scroll_pos();
+        scrollable.jump_to(Point::new(pos.x, pos.y + shift));
+      }
+      update_range(&c_this, &scrollable, &rows);
+    });
+
+    let view = @ $view {
+      scrollable: Scrollable::Y,
+      on_disposed: move |_| {
+        u1.unsubscribe();
+        u2.unsubscribe();
+      },
+    };
+    // Every chunk of the slots is built once when the capacity reaches it.
+    let mut view = view.map(|v| MultiPair { parent: v.into_widget(), children: vec![] });
+    for k in 0..SLOT_CHUNKS {
+      let chunk = slot_chunk(k);
+      let items = items.clone();
+      let this = this.clone_writer();
+      let built = $this.built().capacity() > chunk.start;
+      let slots = pipe!($this.built().capacity() > chunk.start)
+        .value_chain(move |s| changed_from(built, s))
+        .map(move |built| {
+          let slots = if built { chunk.clone() } else { 0..0 };
+          let items = items.clone();
+          let this = this.clone_writer();
+          slots.map(move |slot| slot_widget(&this, slot, items.clone()))
+        });
+      view = view.map(|v| v.with_child(slots));
+    }
+    view
+  }
+  .into_widget()
+}
+
+/// The widget of a slot, it's rebuilt when the slot changes its item.
+fn slot_widget<T: LazyItems>(
+  this: &impl StateWriter<Value = T>, slot: usize, items: Rc<RefCell<HashMap<WidgetId, usize>>>,
+) -> Widget<'static> {
+  let item = this.read().built().slot_item(slot);
+  let c_this = this.clone_writer();
+  pipe!($this.built().slot_item(slot))
+    .value_chain(move |s| changed_from(item, s))
+    .map(move |item| match item {
+      Some(idx) => {
+        let items = items.clone();
+        c_this
+          .read()
+          .item_builder()
+          .build(idx)
+          .on_build(move |id| {
+            items.borrow_mut().insert(id, idx);
+          })
+      }
+      None => Void.into_widget(),
+    })
+    .into_widget()
+}
+
+/// Update the built range to the rows in the viewport and the overscan.
+fn update_range<T: LazyItems>(
+  this: &impl StateWriter<Value = T>, scrollable: &impl StateWatcher<Value = ScrollableWidget>,
+  rows: &RefCell<RowExtents>,
+) {
+  let (pos, page) = {
+    let scrollable = scrollable.read();
+    (scrollable.get_scroll_pos(), scrollable.scroll_view_size())
+  };
+  let range = {
+    let this = this.read();
+    let mut rows = rows.borrow_mut();
+    if rows.offsets.len() != rows.measured.len() + 1 {
+      // Not laid out yet.
+      return;
+    }
+    rows.anchor = rows.row_at(pos.y);
+    let overscan = this.overscan();
+    let top = rows.row_at(pos.y - overscan);
+    let bottom = rows.row_before(pos.y + page.height + overscan);
+    let GridArgs { count, columns, .. } = this.grid_args();
+    if page.height <= 0. || count == 0 {
+      0..0
+    } else {
+      top * columns..((bottom + 1) * columns).min(count)
+    }
+  };
+  if this.read().built().range != range {
+    this.write().built_mut().set_range(range);
+  }
+}
+
+/// Only emit the values changed, the first value is compared with `init`.
+fn changed_from<V: PartialEq + Clone + 'static>(init: V, s: ValueStream<V>) -> ValueStream<V> {
+  let last = RefCell::new(init);
+  s.filter(move |(_, v)| {
+    let changed = *v != *last.borrow();
+    if changed {
+      *last.borrow_mut() = v.clone();
+    }
+    changed
+  })
+  .box_it()
+}
+
+/// The heights of the rows of a lazy widget, the rows not built yet are
+/// estimated.
+#[derive(Default)]
+struct RowExtents {
+  /// The heights of the rows measured, `None` if it's not built yet.
+  measured: Vec<Option<f32>>,
+  /// The offset of every row and the end of the last row.
+  offsets: Vec<f32>,
+  estimated: f32,
+  spacing: f32,
+  dirty: bool,
+  /// The row at the top of the viewport.
+  anchor: usize,
+  /// The height changed above the `anchor` row, the scroll position should
+  /// shift it to keep the rows in the viewport stable.
+  shift: f32,
+}
+
+impl RowExtents {
+  fn update_args(&mut self, args: &GridArgs) {
+    let rows = args.row_count();
+    if self.measured.len() != rows
+      || self.estimated != args.estimated_extent
+      || self.spacing != args.row_spacing
+    {
+      self.measured.resize(rows, None);
+      self.estimated = args.estimated_extent;
+      self.spacing = args.row_spacing;
+      self.dirty = true;
+    }
+  }
+
+  fn extent(&self, row: usize) -> f32 { self.measured[row].unwrap_or(self.estimated) }
+
+  fn measure(&mut self, row: usize, height: f32) {
+    let old = self.extent(row);
+    if self.measured[row] != Some(height) {
+      self.measured[row] = Some(height);
+      self.dirty = true;
+      if row < self.anchor {
+        self.shift += height - old;
+      }
+    }
+  }
+
+  fn update_offsets(&mut self) {
+    if !self.dirty && self.offsets.len() == self.measured.len() + 1 {
+      return;
+    }
+    self.dirty = false;
+    self.offsets.clear();
+    let mut offset = 0.;
+    for row in 0..self.measured.len() {
+      self.offsets.push(offset);
+      offset += self.extent(row) + self.spacing;
+    }
+    self.offsets.push(offset);
+  }
+
+  fn offset(&self, row: usize) -> f32 { self.offsets[row] }
+
+  fn total(&self) -> f32 {
+    let last = self.offsets.last().copied().unwrap_or_default();
+    if self.measured.is_empty() { 0. } else { last - self.spacing }
+  }
+
+  /// The row at the offset `y`.
+  fn row_at(&self, y: f32) -> usize { self.row_by(|o| o <= y) }
+
+  /// The last row starts before the offset `y`.
+  fn row_before(&self, y: f32) -> usize { self.row_by(|o| o < y) }
+
+  fn row_by(&self, before: impl Fn(f32) -> bool) -> usize {
+    let rows = self.measured.len();
+    let starts = &self.offsets[..rows.min(self.offsets.len())];
+    starts
+      .partition_point(|o| before(*o))
+      .saturating_sub(1)
+      .min(rows.saturating_sub(1))
+  }
+}
+
+/// The render of a lazy widget, its children are the slots of the items and
+/// it places the items in the grid, the rows not built are sized by
+/// estimation.
+#[derive(MultiChild, Declare)]
+struct LazyView {
+  /// The range of the items built.
+  range: Range<usize>,
+  args: GridArgs,
+  rows: Rc<RefCell<RowExtents>>,
+  /// The item indexes of the widgets built, a slot without an item is not in
+  /// it.
+  items: Rc<RefCell<HashMap<WidgetId, usize>>>,
+}
+
+impl Render for LazyView {
+  fn perform_layout(&self, clamp: BoxClamp, ctx: &mut LayoutCtx) -> Size {
+    let GridArgs { columns, column_spacing, .. } = self.args;
+    let mut rows = self.rows.borrow_mut();
+    rows.update_args(&self.args);
+
+    let width = clamp.max.width;
+    let column_width = if width.is_finite() {
+      ((width - column_spacing * (columns - 1) as f32) / columns as f32).max(0.)
+    } else {
+      f32::INFINITY
+    };
+    let item_clamp = BoxClamp {
+      min: Size::new(if width.is_finite() { column_width } else { 0. }, 0.),
+      max: Size::new(column_width, f32::INFINITY),
+    };
+
+    let (ctx, children) = ctx.split_children();
+    let mut built = self.items.borrow_mut();
+    let children: Vec<_> = children.collect();
+    built.retain(|id, _| children.contains(id));
+    let mut items = vec![];
+    let mut row_heights: Vec<(usize, f32)> = vec![];
+    for c in children {
+      let item = built.get(&c).copied();
+      let Some(idx) = item.filter(|idx| self.range.contains(idx)) else {
+        ctx.perform_child_layout(c, BoxClamp::default());
+        continue;
+      };
+      let size = ctx.perform_child_layout(c, item_clamp);
+      let row = idx / columns;
+      match row_heights.iter_mut().find(|(r, _)| *r == row) {
+        Some((_, h)) => *h = h.max(size.height),
+        None => row_heights.push((row, size.height)),
+      }
+      items.push((c, idx, size));
+    }
+    for (row, height) in row_heights {
+      if row < rows.measured.len() {
+        rows.measure(row, height);
+      }
+    }
+    rows.update_offsets();
+
+    let column_width = if column_width.is_finite() {
+      column_width
+    } else {
+      items
+        .iter()
+        .fold(0., |w: f32, (_, _, size)| w.max(size.width))
+    };
+    for (c, idx, _) in items {
+      let (row, column) = (idx / columns, idx % columns);
+      if row < rows.measured.len() {
+        let x = column as f32 * (column_width + column_spacing);
+        ctx.update_position(c, Point::new(x, rows.offset(row)));
+      }
+    }
+
+    let content_width = column_width * columns as f32 + column_spacing * (columns - 1) as f32;
+    let width = if width.is_finite() { width } else { content_width };
+    clamp.clamp(Size::new(width, rows.total()))
+  }
+
+  fn paint(&self, _: &mut PaintingCtx) {}
+
+  /// The width fits the widest item built in every column, and the height is
+  /// the total of the rows, the rows not built yet are estimated.
+  fn intrinsic_size(&self, kind: IntrinsicSize, _: f32, ctx: &mut LayoutCtx) -> f32 {
+    let GridArgs { columns, column_spacing, .. } = self.args;
+    if kind.is_width() {
+      let built = self.items.borrow();
+      let (ctx, children) = ctx.split_children();
+      let widest = children
+        .filter(|c| {
+          built
+            .get(c)
+            .is_some_and(|idx| self.range.contains(idx))
+        })
+        .fold(0f32, |w, c| w.max(ctx.child_intrinsic_size(c, kind, f32::INFINITY)));
+      widest * columns as f32 + column_spacing * (columns - 1) as f32
+    } else {
+      let mut rows = self.rows.borrow_mut();
+      rows.update_args(&self.args);
+      rows.update_offsets();
+      rows.total()
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::cell::Cell;
+
+  use ribir_core::{reset_test_env, test_helper::*};
+  use winit::event::{DeviceId, MouseScrollDelta, TouchPhase, WindowEvent};
+
+  use super::*;
+  use crate::prelude::*;
+
+  fn wheel(wnd: &mut TestWindow, delta_y: f32) {
+    let device_id = unsafe { DeviceId::dummy() };
+    #[allow(deprecated)]
+    wnd.processes_native_event(WindowEvent::MouseWheel {
+      device_id,
+      delta: MouseScrollDelta::PixelDelta((0., delta_y).into()),
+      phase: TouchPhase::Started,
+    });
+    wnd.draw_frame();
+  }
+
+  #[test]
+  fn build_visible_items_only() {
+    reset_test_env!();
+    let built = Rc::new(Cell::new(0));
+    let c_built = built.clone();
+    let list = Stateful::new(LazyList {
+      count: 50_000,
+      item_builder: ItemBuilder::from(move |_| {
+        c_built.set(c_built.get() + 1);
+        SizedBox { size: Size::new(10., 20.) }.into_widget()
+      }),
+      estimated_extent: 20.,
+      spacing: 0.,
+      overscan: 20.,
+      built: BuiltItems::default(),
+    });
+    let c_list = list.clone_writer();
+    let mut wnd =
+      TestWindow::new_with_size(move || c_list.clone_writer().into_widget(), Size::new(100., 100.));
+    wnd.draw_frame();
+    // The viewport is known after the first layout.
+    wnd.draw_frame();
+    // The viewport and one item of the overscan.
+    assert_eq!(list.read().built_range(), 0..6);
+    assert_eq!(built.get(), 6);
+
+    // Only the items scrolled in are built in the same frame, even if more
+    // slots are needed.
+    wheel(&mut wnd, -100.);
+    assert_eq!(list.read().built_range(), 4..11);
+    assert_eq!(built.get(), 11);
+    assert!(wnd.layout_info_by_path(&[0, 0, 0, 10]).is_some());
+
+    wheel(&mut wnd, -100.);
+    assert_eq!(list.read().built_range(), 9..16);
+    assert_eq!(built.get(), 16);
+    let size = wnd
+      .layout_info_by_path(&[0, 0, 0])
+      .unwrap()
+      .size
+      .unwrap();
+    assert_eq!(size, Size::new(100., 1_000_000.));
+  }
+
+  /// A leaf that counts how many times it's painted.
+  struct PaintCounter(Rc<Cell<usize>>);
+
+  impl Render for PaintCounter {
+    fn perform_layout(&self, clamp: BoxClamp, _: &mut LayoutCtx) -> Size {
+      clamp.clamp(Size::new(10., 20.))
+    }
+
+    fn paint(&self, _: &mut PaintingCtx) { self.0.set(self.0.get() + 1); }
+  }
+
+  #[test]
+  fn keep_alive_item_scrolled_out() {
+    reset_test_env!();
+    let painted = Rc::new(Cell::new(0));
+    let alive = Stateful::new(true);
+    let c_painted = painted.clone();
+    let c_alive = alive.clone_writer();
+    let list = Stateful::new(LazyList {
+      count: 100,
+      item_builder: ItemBuilder::from(move |idx| {
+        if idx == 0 {
+          let alive = c_alive.clone_writer();
+          let painted = c_painted.clone();
+          fn_widget! {
+            FatObj::new(PaintCounter(painted.clone()))
+              .keep_alive(pipe!(*$alive))
+              .into_widget()
+          }
+          .into_widget()
+        } else {
+          SizedBox { size: Size::new(10., 20.) }.into_widget()
+        }
+      }),
+      estimated_extent: 20.,
+      spacing: 0.,
+      overscan: 0.,
+      built: BuiltItems::default(),
+    });
+    let c_list = list.clone_writer();
+    let mut wnd =
+      TestWindow::new_with_size(move || c_list.clone_writer().into_widget(), Size::new(100., 100.));
+    wnd.draw_frame();
+    wnd.draw_frame();
+    assert!(painted.get() > 0);
+
+    // The item scrolled out is still painted while it's kept alive.
+    wheel(&mut wnd, -200.);
+    assert!(!list.read().built_range().contains(&0));
+    let cnt = painted.get();
+    wheel(&mut wnd, -20.);
+    assert!(painted.get() > cnt);
+
+    // It's dropped once it's not kept alive.
+    *alive.write() = false;
+    wnd.draw_frame();
+    let cnt = painted.get();
+    wheel(&mut wnd, -20.);
+    assert_eq!(painted.get(), cnt);
+  }
+
+  #[test]
+  fn keep_scroll_position() {
+    reset_test_env!();
+    let heights = Stateful::new(vec![20.; 100]);
+    let c_heights = heights.clone_writer();
+    let list = Stateful::new(LazyList {
+      count: 100,
+      item_builder: ItemBuilder::from(move |idx| {
+        let heights = c_heights.clone_writer();
+        fn_widget! {
+          @SizedBox { size: pipe!(Size::new(10., $heights[idx])) }
+        }
+        .into_widget()
+      }),
+      estimated_extent: 10.,
+      spacing: 0.,
+      overscan: 40.,
+      built: BuiltItems::default(),
+    });
+    let c_list = list.clone_writer();
+    let mut wnd =
+      TestWindow::new_with_size(move || c_list.clone_writer().into_widget(), Size::new(100., 100.));
+    wnd.draw_frame();
+
+    wheel(&mut wnd, -200.);
+    let content_y = |wnd: &TestWindow| wnd.layout_info_by_path(&[0, 0, 0]).unwrap().pos.y;
+    let y = content_y(&wnd);
+
+    // An item above the viewport grows, the content moves up to keep the items
+    // in the viewport.
+    let first = list.read().built_range().start;
+    heights.write()[first] = 50.;
+    wnd.draw_frame();
+    wnd.draw_frame();
+    assert_eq!(content_y(&wnd), y - 30.);
+  }
+
+  #[test]
+  fn grid_rows() {
+    reset_test_env!();
+    let grid = Stateful::new(LazyGrid {
+      count: 100,
+      item_builder: ItemBuilder::from(|idx| {
+        SizedBox { size: Size::new(10., if idx % 3 == 0 { 30. } else { 20. }) }.into_widget()
+      }),
+      columns: 3,
+      estimated_extent: 30.,
+      row_spacing: 10.,
+      column_spacing: 5.,
+      overscan: 0.,
+      built: BuiltItems::default(),
+    });
+    let c_grid = grid.clone_writer();
+    let mut wnd =
+      TestWindow::new_with_size(move || c_grid.clone_writer().into_widget(), Size::new(100., 100.));
+    wnd.draw_frame();
+    wnd.draw_frame();
+    // The rows in the 100 pixels high viewport.
+    assert_eq!(grid.read().built_range(), 0..9);
+    let pos = |path: &[usize]| wnd.layout_info_by_path(path).unwrap().pos;
+    assert_eq!(pos(&[0, 0, 0, 1]), Point::new(35., 0.));
+    assert_eq!(pos(&[0, 0, 0, 4]), Point::new(35., 40.));
+    // The rows not built are estimated.
+    let size = wnd.layout_info_by_path(&[0, 0, 0]).unwrap().size;
+    assert_eq!(size, Some(Size::new(100., 1350.)));
+  }
+}
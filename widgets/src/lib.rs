@@ -3,25 +3,30 @@ pub mod buttons;
 pub mod checkbox;
 pub mod common_widget;
 pub mod divider;
+pub mod form;
 pub mod grid_view;
 pub mod icon;
 pub mod input;
 pub mod label;
 pub mod layout;
+pub mod lazy_list;
 pub mod link;
 pub mod lists;
 pub mod path;
 pub mod progress;
 pub mod radio;
+pub mod rich_text;
 pub mod scrollbar;
+pub mod sticky;
 pub mod tabs;
 pub mod text;
 pub mod text_field;
 pub mod transform_box;
 pub mod prelude {
   pub use super::{
-    avatar::*, buttons::*, checkbox::*, common_widget::*, divider::*, grid_view::*, icon::*,
-    input::*, label::*, layout::*, link::*, lists::*, path::*, progress::*, radio::*, scrollbar::*,
-    tabs::*, text::*, text_field::*, transform_box::*,
+    avatar::*, buttons::*, checkbox::*, common_widget::*, divider::*, form::*, grid_view::*,
+    icon::*, input::*, label::*, layout::*, lazy_list::*, link::*, lists::*, path::*, progress::*,
+    radio::*, rich_text::*, scrollbar::*, sticky::*, tabs::*, text::*, text_field::*,
+    transform_box::*,
   };
 }
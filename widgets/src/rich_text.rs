@@ -0,0 +1,280 @@
+use std::cell::{Ref, RefCell};
+
+use ribir_core::prelude::*;
+
+use crate::{
+  prelude::*,
+  text::{intrinsic_bounds, text_baselines},
+};
+
+/// A paragraph of text with widgets embedded in it, the widgets are laid out
+/// like glyphs, they wrap with the text and their first baseline sits on the
+/// baseline of the line, or their bottom if they have no baseline.
+///
+/// The `PLACEHOLDER_CHAR`s in the text spans are removed, they mark the
+/// widgets.
+///
+/// # Example
+///
+/// ```
+/// # use ribir_core::prelude::*;
+/// # use ribir_widgets::prelude::*;
+///
+/// fn_widget! {
+///   @RichText {
+///     @ { Label::new("Press ") }
+///     @InlineWidget {
+///       @SizedBox { size: Size::new(12., 12.), background: Color::RED }
+///     }
+///     @ { Label::new(" to start.") }
+///   }
+/// };
+/// ```
+#[derive(Declare)]
+pub struct RichText {
+  #[declare(default = TextAlign::Start)]
+  pub text_align: TextAlign,
+}
+
+/// The child of `RichText`, a text span or an inline widget.
+#[derive(Template)]
+pub enum RichTextItem<'w> {
+  Text(Label),
+  Inline(InlineWidget<'w>),
+}
+
+/// A widget embedded in the `RichText`.
+#[derive(Template)]
+pub struct InlineWidget<'w> {
+  child: Widget<'w>,
+}
+
+impl<'w> IntoChild<RichTextItem<'w>, 0> for Label {
+  #[inline]
+  fn into_child(self) -> RichTextItem<'w> { RichTextItem::Text(self) }
+}
+
+impl<'w> IntoChild<RichTextItem<'w>, 0> for InlineWidgetBuilder<'w> {
+  #[inline]
+  fn into_child(self) -> RichTextItem<'w> { RichTextItem::Inline(self.build_tml()) }
+}
+
+/// The render widget of `RichText`, it lays out its children at the
+/// placeholders of the text.
+#[derive(Declare, MultiChild)]
+pub struct RichTextLayout {
+  /// The text that marks the children with `PLACEHOLDER_CHAR`.
+  pub text: CowArc<str>,
+  #[declare(default = TextAlign::Start)]
+  pub text_align: TextAlign,
+  #[declare(default)]
+  glyphs: RefCell<Option<VisualGlyphs>>,
+}
+
+impl<'c> ComposeChild<'c> for RichText {
+  type Child = Vec<RichTextItem<'c>>;
+
+  fn compose_child(this: impl StateWriter<Value = Self>, child: Self::Child) -> Widget<'c> {
+    let mut spans = vec![];
+    let mut span_streams = vec![];
+    let mut inlines = vec![];
+    for item in child {
+      match item {
+        RichTextItem::Text(label) => {
+          let (v, o) = label.0.unzip();
+          if let Some(o) = o {
+            span_streams.push((spans.len(), o));
+          }
+          spans.push(strip_placeholders(v));
+        }
+        RichTextItem::Inline(w) => {
+          spans.push(CowArc::from(PLACEHOLDER_CHAR.to_string()));
+          inlines.push(w.child);
+        }
+      }
+    }
+    let spans = Stateful::new(spans);
+
+    fn_widget! {
+      let mut layout = @RichTextLayout {
+        text: pipe!(join_spans(&$spans)),
+        text_align: pipe!($this.text_align),
+      };
+      for (idx, o) in span_streams {
+        let c_spans = spans.clone_writer();
+        let u = o.subscribe(move |(_, v)| c_spans.write()[idx] = strip_placeholders(v));
+        layout = layout.on_disposed(move |_| u.unsubscribe());
+      }
+      @ $layout { @ { inlines } }
+    }
+    .into_widget()
+  }
+}
+
+fn strip_placeholders(span: CowArc<str>) -> CowArc<str> {
+  if span.contains(PLACEHOLDER_CHAR) { span.replace(PLACEHOLDER_CHAR, "").into() } else { span }
+}
+
+fn join_spans(spans: &[CowArc<str>]) -> CowArc<str> {
+  spans
+    .iter()
+    .map(|s| &**s)
+    .collect::<String>()
+    .into()
+}
+
+impl Render for RichTextLayout {
+  fn perform_layout(&self, clamp: BoxClamp, ctx: &mut LayoutCtx) -> Size {
+    let style = Provider::of::<TextStyle>(&ctx).unwrap().clone();
+    let (ctx, children) = ctx.split_children();
+    let children: Vec<_> = children.collect();
+    let child_clamp = clamp.loose();
+    let placeholders: Vec<_> = children
+      .iter()
+      .map(|c| {
+        let size = ctx.perform_child_layout(*c, child_clamp);
+        let baseline_offset = ctx
+          .child_baselines(*c)
+          .map_or(size.height, |b| b.first);
+        InlinePlaceholder { size, baseline_offset }
+      })
+      .collect();
+
+    let info = AppCtx::typography_store()
+      .borrow_mut()
+      .typography_with_placeholders(
+        self.text.substr(..),
+        &style,
+        clamp.max,
+        self.text_align,
+        style.writing_mode.line_dir(),
+        &placeholders,
+      );
+    for (c, rect) in children.iter().zip(info.placeholder_rects()) {
+      ctx.update_position(*c, rect.origin);
+    }
+
+    let size = info.visual_rect().size;
+    *self.glyphs.borrow_mut() = Some(info);
+
+    clamp.clamp(size)
+  }
+
+  #[inline]
+  fn only_sized_by_parent(&self) -> bool { false }
+
+  fn baselines(&self, _: &LayoutCtx) -> Option<Baselines> {
+    self.glyphs().and_then(|g| text_baselines(&g))
+  }
+
+  /// Measured like a `Text`, the widgets take their maximum intrinsic sizes
+  /// and sit on the baseline by their bottom.
+  fn intrinsic_size(&self, kind: IntrinsicSize, extent: f32, ctx: &mut LayoutCtx) -> f32 {
+    let style = Provider::of::<TextStyle>(&ctx).unwrap().clone();
+    let line_dir = style.writing_mode.line_dir();
+    let (ctx, children) = ctx.split_children();
+    let placeholders: Vec<_> = children
+      .map(|c| {
+        let width = ctx.child_intrinsic_size(c, IntrinsicSize::MaxWidth, f32::INFINITY);
+        let height = ctx.child_intrinsic_size(c, IntrinsicSize::MaxHeight, width);
+        InlinePlaceholder::new(Size::new(width, height))
+      })
+      .collect();
+    let info = AppCtx::typography_store()
+      .borrow_mut()
+      .typography_with_placeholders(
+        self.text.substr(..),
+        &style,
+        intrinsic_bounds(kind, extent, line_dir),
+        self.text_align,
+        line_dir,
+        &placeholders,
+      );
+    kind.axis_of(info.visual_rect().size)
+  }
+
+  fn paint(&self, ctx: &mut PaintingCtx) {
+    let box_rect = Rect::from_size(ctx.box_size().unwrap());
+    if ctx
+      .painter()
+      .intersection_paint_bounds(&box_rect)
+      .is_none()
+    {
+      return;
+    };
+
+    let visual_glyphs = self.glyphs().unwrap();
+    let font_db = AppCtx::font_db().clone();
+    ctx
+      .painter()
+      .draw_glyphs_in_rect(&visual_glyphs, box_rect, &font_db.borrow());
+  }
+}
+
+impl RichTextLayout {
+  pub fn glyphs(&self) -> Option<Ref<'_, VisualGlyphs>> {
+    Ref::filter_map(self.glyphs.borrow(), |v| v.as_ref()).ok()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ribir_core::{reset_test_env, test_helper::*};
+  use ribir_dev_helper::*;
+
+  use super::*;
+
+  widget_layout_test!(
+    inline_widgets,
+    WidgetTester::new(fn_widget! {
+      @RichText {
+        text_style: TextStyle { font_size: 16., line_height: 16., ..<_>::default() },
+        @InlineWidget { @SizedBox { size: Size::new(20., 30.) } }
+        @ { Label::new("ab") }
+        @InlineWidget { @SizedBox { size: Size::new(10., 10.) } }
+      }
+    }),
+    // The line grows to contain the higher widget, and the widgets sit on the
+    // baseline.
+    LayoutCase::default().with_height(30.),
+    LayoutCase::new(&[0, 0]).with_rect(ribir_geom::rect(0., 0., 20., 30.)),
+    LayoutCase::new(&[0, 1]).with_y(14. + 16. - 10.)
+  );
+
+  widget_layout_test!(
+    inline_text_on_baseline,
+    WidgetTester::new(fn_widget! {
+      @RichText {
+        text_style: TextStyle { font_size: 16., line_height: 16., ..<_>::default() },
+        @ { Label::new("ab") }
+        @InlineWidget { @Text { text: "cd" } }
+      }
+    }),
+    // The inline text sits on the baseline by its baseline, so it's in the
+    // same line box as the text.
+    LayoutCase::default().with_height(16.),
+    LayoutCase::new(&[0, 0]).with_y(0.)
+  );
+
+  #[test]
+  fn strip_placeholder_chars() {
+    reset_test_env!();
+    let mut wnd = TestWindow::new(fn_widget! {
+      let inline = || @InlineWidget { @SizedBox { size: Size::new(10., 10.) } };
+      @Column {
+        @RichText {
+          @ { Label::new("\u{FFFC}ab") }
+          @ { inline() }
+        }
+        @RichText {
+          @ { Label::new("ab") }
+          @ { inline() }
+        }
+      }
+    });
+    wnd.draw_frame();
+    let x = |path| wnd.layout_info_by_path(path).unwrap().pos.x;
+    assert!(x(&[0, 0, 0]) > 0.);
+    assert_eq!(x(&[0, 0, 0]), x(&[0, 1, 0]));
+  }
+}
@@ -1,6 +1,11 @@
+use std::{
+  cell::{Cell, RefCell},
+  rc::Rc,
+};
+
 use ribir_core::prelude::*;
 
-use crate::layout::Stack;
+use crate::layout::{Column, Expanded, Row, Stack};
 
 /// This widget wraps its child in a `ScrollableWidget` and adds two scrollbar
 /// for interactivity and visual scroll position indication.
@@ -13,7 +18,15 @@ use crate::layout::Stack;
 /// `Scrollbar::inner_scrollable_widget` to access the `ScrollableWidget` and
 /// switch between which scrollbar to enable.
 ///
-/// `Scrollbar` offers five class names for users or themes to customize the
+/// The thumb can be dragged to scroll, it captures the pointer so the drag
+/// keeps working even if the pointer leaves the thumb. When the scroll area
+/// itself is focused, the `PageUp`, `PageDown`, `Home`, `End` and arrow keys
+/// scroll the content. Use `overlay`, `auto_hide` and `arrows` to decide
+/// whether the scrollbars take layout space, fade out when idle and have arrow
+/// buttons. These three options are read once when the scrollbar is composed,
+/// modifying them later has no effect.
+///
+/// `Scrollbar` offers nine class names for users or themes to customize the
 /// scrollbar appearance. The `Scrollbar` positions the scrollbar on the
 /// scrollable child widget, and adjusting the scrollbar's placement (left,
 /// right, top, or bottom) depends on the class names' implementation.
@@ -28,6 +41,22 @@ use crate::layout::Stack;
 pub struct Scrollbar {
   #[declare(default=Stateful::new(ScrollableWidget::default()))]
   scroll: Stateful<ScrollableWidget>,
+  /// Whether the scrollbars float over the content without affecting the
+  /// layout. If it's `false`, the scrollbars are placed beside the content and
+  /// take their own layout space. Only read when the scrollbar is composed.
+  #[declare(default = true)]
+  pub overlay: bool,
+  /// Fade out the scrollbars after the scroll position has been idle for this
+  /// duration, and fade them in again when scrolling or hovering. `None` means
+  /// the scrollbars are always visible. Only read when the scrollbar is
+  /// composed.
+  #[declare(default)]
+  pub auto_hide: Option<Duration>,
+  /// Whether to display arrow buttons at both ends of the tracks, every tap on
+  /// them scrolls the content by a line. Only read when the scrollbar is
+  /// composed.
+  #[declare(default)]
+  pub arrows: bool,
 }
 
 class_names! {
@@ -40,14 +69,26 @@ class_names! {
   #[doc = "Class name for the track of the vertical scrollbar"]
   V_SCROLL_TRACK,
   #[doc = "Class name for the scrollable widget of the scrollbar"]
-  SCROLL_CLIENT_AREA
+  SCROLL_CLIENT_AREA,
+  #[doc = "Class name for the arrow button at the start of the horizontal scrollbar"]
+  SCROLL_ARROW_LEFT,
+  #[doc = "Class name for the arrow button at the end of the horizontal scrollbar"]
+  SCROLL_ARROW_RIGHT,
+  #[doc = "Class name for the arrow button at the start of the vertical scrollbar"]
+  SCROLL_ARROW_UP,
+  #[doc = "Class name for the arrow button at the end of the vertical scrollbar"]
+  SCROLL_ARROW_DOWN
 }
 
+/// The distance scrolled by an arrow button or an arrow key.
+const LINE_STEP: f32 = 40.;
+const FADE_DURATION: Duration = Duration::from_millis(200);
+
 impl Scrollbar {
   pub fn new(scrollable: Scrollable) -> Self {
     let mut inner = ScrollableWidget::default();
     inner.scrollable = scrollable;
-    Self { scroll: Stateful::new(inner) }
+    Self { scroll: Stateful::new(inner), overlay: true, auto_hide: None, arrows: false }
   }
 
   /// Return the `ScrollableWidget` of the scrollbar. You can utilize it to
@@ -58,19 +99,21 @@ impl Scrollbar {
 impl<'c> ComposeChild<'c> for Scrollbar {
   type Child = Widget<'c>;
   fn compose_child(this: impl StateWriter<Value = Self>, child: Self::Child) -> Widget<'c> {
-    let scroll = this.read().scroll.clone_writer();
+    let (scroll, overlay, auto_hide, arrows) = {
+      let this = this.read();
+      (this.scroll.clone_writer(), this.overlay, this.auto_hide, this.arrows)
+    };
     let w = fn_widget! {
       let h_scrollbar = distinct_pipe!($scroll.is_x_scrollable())
         .map(move |need_bar| need_bar.then(||{
-          let mut h_track = @Stack {
-            class: H_SCROLL_TRACK,
-            clamp: BoxClamp::EXPAND_X,
-            on_wheel: move |e| $scroll.write().scroll(-e.delta_x, -e.delta_y),
-          };
-
-          @ $h_track {
+          let mut h_rail = @Stack {};
+          let drag_from: Rc<Cell<Option<(f32, f32)>>> = <_>::default();
+          let drag_move = drag_from.clone();
+          let drag_end = drag_from.clone();
+          let drag_cancel = drag_from.clone();
+          let rail = @ $h_rail {
             on_tap: move |e| if e.is_primary {
-              let rate = e.position().x / $h_track.layout_width();
+              let rate = e.position().x / $h_rail.layout_width();
               let mut scroll = Provider::write_of::<ScrollableWidget>(e).unwrap();
               let x = rate * scroll.max_scrollable().x;
               let scroll_pos = Point::new(x, scroll.get_scroll_pos().y);
@@ -79,28 +122,58 @@ impl<'c> ComposeChild<'c> for Scrollbar {
             @Container {
               class: H_SCROLL_THUMB,
               size: distinct_pipe!{
-                let width = h_thumb_rate(&$scroll) * $h_track.layout_width();
+                let width = h_thumb_rate(&$scroll) * $h_rail.layout_width();
                 Size::new(width, 0.)
               },
               anchor: distinct_pipe!{
-                let pos = $scroll.get_x_scroll_rate() * $h_track.layout_width();
+                let pos = $scroll.get_x_scroll_rate() * $h_rail.layout_width();
                 Anchor::left(pos)
               },
+              on_pointer_down: move |e| if e.is_primary {
+                e.window().set_pointer_capture(e.current_target());
+                let mut scroll = $scroll.write();
+                scroll.stop_motion();
+                drag_from.set(Some((e.global_pos().x, scroll.get_scroll_pos().x)));
+              },
+              on_pointer_move: move |e| if let Some((from, x)) = drag_move.get() {
+                let rate = (e.global_pos().x - from) / $h_rail.layout_width();
+                let mut scroll = $scroll.write();
+                let x = x + rate * scroll.scroll_content_size().width;
+                let scroll_pos = Point::new(x, scroll.get_scroll_pos().y);
+                scroll.jump_to(scroll_pos);
+              },
+              on_pointer_up: move |_| drag_end.set(None),
+              on_pointer_cancel: move |_| drag_cancel.set(None),
+              on_tap: move |e| e.stop_propagation(),
             }
+          };
+
+          let track = @Stack {
+            class: H_SCROLL_TRACK,
+            clamp: BoxClamp::EXPAND_X,
+            on_wheel: move |e| $scroll.write().scroll(-e.delta_x, -e.delta_y),
+            @Row {
+              @ { arrows.then(|| arrow_button(SCROLL_ARROW_LEFT, &scroll, -LINE_STEP, 0.)) }
+              @Expanded { @ { rail } }
+              @ { arrows.then(|| arrow_button(SCROLL_ARROW_RIGHT, &scroll, LINE_STEP, 0.)) }
+            }
+          };
+          match auto_hide {
+            Some(delay) => fade_when_idle(track.into_widget(), scroll.clone_writer(), delay),
+            None => track.into_widget(),
           }
         }));
 
       let v_scrollbar = distinct_pipe!($scroll.is_y_scrollable())
         .map(move |need_bar| need_bar.then(|| {
-          let mut v_track = @Stack {
-            class: V_SCROLL_TRACK,
-            clamp: BoxClamp::EXPAND_Y,
-            on_wheel: move |e| $scroll.write().scroll(-e.delta_x, -e.delta_y),
-          };
-
-          @ $v_track {
+          let mut v_rail = @Stack {};
+          let drag_from: Rc<Cell<Option<(f32, f32)>>> = <_>::default();
+          let drag_move = drag_from.clone();
+          let drag_end = drag_from.clone();
+          let drag_cancel = drag_from.clone();
+          let rail = @ $v_rail {
             on_tap: move |e| if e.is_primary {
-              let rate = e.position().y / $v_track.layout_height();
+              let rate = e.position().y / $v_rail.layout_height();
               let mut scroll = Provider::write_of::<ScrollableWidget>(e).unwrap();
               let y = rate * scroll.max_scrollable().y;
               let scroll_pos = Point::new(scroll.get_scroll_pos().x, y);
@@ -109,25 +182,83 @@ impl<'c> ComposeChild<'c> for Scrollbar {
             @Container {
               class: V_SCROLL_THUMB,
               size: distinct_pipe!{
-                let height = v_thumb_rate(&$scroll) * $v_track.layout_height();
+                let height = v_thumb_rate(&$scroll) * $v_rail.layout_height();
                 Size::new(0., height)
               },
               anchor: distinct_pipe!{
-                let pos = $scroll.get_y_scroll_rate() * $v_track.layout_height();
+                let pos = $scroll.get_y_scroll_rate() * $v_rail.layout_height();
                 Anchor::top(pos)
               },
+              on_pointer_down: move |e| if e.is_primary {
+                e.window().set_pointer_capture(e.current_target());
+                let mut scroll = $scroll.write();
+                scroll.stop_motion();
+                drag_from.set(Some((e.global_pos().y, scroll.get_scroll_pos().y)));
+              },
+              on_pointer_move: move |e| if let Some((from, y)) = drag_move.get() {
+                let rate = (e.global_pos().y - from) / $v_rail.layout_height();
+                let mut scroll = $scroll.write();
+                let y = y + rate * scroll.scroll_content_size().height;
+                let scroll_pos = Point::new(scroll.get_scroll_pos().x, y);
+                scroll.jump_to(scroll_pos);
+              },
+              on_pointer_up: move |_| drag_end.set(None),
+              on_pointer_cancel: move |_| drag_cancel.set(None),
+              on_tap: move |e| e.stop_propagation(),
+            }
+          };
+
+          let track = @Stack {
+            class: V_SCROLL_TRACK,
+            clamp: BoxClamp::EXPAND_Y,
+            on_wheel: move |e| $scroll.write().scroll(-e.delta_x, -e.delta_y),
+            @Column {
+              @ { arrows.then(|| arrow_button(SCROLL_ARROW_UP, &scroll, 0., -LINE_STEP)) }
+              @Expanded { @ { rail } }
+              @ { arrows.then(|| arrow_button(SCROLL_ARROW_DOWN, &scroll, 0., LINE_STEP)) }
             }
+          };
+          match auto_hide {
+            Some(delay) => fade_when_idle(track.into_widget(), scroll.clone_writer(), delay),
+            None => track.into_widget(),
           }
         }));
 
-      let scroll = FatObj::new(scroll);
-      @Stack {
-        @ $scroll {
-          class: SCROLL_CLIENT_AREA,
-          @{ child }
-        }
-        @ { h_scrollbar }
-        @ { v_scrollbar }
+      let client = FatObj::new(scroll.clone_writer());
+      let client = @ $client {
+        class: SCROLL_CLIENT_AREA,
+        // Like the scroll containers of the browsers, clicking the content makes
+        // the scroll area the target of the paging keys. It's only focusable by
+        // the pointer, so the tab order is kept, and a focusable descendant is
+        // still focused by clicking it, because the nearest focusable ancestor
+        // of the pressed widget takes the focus.
+        tab_index: -1_i16,
+        on_key_down: move |e| if e.target() == e.current_target() {
+          let pos = key_scroll_pos(&$scroll, e.key());
+          if let Some(pos) = pos {
+            let mut scroll = $scroll.write();
+            scroll.jump_to(pos);
+          }
+        },
+        @{ child }
+      };
+
+      if overlay {
+        @Stack {
+          @ { client }
+          @ { h_scrollbar }
+          @ { v_scrollbar }
+        }.into_widget()
+      } else {
+        @Column {
+          @Expanded {
+            @Row {
+              @Expanded { @ { client } }
+              @ { v_scrollbar }
+            }
+          }
+          @ { h_scrollbar }
+        }.into_widget()
       }
     };
 
@@ -140,6 +271,82 @@ impl<'c> ComposeChild<'c> for Scrollbar {
   }
 }
 
+fn arrow_button(
+  cls: ClassName, scroll: &Stateful<ScrollableWidget>, x: f32, y: f32,
+) -> Widget<'static> {
+  let scroll = scroll.clone_writer();
+  fn_widget! {
+    @Container {
+      class: cls,
+      size: Size::zero(),
+      on_tap: move |e| {
+        $scroll.write().scroll(x, y);
+        e.stop_propagation();
+      },
+    }
+  }
+  .into_widget()
+}
+
+/// Show the scrollbar when scrolling or hovering it, and fade it out after the
+/// scroll position has been idle for `delay`.
+fn fade_when_idle(
+  bar: Widget<'static>, scroll: Stateful<ScrollableWidget>, delay: Duration,
+) -> Widget<'static> {
+  fn_widget! {
+    let mut bar = FatObj::new(bar).opacity(0.);
+    let hide = Rc::new(RefCell::new(None));
+    let hide2 = hide.clone();
+    let u = watch!((ScrollableWidget::get_scroll_pos(&$scroll), $bar.is_hover()))
+      .distinct_until_changed()
+      .subscribe(move |_| {
+        $bar.write().opacity = 1.;
+        let h = observable::timer((), delay, AppCtx::scheduler())
+          .filter(move |_| !$bar.is_hover())
+          .subscribe(move |_| $bar.write().opacity = 0.);
+        if let Some(h) = hide2.borrow_mut().replace(h) {
+          h.unsubscribe();
+        }
+      });
+    part_writer!(&mut bar.opacity)
+      .transition(EasingTransition { easing: easing::LINEAR, duration: FADE_DURATION });
+
+    @ $bar {
+      on_disposed: move |_| {
+        u.unsubscribe();
+        if let Some(h) = hide.borrow_mut().take() {
+          h.unsubscribe();
+        }
+      }
+    }
+  }
+  .into_widget()
+}
+
+/// The scroll position after pressing the `key`, `None` if the key isn't used
+/// to scroll.
+fn key_scroll_pos(s: &ScrollableWidget, key: &VirtualKey) -> Option<Point> {
+  let pos = s.get_scroll_pos();
+  let view = s.scroll_view_size();
+  let (page, home, end) = if s.is_y_scrollable() {
+    (Vector::new(0., view.height), Point::new(pos.x, 0.), Point::new(pos.x, s.max_scrollable().y))
+  } else {
+    (Vector::new(view.width, 0.), Point::new(0., pos.y), Point::new(s.max_scrollable().x, pos.y))
+  };
+  let to = match key {
+    VirtualKey::Named(NamedKey::PageDown) => pos + page,
+    VirtualKey::Named(NamedKey::PageUp) => pos - page,
+    VirtualKey::Named(NamedKey::Home) => home,
+    VirtualKey::Named(NamedKey::End) => end,
+    VirtualKey::Named(NamedKey::ArrowDown) => pos + Vector::new(0., LINE_STEP),
+    VirtualKey::Named(NamedKey::ArrowUp) => pos - Vector::new(0., LINE_STEP),
+    VirtualKey::Named(NamedKey::ArrowRight) => pos + Vector::new(LINE_STEP, 0.),
+    VirtualKey::Named(NamedKey::ArrowLeft) => pos - Vector::new(LINE_STEP, 0.),
+    _ => return None,
+  };
+  Some(to)
+}
+
 fn h_thumb_rate(s: &ScrollableWidget) -> f32 {
   s.scroll_view_size().width / s.scroll_content_size().width
 }
@@ -149,8 +356,12 @@ fn v_thumb_rate(s: &ScrollableWidget) -> f32 {
 
 #[cfg(test)]
 mod test {
-  use ribir_core::test_helper::*;
+  use ribir_core::{reset_test_env, test_helper::*};
   use ribir_dev_helper::*;
+  use winit::{
+    event::{DeviceId, ElementState, MouseButton, WindowEvent},
+    keyboard::NativeKeyCode,
+  };
 
   use super::*;
 
@@ -175,7 +386,7 @@ mod test {
       let inner2 = inner.clone_writer();
 
       WidgetTester::new(fn_widget! {
-        let scrollbar = Scrollbar { scroll : inner.clone_writer() };
+        let scrollbar = Scrollbar { scroll: inner.clone_writer(), ..Scrollbar::new(Scrollable::Both) };
         @ $scrollbar {
           @Container { size: Size::new(500., 500.) }
         }
@@ -190,4 +401,151 @@ mod test {
     },
     LayoutCase::default().with_size(Size::new(100., 100.))
   );
+
+  fn bar_classes() -> Classes {
+    let mut classes = Classes::default();
+    classes.insert(V_SCROLL_TRACK, style_class! { h_align: HAlign::Right });
+    classes.insert(V_SCROLL_THUMB, style_class! { clamp: BoxClamp::fixed_width(10.) });
+    const ARROW: ClassImpl = style_class! { clamp: BoxClamp::fixed_size(Size::splat(10.)) };
+    classes.insert(SCROLL_ARROW_UP, ARROW);
+    classes.insert(SCROLL_ARROW_DOWN, ARROW);
+    classes
+  }
+
+  fn v_scrollbar(init: impl FnOnce(&mut Scrollbar)) -> (TestWindow, Stateful<ScrollableWidget>) {
+    let mut bar = Scrollbar::new(Scrollable::Y);
+    init(&mut bar);
+    let scroll = bar.inner_scrollable_widget().clone_writer();
+    let mut bar = Some(bar);
+    let mut wnd = TestWindow::new_with_size(
+      fn_widget! {
+        let mut bar = bar.take();
+        bar_classes().with_child(fn_widget! {
+          let bar = bar.take().unwrap();
+          @ $bar { @Container { size: Size::new(100., 400.) } }
+        })
+      },
+      Size::new(100., 100.),
+    );
+    wnd.draw_frame();
+    wnd.draw_frame();
+    (wnd, scroll)
+  }
+
+  fn move_to(wnd: &mut TestWindow, pos: Point) {
+    #[allow(deprecated)]
+    wnd.processes_native_event(WindowEvent::CursorMoved {
+      device_id: unsafe { DeviceId::dummy() },
+      position: (pos.x, pos.y).into(),
+    });
+    wnd.draw_frame();
+  }
+
+  fn mouse(wnd: &mut TestWindow, state: ElementState) {
+    wnd.process_mouse_input(unsafe { DeviceId::dummy() }, state, MouseButton::Left);
+    wnd.draw_frame();
+  }
+
+  fn press_key(wnd: &mut TestWindow, key: NamedKey) {
+    wnd.processes_keyboard_event(
+      PhysicalKey::Unidentified(NativeKeyCode::Unidentified),
+      VirtualKey::Named(key),
+      false,
+      KeyLocation::Standard,
+      ElementState::Pressed,
+    );
+    wnd.draw_frame();
+  }
+
+  #[test]
+  fn drag_thumb() {
+    reset_test_env!();
+
+    let (mut wnd, scroll) = v_scrollbar(|_| {});
+    move_to(&mut wnd, Point::new(95., 5.));
+    mouse(&mut wnd, ElementState::Pressed);
+    // The thumb is a quarter of the track, so it moves a quarter of the content.
+    move_to(&mut wnd, Point::new(95., 30.));
+    assert_eq!(scroll.read().get_scroll_pos(), Point::new(0., 100.));
+
+    // Keep dragging even if the pointer leaves the thumb.
+    move_to(&mut wnd, Point::new(20., 55.));
+    assert_eq!(scroll.read().get_scroll_pos(), Point::new(0., 200.));
+    mouse(&mut wnd, ElementState::Released);
+    assert_eq!(scroll.read().get_scroll_pos(), Point::new(0., 200.));
+
+    move_to(&mut wnd, Point::new(95., 90.));
+    assert_eq!(scroll.read().get_scroll_pos(), Point::new(0., 200.));
+  }
+
+  #[test]
+  fn arrows_and_keyboard_paging() {
+    reset_test_env!();
+
+    let (mut wnd, scroll) = v_scrollbar(|bar| bar.arrows = true);
+    move_to(&mut wnd, Point::new(95., 95.));
+    mouse(&mut wnd, ElementState::Pressed);
+    mouse(&mut wnd, ElementState::Released);
+    assert_eq!(scroll.read().get_scroll_pos(), Point::new(0., LINE_STEP));
+
+    // Focus the scroll area.
+    move_to(&mut wnd, Point::new(20., 20.));
+    mouse(&mut wnd, ElementState::Pressed);
+    mouse(&mut wnd, ElementState::Released);
+    press_key(&mut wnd, NamedKey::PageDown);
+    assert_eq!(scroll.read().get_scroll_pos(), Point::new(0., LINE_STEP + 100.));
+    press_key(&mut wnd, NamedKey::End);
+    assert_eq!(scroll.read().get_scroll_pos(), Point::new(0., 300.));
+    press_key(&mut wnd, NamedKey::ArrowUp);
+    assert_eq!(scroll.read().get_scroll_pos(), Point::new(0., 300. - LINE_STEP));
+    press_key(&mut wnd, NamedKey::Home);
+    assert_eq!(scroll.read().get_scroll_pos(), Point::zero());
+  }
+
+  #[test]
+  fn overlay_or_take_space() {
+    reset_test_env!();
+
+    let (_wnd, scroll) = v_scrollbar(|_| {});
+    assert_eq!(scroll.read().scroll_view_size(), Size::new(100., 100.));
+
+    let (_wnd, scroll) = v_scrollbar(|bar| bar.overlay = false);
+    assert_eq!(scroll.read().scroll_view_size(), Size::new(90., 100.));
+  }
+
+  #[test]
+  fn focusable_content_keeps_focus() {
+    reset_test_env!();
+
+    let input = Stateful::new(None);
+    let w_input = input.clone_writer();
+    let mut wnd = TestWindow::new_with_size(
+      fn_widget! {
+        @Scrollbar {
+          @Column {
+            @Container {
+              size: Size::new(100., 50.),
+              tab_index: 0_i16,
+              on_mounted: move |e| *$w_input.write() = Some(e.current_target()),
+            }
+            @Container { size: Size::new(100., 400.) }
+          }
+        }
+      },
+      Size::new(100., 100.),
+    );
+    wnd.draw_frame();
+
+    move_to(&mut wnd, Point::new(20., 20.));
+    mouse(&mut wnd, ElementState::Pressed);
+    mouse(&mut wnd, ElementState::Released);
+    assert_eq!(wnd.focusing(), *input.read());
+
+    // Clicking the content that isn't focusable focuses the scroll area.
+    move_to(&mut wnd, Point::new(20., 80.));
+    mouse(&mut wnd, ElementState::Pressed);
+    mouse(&mut wnd, ElementState::Released);
+    assert!(wnd.focusing().is_some());
+    assert_ne!(wnd.focusing(), *input.read());
+  }
 }
@@ -0,0 +1,464 @@
+use std::{
+  cell::{Cell, RefCell},
+  rc::Rc,
+};
+
+use ribir_core::{prelude::*, ticker::FrameMsg};
+
+/// A section of a scroll view whose header sticks to the top of the view
+/// while the section scrolls, and is pushed out by the end of the section.
+///
+/// The headers of the nested sections stack under the header of their outer
+/// section. With a `collapsed_height`, the header shrinks to it while the
+/// section scrolls under it, and its `collapse_rate` can drive the content of
+/// the header, such as a collapsing app bar.
+///
+/// # Example
+///
+/// ```
+/// use ribir_core::prelude::*;
+/// use ribir_widgets::prelude::*;
+///
+/// let _w = fn_widget! {
+///   @Column {
+///     scrollable: Scrollable::Y,
+///     @Sticky {
+///       @StickyHeader { @Text { text: "A" } }
+///       @Column {
+///         @Text { text: "Alice" }
+///         @Text { text: "Arthur" }
+///       }
+///     }
+///     @Sticky {
+///       @StickyHeader { @Text { text: "B" } }
+///       @Text { text: "Bob" }
+///     }
+///   }
+/// };
+/// ```
+#[derive(Declare)]
+pub struct Sticky {
+  /// The height the header collapses to while its section scrolls under it,
+  /// `None` keeps the full height of the header.
+  #[declare(default)]
+  pub collapsed_height: Option<f32>,
+  #[declare(skip)]
+  collapse_rate: f32,
+}
+
+/// The header of a `Sticky` section.
+#[derive(Template)]
+pub struct StickyHeader<'w> {
+  child: Widget<'w>,
+}
+
+#[derive(Template)]
+pub struct StickyTml<'w> {
+  header: StickyHeader<'w>,
+  body: Widget<'w>,
+}
+
+impl Sticky {
+  /// How much the header collapsed, from `0.` for the full height to `1.` for
+  /// the `collapsed_height`.
+  pub fn collapse_rate(&self) -> f32 { self.collapse_rate }
+}
+
+impl<'c> ComposeChild<'c> for Sticky {
+  type Child = StickyTml<'c>;
+
+  fn compose_child(this: impl StateWriter<Value = Self>, child: Self::Child) -> Widget<'c> {
+    let StickyTml { header, body } = child;
+    let pin = Rc::new(Pin::default());
+    let c_pin = pin.clone();
+    fn_widget! {
+      let pin = c_pin;
+      let section = @StickySection {
+        collapsed_height: pipe!($this.collapsed_height),
+        pin: pin.clone(),
+      };
+
+      // Pin the header by the scroll position once the layout is ready, so
+      // the painting and the hit testing of this frame see the same position.
+      let wnd = BuildCtx::get().window();
+      let c_wnd = wnd.clone();
+      let c_pin = pin.clone();
+      let u = wnd
+        .frame_tick_stream()
+        .filter(|msg| matches!(msg, FrameMsg::LayoutReady(_)))
+        .subscribe(move |_| {
+          c_pin.update(&c_wnd);
+          let rate = c_pin.collapse_rate.get();
+          if $this.collapse_rate != rate {
+            $this.write().collapse_rate = rate;
+          }
+        });
+
+      @ $section {
+        on_disposed: move |_| u.unsubscribe(),
+        @ { body }
+        @StickyHeaderBox { pin: pin.clone(), @ { header.child } }
+      }
+    }
+    .into_widget()
+    .attach_data(Box::new(Queryable(StickyLine(pin))))
+  }
+}
+
+/// The shared state between a section and its header. The section records
+/// where it is in its layout, and the pinned position of the header is updated
+/// from the scroll position after the layout is ready.
+#[derive(Default)]
+struct Pin {
+  offset: Cell<f32>,
+  extent: Cell<Option<f32>>,
+  collapse_rate: Cell<f32>,
+  /// The global y of the bottom of the pinned header, where the headers of the
+  /// nested sections stick to.
+  line: Cell<f32>,
+  section: Cell<Option<WidgetId>>,
+  header_height: Cell<f32>,
+  collapsed_height: Cell<Option<f32>>,
+  /// The widget that hosts the `ScrollableWidget` scrolling the section.
+  scroll_host: Cell<Option<WidgetId>>,
+  /// The pin of the outer section, the header sticks under its header.
+  outer: RefCell<Option<Rc<Pin>>>,
+}
+
+impl Pin {
+  fn update(&self, wnd: &Window) {
+    let Some((section, size)) = self
+      .section
+      .get()
+      .and_then(|id| Some((id, wnd.widget_size(id)?)))
+    else {
+      return;
+    };
+    let header = self.header_height.get();
+    let view_top = self
+      .scroll_host
+      .get()
+      .filter(|host| wnd.widget_size(*host).is_some())
+      .map(|host| {
+        let top = wnd.map_to_global(Point::zero(), host);
+        wnd.map_from_global(top, section).y
+      });
+
+    let (offset, extent) = match view_top {
+      Some(view_top) => {
+        // Stick under the header of the outer section.
+        let line = self.outer.borrow().as_ref().map(|outer| {
+          outer.update(wnd);
+          wnd
+            .map_from_global(Point::new(0., outer.line.get()), section)
+            .y
+        });
+        let top = line.map_or(view_top, |line| line.max(view_top));
+        let extent = match self.collapsed_height.get() {
+          Some(collapsed) => (header - top.max(0.)).max(collapsed.min(header)),
+          None => header,
+        };
+        (top.clamp(0., (size.height - extent).max(0.)), extent)
+      }
+      None => (0., header),
+    };
+
+    self.offset.set(offset);
+    self
+      .extent
+      .set((extent < header).then_some(extent));
+    let rate = match self.collapsed_height.get() {
+      Some(collapsed) if collapsed < header => (header - extent) / (header - collapsed),
+      _ => 0.,
+    };
+    self.collapse_rate.set(rate);
+    let bottom = wnd
+      .map_to_global(Point::new(0., offset + extent), section)
+      .y;
+    self.line.set(bottom);
+  }
+}
+
+/// Lays out the header above the body, and paints it after the body to cover
+/// the body scrolled under it.
+#[derive(Declare, MultiChild)]
+struct StickySection {
+  #[declare(default)]
+  collapsed_height: Option<f32>,
+  pin: Rc<Pin>,
+}
+
+/// The query data of a section for its nested sections.
+struct StickyLine(Rc<Pin>);
+
+impl Render for StickySection {
+  fn perform_layout(&self, clamp: BoxClamp, ctx: &mut LayoutCtx) -> Size {
+    let pin = &self.pin;
+    pin.section.set(Some(ctx.widget_id()));
+    pin.collapsed_height.set(self.collapsed_height);
+    let outer = (&*ctx)
+      .all_providers::<StickyLine>()
+      .find(|l| !Rc::ptr_eq(&l.0, pin))
+      .map(|l| l.0.clone());
+    *pin.outer.borrow_mut() = outer;
+    let mut host = ctx.parent();
+    while let Some(id) = host {
+      if ctx
+        .query_of_widget::<ScrollableWidget>(id)
+        .is_some()
+      {
+        break;
+      }
+      host = ctx.widget_parent(id);
+    }
+    pin.scroll_host.set(host);
+
+    let (ctx, children) = ctx.split_children();
+    let children: Vec<_> = children.collect();
+    let [body, header] = children[..] else {
+      return clamp.min;
+    };
+
+    let header_size = ctx.perform_child_layout(header, clamp.loose());
+    let mut body_clamp = clamp.loose();
+    body_clamp.max.height = (body_clamp.max.height - header_size.height).max(0.);
+    let body_size = ctx.perform_child_layout(body, body_clamp);
+    ctx.update_position(header, Point::zero());
+    ctx.update_position(body, Point::new(0., header_size.height));
+    pin.header_height.set(header_size.height);
+
+    let width = header_size.width.max(body_size.width);
+    clamp.clamp(Size::new(width, header_size.height + body_size.height))
+  }
+
+  /// The header is above the body, so the width is the wider one and the
+  /// height is the sum of them.
+  fn intrinsic_size(&self, kind: IntrinsicSize, extent: f32, ctx: &mut LayoutCtx) -> f32 {
+    let (ctx, children) = ctx.split_children();
+    let sizes = children.map(|c| ctx.child_intrinsic_size(c, kind, extent));
+    if kind.is_width() { sizes.fold(0., f32::max) } else { sizes.sum() }
+  }
+}
+
+/// Moves the header to its pinned position and clips it to the collapsed
+/// height.
+#[derive(Declare, SingleChild)]
+struct StickyHeaderBox {
+  pin: Rc<Pin>,
+}
+
+impl Render for StickyHeaderBox {
+  fn perform_layout(&self, clamp: BoxClamp, ctx: &mut LayoutCtx) -> Size {
+    ctx.assert_perform_single_child_layout(clamp)
+  }
+
+  fn paint(&self, ctx: &mut PaintingCtx) {
+    let offset = self.pin.offset.get();
+    let width = ctx.box_size().map_or(0., |s| s.width);
+    let painter = ctx.painter();
+    painter.translate(0., offset);
+    if let Some(extent) = self.pin.extent.get() {
+      painter.clip(Path::rect(&Rect::from_size(Size::new(width, extent))).into());
+    }
+  }
+
+  fn hit_test(&self, ctx: &HitTestCtx, pos: Point) -> HitTest {
+    let offset = self.pin.offset.get();
+    let pos = Point::new(pos.x, pos.y - offset);
+    let extent = self.pin.extent.get();
+    let hit = ctx.box_hit_test(pos);
+    let in_extent = extent.is_none_or(|extent| {
+      let top = ctx.box_pos().map_or(0., |p| p.y);
+      pos.y < top + extent
+    });
+    HitTest { hit: hit.hit && in_extent, can_hit_child: hit.can_hit_child && in_extent }
+  }
+
+  fn get_transform(&self) -> Option<Transform> {
+    Some(Transform::translation(0., self.pin.offset.get()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use ribir_core::{reset_test_env, test_helper::*};
+  use winit::event::{DeviceId, ElementState, MouseButton, WindowEvent};
+
+  use super::*;
+  use crate::prelude::*;
+
+  fn scroll_window(w: impl FnOnce() -> Widget<'static> + 'static) -> (TestWindow, Stateful<f32>) {
+    let mut w = Some(w);
+    let scroll_y = Stateful::new(0.);
+    let c_scroll_y = scroll_y.clone_writer();
+    let w = fn_widget! {
+      let mut column = @Column { scrollable: Scrollable::Y };
+      let view_scroll = column.get_scrollable_widget();
+      watch!(*$c_scroll_y).subscribe(move |y| {
+        let mut view_scroll = $view_scroll.write();
+        view_scroll.jump_to(Point::new(0., y));
+      });
+      @ $column { @ { w.take().unwrap()() } }
+    };
+    let wnd = TestWindow::new_with_size(w, Size::new(100., 100.));
+    (wnd, scroll_y)
+  }
+
+  fn section(header: f32, body: f32, id: Rc<Cell<Option<WidgetId>>>) -> Widget<'static> {
+    fn_widget! {
+      let id = id.clone();
+      @Sticky {
+        @StickyHeader {
+          @SizedBox {
+            size: Size::new(100., header),
+            on_mounted: move |e| id.set(Some(e.current_target())),
+          }
+        }
+        @SizedBox { size: Size::new(100., body) }
+      }
+    }
+    .into_widget()
+  }
+
+  fn global_y(wnd: &TestWindow, id: &Rc<Cell<Option<WidgetId>>>) -> f32 {
+    wnd
+      .map_to_global(Point::zero(), id.get().unwrap())
+      .y
+  }
+
+  #[test]
+  fn stick_and_push_out() {
+    reset_test_env!();
+    let a = Rc::new(Cell::new(None));
+    let b = Rc::new(Cell::new(None));
+    let (c_a, c_b) = (a.clone(), b.clone());
+    let (mut wnd, scroll_y) = scroll_window(move || {
+      fn_widget! {
+        @Column {
+          @ { section(20., 200., c_a.clone()) }
+          @ { section(20., 200., c_b.clone()) }
+        }
+      }
+      .into_widget()
+    });
+    wnd.draw_frame();
+    assert_eq!(global_y(&wnd, &a), 0.);
+    assert_eq!(global_y(&wnd, &b), 220.);
+
+    *scroll_y.write() = 50.;
+    wnd.draw_frame();
+    assert_eq!(global_y(&wnd, &a), 0.);
+    assert_eq!(global_y(&wnd, &b), 170.);
+
+    // The end of the first section pushes its header out.
+    *scroll_y.write() = 210.;
+    wnd.draw_frame();
+    assert_eq!(global_y(&wnd, &a), -10.);
+    assert_eq!(global_y(&wnd, &b), 10.);
+  }
+
+  #[test]
+  fn stack_nested_headers() {
+    reset_test_env!();
+    let outer = Rc::new(Cell::new(None));
+    let inner = Rc::new(Cell::new(None));
+    let (c_outer, c_inner) = (outer.clone(), inner.clone());
+    let (mut wnd, scroll_y) = scroll_window(move || {
+      fn_widget! {
+        let c_outer = c_outer.clone();
+        @Sticky {
+          @StickyHeader {
+            @SizedBox {
+              size: Size::new(100., 20.),
+              on_mounted: move |e| c_outer.set(Some(e.current_target())),
+            }
+          }
+          @Column {
+            @SizedBox { size: Size::new(100., 30.) }
+            @ { section(10., 300., c_inner.clone()) }
+          }
+        }
+      }
+      .into_widget()
+    });
+    wnd.draw_frame();
+    assert_eq!(global_y(&wnd, &inner), 50.);
+
+    *scroll_y.write() = 100.;
+    wnd.draw_frame();
+    assert_eq!(global_y(&wnd, &outer), 0.);
+    assert_eq!(global_y(&wnd, &inner), 20.);
+  }
+
+  #[test]
+  fn collapse_header() {
+    reset_test_env!();
+    let rate = Stateful::new(0.);
+    let c_rate = rate.clone_writer();
+    let header = Rc::new(Cell::new(None));
+    let c_header = header.clone();
+    let (mut wnd, scroll_y) = scroll_window(move || {
+      fn_widget! {
+        let c_header = c_header.clone();
+        let sticky = @Sticky { collapsed_height: Some(10.) };
+        watch!($sticky.collapse_rate()).subscribe(move |v| *$c_rate.write() = v);
+        @ $sticky {
+          @StickyHeader {
+            @SizedBox {
+              size: Size::new(100., 50.),
+              on_mounted: move |e| c_header.set(Some(e.current_target())),
+            }
+          }
+          @SizedBox { size: Size::new(100., 500.) }
+        }
+      }
+      .into_widget()
+    });
+    wnd.draw_frame();
+    assert_eq!(*rate.read(), 0.);
+
+    *scroll_y.write() = 30.;
+    wnd.draw_frame();
+    assert_eq!(global_y(&wnd, &header), 0.);
+    assert_eq!(*rate.read(), 0.75);
+
+    *scroll_y.write() = 100.;
+    wnd.draw_frame();
+    assert_eq!(*rate.read(), 1.);
+  }
+
+  #[test]
+  fn hit_pinned_header_in_the_scrolled_frame() {
+    reset_test_env!();
+    let pressed = Stateful::new(vec![]);
+    let c_pressed = pressed.clone_writer();
+    let (mut wnd, scroll_y) = scroll_window(move || {
+      fn_widget! {
+        @Sticky {
+          @StickyHeader {
+            @SizedBox {
+              size: Size::new(100., 20.),
+              on_pointer_down: move |_| $c_pressed.write().push("header"),
+            }
+          }
+          @SizedBox {
+            size: Size::new(100., 300.),
+            on_pointer_down: move |_| $c_pressed.write().push("body"),
+          }
+        }
+      }
+      .into_widget()
+    });
+    wnd.draw_frame();
+
+    // Only lay out the scrolled frame, the header is pinned before painting.
+    *scroll_y.write() = 50.;
+    wnd.layout();
+    let device_id = unsafe { DeviceId::dummy() };
+    #[allow(deprecated)]
+    wnd.processes_native_event(WindowEvent::CursorMoved { device_id, position: (50., 10.).into() });
+    wnd.process_mouse_input(device_id, ElementState::Pressed, MouseButton::Left);
+    wnd.run_frame_tasks();
+    assert_eq!(*pressed.read(), ["header"]);
+  }
+}
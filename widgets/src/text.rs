@@ -1,30 +1,47 @@
-use std::cell::{Ref, RefCell};
+use std::cell::{Cell, Ref, RefCell};
 
 use ribir_core::prelude::*;
-use typography::PlaceLineDirection;
+
+use crate::input::{GlyphsText, SelectionArea, paint_selection};
 
 /// The text widget display text with a single style.
+///
+/// The text is set vertically if the `writing_mode` of its `TextStyle` is
+/// vertical.
 #[derive(Declare)]
 pub struct Text {
   pub text: CowArc<str>,
+  /// The text edited in a `Rope`, it's shown in place of the `text` if it's
+  /// set. Only the paragraphs edited from the rope laid out last are laid out
+  /// again, and the text is not copied out of the rope.
+  #[declare(default)]
+  pub rope: Option<Rope>,
   #[declare(default = TextAlign::Start)]
   pub text_align: TextAlign,
   #[declare(default)]
   glyphs: RefCell<Option<VisualGlyphs>>,
+  /// If the text is laid out in a `SelectionArea`.
+  #[declare(skip)]
+  in_selection_area: Cell<bool>,
+  /// The glyphs are painted by others, such as the highlighter of the
+  /// `CodeEditor`, the text only paints its selection.
+  #[declare(skip)]
+  pub(crate) glyphs_painted_by_others: bool,
 }
 
 impl Render for Text {
   fn perform_layout(&self, clamp: BoxClamp, ctx: &mut LayoutCtx) -> Size {
     let style = Provider::of::<TextStyle>(&ctx).unwrap();
-    let info = AppCtx::typography_store()
-      .borrow_mut()
-      .typography(
-        self.text.substr(..),
-        &style,
-        clamp.max,
-        self.text_align,
-        PlaceLineDirection::TopToBottom,
-      );
+    let store = AppCtx::typography_store();
+    let mut store = store.borrow_mut();
+    let line_dir = style.writing_mode.line_dir();
+    let info = self.layout_text(&mut store, &style, clamp.max, line_dir);
+
+    let area = Provider::of::<SelectionArea>(&ctx);
+    self.in_selection_area.set(area.is_some());
+    if let Some(area) = area {
+      area.register(ctx.widget_id(), self.plain_text(), info.clone());
+    }
 
     let size = info.visual_rect().size;
     *self.glyphs.borrow_mut() = Some(info);
@@ -35,6 +52,22 @@ impl Render for Text {
   #[inline]
   fn only_sized_by_parent(&self) -> bool { false }
 
+  fn baselines(&self, _: &LayoutCtx) -> Option<Baselines> {
+    self.glyphs().and_then(|g| text_baselines(&g))
+  }
+
+  /// The minimum size along the line is the widest word if the text wraps,
+  /// and the maximum one lays out every paragraph in a single line. The size
+  /// across the lines is the size of the text laid out within the `extent`.
+  fn intrinsic_size(&self, kind: IntrinsicSize, extent: f32, ctx: &mut LayoutCtx) -> f32 {
+    let style = Provider::of::<TextStyle>(&ctx).unwrap();
+    let line_dir = style.writing_mode.line_dir();
+    let bounds = intrinsic_bounds(kind, extent, line_dir);
+    let store = AppCtx::typography_store();
+    let info = self.layout_text(&mut store.borrow_mut(), &style, bounds, line_dir);
+    kind.axis_of(info.visual_rect().size)
+  }
+
   fn paint(&self, ctx: &mut PaintingCtx) {
     let box_rect = Rect::from_size(ctx.box_size().unwrap());
     if ctx
@@ -46,10 +79,15 @@ impl Render for Text {
     };
 
     let visual_glyphs = self.glyphs().unwrap();
-    let font_db = AppCtx::font_db().clone();
-    ctx
-      .painter()
-      .draw_glyphs_in_rect(&visual_glyphs, box_rect, &font_db.borrow());
+    if self.in_selection_area.get() {
+      paint_selection(ctx, &self.plain_text(), &visual_glyphs);
+    }
+    if !self.glyphs_painted_by_others {
+      let font_db = AppCtx::font_db().clone();
+      ctx
+        .painter()
+        .draw_glyphs_in_rect(&visual_glyphs, box_rect, &font_db.borrow());
+    }
   }
 }
 
@@ -57,6 +95,68 @@ impl Text {
   pub fn glyphs(&self) -> Option<Ref<VisualGlyphs>> {
     Ref::filter_map(self.glyphs.borrow(), |v| v.as_ref()).ok()
   }
+
+  /// The text shown, it's copied out of the `rope` if the rope is set.
+  pub fn plain_text(&self) -> CowArc<str> {
+    match &self.rope {
+      Some(rope) => rope.to_string().into(),
+      None => self.text.clone(),
+    }
+  }
+
+  /// The text the glyphs are laid out from.
+  pub(crate) fn glyphs_text(&self) -> GlyphsText {
+    match &self.rope {
+      Some(rope) => GlyphsText::Rope(rope.clone()),
+      None => GlyphsText::Str(self.text.clone()),
+    }
+  }
+
+  fn layout_text(
+    &self, store: &mut TypographyStore, style: &TextStyle, bounds: Size,
+    line_dir: typography::PlaceLineDirection,
+  ) -> VisualGlyphs {
+    match &self.rope {
+      // The rope may be edited from the last layout, keep its paragraphs.
+      Some(rope) => {
+        let prev = self.glyphs.borrow();
+        store.typography_rope(rope, prev.as_ref(), style, bounds, self.text_align, line_dir)
+      }
+      None => store.typography(self.text.substr(..), style, bounds, self.text_align, line_dir),
+    }
+  }
+}
+
+/// The bounds to lay out a text in to measure its intrinsic size of the `kind`
+/// for the `extent`.
+pub(crate) fn intrinsic_bounds(
+  kind: IntrinsicSize, extent: f32, line_dir: typography::PlaceLineDirection,
+) -> Size {
+  // The lines are placed along the `line_dir`, so a line spans the other axis.
+  let vertical = line_dir.is_horizontal();
+  let along_line = kind.is_width() != vertical;
+  let line_extent = match (along_line, kind.is_min()) {
+    (true, true) => 0.,
+    (true, false) => f32::INFINITY,
+    (false, _) => extent,
+  };
+  if vertical {
+    Size::new(f32::INFINITY, line_extent)
+  } else {
+    Size::new(line_extent, f32::INFINITY)
+  }
+}
+
+/// Return the baselines of the horizontal text, vertical text has no baseline.
+pub(crate) fn text_baselines(glyphs: &VisualGlyphs) -> Option<Baselines> {
+  if glyphs.line_dir().is_horizontal() {
+    return None;
+  }
+  let metrics = glyphs.metrics();
+  let top = glyphs.visual_rect().min_y();
+  let first = metrics.lines.first()?.baseline;
+  let last = metrics.lines.last()?.baseline;
+  Some(Baselines::new(top + first, top + last))
 }
 
 macro_rules! define_text_with_theme_style {
@@ -9,6 +9,10 @@ pub struct TextField {
   /// textfield's input value
   #[declare(skip)]
   text: CowArc<str>,
+  /// The error message shown below the text field, the `FormField` sets it by
+  /// the validators.
+  #[declare(default)]
+  pub error: Option<CowArc<str>>,
 }
 
 impl TextField {
@@ -74,6 +78,12 @@ pub struct TextFieldTheme {
 
   /// edit area's padding when expand
   pub input_expand_padding: EdgeInsets,
+
+  /// indicator's and error text's color when the text field has an error
+  pub error_color: Color,
+
+  /// error text's style
+  pub error_text: TextStyle,
 }
 
 #[derive(Clone)]
@@ -185,6 +195,7 @@ impl TextFieldThemeSuit {
     let body: &TextStyle = &typo_theme.body_large.text;
     let header = &typo_theme.title_large.text;
     let caption = &typo_theme.label_small.text;
+    let error_text = &typo_theme.body_small.text;
 
     let mut themes = HashMap::new();
 
@@ -205,6 +216,8 @@ impl TextFieldThemeSuit {
       label_expand: header.clone(),
       input_collapse_padding,
       input_expand_padding,
+      error_color: palette.error(),
+      error_text: error_text.clone(),
     });
 
     themes.insert(TextFieldState::Focused, TextFieldTheme {
@@ -220,6 +233,8 @@ impl TextFieldThemeSuit {
       label_expand: header.clone(),
       input_collapse_padding,
       input_expand_padding,
+      error_color: palette.error(),
+      error_text: error_text.clone(),
     });
 
     themes.insert(TextFieldState::Hovered, TextFieldTheme {
@@ -235,6 +250,8 @@ impl TextFieldThemeSuit {
       label_expand: header.clone(),
       input_collapse_padding,
       input_expand_padding,
+      error_color: palette.error(),
+      error_text: error_text.clone(),
     });
 
     // themes.insert(
@@ -277,8 +294,17 @@ impl<'c> ComposeChild<'c> for TextField {
         state: TextFieldState::default(),
       }.into_inner();
       let indicator_size = pipe!(Size::new(f32::MAX, $theme.indicator_height));
-      let indicator_bg =  pipe!($theme.indicator);
-      @Stack {
+      let indicator_bg = pipe! {
+        if $this.error.is_some() { $theme.error_color } else { $theme.indicator }
+      };
+      let error = @Text {
+        visible: pipe!($this.error.is_some()),
+        margin: EdgeInsets::new(4., 16., 0., 16.),
+        text: pipe!($this.error.clone().unwrap_or_default()),
+        foreground: pipe!($theme.error_color),
+        text_style: pipe!($theme.error_text.clone()),
+      };
+      let field = @Stack {
         @Container {
           size: pipe!(Size::new(0., $theme.container_height)),
           background: pipe!($theme.container_color),
@@ -308,6 +334,10 @@ impl<'c> ComposeChild<'c> for TextField {
           size: indicator_size,
           background: indicator_bg,
         }
+      };
+      @Column {
+        @ { field }
+        @ { error }
       }
     }
     .into_widget()